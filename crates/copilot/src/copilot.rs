@@ -1254,7 +1254,7 @@ mod tests {
             self.abs_path.clone()
         }
 
-        fn load(&self, _: &AppContext) -> Task<Result<String>> {
+        fn load_bytes(&self, _: &AppContext) -> Task<Result<Vec<u8>>> {
             unimplemented!()
         }
 
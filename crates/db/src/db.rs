@@ -1,3 +1,4 @@
+pub mod file_history;
 pub mod kvp;
 pub mod query;
 
@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use sqlez_macros::sql;
+
+use crate::{define_connection, query};
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // file_history(
+    //   id: i64,
+    //   path: PathBuf,
+    //   saved_at: i64, // Unix timestamp, in seconds
+    //   content: String,
+    // )
+    pub static ref FILE_HISTORY: FileHistoryDb<()> =
+        &[sql!(
+            CREATE TABLE file_history(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path BLOB NOT NULL,
+                saved_at INTEGER NOT NULL,
+                content TEXT NOT NULL
+            ) STRICT;
+            CREATE INDEX file_history_path ON file_history(path);
+        ),
+        sql!(
+            CREATE INDEX file_history_saved_at ON file_history(saved_at);
+        )];
+);
+
+impl FileHistoryDb {
+    // Records a snapshot of the file at the given path, unless its content
+    // is the same as that of the latest snapshot.
+    query! {
+        pub async fn save_snapshot(path: PathBuf, saved_at: i64, content: String) -> Result<()> {
+            INSERT INTO file_history(path, saved_at, content)
+            SELECT ?1, ?2, ?3
+            WHERE ?3 IS NOT (
+                SELECT content FROM file_history
+                WHERE path = ?1
+                ORDER BY id DESC
+                LIMIT 1
+            )
+        }
+    }
+
+    // Returns the id, save time and length of each snapshot of the file at
+    // the given path, newest first.
+    query! {
+        pub fn snapshots(path: PathBuf) -> Result<Vec<(i64, i64, usize)>> {
+            SELECT id, saved_at, length(CAST(content AS BLOB))
+            FROM file_history
+            WHERE path = ?
+            ORDER BY id DESC
+        }
+    }
+
+    query! {
+        pub fn snapshot_content(id: i64) -> Result<Option<String>> {
+            SELECT content FROM file_history WHERE id = ?
+        }
+    }
+
+    query! {
+        pub fn has_snapshots(path: PathBuf) -> Result<bool> {
+            SELECT EXISTS(SELECT 1 FROM file_history WHERE path = ?)
+        }
+    }
+
+    // Deletes the snapshots of the file at the given path that were saved
+    // before the given time, or that aren't among its newest `max_count`.
+    query! {
+        pub async fn prune_snapshots(path: PathBuf, saved_before: i64, max_count: u32) -> Result<()> {
+            DELETE FROM file_history
+            WHERE path = ?1 AND (
+                saved_at < ?2 OR id NOT IN (
+                    SELECT id FROM file_history
+                    WHERE path = ?1
+                    ORDER BY id DESC
+                    LIMIT ?3
+                )
+            )
+        }
+    }
+
+    // Deletes the snapshots of every file that were saved before the given
+    // time, so that files which stopped being saved don't stay in the history.
+    query! {
+        pub async fn prune_expired_snapshots(saved_before: i64) -> Result<()> {
+            DELETE FROM file_history WHERE saved_at < ?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::file_history::FileHistoryDb;
+
+    #[gpui::test]
+    async fn test_file_history() {
+        let db = FileHistoryDb(crate::open_test_db("test_file_history").await);
+        let path = PathBuf::from("/root/a.txt");
+        let other_path = PathBuf::from("/root/b.txt");
+
+        assert!(!db.has_snapshots(path.clone()).unwrap());
+        db.save_snapshot(path.clone(), 10, "one".into())
+            .await
+            .unwrap();
+        // Saving the same content again doesn't record another snapshot.
+        db.save_snapshot(path.clone(), 20, "one".into())
+            .await
+            .unwrap();
+        db.save_snapshot(path.clone(), 30, "three".into())
+            .await
+            .unwrap();
+        db.save_snapshot(path.clone(), 40, "four".into())
+            .await
+            .unwrap();
+        db.save_snapshot(other_path.clone(), 40, "other".into())
+            .await
+            .unwrap();
+        assert!(db.has_snapshots(path.clone()).unwrap());
+
+        let snapshots = db.snapshots(path.clone()).unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|(_, saved_at, len)| (*saved_at, *len))
+                .collect::<Vec<_>>(),
+            [(40, 4), (30, 5), (10, 3)]
+        );
+        assert_eq!(
+            db.snapshot_content(snapshots[1].0).unwrap().as_deref(),
+            Some("three")
+        );
+
+        db.prune_snapshots(path.clone(), 0, 2).await.unwrap();
+        assert_eq!(
+            db.snapshots(path.clone())
+                .unwrap()
+                .iter()
+                .map(|(_, saved_at, _)| *saved_at)
+                .collect::<Vec<_>>(),
+            [40, 30]
+        );
+
+        db.prune_snapshots(path.clone(), 35, 2).await.unwrap();
+        assert_eq!(db.snapshots(path.clone()).unwrap().len(), 1);
+        assert_eq!(db.snapshots(other_path.clone()).unwrap().len(), 1);
+
+        db.prune_expired_snapshots(45).await.unwrap();
+        assert!(!db.has_snapshots(path).unwrap());
+        assert!(!db.has_snapshots(other_path).unwrap());
+    }
+}
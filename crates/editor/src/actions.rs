@@ -13,6 +13,43 @@ pub struct SelectPrevious {
     pub replace_newest: bool,
 }
 
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct InsertNumbers {
+    /// The number inserted at the first selection.
+    #[serde(default = "InsertNumbers::default_start")]
+    pub start: i64,
+    /// How much the number increases from one selection to the next.
+    #[serde(default = "InsertNumbers::default_step")]
+    pub step: i64,
+}
+
+impl InsertNumbers {
+    fn default_start() -> i64 {
+        1
+    }
+
+    fn default_step() -> i64 {
+        1
+    }
+}
+
+impl Default for InsertNumbers {
+    fn default() -> Self {
+        Self {
+            start: Self::default_start(),
+            step: Self::default_step(),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct SelectRegexMatches {
+    /// The regular expression whose matches within the selections are selected.
+    /// When it's empty, the user is prompted for one.
+    #[serde(default)]
+    pub pattern: String,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct SelectToBeginningOfLine {
     #[serde(default)]
@@ -99,6 +136,8 @@ impl_actions!(
     [
         SelectNext,
         SelectPrevious,
+        InsertNumbers,
+        SelectRegexMatches,
         SelectToBeginningOfLine,
         MovePageUp,
         MovePageDown,
@@ -121,6 +160,7 @@ gpui::actions!(
     [
         AddSelectionAbove,
         AddSelectionBelow,
+        AlignSelections,
         Backspace,
         Cancel,
         ConfirmRename,
@@ -173,6 +213,10 @@ gpui::actions!(
         Hover,
         Indent,
         JoinLines,
+        KillRingCut,
+        KillRingSave,
+        KillRingYank,
+        KillRingYankPop,
         LineDown,
         LineUp,
         MoveDown,
@@ -209,6 +253,8 @@ gpui::actions!(
         RestartLanguageServer,
         RevealInFinder,
         ReverseLines,
+        RotateSelectionsBackward,
+        RotateSelectionsForward,
         ScrollCursorBottom,
         ScrollCursorCenter,
         ScrollCursorTop,
@@ -218,6 +264,7 @@ gpui::actions!(
         SelectLargerSyntaxNode,
         SelectLeft,
         SelectLine,
+        SelectNextSkip,
         SelectRight,
         SelectSmallerSyntaxNode,
         SelectToBeginning,
@@ -229,12 +276,14 @@ gpui::actions!(
         SelectToPreviousWordStart,
         SelectToStartOfParagraph,
         SelectUp,
+        SetMark,
         ShowCharacterPalette,
         ShowCompletions,
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        SwapSelectionEnds,
         Tab,
         TabPrev,
         ToggleInlayHints,
@@ -18,19 +18,25 @@ pub mod display_map;
 mod editor_settings;
 mod element;
 mod inlay_hint_cache;
+mod kill_ring;
+mod large_file_banner;
 
 mod debounced_delay;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
+mod indent_guides;
 pub mod items;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod rainbow_brackets;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod sticky_scroll;
+mod user_snippets;
 
 #[cfg(test)]
 mod editor_tests;
@@ -39,7 +45,7 @@ pub mod test;
 use ::git::diff::DiffHunk;
 pub(crate) use actions::*;
 use aho_corasick::AhoCorasick;
-use anyhow::{anyhow, Context as _, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
 use blink_manager::BlinkManager;
 use client::{Collaborator, ParticipantIndex};
 use clock::ReplicaId;
@@ -66,9 +72,12 @@ use gpui::{
 };
 use highlight_matching_bracket::refresh_matching_bracket_highlights;
 use hover_popover::{hide_hover, HoverState};
+pub use indent_guides::IndentGuide;
+use indent_guides::IndentGuideCache;
 use inlay_hint_cache::{InlayHintCache, InlaySplice, InvalidationStrategy};
 pub use items::MAX_TAB_TITLE_LEN;
 use itertools::Itertools;
+use kill_ring::{KillRing, KillRingPosition, YankState};
 use language::{char_kind, CharKind};
 use language::{
     language_settings::{self, all_language_settings, InlayHintSettings},
@@ -76,6 +85,7 @@ use language::{
     CodeLabel, Completion, CursorShape, Diagnostic, Documentation, IndentKind, IndentSize,
     Language, OffsetRangeExt, Point, Selection, SelectionGoal, TransactionId,
 };
+pub use large_file_banner::LargeFileBanner;
 
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
 use lsp::{DiagnosticSeverity, LanguageServerId};
@@ -90,7 +100,9 @@ use ordered_float::OrderedFloat;
 use parking_lot::{Mutex, RwLock};
 use project::project_settings::{GitGutterSetting, ProjectSettings};
 use project::{FormatTrigger, Location, Project, ProjectPath, ProjectTransaction};
+use rainbow_brackets::{refresh_rainbow_brackets, RainbowBrackets};
 use rand::prelude::*;
+use regex::Regex;
 use rpc::proto::*;
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager, ScrollbarAutoHide};
 use selections_collection::{resolve_multiple, MutableSelectionsCollection, SelectionsCollection};
@@ -109,6 +121,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use sticky_scroll::StickyHeaderCache;
 pub use sum_tree::Bias;
 use sum_tree::TreeMap;
 use text::{BufferId, OffsetUtf16, Rope};
@@ -120,6 +133,8 @@ use ui::{
     h_flex, prelude::*, ButtonSize, ButtonStyle, IconButton, IconName, IconSize, ListItem, Popover,
     Tooltip,
 };
+use user_snippets::{snippet_completions, SNIPPET_SERVER_ID};
+pub use user_snippets::{UserSnippet, UserSnippets};
 use util::{maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
 use workspace::Toast;
 use workspace::{searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace};
@@ -386,6 +401,10 @@ pub struct Editor {
     show_breadcrumbs: bool,
     show_gutter: bool,
     show_wrap_guides: Option<bool>,
+    show_indent_guides: Option<bool>,
+    indent_guide_cache: IndentGuideCache,
+    sticky_header_cache: StickyHeaderCache,
+    rainbow_brackets: Option<RainbowBrackets>,
     placeholder_text: Option<Arc<str>>,
     highlighted_rows: Option<Range<u32>>,
     background_highlights: BTreeMap<TypeId, BackgroundHighlight>,
@@ -406,6 +425,11 @@ pub struct Editor {
     autoindent_mode: Option<AutoindentMode>,
     workspace: Option<(WeakView<Workspace>, i64)>,
     keymap_context_layers: BTreeMap<TypeId, KeyContext>,
+    /// Set by `SetMark`, so that movements extend the selections (as with
+    /// emacs' mark) until the selections are used or cancelled.
+    selection_mark_mode: bool,
+    last_kill: Option<KillRingPosition>,
+    last_yank: Option<YankState>,
     input_enabled: bool,
     use_modal_editing: bool,
     read_only: bool,
@@ -605,6 +629,7 @@ struct AutocloseRegion {
 #[derive(Debug)]
 struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
+    choices: Vec<Vec<String>>,
     active_index: usize,
 }
 
@@ -1330,7 +1355,7 @@ struct ActiveDiagnosticGroup {
     is_valid: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ClipboardSelection {
     pub len: usize,
     pub is_entire_line: bool,
@@ -1513,6 +1538,10 @@ impl Editor {
             show_breadcrumbs: EditorSettings::get_global(cx).toolbar.breadcrumbs,
             show_gutter: mode == EditorMode::Full,
             show_wrap_guides: None,
+            show_indent_guides: None,
+            indent_guide_cache: Default::default(),
+            sticky_header_cache: Default::default(),
+            rainbow_brackets: None,
             placeholder_text: None,
             highlighted_rows: None,
             background_highlights: Default::default(),
@@ -1534,6 +1563,9 @@ impl Editor {
             collapse_matches: false,
             workspace: None,
             keymap_context_layers: Default::default(),
+            selection_mark_mode: false,
+            last_kill: None,
+            last_yank: None,
             input_enabled: true,
             use_modal_editing: mode == EditorMode::Full,
             read_only: false,
@@ -1600,6 +1632,9 @@ impl Editor {
         if self.pending_rename.is_some() {
             key_context.add("renaming");
         }
+        if self.selection_mark_mode {
+            key_context.add("selection_mode");
+        }
         if self.context_menu_visible() {
             match self.context_menu.read().as_ref() {
                 Some(ContextMenu::Completions(_)) => {
@@ -2346,6 +2381,16 @@ impl Editor {
             return;
         }
 
+        if self.selection_mark_mode {
+            self.selection_mark_mode = false;
+            self.change_selections(None, cx, |s| {
+                s.move_with(|_, selection| {
+                    selection.collapse_to(selection.head(), SelectionGoal::None)
+                })
+            });
+            return;
+        }
+
         if self.mode == EditorMode::Full {
             if self.change_selections(Some(Autoscroll::fit()), cx, |s| s.try_cancel()) {
                 return;
@@ -2392,6 +2437,7 @@ impl Editor {
         if self.read_only(cx) {
             return;
         }
+        self.selection_mark_mode = false;
 
         let selections = self.selections.all_adjusted(cx);
         let mut brace_inserted = false;
@@ -3264,12 +3310,18 @@ impl Editor {
 
         let query = Self::completion_query(&self.buffer.read(cx).read(cx), position.clone());
         let completions = provider.completions(&buffer, buffer_position, cx);
+        let snippet_completions = if self.mode == EditorMode::Full {
+            snippet_completions(buffer.read(cx), buffer_position, cx)
+        } else {
+            Vec::new()
+        };
 
         let id = post_inc(&mut self.next_completion_id);
         let task = cx.spawn(|this, mut cx| {
             async move {
                 let completions = completions.await.log_err();
-                let menu = if let Some(completions) = completions {
+                let menu = if let Some(mut completions) = completions {
+                    completions.extend(snippet_completions);
                     let mut menu = CompletionsMenu {
                         id,
                         initial_position: position,
@@ -3390,7 +3442,12 @@ impl Editor {
         let snippet;
         let text;
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, &|name| {
+                    self.snippet_variable(name, cx)
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -3482,6 +3539,9 @@ impl Editor {
             this.refresh_copilot_suggestions(true, cx);
         });
 
+        if completion.server_id == SNIPPET_SERVER_ID {
+            return Some(Task::ready(Ok(())));
+        }
         let provider = self.completion_provider.as_ref()?;
         let apply_edits = provider.apply_additional_edits_for_completion(
             buffer_handle,
@@ -4219,9 +4279,13 @@ impl Editor {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.select_ranges(tabstop.iter().cloned());
             });
+            if let Some(choices) = snippet.choices.first() {
+                self.show_snippet_choices(choices, cx);
+            }
             self.snippet_stack.push(SnippetState {
                 active_index: 0,
                 ranges: tabstops,
+                choices: snippet.choices,
             });
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
@@ -4295,6 +4359,9 @@ impl Editor {
                 self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.select_anchor_ranges(current_ranges.iter().cloned())
                 });
+                if let Some(choices) = snippet.choices.get(snippet.active_index) {
+                    self.show_snippet_choices(choices, cx);
+                }
                 // If snippet state is not at the last tabstop, push it back on the stack
                 if snippet.active_index + 1 < snippet.ranges.len() {
                     self.snippet_stack.push(snippet);
@@ -4306,6 +4373,70 @@ impl Editor {
         false
     }
 
+    /// Offers the values of a snippet's choice, such as `${1|one,two|}`, in
+    /// a completions menu at the tabstop.
+    fn show_snippet_choices(&mut self, choices: &[String], cx: &mut ViewContext<Self>) {
+        if choices.is_empty() {
+            return;
+        }
+        let selection = self.selections.newest_anchor().clone();
+        let multibuffer = self.buffer.read(cx);
+        let Some((buffer, start)) = multibuffer.text_anchor_for_position(selection.start, cx)
+        else {
+            return;
+        };
+        let Some((_, end)) = multibuffer.text_anchor_for_position(selection.end, cx) else {
+            return;
+        };
+
+        let completions = choices
+            .iter()
+            .map(|choice| Completion {
+                old_range: start..end,
+                new_text: choice.clone(),
+                label: CodeLabel::plain(choice.clone(), None),
+                server_id: SNIPPET_SERVER_ID,
+                documentation: Some(Documentation::Undocumented),
+                lsp_completion: lsp::CompletionItem {
+                    label: choice.clone(),
+                    ..Default::default()
+                },
+            })
+            .collect::<Vec<_>>();
+        let match_candidates = choices
+            .iter()
+            .enumerate()
+            .map(|(id, choice)| StringMatchCandidate::new(id, choice.clone()))
+            .collect::<Vec<_>>();
+        // The choices keep their order, rather than being sorted as matches.
+        let matches = match_candidates
+            .iter()
+            .map(|candidate| StringMatch {
+                candidate_id: candidate.id,
+                score: Default::default(),
+                positions: Default::default(),
+                string: candidate.string.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        self.completion_tasks.clear();
+        self.discard_copilot_suggestion(cx);
+        *self.context_menu.write() = Some(ContextMenu::Completions(CompletionsMenu {
+            id: post_inc(&mut self.next_completion_id),
+            initial_position: selection.start,
+            buffer,
+            completions: Arc::new(RwLock::new(completions.into())),
+            match_candidates: match_candidates.into(),
+            matches: matches.into(),
+            selected_item: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+            selected_completion_documentation_resolve_debounce: Arc::new(Mutex::new(
+                DebouncedDelay::new(),
+            )),
+        }));
+        cx.notify();
+    }
+
     pub fn clear(&mut self, cx: &mut ViewContext<Self>) {
         self.transact(cx, |this, cx| {
             this.select_all(&SelectAll, cx);
@@ -5004,6 +5135,173 @@ impl Editor {
         });
     }
 
+    pub fn insert_numbers(&mut self, action: &InsertNumbers, cx: &mut ViewContext<Self>) {
+        let selection_count = self.selections.count();
+        let mut number = action.start;
+        let numbers = (0..selection_count)
+            .map(|_| {
+                let text = number.to_string();
+                number = number.saturating_add(action.step);
+                text
+            })
+            .collect();
+        self.replace_selection_contents(numbers, false, cx);
+    }
+
+    pub fn rotate_selections_forward(
+        &mut self,
+        _: &RotateSelectionsForward,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.rotate_selections(true, cx);
+    }
+
+    pub fn rotate_selections_backward(
+        &mut self,
+        _: &RotateSelectionsBackward,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.rotate_selections(false, cx);
+    }
+
+    fn rotate_selections(&mut self, forward: bool, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let mut texts = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .map(|selection| buffer.text_for_range(selection.range()).collect::<String>())
+            .collect::<Vec<_>>();
+        if texts.len() < 2 {
+            return;
+        }
+
+        if forward {
+            texts.rotate_right(1);
+        } else {
+            texts.rotate_left(1);
+        }
+        self.replace_selection_contents(texts, true, cx);
+    }
+
+    /// Replaces the contents of each selection with the corresponding text,
+    /// either selecting the inserted text or placing the cursor after it.
+    fn replace_selection_contents(
+        &mut self,
+        texts: Vec<String>,
+        select_inserted_text: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut new_selections = Vec::new();
+        let mut edits = Vec::new();
+        let mut selection_adjustment = 0i32;
+
+        for (selection, text) in self.selections.all::<usize>(cx).into_iter().zip(texts) {
+            let old_length = (selection.end - selection.start) as i32;
+            let start = (selection.start as i32 - selection_adjustment) as usize;
+            let end = start + text.len();
+            edits.push((selection.start..selection.end, text));
+
+            new_selections.push(Selection {
+                start: if select_inserted_text { start } else { end },
+                end,
+                reversed: false,
+                goal: SelectionGoal::None,
+                ..selection
+            });
+
+            selection_adjustment += old_length - (end - start) as i32;
+        }
+
+        self.transact(cx, |this, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        });
+    }
+
+    pub fn align_selections(&mut self, _: &AlignSelections, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(cx);
+
+        // Group the selections by the row they start on, along with the column
+        // they start at, counted in characters.
+        let mut rows: Vec<Vec<(usize, u32)>> = Vec::new();
+        let mut last_row = None;
+        for (ix, selection) in selections.iter().enumerate() {
+            let line_start = Point::new(selection.start.row, 0);
+            let column = buffer
+                .text_for_range(line_start..selection.start)
+                .flat_map(str::chars)
+                .count() as u32;
+            if last_row == Some(selection.start.row) {
+                if let Some(row) = rows.last_mut() {
+                    row.push((ix, column));
+                }
+            } else {
+                rows.push(vec![(ix, column)]);
+            }
+            last_row = Some(selection.start.row);
+        }
+
+        // The first selections of every row are aligned with each other, then
+        // the second ones, taking the padding inserted before them into account.
+        let mut paddings = vec![0; selections.len()];
+        let mut row_shifts = vec![0; rows.len()];
+        let max_selections_per_row = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for nth in 0..max_selections_per_row {
+            let target_column = rows
+                .iter()
+                .zip(&row_shifts)
+                .filter_map(|(row, shift)| Some(row.get(nth)?.1 + shift))
+                .max()
+                .unwrap_or(0);
+            for (row, shift) in rows.iter().zip(&mut row_shifts) {
+                if let Some((ix, column)) = row.get(nth) {
+                    paddings[*ix] = target_column - (column + *shift);
+                    *shift += paddings[*ix];
+                }
+            }
+        }
+
+        let mut edits = Vec::new();
+        let mut new_selections = Vec::new();
+        let mut selection_adjustment = 0;
+        for (selection, padding) in selections.into_iter().zip(paddings) {
+            let start = selection.start.to_offset(&buffer);
+            let end = selection.end.to_offset(&buffer);
+            if padding > 0 {
+                edits.push((start..start, " ".repeat(padding as usize)));
+            }
+
+            selection_adjustment += padding as usize;
+            new_selections.push(Selection {
+                id: selection.id,
+                start: start + selection_adjustment,
+                end: end + selection_adjustment,
+                reversed: selection.reversed,
+                goal: SelectionGoal::None,
+            });
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(cx, |this, cx| {
+            this.buffer.update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select(new_selections);
+            });
+        });
+    }
+
     pub fn duplicate_line(&mut self, _: &DuplicateLine, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
@@ -6073,6 +6371,151 @@ impl Editor {
         });
     }
 
+    pub fn set_mark(&mut self, _: &SetMark, cx: &mut ViewContext<Self>) {
+        self.change_selections(None, cx, |s| {
+            s.move_with(|_, selection| selection.collapse_to(selection.head(), SelectionGoal::None))
+        });
+        self.selection_mark_mode = true;
+        cx.notify();
+    }
+
+    pub fn swap_selection_ends(&mut self, _: &SwapSelectionEnds, cx: &mut ViewContext<Self>) {
+        self.change_selections(None, cx, |s| {
+            s.move_with(|_, selection| {
+                if !selection.is_empty() {
+                    selection.reversed = !selection.reversed;
+                }
+            })
+        });
+        self.selection_mark_mode = true;
+        cx.notify();
+    }
+
+    /// Cuts the selections into the kill ring, or if they are empty the rest
+    /// of each line (or the newline at the end of it). Consecutive kills are
+    /// joined into one entry.
+    pub fn kill_ring_cut(&mut self, _: &KillRingCut, cx: &mut ViewContext<Self>) {
+        if self.read_only(cx) {
+            return;
+        }
+
+        let append = self.last_kill.as_ref() == Some(&self.kill_ring_position(cx));
+        self.change_selections(None, cx, |s| {
+            s.move_with(|map, selection| {
+                if selection.is_empty() {
+                    let head = selection.head();
+                    let mut end = movement::line_end(map, head, false);
+                    if end == head {
+                        end = movement::right(map, head);
+                    }
+                    selection.start = head;
+                    selection.end = end;
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                }
+            })
+        });
+        let text = self.selections_text(cx);
+        if text.is_empty() {
+            return;
+        }
+        self.transact(cx, |this, cx| this.insert("", cx));
+
+        KillRing::push(text, append, cx);
+        self.selection_mark_mode = false;
+        self.last_kill = Some(self.kill_ring_position(cx));
+    }
+
+    /// Copies the selections into the kill ring.
+    pub fn kill_ring_save(&mut self, _: &KillRingSave, cx: &mut ViewContext<Self>) {
+        let text = self.selections_text(cx);
+        KillRing::push(text, false, cx);
+        self.selection_mark_mode = false;
+        self.change_selections(None, cx, |s| {
+            s.move_with(|_, selection| selection.collapse_to(selection.head(), SelectionGoal::None))
+        });
+    }
+
+    pub fn kill_ring_yank(&mut self, _: &KillRingYank, cx: &mut ViewContext<Self>) {
+        if let Some(text) = KillRing::get(0, cx) {
+            self.insert_yank(&text, 0, cx);
+        }
+    }
+
+    /// Replaces the text inserted by the previous yank with an older entry
+    /// from the kill ring.
+    pub fn kill_ring_yank_pop(&mut self, _: &KillRingYankPop, cx: &mut ViewContext<Self>) {
+        let Some(yank) = self.last_yank.take() else {
+            return;
+        };
+        if yank.position != self.kill_ring_position(cx) {
+            return;
+        }
+        let index = yank.index + 1;
+        let Some(text) = KillRing::get(index, cx) else {
+            return;
+        };
+
+        self.transact(cx, |this, cx| {
+            this.change_selections(None, cx, |s| s.select_anchor_ranges(yank.ranges));
+            this.insert_yank(&text, index, cx);
+        });
+    }
+
+    fn insert_yank(&mut self, text: &str, index: usize, cx: &mut ViewContext<Self>) {
+        if self.read_only(cx) {
+            return;
+        }
+
+        // The inserted text is autoindented, so its length can differ from the
+        // yanked text's. Anchor its start before inserting it instead.
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let starts = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .map(|selection| snapshot.anchor_before(selection.start))
+            .collect::<Vec<_>>();
+        self.transact(cx, |this, cx| this.insert(text, cx));
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let ranges = starts
+            .into_iter()
+            .zip(self.selections.all::<usize>(cx))
+            .map(|(start, selection)| start..snapshot.anchor_after(selection.head()))
+            .collect();
+        self.selection_mark_mode = false;
+        self.last_yank = Some(YankState {
+            ranges,
+            index,
+            position: self.kill_ring_position(cx),
+        });
+    }
+
+    fn kill_ring_position(&self, cx: &mut ViewContext<Self>) -> KillRingPosition {
+        KillRingPosition {
+            edit_count: self.buffer.read(cx).snapshot(cx).edit_count(),
+            cursors: self
+                .selections
+                .all::<usize>(cx)
+                .iter()
+                .map(|selection| selection.head())
+                .collect(),
+        }
+    }
+
+    fn selections_text(&self, cx: &mut ViewContext<Self>) -> String {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        self.selections
+            .all::<usize>(cx)
+            .iter()
+            .map(|selection| {
+                buffer
+                    .text_for_range(selection.start..selection.end)
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
+
     pub fn move_to_start_of_paragraph(
         &mut self,
         _: &MoveToStartOfParagraph,
@@ -6630,6 +7073,57 @@ impl Editor {
         Ok(())
     }
 
+    pub fn select_regex_matches(
+        &mut self,
+        action: &SelectRegexMatches,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<usize> {
+        if action.pattern.is_empty() {
+            bail!("no pattern to select the matches of");
+        }
+        let regex = Regex::new(&action.pattern)?;
+
+        // Matches are searched for within the non-empty selections, or in the
+        // whole buffer if there are none.
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let mut search_ranges = self
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| selection.range())
+            .collect::<Vec<_>>();
+        if search_ranges.is_empty() {
+            search_ranges.push(0..buffer.len());
+        }
+
+        let mut new_selection_ranges = Vec::new();
+        for search_range in search_ranges {
+            let text = buffer
+                .text_for_range(search_range.clone())
+                .collect::<String>();
+            for regex_match in regex.find_iter(&text) {
+                if !regex_match.range().is_empty() {
+                    new_selection_ranges.push(
+                        search_range.start + regex_match.start()
+                            ..search_range.start + regex_match.end(),
+                    );
+                }
+            }
+        }
+        let match_count = new_selection_ranges.len();
+        if match_count == 0 {
+            return Ok(0);
+        }
+
+        self.push_to_selection_history();
+        self.unfold_ranges(new_selection_ranges.clone(), false, false, cx);
+        self.change_selections(Some(Autoscroll::fit()), cx, |selections| {
+            selections.select_ranges(new_selection_ranges)
+        });
+        Ok(match_count)
+    }
+
     pub fn select_next(&mut self, action: &SelectNext, cx: &mut ViewContext<Self>) -> Result<()> {
         self.push_to_selection_history();
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
@@ -6642,6 +7136,21 @@ impl Editor {
         Ok(())
     }
 
+    /// Deselects the newest selection and selects the next match instead,
+    /// skipping over the match that was selected last.
+    pub fn select_next_skip(
+        &mut self,
+        _: &SelectNextSkip,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.select_next(
+            &SelectNext {
+                replace_newest: true,
+            },
+            cx,
+        )
+    }
+
     pub fn select_previous(
         &mut self,
         action: &SelectPrevious,
@@ -8526,6 +9035,33 @@ impl Editor {
         wrap_guides
     }
 
+    /// Returns the indent guides to draw for the given buffer rows, with the
+    /// guide around the newest cursor marked active.
+    pub fn indent_guides(
+        &mut self,
+        rows: Range<u32>,
+        snapshot: &DisplaySnapshot,
+        cx: &AppContext,
+    ) -> Vec<IndentGuide> {
+        if self.show_indent_guides == Some(false) {
+            return Vec::new();
+        }
+
+        let cursor_row = self
+            .selections
+            .newest_anchor()
+            .head()
+            .to_point(&snapshot.buffer_snapshot)
+            .row;
+        indent_guides::indent_guides_in_range(
+            rows,
+            Some(cursor_row),
+            &snapshot.buffer_snapshot,
+            &mut self.indent_guide_cache,
+            cx,
+        )
+    }
+
     pub fn soft_wrap_mode(&self, cx: &AppContext) -> SoftWrap {
         let settings = self.buffer.read(cx).settings_at(0, cx);
         let mode = self
@@ -8596,6 +9132,11 @@ impl Editor {
         cx.notify();
     }
 
+    pub fn set_show_indent_guides(&mut self, show_indent_guides: bool, cx: &mut ViewContext<Self>) {
+        self.show_indent_guides = Some(show_indent_guides);
+        cx.notify();
+    }
+
     pub fn reveal_in_finder(&mut self, _: &RevealInFinder, cx: &mut ViewContext<Self>) {
         if let Some(buffer) = self.buffer().read(cx).as_singleton() {
             if let Some(file) = buffer.read(cx).file().and_then(|f| f.as_local()) {
@@ -8818,6 +9359,13 @@ impl Editor {
             })
     }
 
+    /// The ranges of the matches that the buffer search bar has highlighted.
+    pub fn search_matches(&self) -> &[Range<Anchor>] {
+        self.background_highlights
+            .get(&TypeId::of::<items::BufferSearchHighlights>())
+            .map_or(&[], |(_, ranges)| ranges.as_slice())
+    }
+
     pub fn has_background_highlights<T: 'static>(&self) -> bool {
         self.background_highlights
             .get(&TypeId::of::<T>())
@@ -9023,6 +9571,7 @@ impl Editor {
             } => {
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
+                refresh_rainbow_brackets(self, cx);
                 if self.has_active_copilot_suggestion(cx) {
                     self.update_visible_copilot_suggestion(cx);
                 }
@@ -9073,13 +9622,22 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.rainbow_brackets.take();
+                refresh_rainbow_brackets(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                self.rainbow_brackets.take();
+                refresh_rainbow_brackets(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
-            multi_buffer::Event::Reparsed => cx.emit(EditorEvent::Reparsed),
+            multi_buffer::Event::Reparsed => {
+                refresh_rainbow_brackets(self, cx);
+                cx.emit(EditorEvent::Reparsed);
+            }
             multi_buffer::Event::LanguageChanged => {
+                self.rainbow_brackets.take();
+                refresh_rainbow_brackets(self, cx);
                 cx.emit(EditorEvent::Reparsed);
                 cx.notify();
             }
@@ -9098,6 +9656,8 @@ impl Editor {
     }
 
     fn on_display_map_changed(&mut self, _: Model<DisplayMap>, cx: &mut ViewContext<Self>) {
+        // Folds and soft wraps can bring rows into view without scrolling.
+        refresh_rainbow_brackets(self, cx);
         cx.notify();
     }
 
@@ -9114,6 +9674,10 @@ impl Editor {
         let editor_settings = EditorSettings::get_global(cx);
         self.scroll_manager.vertical_scroll_margin = editor_settings.vertical_scroll_margin;
         self.show_breadcrumbs = editor_settings.toolbar.breadcrumbs;
+        self.rainbow_brackets.take();
+        refresh_rainbow_brackets(self, cx);
+        // Headers are highlighted with the theme, which is also a setting.
+        self.sticky_header_cache = Default::default();
         cx.notify();
     }
 
@@ -13,6 +13,8 @@ pub struct EditorSettings {
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub minimap: Minimap,
+    pub sticky_scroll: StickyScroll,
     pub vertical_scroll_margin: f32,
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
@@ -53,6 +55,18 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Minimap {
+    pub enabled: bool,
+    pub max_column: u32,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -107,6 +121,10 @@ pub struct EditorSettingsContent {
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
 
     /// The number of lines to keep above/below the cursor when auto-scrolling.
     ///
@@ -184,6 +202,33 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct MinimapContent {
+    /// Whether to show a downscaled view of the buffer next to the scrollbar.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The most columns of each line to show in the minimap.
+    ///
+    /// Default: 120
+    pub max_column: Option<u32>,
+}
+
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the items enclosing the top of the editor (such as the
+    /// module, impl and function) above the text.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The most enclosing items to pin.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
 impl Settings for EditorSettings {
     const KEY: Option<&'static str> = None;
 
@@ -1570,6 +1570,97 @@ async fn test_autoscroll(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_sticky_headers(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_outline_query(
+            r#"
+            (mod_item
+                "mod" @context
+                name: (_) @name) @item
+            (function_item
+                "fn" @context
+                name: (_) @name) @item
+            "#,
+        )
+        .unwrap(),
+    );
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.set_state(indoc! {"
+        ˇmod a {
+            fn b() {
+                one();
+                two();
+                three();
+            }
+        }
+    "});
+    cx.executor().run_until_parked();
+
+    let headers_at = |row: f32, cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, cx| {
+            editor.set_scroll_position(gpui::Point::new(0., row), cx);
+            let snapshot = editor.snapshot(cx);
+            editor
+                .sticky_headers(&snapshot, None, cx)
+                .into_iter()
+                .map(|item| item.text)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert!(headers_at(0., &mut cx).is_empty());
+    // Once `mod a` is pinned it covers the first line of `fn b`, which is
+    // pinned below it.
+    assert_eq!(headers_at(1., &mut cx), &["mod a", "fn b"]);
+    assert_eq!(headers_at(3., &mut cx), &["mod a", "fn b"]);
+
+    // Headers are looked up again once the buffer changes.
+    cx.set_state(indoc! {"
+        ˇmod a {
+            fn c() {
+                one();
+                two();
+                three();
+            }
+        }
+    "});
+    cx.executor().run_until_parked();
+    assert_eq!(headers_at(3., &mut cx), &["mod a", "fn c"]);
+
+    _ = cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.sticky_scroll = Some(editor_settings::StickyScrollContent {
+                    enabled: None,
+                    max_depth: Some(1),
+                });
+            });
+        })
+    });
+    assert_eq!(headers_at(3., &mut cx), &["mod a"]);
+
+    _ = cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.sticky_scroll = Some(editor_settings::StickyScrollContent {
+                    enabled: Some(false),
+                    max_depth: None,
+                });
+            });
+        })
+    });
+    assert!(headers_at(3., &mut cx).is_empty());
+}
+
 #[gpui::test]
 async fn test_move_page_up_page_down(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -2141,6 +2232,155 @@ async fn test_tab_with_mixed_whitespace(cx: &mut gpui::TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_indent_guides(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.tab_size = NonZeroU32::new(4);
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let indent_guides = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let max_row = snapshot.buffer_snapshot.max_buffer_row();
+            editor
+                .indent_guides(0..max_row + 1, &snapshot, cx)
+                .into_iter()
+                .map(|guide| (guide.rows, guide.column, guide.active))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // Blank lines continue the guides around them, and hard tabs count as a
+    // full level of indentation.
+    cx.set_state(indoc! {"
+        fn a() {
+            if b {
+                ˇc();
+
+                d();
+            }
+
+            e();
+        }
+        fn f() {
+        	g();
+        }
+    "});
+    assert_eq!(
+        indent_guides(&mut cx),
+        &[(1..8, 0, false), (2..5, 4, true), (10..11, 0, false)]
+    );
+
+    // On a line that opens a block, that block's guide is highlighted.
+    cx.set_state(indoc! {"
+        fn a() {
+            ˇif b {
+                c();
+            }
+        }
+    "});
+    assert_eq!(indent_guides(&mut cx), &[(1..4, 0, false), (2..3, 4, true)]);
+
+    // Only the lines in the requested range are considered.
+    let guides = cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        editor.indent_guides(2..4, &snapshot, cx)
+    });
+    assert_eq!(
+        guides
+            .into_iter()
+            .map(|guide| (guide.rows, guide.column))
+            .collect::<Vec<_>>(),
+        &[(2..4, 0), (2..3, 4)]
+    );
+
+    update_test_language_settings(&mut cx, |settings| {
+        settings.defaults.show_indent_guides = Some(false);
+    });
+    assert!(indent_guides(&mut cx).is_empty());
+}
+
+#[gpui::test]
+fn test_indent_guides_in_multibuffer(cx: &mut TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.tab_size = NonZeroU32::new(4);
+        settings.languages.insert(
+            "Rust".into(),
+            LanguageSettingsContent {
+                show_indent_guides: Some(false),
+                ..Default::default()
+            },
+        );
+    });
+
+    let toml_language = Arc::new(Language::new(
+        LanguageConfig {
+            name: "TOML".into(),
+            ..Default::default()
+        },
+        None,
+    ));
+    let rust_language = Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            ..Default::default()
+        },
+        None,
+    ));
+    let rust_buffer = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "fn a() {\n    b();\n}\n",
+        )
+        .with_language(rust_language, cx)
+    });
+    let toml_buffer = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "[a]\n    b = 1\n",
+        )
+        .with_language(toml_language, cx)
+    });
+    let multibuffer = cx.new_model(|cx| {
+        let mut multibuffer = MultiBuffer::new(0, ReadWrite);
+        multibuffer.push_excerpts(
+            rust_buffer.clone(),
+            [ExcerptRange {
+                context: Point::new(0, 0)..Point::new(3, 0),
+                primary: None,
+            }],
+            cx,
+        );
+        multibuffer.push_excerpts(
+            toml_buffer.clone(),
+            [ExcerptRange {
+                context: Point::new(0, 0)..Point::new(2, 0),
+                primary: None,
+            }],
+            cx,
+        );
+        multibuffer
+    });
+
+    // Each excerpt follows the settings of its own language, so only the
+    // TOML excerpt gets a guide.
+    cx.add_window(|cx| {
+        let mut editor = build_editor(multibuffer, cx);
+        let snapshot = editor.snapshot(cx);
+        let max_row = snapshot.buffer_snapshot.max_buffer_row();
+        let guides = editor
+            .indent_guides(0..max_row + 1, &snapshot, cx)
+            .into_iter()
+            .map(|guide| (guide.rows, guide.column))
+            .collect::<Vec<_>>();
+        assert_eq!(guides, &[(5..6, 0)]);
+        editor
+    });
+}
+
 #[gpui::test]
 async fn test_indent_outdent(cx: &mut gpui::TestAppContext) {
     init_test(cx, |settings| {
@@ -3460,6 +3700,104 @@ async fn test_clipboard(cx: &mut gpui::TestAppContext) {
         tˇhe lazy dog"});
 }
 
+#[gpui::test]
+async fn test_kill_ring(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    // Killing at the end of a line kills the newline, and consecutive kills are joined.
+    cx.set_state(indoc! {"
+        one ˇtwo
+        three
+        four"});
+    cx.update_editor(|e, cx| {
+        e.kill_ring_cut(&KillRingCut, cx);
+        e.kill_ring_cut(&KillRingCut, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        one ˇthree
+        four"});
+    cx.update_editor(|e, cx| {
+        e.move_to_end(&MoveToEnd, cx);
+        e.kill_ring_yank(&KillRingYank, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        one three
+        fourtwo
+        ˇ"});
+
+    // Yank-pop replaces the yanked text with older kills, wrapping around.
+    cx.set_state("ˇa b");
+    cx.update_editor(|e, cx| e.kill_ring_cut(&KillRingCut, cx));
+    cx.assert_editor_state("ˇ");
+    cx.update_editor(|e, cx| e.kill_ring_yank(&KillRingYank, cx));
+    cx.assert_editor_state("a bˇ");
+    cx.update_editor(|e, cx| e.kill_ring_yank_pop(&KillRingYankPop, cx));
+    cx.assert_editor_state("two\nˇ");
+    cx.update_editor(|e, cx| e.kill_ring_yank_pop(&KillRingYankPop, cx));
+    cx.assert_editor_state("a bˇ");
+
+    // Yank-pop does nothing once the cursor has moved.
+    cx.update_editor(|e, cx| {
+        e.move_left(&MoveLeft, cx);
+        e.kill_ring_yank_pop(&KillRingYankPop, cx);
+    });
+    cx.assert_editor_state("a ˇb");
+
+    // The mark can be swapped with the cursor, and the region saved without killing it.
+    cx.set_state("one ˇtwo three");
+    cx.update_editor(|e, cx| {
+        e.set_mark(&SetMark, cx);
+        e.select_to_next_word_end(&SelectToNextWordEnd, cx);
+        e.swap_selection_ends(&SwapSelectionEnds, cx);
+    });
+    cx.assert_editor_state("one «ˇtwo» three");
+    cx.update_editor(|e, cx| e.kill_ring_save(&KillRingSave, cx));
+    cx.assert_editor_state("one ˇtwo three");
+    cx.update_editor(|e, cx| {
+        e.move_to_end(&MoveToEnd, cx);
+        e.kill_ring_yank(&KillRingYank, cx);
+    });
+    cx.assert_editor_state("one two threetwoˇ");
+}
+
+#[gpui::test]
+async fn test_kill_ring_yank_pop_autoindented(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let language = Arc::new(Language::new(
+        LanguageConfig::default(),
+        Some(tree_sitter_rust::language()),
+    ));
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+
+    cx.set_state("«oneˇ»");
+    cx.update_editor(|e, cx| e.kill_ring_save(&KillRingSave, cx));
+    cx.set_state("«a(\nb\n)ˇ»");
+    cx.update_editor(|e, cx| e.kill_ring_save(&KillRingSave, cx));
+
+    // The yanked lines are indented along with the line they're yanked into,
+    // and yank-pop replaces them entirely.
+    cx.set_state(indoc! {"
+        fn f() {
+            ˇ
+        }"});
+    cx.update_editor(|e, cx| e.kill_ring_yank(&KillRingYank, cx));
+    cx.assert_editor_state(indoc! {"
+        fn f() {
+            a(
+            b
+            )ˇ
+        }"});
+    cx.update_editor(|e, cx| e.kill_ring_yank_pop(&KillRingYankPop, cx));
+    cx.assert_editor_state(indoc! {"
+        fn f() {
+            oneˇ
+        }"});
+}
+
 #[gpui::test]
 async fn test_paste_multiline(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -4004,6 +4342,153 @@ async fn test_select_all_matches(cx: &mut gpui::TestAppContext) {
     cx.assert_editor_state("«abcˇ»\n«abcˇ» «abcˇ»\ndefabc\n«abcˇ»");
 }
 
+#[gpui::test]
+async fn test_select_next_skip(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("abc\nˇabc abc\ndefabc\nabc");
+
+    cx.update_editor(|e, cx| e.select_next(&SelectNext::default(), cx))
+        .unwrap();
+    cx.update_editor(|e, cx| e.select_next(&SelectNext::default(), cx))
+        .unwrap();
+    cx.assert_editor_state("abc\n«abcˇ» «abcˇ»\ndefabc\nabc");
+
+    cx.update_editor(|e, cx| e.select_next_skip(&SelectNextSkip, cx))
+        .unwrap();
+    cx.assert_editor_state("abc\n«abcˇ» abc\ndefabc\n«abcˇ»");
+
+    cx.update_editor(|e, cx| e.select_next_skip(&SelectNextSkip, cx))
+        .unwrap();
+    cx.assert_editor_state("«abcˇ»\n«abcˇ» abc\ndefabc\nabc");
+}
+
+#[gpui::test]
+async fn test_select_regex_matches(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    let select_regex_matches = |pattern: &str| SelectRegexMatches {
+        pattern: pattern.to_string(),
+    };
+
+    // Matches are only selected within the non-empty selections.
+    cx.set_state("«let a = 1;\nlet bb = 22;ˇ»\nlet c = 3;");
+    let match_count = cx
+        .update_editor(|e, cx| e.select_regex_matches(&select_regex_matches(r"\d+"), cx))
+        .unwrap();
+    assert_eq!(match_count, 2);
+    cx.assert_editor_state("let a = «1ˇ»;\nlet bb = «22ˇ»;\nlet c = 3;");
+
+    // Without a non-empty selection, the whole buffer is searched.
+    cx.set_state("ˇfoo1 bar2");
+    cx.update_editor(|e, cx| e.select_regex_matches(&select_regex_matches("[a-z]+"), cx))
+        .unwrap();
+    cx.assert_editor_state("«fooˇ»1 «barˇ»2");
+
+    // Selections are unchanged when nothing matches.
+    let match_count = cx
+        .update_editor(|e, cx| e.select_regex_matches(&select_regex_matches("x"), cx))
+        .unwrap();
+    assert_eq!(match_count, 0);
+    cx.assert_editor_state("«fooˇ»1 «barˇ»2");
+
+    // Invalid and empty patterns are reported as errors.
+    for pattern in ["(", ""] {
+        assert!(cx
+            .update_editor(|e, cx| e.select_regex_matches(&select_regex_matches(pattern), cx))
+            .is_err());
+    }
+}
+
+#[gpui::test]
+async fn test_insert_numbers(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state("ˇa\nˇb\nˇc");
+    cx.update_editor(|e, cx| e.insert_numbers(&InsertNumbers::default(), cx));
+    cx.assert_editor_state("1ˇa\n2ˇb\n3ˇc");
+
+    // Selected text is replaced by the numbers.
+    cx.set_state("«xˇ» «yyˇ» «zˇ»");
+    cx.update_editor(|e, cx| {
+        e.insert_numbers(
+            &InsertNumbers {
+                start: 10,
+                step: -5,
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state("10ˇ 5ˇ 0ˇ");
+
+    cx.update_editor(|e, cx| e.undo(&Undo, cx));
+    cx.assert_editor_state("«xˇ» «yyˇ» «zˇ»");
+}
+
+#[gpui::test]
+async fn test_align_selections(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        let aˇ = 1;
+        let bbbˇ = 2;
+        let ccˇ = 3;
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections, cx));
+    cx.assert_editor_state(indoc! {"
+        let a  ˇ = 1;
+        let bbbˇ = 2;
+        let cc ˇ = 3;
+    "});
+
+    // Multiple selections on a row are aligned with the ones at the same
+    // position on the other rows.
+    cx.set_state(indoc! {"
+        aˇ bbˇ c
+        aaaˇ bˇ c
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections, cx));
+    cx.assert_editor_state(indoc! {"
+        a  ˇ bbˇ c
+        aaaˇ b ˇ c
+    "});
+
+    // Columns are counted in characters rather than bytes.
+    cx.set_state(indoc! {"
+        éˇ = 1
+        abˇ = 2
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections, cx));
+    cx.assert_editor_state(indoc! {"
+        é ˇ = 1
+        abˇ = 2
+    "});
+}
+
+#[gpui::test]
+async fn test_rotate_selections(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state("«aˇ» «bbˇ» «cccˇ»");
+    cx.update_editor(|e, cx| e.rotate_selections_forward(&RotateSelectionsForward, cx));
+    cx.assert_editor_state("«cccˇ» «aˇ» «bbˇ»");
+
+    cx.update_editor(|e, cx| e.rotate_selections_backward(&RotateSelectionsBackward, cx));
+    cx.assert_editor_state("«aˇ» «bbˇ» «cccˇ»");
+
+    cx.set_state("«aˇ»\n«bˇ»\nˇc");
+    cx.update_editor(|e, cx| e.rotate_selections_backward(&RotateSelectionsBackward, cx));
+    cx.assert_editor_state("«bˇ»\nˇ\n«aˇ»c");
+}
+
 #[gpui::test]
 async fn test_select_next_with_multiple_carets(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -5229,6 +5714,48 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_choices(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges(
+        indoc! {"
+            ˇ
+            ˇ
+        "},
+        false,
+    );
+
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    _ = editor.update(cx, |editor, cx| {
+        let snippet = Snippet::parse("let ${1|x,y|}: ${2|i32,u8|} = $1;").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+        assert_eq!(editor.text(cx), "let x: i32 = x;\nlet x: i32 = x;\n");
+
+        // The first tabstop's choices are offered right away, and picking one
+        // replaces every occurrence of the tabstop.
+        assert!(editor.context_menu_visible());
+        editor
+            .confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx)
+            .unwrap()
+            .detach();
+        assert_eq!(editor.text(cx), "let y: i32 = y;\nlet y: i32 = y;\n");
+        assert!(!editor.context_menu_visible());
+
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert!(editor.context_menu_visible());
+        editor
+            .confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx)
+            .unwrap()
+            .detach();
+        assert_eq!(editor.text(cx), "let y: u8 = y;\nlet y: u8 = y;\n");
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -25,8 +25,8 @@ use gpui::{
     DispatchPhase, Edges, Element, ElementInputHandler, Entity, Hsla, InteractiveBounds,
     InteractiveElement, IntoElement, ModifiersChangedEvent, MouseButton, MouseDownEvent,
     MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, ScrollDelta, ScrollWheelEvent, ShapedLine,
-    SharedString, Size, StackingOrder, StatefulInteractiveElement, Style, Styled, TextRun,
-    TextStyle, View, ViewContext, WindowContext,
+    SharedString, Size, StackingOrder, StatefulInteractiveElement, Style, Styled, StyledText,
+    TextRun, TextStyle, View, ViewContext, WindowContext,
 };
 use itertools::Itertools;
 use language::language_settings::ShowWhitespaceSetting;
@@ -177,6 +177,12 @@ impl EditorElement {
         register_action(view, cx, Editor::delete_to_beginning_of_line);
         register_action(view, cx, Editor::delete_to_end_of_line);
         register_action(view, cx, Editor::cut_to_end_of_line);
+        register_action(view, cx, Editor::set_mark);
+        register_action(view, cx, Editor::swap_selection_ends);
+        register_action(view, cx, Editor::kill_ring_cut);
+        register_action(view, cx, Editor::kill_ring_save);
+        register_action(view, cx, Editor::kill_ring_yank);
+        register_action(view, cx, Editor::kill_ring_yank_pop);
         register_action(view, cx, Editor::duplicate_line);
         register_action(view, cx, Editor::move_line_up);
         register_action(view, cx, Editor::move_line_down);
@@ -248,6 +254,16 @@ impl EditorElement {
         register_action(view, cx, |editor, action, cx| {
             editor.select_previous(action, cx).log_err();
         });
+        register_action(view, cx, |editor, action, cx| {
+            editor.select_next_skip(action, cx).log_err();
+        });
+        register_action(view, cx, |editor, action, cx| {
+            editor.select_regex_matches(action, cx).log_err();
+        });
+        register_action(view, cx, Editor::align_selections);
+        register_action(view, cx, Editor::insert_numbers);
+        register_action(view, cx, Editor::rotate_selections_forward);
+        register_action(view, cx, Editor::rotate_selections_backward);
         register_action(view, cx, Editor::toggle_comments);
         register_action(view, cx, Editor::select_larger_syntax_node);
         register_action(view, cx, Editor::select_smaller_syntax_node);
@@ -680,6 +696,41 @@ impl EditorElement {
             let scroll_left =
                 layout.position_map.snapshot.scroll_position().x * layout.position_map.em_width;
 
+            for guide in &layout.indent_guides {
+                let x = text_bounds.origin.x + layout.gutter_dimensions.margin + guide.offset
+                    - scroll_left;
+                if x < text_bounds.origin.x {
+                    continue;
+                }
+
+                let line_height = layout.position_map.line_height;
+                let top = cmp::max(
+                    text_bounds.origin.y + line_height * guide.display_rows.start as f32
+                        - scroll_top,
+                    text_bounds.top(),
+                );
+                let bottom = cmp::min(
+                    text_bounds.origin.y + line_height * guide.display_rows.end as f32 - scroll_top,
+                    text_bounds.bottom(),
+                );
+                if bottom <= top {
+                    continue;
+                }
+
+                let color = if guide.active {
+                    cx.theme().colors().editor_indent_guide_active
+                } else {
+                    cx.theme().colors().editor_indent_guide
+                };
+                cx.paint_quad(fill(
+                    Bounds {
+                        origin: point(x, top),
+                        size: size(px(1.), bottom - top),
+                    },
+                    color,
+                ));
+            }
+
             for (wrap_position, active) in layout.wrap_guides.iter() {
                 let x = (text_bounds.origin.x + *wrap_position + layout.position_map.em_width / 2.)
                     - scroll_left;
@@ -1651,6 +1702,191 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut LayoutState, cx: &mut ElementContext) {
+        let Some(minimap) = layout.minimap.take() else {
+            return;
+        };
+
+        let bounds = minimap.bounds;
+        let line_height = minimap.line_height;
+        let start_row = minimap.start_row;
+        let end_row = start_row + minimap.lines.len() as u32;
+        let y_for_row = |row: f32| bounds.origin.y + (row - start_row as f32) * line_height;
+        let snapshot = &layout.position_map.snapshot;
+        let marker_width = px(2.);
+
+        cx.with_content_mask(Some(ContentMask { bounds }), |cx| {
+            cx.paint_quad(quad(
+                bounds,
+                Corners::default(),
+                self.style.background,
+                Edges {
+                    top: Pixels::ZERO,
+                    right: Pixels::ZERO,
+                    bottom: Pixels::ZERO,
+                    left: px(1.),
+                },
+                cx.theme().colors().border_variant,
+            ));
+
+            for (ix, line) in minimap.lines.iter().enumerate() {
+                let origin = point(
+                    bounds.origin.x + marker_width * 2.,
+                    y_for_row((start_row + ix as u32) as f32),
+                );
+                line.line.paint(origin, line_height, cx).log_err();
+            }
+
+            let start = DisplayPoint::new(start_row, 0).to_point(snapshot);
+            let end = if end_row > snapshot.max_point().row() {
+                snapshot.buffer_snapshot.max_point()
+            } else {
+                DisplayPoint::new(end_row, 0).to_point(snapshot)
+            };
+            let marker_bounds = |start_row: u32, end_row: u32, x: Pixels| {
+                let start_y = y_for_row(start_row as f32);
+                let end_y = cmp::max(y_for_row(end_row as f32), start_y + px(1.));
+                Bounds::from_corners(point(x, start_y), point(x + marker_width, end_y))
+            };
+
+            for hunk in snapshot
+                .buffer_snapshot
+                .git_diff_hunks_in_range(start.row..end.row + 1)
+            {
+                let start_display = Point::new(hunk.buffer_range.start, 0)
+                    .to_display_point(&snapshot.display_snapshot);
+                let end_display = Point::new(hunk.buffer_range.end, 0)
+                    .to_display_point(&snapshot.display_snapshot);
+                let end_row = if hunk.buffer_range.start == hunk.buffer_range.end {
+                    end_display.row() + 1
+                } else {
+                    end_display.row()
+                };
+                let color = match hunk.status() {
+                    DiffHunkStatus::Added => cx.theme().status().created,
+                    DiffHunkStatus::Modified => cx.theme().status().modified,
+                    DiffHunkStatus::Removed => cx.theme().status().deleted,
+                };
+                cx.paint_quad(fill(
+                    marker_bounds(start_display.row(), end_row, bounds.origin.x + px(1.)),
+                    color,
+                ));
+            }
+
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<_, Point>(start..end, false)
+                // We want to sort by severity, in order to paint the most severe diagnostics last.
+                .sorted_by_key(|diagnostic| std::cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let start_display = diagnostic
+                    .range
+                    .start
+                    .to_display_point(&snapshot.display_snapshot);
+                let end_display = diagnostic
+                    .range
+                    .end
+                    .to_display_point(&snapshot.display_snapshot);
+                let color = match diagnostic.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => cx.theme().status().error,
+                    DiagnosticSeverity::WARNING => cx.theme().status().warning,
+                    DiagnosticSeverity::INFORMATION => cx.theme().status().info,
+                    _ => cx.theme().status().hint,
+                };
+                cx.paint_quad(fill(
+                    marker_bounds(
+                        start_display.row(),
+                        end_display.row() + 1,
+                        bounds.origin.x + px(1.) + marker_width,
+                    ),
+                    color,
+                ));
+            }
+
+            let viewport = layout.scrollbar_row_range.clone();
+            cx.paint_quad(fill(
+                Bounds::from_corners(
+                    point(bounds.origin.x, y_for_row(viewport.start)),
+                    point(bounds.upper_right().x, y_for_row(viewport.end)),
+                ),
+                cx.theme().colors().scrollbar_thumb_background,
+            ));
+        });
+
+        let interactive_bounds = InteractiveBounds {
+            bounds,
+            stacking_order: cx.stacking_order().clone(),
+        };
+        if bounds.contains(&cx.mouse_position()) {
+            cx.set_cursor_style(
+                CursorStyle::Arrow,
+                interactive_bounds.stacking_order.clone(),
+            );
+        }
+
+        // Clicking centers the viewport on the clicked line, and dragging
+        // moves it along with the mouse.
+        let visible_rows = layout.scrollbar_row_range.end - layout.scrollbar_row_range.start;
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            move |event: &MouseDownEvent, phase, cx| {
+                if phase == DispatchPhase::Capture
+                    || event.button != MouseButton::Left
+                    || !interactive_bounds.visibly_contains(&event.position, cx)
+                {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    let row = start_row as f32 + (event.position.y - bounds.origin.y) / line_height;
+                    let mut position = editor.scroll_position(cx);
+                    position.y = (row - visible_rows / 2.).max(0.);
+                    editor.set_scroll_position(position, cx);
+                    editor.scroll_manager.set_is_dragging_minimap(true, cx);
+                });
+                cx.stop_propagation();
+            }
+        });
+
+        if self.editor.read(cx).scroll_manager.is_dragging_minimap() {
+            let mut mouse_position = cx.mouse_position();
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseMoveEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            let mut position = editor.scroll_position(cx);
+                            position.y += (event.position.y - mouse_position.y) / line_height;
+                            position.y = position.y.max(0.);
+                            editor.set_scroll_position(position, cx);
+                            mouse_position = event.position;
+                            cx.stop_propagation();
+                        } else {
+                            editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        }
+                    });
+                }
+            });
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn paint_highlighted_range(
         &self,
@@ -2001,7 +2237,16 @@ impl EditorElement {
 
             editor.gutter_width = gutter_dimensions.width;
 
-            let text_width = bounds.size.width - gutter_dimensions.width;
+            let minimap_settings = EditorSettings::get_global(cx).minimap;
+            let minimap_width = if minimap_settings.enabled && snapshot.mode == EditorMode::Full {
+                let content_width =
+                    em_advance * MINIMAP_SCALE * minimap_settings.max_column as f32;
+                (content_width + self.style.scrollbar_width).min(bounds.size.width / 4.)
+            } else {
+                Pixels::ZERO
+            };
+
+            let text_width = bounds.size.width - gutter_dimensions.width - minimap_width;
             let overscroll = size(em_width, px(0.));
             let _snapshot = {
                 editor.set_visible_line_count((bounds.size.height / line_height).into(), cx);
@@ -2071,6 +2316,30 @@ impl EditorElement {
 
             let redacted_ranges = editor.redacted_ranges(start_anchor..end_anchor, &snapshot.display_snapshot, cx);
 
+            let indent_guides = if snapshot.mode == EditorMode::Full {
+                let start_buffer_row = DisplayPoint::new(start_row, 0).to_point(&snapshot).row;
+                let end_buffer_row =
+                    DisplayPoint::new(cmp::min(end_row, max_row), 0).to_point(&snapshot).row + 1;
+                editor
+                    .indent_guides(start_buffer_row..end_buffer_row, &snapshot, cx)
+                    .into_iter()
+                    .map(|guide| {
+                        let last_row = guide.rows.end - 1;
+                        let end = Point::new(last_row, snapshot.buffer_snapshot.line_len(last_row));
+                        IndentGuideLayout {
+                            display_rows: Point::new(guide.rows.start, 0)
+                                .to_display_point(&snapshot)
+                                .row()
+                                ..end.to_display_point(&snapshot).row() + 1,
+                            offset: em_advance * guide.column as f32,
+                            active: guide.active,
+                        }
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
             let mut newest_selection_head = None;
 
             if editor.show_local_selections {
@@ -2342,6 +2611,29 @@ impl EditorElement {
                 )
             };
 
+            let minimap = (minimap_width > Pixels::ZERO).then(|| {
+                self.layout_minimap(
+                    &snapshot,
+                    bounds,
+                    minimap_width,
+                    height_in_lines,
+                    minimap_settings.max_column,
+                    font_size,
+                    line_height,
+                    cx,
+                )
+            });
+
+            let sticky_headers = self.layout_sticky_headers(
+                &snapshot,
+                bounds.size.width,
+                gutter_dimensions.width + gutter_dimensions.margin,
+                em_advance,
+                line_height,
+                editor,
+                cx,
+            );
+
             let editor_view = cx.view().clone();
             let fold_indicators = if gutter_settings.folds {
                 cx.with_element_context(|cx| {
@@ -2410,6 +2702,7 @@ impl EditorElement {
                 visible_anchor_range: start_anchor..end_anchor,
                 visible_display_row_range: start_row..end_row,
                 wrap_guides,
+                indent_guides,
                 gutter_size,
                 gutter_dimensions,
                 text_size,
@@ -2427,6 +2720,8 @@ impl EditorElement {
                 selections,
                 context_menu,
                 code_actions_indicator,
+                sticky_headers,
+                minimap,
                 fold_indicators,
                 tab_invisible,
                 space_invisible,
@@ -2435,6 +2730,107 @@ impl EditorElement {
         })
     }
 
+    /// Lays out the minimap: the display rows around the viewport, shaped
+    /// with their syntax colors at a fraction of the editor's font size. Once
+    /// the buffer is too long to fit, the minimap scrolls in proportion to
+    /// the editor.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        width: Pixels,
+        visible_rows: f32,
+        max_column: u32,
+        font_size: Pixels,
+        line_height: Pixels,
+        cx: &mut ViewContext<Editor>,
+    ) -> MinimapLayout {
+        let minimap_bounds = Bounds::from_corners(
+            point(bounds.upper_right().x - width, bounds.origin.y),
+            bounds.lower_right(),
+        );
+        let line_height = line_height * MINIMAP_SCALE;
+        let row_count = snapshot.max_point().row() + 1;
+        let minimap_rows = (bounds.size.height / line_height).ceil() as u32;
+        let start_row = if row_count <= minimap_rows {
+            0
+        } else {
+            let max_scroll = (row_count as f32 - visible_rows).max(1.);
+            let progress = (snapshot.scroll_position().y / max_scroll).clamp(0., 1.);
+            (progress * (row_count - minimap_rows) as f32) as u32
+        };
+        let rows = start_row..cmp::min(start_row + minimap_rows, row_count);
+
+        let mut text_style = self.style.text.clone();
+        text_style.font_size = (font_size * MINIMAP_SCALE).into();
+        let lines = LineWithInvisibles::from_chunks(
+            snapshot.highlighted_chunks(rows.clone(), true, &self.style),
+            &text_style,
+            max_column as usize,
+            rows.len(),
+            &[],
+            snapshot.mode,
+            cx,
+        );
+
+        MinimapLayout {
+            bounds: minimap_bounds,
+            line_height,
+            start_row,
+            lines,
+        }
+    }
+
+    /// Lays out the items enclosing the top of the editor, one per line, to
+    /// be drawn over the first lines of text.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        width: Pixels,
+        text_x: Pixels,
+        em_advance: Pixels,
+        line_height: Pixels,
+        editor: &mut Editor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<AnyElement> {
+        let headers = editor.sticky_headers(snapshot, Some(&cx.theme().syntax()), cx);
+        if headers.is_empty() {
+            return None;
+        }
+
+        let hover_background = cx.theme().colors().element_hover;
+        let rows = headers.into_iter().enumerate().map(|(ix, header)| {
+            let position = header.range.start;
+            let column = position.to_point(&snapshot.buffer_snapshot).column;
+            h_flex()
+                .id(("sticky header", ix))
+                .h(line_height)
+                .pl(text_x + em_advance * column as f32)
+                .cursor_pointer()
+                .hover(|style| style.bg(hover_background))
+                .child(
+                    StyledText::new(header.text)
+                        .with_highlights(&self.style.text, header.highlight_ranges),
+                )
+                .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
+                .on_click(cx.listener_for(&self.editor, move |editor, _, cx| {
+                    editor.jump_to_sticky_header(position, cx);
+                }))
+        });
+
+        Some(
+            v_flex()
+                .w(width)
+                .bg(self.style.background)
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .children(rows)
+                .into_any(),
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_blocks(
         &self,
@@ -3161,6 +3557,20 @@ impl Element for EditorElement {
                                 })
                             }
 
+                            if layout.minimap.is_some() {
+                                cx.with_z_index(1, |cx| self.paint_minimap(&mut layout, cx));
+                            }
+
+                            if let Some(mut sticky_headers) = layout.sticky_headers.take() {
+                                cx.with_z_index(1, |cx| {
+                                    let available_space = size(
+                                        AvailableSpace::Definite(bounds.size.width),
+                                        AvailableSpace::MinContent,
+                                    );
+                                    sticky_headers.draw(bounds.origin, available_space, cx);
+                                });
+                            }
+
                             cx.with_z_index(1, |cx| {
                                 self.paint_overlays(text_bounds, &mut layout, cx);
                             });
@@ -3188,6 +3598,9 @@ impl IntoElement for EditorElement {
 
 type BufferRow = u32;
 
+/// The size of the minimap's text relative to the editor's.
+const MINIMAP_SCALE: f32 = 0.15;
+
 pub struct LayoutState {
     position_map: Arc<PositionMap>,
     gutter_size: Size<Pixels>,
@@ -3195,6 +3608,7 @@ pub struct LayoutState {
     text_size: gpui::Size<Pixels>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
+    indent_guides: Vec<IndentGuideLayout>,
     visible_anchor_range: Range<Anchor>,
     visible_display_row_range: Range<u32>,
     active_rows: BTreeMap<u32, bool>,
@@ -3211,6 +3625,8 @@ pub struct LayoutState {
     max_row: u32,
     context_menu: Option<(DisplayPoint, AnyElement)>,
     code_actions_indicator: Option<CodeActionsIndicator>,
+    sticky_headers: Option<AnyElement>,
+    minimap: Option<MinimapLayout>,
     hover_popovers: Option<(DisplayPoint, Vec<AnyElement>)>,
     fold_indicators: Vec<Option<IconButton>>,
     tab_invisible: ShapedLine,
@@ -3223,6 +3639,19 @@ impl LayoutState {
     }
 }
 
+struct MinimapLayout {
+    bounds: Bounds<Pixels>,
+    line_height: Pixels,
+    start_row: u32,
+    lines: Vec<LineWithInvisibles>,
+}
+
+struct IndentGuideLayout {
+    display_rows: Range<u32>,
+    offset: Pixels,
+    active: bool,
+}
+
 struct CodeActionsIndicator {
     row: u32,
     button: IconButton,
@@ -3602,6 +4031,7 @@ mod tests {
     use gpui::TestAppContext;
     use language::language_settings;
     use log::info;
+    use settings::SettingsStore;
     use std::{num::NonZeroU32, sync::Arc};
     use util::test::sample_text;
 
@@ -3895,6 +4325,62 @@ mod tests {
         .unwrap()
     }
 
+    #[gpui::test]
+    fn test_layout_minimap(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.minimap = Some(crate::editor_settings::MinimapContent {
+                        enabled: Some(true),
+                        max_column: None,
+                    });
+                });
+            });
+        });
+
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple(&vec!["line"; 1000].join("\n"), cx);
+            Editor::new(EditorMode::Full, buffer, None, cx)
+        });
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+        let mut element = EditorElement::new(&editor, style);
+        let bounds = Bounds {
+            origin: point(px(500.), px(500.)),
+            size: size(px(500.), px(500.)),
+        };
+        let mut compute_layout = |cx: &mut TestAppContext| {
+            cx.update_window(window.into(), |view, cx| {
+                cx.with_element_context(|cx| {
+                    cx.with_view_id(view.entity_id(), |cx| element.compute_layout(bounds, cx))
+                })
+            })
+            .unwrap()
+        };
+
+        let state = compute_layout(cx);
+        let minimap = state.minimap.as_ref().unwrap();
+        assert_eq!(minimap.bounds.upper_right(), bounds.upper_right());
+        assert_eq!(
+            state.text_size.width,
+            bounds.size.width - state.gutter_size.width - minimap.bounds.size.width
+        );
+        assert_eq!(minimap.start_row, 0);
+        assert!(!minimap.lines.is_empty());
+
+        // Scrolled to the end, the minimap shows the end of the buffer too.
+        window
+            .update(cx, |editor, cx| {
+                editor.set_scroll_position(gpui::Point::new(0., 1000.), cx)
+            })
+            .unwrap();
+        let state = compute_layout(cx);
+        let minimap = state.minimap.as_ref().unwrap();
+        assert!(minimap.start_row > 0);
+        assert_eq!(minimap.start_row + minimap.lines.len() as u32, 1000);
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
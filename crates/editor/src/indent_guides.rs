@@ -0,0 +1,214 @@
+use std::ops::Range;
+
+use gpui::AppContext;
+use language::Point;
+use multi_buffer::MultiBufferSnapshot;
+
+/// How far to look past the edges of the visible rows for a non-blank line,
+/// when working out the indentation of the blank lines at those edges.
+const MAX_BLANK_LINE_SCAN: u32 = 256;
+
+/// A vertical guide drawn at one level of indentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndentGuide {
+    /// The buffer rows the guide spans.
+    pub rows: Range<u32>,
+    /// The column the guide is drawn at.
+    pub column: u32,
+    /// Whether this is the guide of the scope containing the newest cursor.
+    pub active: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct LineIndent {
+    spaces: u32,
+    tabs: u32,
+    blank: bool,
+}
+
+impl LineIndent {
+    fn measure(snapshot: &MultiBufferSnapshot, row: u32) -> Self {
+        let mut indent = Self {
+            blank: true,
+            ..Default::default()
+        };
+        for c in snapshot.chars_at(Point::new(row, 0)) {
+            match c {
+                ' ' => indent.spaces += 1,
+                '\t' => indent.tabs += 1,
+                '\n' => break,
+                c if c.is_whitespace() => {}
+                _ => {
+                    indent.blank = false;
+                    break;
+                }
+            }
+        }
+        indent
+    }
+
+    fn columns(&self, tab_size: u32) -> u32 {
+        self.spaces + self.tabs * tab_size
+    }
+}
+
+/// The indentation of the lines laid out most recently, so that scrolling
+/// only has to measure the lines coming into view.
+#[derive(Default)]
+pub(crate) struct IndentGuideCache {
+    edit_count: usize,
+    start_row: u32,
+    lines: Vec<LineIndent>,
+}
+
+impl IndentGuideCache {
+    fn lines(&mut self, rows: Range<u32>, snapshot: &MultiBufferSnapshot) -> &[LineIndent] {
+        // Excerpts can change without an edit, so only singleton buffers are cached.
+        let valid = snapshot.as_singleton().is_some() && snapshot.edit_count() == self.edit_count;
+        let cached_rows = self.start_row..self.start_row + self.lines.len() as u32;
+        let lines = rows
+            .clone()
+            .map(|row| {
+                if valid && cached_rows.contains(&row) {
+                    self.lines[(row - cached_rows.start) as usize]
+                } else {
+                    LineIndent::measure(snapshot, row)
+                }
+            })
+            .collect();
+        *self = Self {
+            edit_count: snapshot.edit_count(),
+            start_row: rows.start,
+            lines,
+        };
+        &self.lines
+    }
+}
+
+/// Computes the indent guides for the given buffer rows. Only those rows are
+/// measured (plus whatever it takes to place the blank lines at their edges),
+/// so the cost follows the size of the viewport rather than of the buffer.
+pub(crate) fn indent_guides_in_range(
+    rows: Range<u32>,
+    cursor_row: Option<u32>,
+    snapshot: &MultiBufferSnapshot,
+    cache: &mut IndentGuideCache,
+    cx: &AppContext,
+) -> Vec<IndentGuide> {
+    if rows.is_empty() {
+        return Vec::new();
+    }
+
+    // Excerpts can use different languages and so different tab sizes and
+    // settings, and guides don't continue from one excerpt into the next.
+    let excerpt_settings_at = |row: u32| {
+        let settings = snapshot.settings_at(Point::new(row, 0), cx);
+        (row, settings.tab_size.get(), settings.show_indent_guides)
+    };
+    let mut excerpt_settings = vec![excerpt_settings_at(rows.start)];
+    for boundary in
+        snapshot.excerpt_boundaries_in_range(Point::new(rows.start, 0)..Point::new(rows.end, 0))
+    {
+        if boundary.row > rows.start && boundary.row < rows.end {
+            excerpt_settings.push(excerpt_settings_at(boundary.row));
+        }
+    }
+    let excerpt_settings_for_row = |row: u32| {
+        let ix = excerpt_settings.partition_point(|(start, _, _)| *start <= row);
+        excerpt_settings[ix.saturating_sub(1)]
+    };
+    let tab_size_at = |row: u32| excerpt_settings_for_row(row).1;
+    if excerpt_settings.iter().all(|(_, _, show)| !show) {
+        return Vec::new();
+    }
+
+    let lines = cache.lines(rows.clone(), snapshot);
+    let indents = lines
+        .iter()
+        .zip(rows.clone())
+        .map(|(line, row)| (!line.blank).then(|| line.columns(tab_size_at(row))))
+        .collect::<Vec<_>>();
+
+    // A blank line takes the smaller indentation of the lines around it, so
+    // that guides run through the gaps between statements but not past the
+    // end of a block.
+    let scan_indent = |candidates: &mut dyn Iterator<Item = u32>| {
+        candidates
+            .take(MAX_BLANK_LINE_SCAN as usize)
+            .find_map(|row| {
+                let line = LineIndent::measure(snapshot, row);
+                (!line.blank).then(|| line.columns(tab_size_at(row)))
+            })
+    };
+    let mut previous = scan_indent(&mut (0..rows.start).rev()).unwrap_or(0);
+    let mut following = Vec::with_capacity(indents.len());
+    let mut next = scan_indent(&mut (rows.end..=snapshot.max_buffer_row())).unwrap_or(0);
+    for indent in indents.iter().rev() {
+        following.push(next);
+        if let Some(indent) = indent {
+            next = *indent;
+        }
+    }
+    following.reverse();
+    let indents = indents
+        .iter()
+        .zip(following)
+        .map(|(indent, next)| {
+            let indent = indent.unwrap_or(previous.min(next));
+            previous = indent;
+            indent
+        })
+        .collect::<Vec<_>>();
+
+    let mut guides = Vec::new();
+    let mut open_guides: Vec<IndentGuide> = Vec::new();
+    for (row, indent) in rows.clone().zip(indents.iter().copied()) {
+        if excerpt_settings.iter().any(|(start, _, _)| *start == row) {
+            guides.extend(open_guides.drain(..));
+        }
+        while open_guides
+            .last()
+            .map_or(false, |guide| guide.column >= indent)
+        {
+            guides.extend(open_guides.pop());
+        }
+        for guide in &mut open_guides {
+            guide.rows.end = row + 1;
+        }
+        let tab_size = tab_size_at(row);
+        let mut column = open_guides
+            .last()
+            .map_or(0, |guide| guide.column + tab_size);
+        while column < indent {
+            open_guides.push(IndentGuide {
+                rows: row..row + 1,
+                column,
+                active: false,
+            });
+            column += tab_size;
+        }
+    }
+    guides.extend(open_guides);
+    guides.retain(|guide| excerpt_settings_for_row(guide.rows.start).2);
+
+    if let Some(cursor_row) = cursor_row.filter(|row| rows.contains(row)) {
+        // On a line that opens a block, the block's own guide is the active one.
+        let cursor_ix = (cursor_row - rows.start) as usize;
+        let scope_row = lines[cursor_ix + 1..]
+            .iter()
+            .position(|line| !line.blank)
+            .map(|ix| cursor_ix + 1 + ix)
+            .filter(|ix| indents[*ix] > indents[cursor_ix])
+            .map_or(cursor_row, |ix| rows.start + ix as u32);
+        if let Some(guide) = guides
+            .iter_mut()
+            .filter(|guide| guide.rows.contains(&scope_row))
+            .max_by_key(|guide| guide.column)
+        {
+            guide.active = true;
+        }
+    }
+
+    guides.sort_by_key(|guide| (guide.rows.start, guide.column));
+    guides
+}
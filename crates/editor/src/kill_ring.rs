@@ -0,0 +1,60 @@
+use std::ops::Range;
+
+use gpui::{AppContext, Global};
+use multi_buffer::Anchor;
+
+/// The most entries kept in the kill ring before the oldest are dropped.
+const MAX_ENTRIES: usize = 60;
+
+/// Text removed with `KillRingCut` or copied with `KillRingSave`, shared by
+/// every editor. Unlike the clipboard it keeps older entries, so that
+/// `KillRingYankPop` can cycle back through them.
+#[derive(Default)]
+pub(crate) struct KillRing {
+    entries: Vec<String>,
+}
+
+impl Global for KillRing {}
+
+impl KillRing {
+    /// Adds a new entry, or extends the latest one when `append` is set (as
+    /// consecutive kills do).
+    pub fn push(text: String, append: bool, cx: &mut AppContext) {
+        let ring = cx.default_global::<Self>();
+        match ring.entries.last_mut() {
+            Some(last) if append => last.push_str(&text),
+            _ => {
+                ring.entries.push(text);
+                if ring.entries.len() > MAX_ENTRIES {
+                    ring.entries.remove(0);
+                }
+            }
+        }
+    }
+
+    /// Returns the entry `index` kills ago, wrapping around the ring.
+    pub fn get(index: usize, cx: &AppContext) -> Option<String> {
+        let entries = &cx.try_global::<Self>()?.entries;
+        if entries.is_empty() {
+            return None;
+        }
+        entries
+            .get(entries.len() - 1 - index % entries.len())
+            .cloned()
+    }
+}
+
+/// Where the cursors were after a kill or yank, used to tell whether the next
+/// kill or yank-pop directly follows it.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct KillRingPosition {
+    pub edit_count: usize,
+    pub cursors: Vec<usize>,
+}
+
+/// The text inserted by the last yank, which yank-pop replaces.
+pub(crate) struct YankState {
+    pub ranges: Vec<Range<Anchor>>,
+    pub index: usize,
+    pub position: KillRingPosition,
+}
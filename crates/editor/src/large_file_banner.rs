@@ -0,0 +1,77 @@
+use gpui::{AppContext, EventEmitter, Model, Render, Subscription};
+use language::Buffer;
+use ui::prelude::*;
+use workspace::{item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
+
+use crate::Editor;
+
+/// A banner shown above files opened in large file mode, which lets the user
+/// turn the features that are disabled in that mode back on.
+pub struct LargeFileBanner {
+    buffer: Option<Model<Buffer>>,
+    _buffer_subscription: Option<Subscription>,
+}
+
+impl LargeFileBanner {
+    pub fn new() -> Self {
+        Self {
+            buffer: None,
+            _buffer_subscription: None,
+        }
+    }
+
+    fn location(&self, cx: &AppContext) -> ToolbarItemLocation {
+        match &self.buffer {
+            Some(buffer) if buffer.read(cx).is_in_large_file_mode() => {
+                ToolbarItemLocation::Secondary
+            }
+            _ => ToolbarItemLocation::Hidden,
+        }
+    }
+}
+
+impl Render for LargeFileBanner {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .w_full()
+            .gap_2()
+            .child(Icon::new(IconName::Info).color(Color::Muted))
+            .child(
+                Label::new(
+                    "This file is large, so syntax highlighting, language servers and git diffs are turned off.",
+                )
+                .color(Color::Muted),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("enable-large-file-features", "Enable Features").on_click(
+                    cx.listener(|this, _, cx| {
+                        if let Some(buffer) = &this.buffer {
+                            buffer.update(cx, |buffer, cx| buffer.set_large_file_mode(false, cx));
+                        }
+                    }),
+                ),
+            )
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for LargeFileBanner {}
+
+impl ToolbarItemView for LargeFileBanner {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) -> ToolbarItemLocation {
+        self.buffer = active_pane_item
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        self._buffer_subscription = self.buffer.as_ref().map(|buffer| {
+            cx.observe(buffer, |this, _, cx| {
+                cx.emit(ToolbarItemEvent::ChangeLocation(this.location(cx)));
+                cx.notify();
+            })
+        });
+        self.location(cx)
+    }
+}
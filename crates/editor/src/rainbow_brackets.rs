@@ -0,0 +1,254 @@
+use std::{any::TypeId, cmp::Reverse, ops::Range};
+
+use gpui::{HighlightStyle, ViewContext};
+use language::Point;
+use multi_buffer::MultiBufferSnapshot;
+use theme::ActiveTheme;
+
+use crate::{DisplayPoint, Editor, EditorMode, RangeToAnchorExt};
+
+/// How many rows above and below the visible ones are colorized, so that
+/// scrolling a little way doesn't have to query the syntax tree again.
+const ROW_MARGIN: u32 = 64;
+
+/// The brackets at each nesting level are highlighted under their own type,
+/// cycling back to the first once the theme's accent colors run out.
+enum RainbowBracketHighlight<const LEVEL: usize> {}
+
+const LEVELS: [fn() -> TypeId; 8] = [
+    TypeId::of::<RainbowBracketHighlight<0>>,
+    TypeId::of::<RainbowBracketHighlight<1>>,
+    TypeId::of::<RainbowBracketHighlight<2>>,
+    TypeId::of::<RainbowBracketHighlight<3>>,
+    TypeId::of::<RainbowBracketHighlight<4>>,
+    TypeId::of::<RainbowBracketHighlight<5>>,
+    TypeId::of::<RainbowBracketHighlight<6>>,
+    TypeId::of::<RainbowBracketHighlight<7>>,
+];
+
+/// The buffer rows whose brackets were colorized last, and the version of
+/// the buffer and its syntax trees they were colorized from.
+pub(crate) struct RainbowBrackets {
+    edit_count: usize,
+    parse_count: usize,
+    rows: Range<u32>,
+}
+
+pub fn refresh_rainbow_brackets(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode == EditorMode::SingleLine {
+        return;
+    }
+    // The brackets are colorized once the editor has been laid out.
+    let Some(visible_line_count) = editor.visible_line_count() else {
+        return;
+    };
+
+    let snapshot = editor.snapshot(cx);
+    let buffer = &snapshot.buffer_snapshot;
+    let start_row = snapshot.scroll_position().y as u32;
+    let end_row = (start_row + visible_line_count.ceil() as u32).min(snapshot.max_point().row());
+    let visible_rows = DisplayPoint::new(start_row, 0).to_point(&snapshot).row
+        ..DisplayPoint::new(end_row, 0).to_point(&snapshot).row + 1;
+    if let Some(colorized) = &editor.rainbow_brackets {
+        if colorized.edit_count == buffer.edit_count()
+            && colorized.parse_count == buffer.parse_count()
+            && colorized.rows.start <= visible_rows.start
+            && colorized.rows.end >= visible_rows.end
+        {
+            return;
+        }
+    }
+
+    let rows = visible_rows.start.saturating_sub(ROW_MARGIN)
+        ..(visible_rows.end + ROW_MARGIN).min(buffer.max_buffer_row() + 1);
+    editor.rainbow_brackets = Some(RainbowBrackets {
+        edit_count: buffer.edit_count(),
+        parse_count: buffer.parse_count(),
+        rows: rows.clone(),
+    });
+
+    let colors = cx.theme().accents().to_vec();
+    let level_count = colors.len().min(LEVELS.len());
+    let mut ranges_by_level = vec![Vec::new(); level_count];
+    if level_count > 0 {
+        for range in excerpt_ranges(buffer, rows) {
+            if !buffer.settings_at(range.start, cx).colorize_brackets {
+                continue;
+            }
+            let Some(pairs) = buffer.bracket_ranges(range) else {
+                continue;
+            };
+
+            // Visiting the pairs outermost first, a pair's depth is the number
+            // of pairs seen so far that haven't closed before it opens.
+            let mut pairs = pairs.collect::<Vec<_>>();
+            pairs.sort_by_key(|(open, close)| (open.start, Reverse(close.end)));
+            pairs.dedup();
+            let mut enclosing_ends: Vec<usize> = Vec::new();
+            for (open, close) in pairs {
+                while enclosing_ends
+                    .last()
+                    .map_or(false, |end| *end <= open.start)
+                {
+                    enclosing_ends.pop();
+                }
+                let level = enclosing_ends.len() % level_count;
+                enclosing_ends.push(close.end);
+                ranges_by_level[level].push(open);
+                ranges_by_level[level].push(close);
+            }
+        }
+    }
+
+    editor.display_map.update(cx, |map, _| {
+        let mut ranges_by_level = ranges_by_level.into_iter();
+        for (level, type_id) in LEVELS.iter().enumerate() {
+            let mut ranges = ranges_by_level.next().unwrap_or_default();
+            if ranges.is_empty() {
+                map.clear_highlights(type_id());
+                continue;
+            }
+            ranges.sort_by_key(|range| range.start);
+            let ranges = ranges
+                .into_iter()
+                .map(|range| range.to_anchors(buffer))
+                .collect();
+            let style = HighlightStyle {
+                color: Some(colors[level]),
+                ..Default::default()
+            };
+            map.highlight_text(type_id(), ranges, style);
+        }
+    });
+    cx.notify();
+}
+
+/// Splits the given rows at excerpt boundaries, as brackets are only paired
+/// up within a single excerpt.
+fn excerpt_ranges(buffer: &MultiBufferSnapshot, rows: Range<u32>) -> Vec<Range<Point>> {
+    let mut starts = vec![rows.start];
+    starts.extend(
+        buffer
+            .excerpt_boundaries_in_range(Point::new(rows.start, 0)..Point::new(rows.end, 0))
+            .map(|boundary| boundary.row)
+            .filter(|row| *row > rows.start && *row < rows.end),
+    );
+    starts.push(rows.end);
+    starts
+        .windows(2)
+        .filter(|rows| rows[0] < rows[1])
+        .map(|rows| {
+            let last_row = rows[1] - 1;
+            Point::new(rows[0], 0)..Point::new(last_row, buffer.line_len(last_row))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::{init_test, update_test_language_settings},
+        test::editor_lsp_test_context::EditorLspTestContext,
+    };
+    use indoc::indoc;
+    use language::{BracketPair, BracketPairConfig, Language, LanguageConfig, LanguageMatcher};
+
+    #[gpui::test]
+    async fn test_rainbow_brackets(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.colorize_brackets = Some(true);
+        });
+
+        let mut cx = EditorLspTestContext::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    brackets: BracketPairConfig {
+                        pairs: vec![
+                            BracketPair {
+                                start: "{".to_string(),
+                                end: "}".to_string(),
+                                close: false,
+                                newline: true,
+                            },
+                            BracketPair {
+                                start: "(".to_string(),
+                                end: ")".to_string(),
+                                close: false,
+                                newline: true,
+                            },
+                        ],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_brackets_query(indoc! {r#"
+                ("{" @open "}" @close)
+                ("(" @open ")" @close)
+                "#})
+            .unwrap(),
+            Default::default(),
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {r#"
+            fn test() {
+                a(b(c()));
+            }ˇ
+        "#});
+        cx.update_editor(|editor, cx| {
+            editor.set_visible_line_count(10., cx);
+            refresh_rainbow_brackets(editor, cx);
+        });
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<0>>(indoc! {r#"
+            fn test«(»«)» «{»
+                a(b(c()));
+            «}»
+        "#});
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<1>>(indoc! {r#"
+            fn test() {
+                a«(»b(c())«)»;
+            }
+        "#});
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<2>>(indoc! {r#"
+            fn test() {
+                a(b«(»c()«)»);
+            }
+        "#});
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<3>>(indoc! {r#"
+            fn test() {
+                a(b(c«(»«)»));
+            }
+        "#});
+
+        // Edits recolor the brackets whose depth changed.
+        cx.update_editor(|editor, cx| editor.insert("\nfn other() {}", cx));
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<0>>(indoc! {r#"
+            fn test«(»«)» «{»
+                a(b(c()));
+            «}»
+            fn other«(»«)» «{»«}»
+        "#});
+
+        // Bracket colors are removed once they're turned off.
+        update_test_language_settings(&mut cx, |settings| {
+            settings.defaults.colorize_brackets = Some(false);
+        });
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<0>>(indoc! {r#"
+            fn test() {
+                a(b(c()));
+            }
+            fn other() {}
+        "#});
+    }
+}
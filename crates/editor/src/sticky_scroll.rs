@@ -0,0 +1,111 @@
+use gpui::{AppContext, ViewContext};
+use language::{OutlineItem, Point};
+use settings::Settings;
+use theme::SyntaxTheme;
+
+use crate::{
+    display_map::ToDisplayPoint, scroll::Autoscroll, Anchor, DisplayPoint, Editor, EditorMode,
+    EditorSettings, EditorSnapshot, ToPoint,
+};
+
+/// The sticky headers laid out most recently, so that they're only looked up
+/// again once the buffer or the lines at the top of the editor change.
+#[derive(Default)]
+pub(crate) struct StickyHeaderCache {
+    key: Option<StickyHeaderCacheKey>,
+    headers: Vec<OutlineItem<Anchor>>,
+}
+
+#[derive(PartialEq, Eq)]
+struct StickyHeaderCacheKey {
+    edit_count: usize,
+    parse_count: usize,
+    max_depth: usize,
+    /// The buffer positions of the lines that headers can cover, which change
+    /// when the editor scrolls or when lines are folded or wrapped.
+    positions: Vec<Point>,
+}
+
+impl Editor {
+    /// Returns the outline items (such as the module, impl and function)
+    /// enclosing the top of the editor whose first line has scrolled out of
+    /// view, outermost first. Each one takes up a line pinned to the top of
+    /// the editor, so an item is included once its first line is hidden
+    /// behind the items already pinned above it.
+    pub fn sticky_headers(
+        &mut self,
+        snapshot: &EditorSnapshot,
+        theme: Option<&SyntaxTheme>,
+        cx: &AppContext,
+    ) -> Vec<OutlineItem<Anchor>> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if self.mode != EditorMode::Full || !settings.enabled {
+            return Vec::new();
+        }
+
+        let top_row = snapshot.scroll_position().y as u32;
+        let max_row = snapshot.max_point().row();
+        let key = StickyHeaderCacheKey {
+            edit_count: snapshot.buffer_snapshot.edit_count(),
+            parse_count: snapshot.buffer_snapshot.parse_count(),
+            max_depth: settings.max_depth,
+            positions: (top_row..=max_row)
+                .take(settings.max_depth)
+                .map(|row| DisplayPoint::new(row, 0).to_point(snapshot))
+                .collect(),
+        };
+        if self.sticky_header_cache.key.as_ref() != Some(&key) {
+            self.sticky_header_cache = StickyHeaderCache {
+                headers: Self::enclosing_outline_items(
+                    snapshot,
+                    top_row,
+                    max_row,
+                    settings.max_depth,
+                    theme,
+                ),
+                key: Some(key),
+            };
+        }
+        self.sticky_header_cache.headers.clone()
+    }
+
+    fn enclosing_outline_items(
+        snapshot: &EditorSnapshot,
+        top_row: u32,
+        max_row: u32,
+        max_depth: usize,
+        theme: Option<&SyntaxTheme>,
+    ) -> Vec<OutlineItem<Anchor>> {
+        let mut headers = Vec::new();
+        while headers.len() < max_depth {
+            let row = top_row + headers.len() as u32;
+            if row > max_row {
+                break;
+            }
+
+            let position = DisplayPoint::new(row, 0).to_point(snapshot);
+            let Some((_, items)) = snapshot.buffer_snapshot.symbols_containing(position, theme)
+            else {
+                break;
+            };
+            let enclosing = items
+                .into_iter()
+                .filter(|item| item.range.end.to_point(&snapshot.buffer_snapshot) >= position)
+                .take_while(|item| item.range.start.to_display_point(snapshot).row() < row)
+                .take(max_depth)
+                .collect::<Vec<_>>();
+            if enclosing.len() <= headers.len() {
+                break;
+            }
+            headers = enclosing;
+        }
+        headers
+    }
+
+    /// Moves the cursor to the start of a sticky header's item.
+    pub(crate) fn jump_to_sticky_header(&mut self, position: Anchor, cx: &mut ViewContext<Self>) {
+        self.change_selections(Some(Autoscroll::focused()), cx, |selections| {
+            selections.select_anchor_ranges([position..position]);
+        });
+    }
+}
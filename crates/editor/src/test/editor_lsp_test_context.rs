@@ -148,6 +148,23 @@ impl EditorLspTestContext {
                 ("<" @open ">" @close)
                 ("\"" @open "\"" @close)
                 (closure_parameters "|" @open "|" @close)"#})),
+            text_objects: Some(Cow::from(indoc! {r#"
+                (function_item
+                    body: (_
+                        "{"
+                        (_)* @function.inside
+                        "}" )) @function.around
+
+                (impl_item
+                    body: (_
+                        "{"
+                        (_)* @class.inside
+                        "}" )) @class.around
+
+                (line_comment)+ @comment.around
+
+                (parameters (_) @argument.inside)
+                (arguments (_) @argument.inside)"#})),
             ..Default::default()
         })
         .expect("Could not parse queries");
@@ -0,0 +1,342 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use chrono::{Datelike, Local, Timelike};
+use gpui::{AppContext, Global};
+use language::{Buffer, CharKind, CodeLabel, Completion, Documentation, Point, ToOffset};
+use lsp::LanguageServerId;
+use rand::Rng;
+use serde::Deserialize;
+use settings::parse_json_with_comments;
+
+use crate::{Editor, ToPoint};
+
+/// Completions the editor offers itself, rather than getting them from a
+/// language server, use an id no server has, so that the project doesn't try
+/// to resolve them.
+pub(crate) const SNIPPET_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
+/// The snippets defined in the config dir's `snippets` directory, in the
+/// format VS Code uses. A language's snippets go in a file named after it
+/// (such as `rust.json`), while `snippets.json` and any `*.code-snippets`
+/// files hold global snippets, which a `scope` can limit to some languages.
+#[derive(Default)]
+pub struct UserSnippets {
+    files: BTreeMap<PathBuf, SnippetFile>,
+}
+
+impl Global for UserSnippets {}
+
+struct SnippetFile {
+    /// The language the file's snippets are for, or `None` for global ones.
+    language: Option<String>,
+    snippets: Vec<UserSnippet>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserSnippet {
+    pub name: String,
+    pub prefixes: Vec<String>,
+    pub body: String,
+    pub description: Option<String>,
+    /// The languages a global snippet is limited to, if any.
+    pub scope: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct SnippetContent {
+    #[serde(default)]
+    prefix: Option<StringOrLines>,
+    body: StringOrLines,
+    #[serde(default)]
+    description: Option<StringOrLines>,
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrLines {
+    String(String),
+    Lines(Vec<String>),
+}
+
+impl StringOrLines {
+    fn into_lines(self) -> Vec<String> {
+        match self {
+            StringOrLines::String(string) => vec![string],
+            StringOrLines::Lines(lines) => lines,
+        }
+    }
+
+    fn into_string(self) -> String {
+        self.into_lines().join("\n")
+    }
+}
+
+impl UserSnippets {
+    /// Replaces the snippets loaded from the given file with its new content,
+    /// or removes them when the file has been deleted.
+    pub fn update_file(path: &Path, content: Option<&str>, cx: &mut AppContext) -> Result<()> {
+        let snippets = cx.default_global::<Self>();
+        snippets.files.remove(path);
+
+        let (Some(content), Some(language)) = (content, Self::file_language(path)) else {
+            return Ok(());
+        };
+        let file = SnippetFile {
+            language,
+            snippets: Self::parse(content)?,
+        };
+        snippets.files.insert(path.to_path_buf(), file);
+        Ok(())
+    }
+
+    /// Returns the language a snippet file is for: `Some(None)` for a global
+    /// file, and `None` if it isn't a snippet file at all.
+    fn file_language(path: &Path) -> Option<Option<String>> {
+        let stem = path.file_stem()?.to_str()?;
+        match path.extension()?.to_str()? {
+            "code-snippets" => Some(None),
+            "json" if stem == "snippets" => Some(None),
+            "json" => Some(Some(normalize_language_name(stem))),
+            _ => None,
+        }
+    }
+
+    fn parse(content: &str) -> Result<Vec<UserSnippet>> {
+        let snippets = parse_json_with_comments::<BTreeMap<String, SnippetContent>>(content)?;
+        Ok(snippets
+            .into_iter()
+            .filter_map(|(name, snippet)| {
+                Some(UserSnippet {
+                    name,
+                    prefixes: snippet.prefix?.into_lines(),
+                    body: snippet.body.into_string(),
+                    description: snippet.description.map(StringOrLines::into_string),
+                    scope: snippet.scope.map(|scope| {
+                        scope
+                            .split(',')
+                            .map(|language| normalize_language_name(language.trim()))
+                            .collect()
+                    }),
+                })
+            })
+            .collect())
+    }
+
+    /// Returns the snippets that apply to the given language.
+    pub fn for_language<'a>(
+        language: Option<&str>,
+        cx: &'a AppContext,
+    ) -> impl Iterator<Item = &'a UserSnippet> {
+        let language = language.map(normalize_language_name);
+        let file_language = language.clone();
+        cx.try_global::<Self>()
+            .into_iter()
+            .flat_map(|snippets| snippets.files.values())
+            .filter(move |file| match &file.language {
+                Some(language) => Some(language) == file_language.as_ref(),
+                None => true,
+            })
+            .flat_map(|file| &file.snippets)
+            .filter(move |snippet| match (&snippet.scope, &language) {
+                (Some(scope), Some(language)) => scope.contains(language),
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+    }
+}
+
+/// Languages are matched by their lowercased name without spaces, so that
+/// "Shell Script" snippets can go in `shellscript.json`.
+fn normalize_language_name(name: &str) -> String {
+    name.to_lowercase().replace(' ', "")
+}
+
+/// Returns the user snippets whose prefix could complete the word before the
+/// given position.
+pub(crate) fn snippet_completions(
+    buffer: &Buffer,
+    position: text::Anchor,
+    cx: &AppContext,
+) -> Vec<Completion> {
+    let offset = position.to_offset(buffer);
+    let (word_range, kind) = buffer.surrounding_word(offset);
+    if offset <= word_range.start || kind != Some(CharKind::Word) {
+        return Vec::new();
+    }
+    let old_range = buffer.anchor_before(word_range.start)..position;
+
+    let language = buffer.language_at(offset);
+    let language_name = language.as_ref().map(|language| language.name());
+    UserSnippets::for_language(language_name.as_deref(), cx)
+        .flat_map(|snippet| {
+            let old_range = old_range.clone();
+            snippet.prefixes.iter().map(move |prefix| Completion {
+                old_range: old_range.clone(),
+                new_text: snippet.body.clone(),
+                label: CodeLabel::plain(prefix.clone(), None),
+                server_id: SNIPPET_SERVER_ID,
+                documentation: Some(Documentation::MultiLinePlainText(snippet.body.clone())),
+                lsp_completion: lsp::CompletionItem {
+                    label: prefix.clone(),
+                    kind: Some(lsp::CompletionItemKind::SNIPPET),
+                    detail: Some(
+                        snippet
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| snippet.name.clone()),
+                    ),
+                    insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+                    ..Default::default()
+                },
+            })
+        })
+        .collect()
+}
+
+impl Editor {
+    /// Resolves a variable used in a snippet, such as `$TM_FILENAME` or
+    /// `$CURRENT_YEAR`, returning `None` for variables it doesn't know of.
+    pub(crate) fn snippet_variable(&self, name: &str, cx: &AppContext) -> Option<String> {
+        let now = Local::now();
+        let buffer = self.buffer.read(cx);
+        let snapshot = buffer.read(cx);
+        let selection = self.selections.newest_anchor();
+        let head = selection.head().to_point(&snapshot);
+        let file = buffer
+            .as_singleton()
+            .and_then(|buffer| buffer.read(cx).file().cloned());
+        let path = file.as_ref().map(|file| match file.as_local() {
+            Some(file) => file.abs_path(cx),
+            None => file.full_path(cx),
+        });
+
+        let value = match name {
+            "TM_SELECTED_TEXT" => snapshot
+                .text_for_range(selection.start..selection.end)
+                .collect(),
+            "TM_CURRENT_LINE" => snapshot
+                .text_for_range(
+                    Point::new(head.row, 0)..Point::new(head.row, snapshot.line_len(head.row)),
+                )
+                .collect(),
+            "TM_CURRENT_WORD" => {
+                let (word_range, kind) = snapshot.surrounding_word(head);
+                if kind == Some(CharKind::Word) {
+                    snapshot.text_for_range(word_range).collect()
+                } else {
+                    String::new()
+                }
+            }
+            "TM_LINE_INDEX" => head.row.to_string(),
+            "TM_LINE_NUMBER" => (head.row + 1).to_string(),
+            "TM_FILENAME" => path?.file_name()?.to_string_lossy().into_owned(),
+            "TM_FILENAME_BASE" => path?.file_stem()?.to_string_lossy().into_owned(),
+            "TM_DIRECTORY" => path?.parent()?.to_string_lossy().into_owned(),
+            "TM_FILEPATH" => path?.to_string_lossy().into_owned(),
+            "RELATIVE_FILEPATH" => file?.path().to_string_lossy().into_owned(),
+            "CLIPBOARD" => cx
+                .read_from_clipboard()
+                .map(|item| item.text().clone())
+                .unwrap_or_default(),
+            "CURRENT_YEAR" => now.year().to_string(),
+            "CURRENT_YEAR_SHORT" => format!("{:02}", now.year() % 100),
+            "CURRENT_MONTH" => format!("{:02}", now.month()),
+            "CURRENT_MONTH_NAME" => now.format("%B").to_string(),
+            "CURRENT_MONTH_NAME_SHORT" => now.format("%b").to_string(),
+            "CURRENT_DATE" => format!("{:02}", now.day()),
+            "CURRENT_DAY_NAME" => now.format("%A").to_string(),
+            "CURRENT_DAY_NAME_SHORT" => now.format("%a").to_string(),
+            "CURRENT_HOUR" => format!("{:02}", now.hour()),
+            "CURRENT_MINUTE" => format!("{:02}", now.minute()),
+            "CURRENT_SECOND" => format!("{:02}", now.second()),
+            "CURRENT_SECONDS_UNIX" => now.timestamp().to_string(),
+            "RANDOM" => format!("{:06}", rand::thread_rng().gen_range(0..1_000_000)),
+            "RANDOM_HEX" => format!("{:06x}", rand::thread_rng().gen_range(0..0x1000000)),
+            "UUID" => uuid::Uuid::new_v4().to_string(),
+            "LINE_COMMENT" => snapshot
+                .language_scope_at(head)?
+                .line_comment_prefixes()?
+                .first()?
+                .trim_end()
+                .to_string(),
+            "BLOCK_COMMENT_START" => snapshot
+                .language_scope_at(head)?
+                .block_comment_delimiters()?
+                .0
+                .trim_end()
+                .to_string(),
+            "BLOCK_COMMENT_END" => snapshot
+                .language_scope_at(head)?
+                .block_comment_delimiters()?
+                .1
+                .trim_start()
+                .to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    fn test_user_snippets(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            UserSnippets::update_file(
+                Path::new("/snippets/rust.json"),
+                Some(
+                    r#"{
+                        // Comments are allowed.
+                        "Print": {
+                            "prefix": ["pr", "println"],
+                            "body": ["println!(\"$1\");", "$0"],
+                            "description": "Print a line"
+                        },
+                        "Without a prefix": { "body": "unused" }
+                    }"#,
+                ),
+                cx,
+            )
+            .unwrap();
+            UserSnippets::update_file(
+                Path::new("/snippets/global.code-snippets"),
+                Some(
+                    r#"{
+                        "Copyright": { "prefix": "copy", "body": "Copyright $CURRENT_YEAR" },
+                        "Fixme": { "prefix": "fixme", "body": "FIXME", "scope": "rust, Shell Script" }
+                    }"#,
+                ),
+                cx,
+            )
+            .unwrap();
+            UserSnippets::update_file(Path::new("/snippets/notes.txt"), Some("{}"), cx).unwrap();
+
+            let names = |language: Option<&str>| {
+                UserSnippets::for_language(language, cx)
+                    .map(|snippet| snippet.name.as_str())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(names(Some("Rust")), ["Copyright", "Fixme", "Print"]);
+            assert_eq!(names(Some("Shell Script")), ["Copyright", "Fixme"]);
+            assert_eq!(names(Some("Python")), ["Copyright"]);
+            assert_eq!(names(None), ["Copyright"]);
+
+            let print = UserSnippets::for_language(Some("Rust"), cx).last().unwrap();
+            assert_eq!(print.prefixes, ["pr", "println"]);
+            assert_eq!(print.body, "println!(\"$1\");\n$0");
+            assert_eq!(print.description.as_deref(), Some("Print a line"));
+
+            UserSnippets::update_file(Path::new("/snippets/rust.json"), None, cx).unwrap();
+            assert_eq!(names(Some("Rust")), ["Copyright", "Fixme"]);
+        });
+    }
+}
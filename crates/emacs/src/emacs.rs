@@ -0,0 +1,251 @@
+//! Emacs commands that need more than a key binding: the universal argument
+//! (`C-u`), which repeats the next command, and keyboard macros. The rest of
+//! the emacs base keymap (the mark, the kill ring and so on) binds editor
+//! actions directly.
+
+use editor::Editor;
+use gpui::{
+    actions, impl_actions, Action, AppContext, Global, KeyContext, Keystroke, KeystrokeEvent,
+    ViewContext, WeakView, WindowContext,
+};
+use serde::Deserialize;
+
+/// Digit adds a digit to a pending universal argument, as in `C-u 1 2`.
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct Digit(pub usize);
+
+actions!(
+    emacs,
+    [UniversalArgument, StartMacro, EndMacro, ReplayMacro]
+);
+
+impl_actions!(emacs, [Digit]);
+
+/// Initializes the `emacs` crate.
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(Emacs::default());
+    cx.observe_new_views(register).detach();
+    cx.observe_keystrokes(observe_keystrokes).detach();
+}
+
+#[derive(Default)]
+struct Emacs {
+    universal_argument: Option<UniversalArgumentState>,
+    /// The steps of the keyboard macro being recorded, if any.
+    recording: Option<Vec<MacroStep>>,
+    last_macro: Vec<MacroStep>,
+    /// Set while a macro or repeated command is replayed, so that it isn't
+    /// recorded or repeated again.
+    replaying: bool,
+}
+
+impl Global for Emacs {}
+
+struct UniversalArgumentState {
+    editor: WeakView<Editor>,
+    /// Each `C-u` multiplies the argument by 4.
+    multiplier: usize,
+    /// The number typed after `C-u`, which replaces the multiplier.
+    digits: Option<usize>,
+}
+
+/// One step of a keyboard macro: either an action, or a keystroke that typed
+/// some text.
+enum MacroStep {
+    Action(Box<dyn Action>),
+    Keystroke(Keystroke),
+}
+
+impl Clone for MacroStep {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
+        }
+    }
+}
+
+fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let handle = cx.view().downgrade();
+    editor.register_action(move |_: &UniversalArgument, cx| {
+        cx.update_global(|emacs: &mut Emacs, _| {
+            emacs.universal_argument = Some(match emacs.universal_argument.take() {
+                Some(argument) if argument.digits.is_none() => UniversalArgumentState {
+                    multiplier: argument.multiplier * 4,
+                    ..argument
+                },
+                _ => UniversalArgumentState {
+                    editor: handle.clone(),
+                    multiplier: 4,
+                    digits: None,
+                },
+            });
+        });
+        update_keymap_context(&handle, true, cx);
+    });
+    editor.register_action(|&Digit(digit): &Digit, cx| {
+        cx.update_global(|emacs: &mut Emacs, _| {
+            if let Some(argument) = emacs.universal_argument.as_mut() {
+                argument.digits = Some(argument.digits.unwrap_or(0) * 10 + digit);
+            }
+        });
+    });
+    editor.register_action(|_: &StartMacro, cx| {
+        cx.update_global(|emacs: &mut Emacs, _| emacs.recording = Some(Vec::new()));
+    });
+    editor.register_action(|_: &EndMacro, cx| {
+        cx.update_global(|emacs: &mut Emacs, _| {
+            if let Some(steps) = emacs.recording.take() {
+                emacs.last_macro = steps;
+            }
+        });
+    });
+    editor.register_action(|_: &ReplayMacro, cx| {
+        let times = take_universal_argument(cx);
+        let emacs = cx.global::<Emacs>();
+        // A macro can't replay itself.
+        if emacs.recording.is_some() {
+            return;
+        }
+        let steps = (0..times)
+            .flat_map(|_| emacs.last_macro.iter().cloned())
+            .collect();
+        replay(steps, cx);
+    });
+}
+
+/// Records each command into the keyboard macro being recorded, and repeats
+/// it if a universal argument was given.
+fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext) {
+    let Some(emacs) = cx.try_global::<Emacs>() else {
+        return;
+    };
+    if emacs.replaying || (emacs.universal_argument.is_none() && emacs.recording.is_none()) {
+        return;
+    }
+
+    let step = match &keystroke_event.action {
+        // emacs' own actions act on the argument and macros, rather than being repeated.
+        Some(action) if action.name().starts_with("emacs::") => return,
+        Some(action) => MacroStep::Action(action.boxed_clone()),
+        None => {
+            let keystroke = keystroke_event.keystroke.clone().with_simulated_ime();
+            if keystroke.ime_key.is_none() {
+                return;
+            }
+            MacroStep::Keystroke(keystroke_event.keystroke.clone())
+        }
+    };
+
+    let times = take_universal_argument(cx);
+    cx.update_global(|emacs: &mut Emacs, _| {
+        if let Some(recording) = emacs.recording.as_mut() {
+            recording.extend((0..times).map(|_| step.clone()));
+        }
+    });
+    if times > 1 {
+        replay((1..times).map(|_| step.clone()).collect(), cx);
+    }
+}
+
+fn take_universal_argument(cx: &mut WindowContext) -> usize {
+    let Some(argument) = cx.update_global(|emacs: &mut Emacs, _| emacs.universal_argument.take())
+    else {
+        return 1;
+    };
+    update_keymap_context(&argument.editor, false, cx);
+    argument.digits.unwrap_or(argument.multiplier)
+}
+
+/// Dispatches the steps in order once the current action has finished.
+fn replay(steps: Vec<MacroStep>, cx: &mut WindowContext) {
+    cx.update_global(|emacs: &mut Emacs, _| emacs.replaying = true);
+    for step in steps {
+        match step {
+            MacroStep::Action(action) => cx.dispatch_action(action),
+            MacroStep::Keystroke(keystroke) => cx.defer(move |cx| {
+                cx.dispatch_keystroke(keystroke);
+            }),
+        }
+    }
+    cx.defer(|cx| cx.update_global(|emacs: &mut Emacs, _| emacs.replaying = false));
+}
+
+/// While a universal argument is pending, digits add to it rather than
+/// being typed.
+fn update_keymap_context(editor: &WeakView<Editor>, pending: bool, cx: &mut WindowContext) {
+    editor
+        .update(cx, |editor, cx| {
+            if pending {
+                let mut context = KeyContext::default();
+                context.add("emacs_universal_argument");
+                editor.set_keymap_context_layer::<Emacs>(context, cx);
+            } else {
+                editor.remove_keymap_context_layer::<Emacs>(cx);
+            }
+        })
+        .ok();
+}
+
+#[cfg(test)]
+mod test {
+    use editor::{
+        actions::{MoveLeft, MoveRight},
+        test::editor_lsp_test_context::EditorLspTestContext,
+    };
+    use gpui::KeyBinding;
+    use settings::SettingsStore;
+
+    use super::*;
+
+    async fn emacs_context(cx: &mut gpui::TestAppContext) -> EditorLspTestContext {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            release_channel::init("0.0.0", cx);
+            crate::init(cx);
+            cx.bind_keys([
+                KeyBinding::new("ctrl-u", UniversalArgument, None),
+                KeyBinding::new("1", Digit(1), Some("emacs_universal_argument")),
+                KeyBinding::new("2", Digit(2), Some("emacs_universal_argument")),
+                KeyBinding::new("ctrl-f", MoveRight, Some("Editor")),
+                KeyBinding::new("ctrl-b", MoveLeft, Some("Editor")),
+                KeyBinding::new("ctrl-x (", StartMacro, Some("Editor")),
+                KeyBinding::new("ctrl-x )", EndMacro, Some("Editor")),
+                KeyBinding::new("ctrl-x e", ReplayMacro, Some("Editor")),
+            ]);
+        });
+        EditorLspTestContext::new_rust(Default::default(), cx).await
+    }
+
+    #[gpui::test]
+    async fn test_universal_argument(cx: &mut gpui::TestAppContext) {
+        let mut cx = emacs_context(cx).await;
+
+        cx.set_state("ˇabcdefghijklmnopqrstuvwxyz");
+        cx.simulate_keystrokes(["ctrl-u", "ctrl-f"]);
+        cx.assert_editor_state("abcdˇefghijklmnopqrstuvwxyz");
+        cx.simulate_keystrokes(["ctrl-u", "ctrl-u", "ctrl-f"]);
+        cx.assert_editor_state("abcdefghijklmnopqrstˇuvwxyz");
+        cx.simulate_keystrokes(["ctrl-u", "1", "2", "ctrl-b"]);
+        cx.assert_editor_state("abcdefghˇijklmnopqrstuvwxyz");
+
+        // Typed text is repeated too, and the argument only applies once.
+        cx.set_state("ˇ");
+        cx.simulate_keystrokes(["ctrl-u", "2", "x", "y"]);
+        cx.assert_editor_state("xxyˇ");
+    }
+
+    #[gpui::test]
+    async fn test_keyboard_macros(cx: &mut gpui::TestAppContext) {
+        let mut cx = emacs_context(cx).await;
+
+        cx.set_state("ˇone\ntwo\nthree");
+        cx.simulate_keystrokes(["ctrl-x", "(", "-", "ctrl-f", "ctrl-f", "ctrl-x", ")"]);
+        cx.assert_editor_state("-onˇe\ntwo\nthree");
+        cx.simulate_keystrokes(["ctrl-x", "e"]);
+        cx.assert_editor_state("-on-e\nˇtwo\nthree");
+        cx.simulate_keystrokes(["ctrl-u", "2", "ctrl-x", "e"]);
+        cx.assert_editor_state("-on-e\n-tw-o\nˇthree");
+    }
+}
@@ -0,0 +1,111 @@
+use editor::Editor;
+use gpui::{
+    div, IntoElement, Model, ParentElement, Render, Subscription, View, ViewContext, WeakView,
+};
+use language::{Buffer, Encoding, LineEnding};
+use ui::{h_flex, Button, ButtonCommon, Clickable, FluentBuilder, LabelSize, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView, Workspace};
+
+use crate::{line_ending_name, EncodingSelector, SelectorMode};
+
+/// Shows the encoding and line ending of the active buffer's file in the status bar.
+pub struct ActiveBufferEncoding {
+    active_buffer: Option<(Encoding, LineEnding)>,
+    workspace: WeakView<Workspace>,
+    _observe_active_editor: Option<Subscription>,
+    _observe_active_buffer: Option<Subscription>,
+}
+
+impl ActiveBufferEncoding {
+    pub fn new(workspace: &Workspace) -> Self {
+        Self {
+            active_buffer: None,
+            workspace: workspace.weak_handle(),
+            _observe_active_editor: None,
+            _observe_active_buffer: None,
+        }
+    }
+
+    fn update_buffer(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        self.active_buffer = None;
+        self._observe_active_buffer = None;
+
+        if let Some((_, buffer, _)) = editor.read(cx).active_excerpt(cx) {
+            // Only files on the local file system can be reopened or saved
+            // with another encoding.
+            let is_local_file = buffer
+                .read(cx)
+                .file()
+                .map_or(false, |file| file.as_local().is_some());
+            if is_local_file {
+                self.read_buffer(&buffer, cx);
+                self._observe_active_buffer = Some(cx.observe(&buffer, Self::read_buffer_changes));
+            }
+        }
+
+        cx.notify();
+    }
+
+    fn read_buffer_changes(&mut self, buffer: Model<Buffer>, cx: &mut ViewContext<Self>) {
+        self.read_buffer(&buffer, cx);
+        cx.notify();
+    }
+
+    fn read_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ViewContext<Self>) {
+        let buffer = buffer.read(cx);
+        self.active_buffer = Some((buffer.encoding(), buffer.line_ending()));
+    }
+
+    fn toggle_selector(&self, mode: SelectorMode, cx: &mut ViewContext<Self>) {
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace.update(cx, |workspace, cx| {
+                EncodingSelector::toggle(workspace, mode, cx);
+            });
+        }
+    }
+}
+
+impl Render for ActiveBufferEncoding {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.active_buffer, |el, (encoding, line_ending)| {
+            el.child(
+                h_flex()
+                    .child(
+                        Button::new("change-encoding", encoding.name())
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_selector(SelectorMode::ReopenWithEncoding, cx)
+                            }))
+                            .tooltip(|cx| Tooltip::text("Reopen with Encoding", cx)),
+                    )
+                    .child(
+                        Button::new("change-line-ending", line_ending_name(line_ending))
+                            .label_size(LabelSize::Small)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_selector(SelectorMode::SaveWithLineEnding, cx)
+                            }))
+                            .tooltip(|cx| Tooltip::text("Save with Line Ending", cx)),
+                    ),
+            )
+        })
+    }
+}
+
+impl StatusItemView for ActiveBufferEncoding {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, Self::update_buffer));
+            self.update_buffer(editor, cx);
+        } else {
+            self.active_buffer = None;
+            self._observe_active_editor = None;
+            self._observe_active_buffer = None;
+        }
+
+        cx.notify();
+    }
+}
@@ -0,0 +1,475 @@
+mod active_buffer_encoding;
+
+pub use active_buffer_encoding::ActiveBufferEncoding;
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    ParentElement, Render, Styled, View, ViewContext, VisualContext, WeakView,
+};
+use language::{Buffer, Encoding, LineEnding};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+actions!(
+    encoding_selector,
+    [ReopenWithEncoding, SaveWithEncoding, SaveWithLineEnding]
+);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(EncodingSelector::register).detach();
+}
+
+/// What choosing an item in the [EncodingSelector] does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectorMode {
+    /// Reload the buffer's file, decoding it with the chosen encoding.
+    ReopenWithEncoding,
+    /// Save the buffer's file, encoding it with the chosen encoding.
+    SaveWithEncoding,
+    /// Save the buffer's file with the chosen line ending.
+    SaveWithLineEnding,
+}
+
+pub struct EncodingSelector {
+    picker: View<Picker<EncodingSelectorDelegate>>,
+}
+
+impl EncodingSelector {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(move |workspace, _: &ReopenWithEncoding, cx| {
+            Self::toggle(workspace, SelectorMode::ReopenWithEncoding, cx);
+        });
+        workspace.register_action(move |workspace, _: &SaveWithEncoding, cx| {
+            Self::toggle(workspace, SelectorMode::SaveWithEncoding, cx);
+        });
+        workspace.register_action(move |workspace, _: &SaveWithLineEnding, cx| {
+            Self::toggle(workspace, SelectorMode::SaveWithLineEnding, cx);
+        });
+    }
+
+    fn toggle(
+        workspace: &mut Workspace,
+        mode: SelectorMode,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Option<()> {
+        let (_, buffer, _) = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .active_excerpt(cx)?;
+        // Encodings only apply to files that are read from and written to the
+        // local file system.
+        buffer.read(cx).file()?.as_local()?;
+        let project = workspace.project().clone();
+
+        workspace.toggle_modal(cx, move |cx| {
+            EncodingSelector::new(buffer, project, mode, cx)
+        });
+        Some(())
+    }
+
+    fn new(
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        mode: SelectorMode,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate =
+            EncodingSelectorDelegate::new(cx.view().downgrade(), buffer, project, mode, cx);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for EncodingSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for EncodingSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EncodingSelector {}
+impl ModalView for EncodingSelector {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Choice {
+    Encoding(Encoding),
+    LineEnding(LineEnding),
+}
+
+impl Choice {
+    fn description(&self) -> &'static str {
+        match self {
+            Choice::Encoding(encoding) => encoding.description(),
+            Choice::LineEnding(LineEnding::Unix) => "Linux and macOS",
+            Choice::LineEnding(LineEnding::Windows) => "Windows",
+        }
+    }
+}
+
+pub fn line_ending_name(line_ending: LineEnding) -> &'static str {
+    match line_ending {
+        LineEnding::Unix => "LF",
+        LineEnding::Windows => "CRLF",
+    }
+}
+
+pub struct EncodingSelectorDelegate {
+    encoding_selector: WeakView<EncodingSelector>,
+    buffer: Model<Buffer>,
+    project: Model<Project>,
+    mode: SelectorMode,
+    choices: Vec<Choice>,
+    current_choice: Choice,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl EncodingSelectorDelegate {
+    fn new(
+        encoding_selector: WeakView<EncodingSelector>,
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        mode: SelectorMode,
+        cx: &AppContext,
+    ) -> Self {
+        let (choices, current_choice) = match mode {
+            SelectorMode::ReopenWithEncoding | SelectorMode::SaveWithEncoding => (
+                Encoding::all().into_iter().map(Choice::Encoding).collect(),
+                Choice::Encoding(buffer.read(cx).encoding()),
+            ),
+            SelectorMode::SaveWithLineEnding => (
+                vec![
+                    Choice::LineEnding(LineEnding::Unix),
+                    Choice::LineEnding(LineEnding::Windows),
+                ],
+                Choice::LineEnding(buffer.read(cx).line_ending()),
+            ),
+        };
+        let candidates = choices
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, choice)| {
+                let name = match choice {
+                    Choice::Encoding(encoding) => encoding.name(),
+                    Choice::LineEnding(line_ending) => line_ending_name(*line_ending).to_string(),
+                };
+                StringMatchCandidate::new(candidate_id, name)
+            })
+            .collect::<Vec<_>>();
+        let selected_index = choices
+            .iter()
+            .position(|choice| *choice == current_choice)
+            .unwrap_or(0);
+
+        Self {
+            encoding_selector,
+            buffer,
+            project,
+            mode,
+            choices,
+            current_choice,
+            candidates,
+            matches: vec![],
+            selected_index,
+        }
+    }
+}
+
+impl PickerDelegate for EncodingSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.mode {
+            SelectorMode::ReopenWithEncoding => "Reopen with encoding...".into(),
+            SelectorMode::SaveWithEncoding => "Save with encoding...".into(),
+            SelectorMode::SaveWithLineEnding => "Save with line ending...".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let choice = self.choices[mat.candidate_id];
+            let buffer = self.buffer.clone();
+            match (self.mode, choice) {
+                (SelectorMode::ReopenWithEncoding, Choice::Encoding(encoding)) => {
+                    buffer.update(cx, |buffer, cx| {
+                        buffer.set_encoding(encoding, cx);
+                        // Unlike reloading through the project, this also reloads
+                        // buffers with unsaved changes. The reload can be undone.
+                        let _ = buffer.reload(cx);
+                    });
+                }
+                (SelectorMode::SaveWithEncoding, Choice::Encoding(encoding)) => {
+                    let previous_encoding = buffer.read(cx).encoding();
+                    buffer.update(cx, |buffer, cx| buffer.set_encoding(encoding, cx));
+                    let save = self
+                        .project
+                        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx));
+                    cx.spawn(|_, mut cx| async move {
+                        let result = save.await;
+                        // Text that can't be encoded leaves the file as it was,
+                        // so it keeps being saved with its previous encoding.
+                        if result.is_err() {
+                            buffer.update(&mut cx, |buffer, cx| {
+                                buffer.set_encoding(previous_encoding, cx)
+                            })?;
+                        }
+                        result
+                    })
+                    .detach_and_prompt_err(
+                        "Failed to save",
+                        cx,
+                        |error, _| Some(error.to_string()),
+                    );
+                }
+                (SelectorMode::SaveWithLineEnding, Choice::LineEnding(line_ending)) => {
+                    buffer.update(cx, |buffer, cx| buffer.set_line_ending(line_ending, cx));
+                    self.project
+                        .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                        .detach_and_prompt_err("Failed to save", cx, |error, _| {
+                            Some(error.to_string())
+                        });
+                }
+                _ => {}
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.encoding_selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let choice = self.choices[mat.candidate_id];
+        let mut label = mat.string.clone();
+        if choice == self.current_choice {
+            label.push_str(" (current)");
+        }
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(label, mat.positions.clone()))
+                .end_slot(
+                    Label::new(choice.description())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Fs;
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_reopening_with_encoding(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree("/root", json!({ "a.txt": "caf\u{e9}\n" }))
+            .await;
+        let (workspace, cx) = build_workspace(&app_state, cx).await;
+        let editor = open_editor(&workspace, "a.txt", cx).await;
+        let buffer = editor.update(cx, |editor, cx| {
+            editor.buffer().read(cx).as_singleton().unwrap()
+        });
+        buffer.update(cx, |buffer, _| {
+            assert_eq!(buffer.encoding(), Encoding::utf8());
+            assert_eq!(buffer.text(), "caf\u{e9}\n");
+        });
+
+        choose(ReopenWithEncoding, "windows-1252", cx);
+        buffer.update(cx, |buffer, _| {
+            assert_eq!(buffer.encoding().name(), "windows-1252");
+            // Each byte of the UTF-8 encoded character is decoded on its own.
+            assert_eq!(buffer.text(), "caf\u{c3}\u{a9}\n");
+            assert!(!buffer.is_dirty());
+        });
+
+        choose(ReopenWithEncoding, "UTF-8", cx);
+        buffer.update(cx, |buffer, _| {
+            assert_eq!(buffer.encoding(), Encoding::utf8());
+            assert_eq!(buffer.text(), "caf\u{e9}\n");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_saving_with_encoding(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree("/root", json!({ "a.txt": "caf\u{e9}\n" }))
+            .await;
+        let (workspace, cx) = build_workspace(&app_state, cx).await;
+        let editor = open_editor(&workspace, "a.txt", cx).await;
+        let buffer = editor.update(cx, |editor, cx| {
+            editor.buffer().read(cx).as_singleton().unwrap()
+        });
+
+        choose(SaveWithEncoding, "UTF-16LE", cx);
+        buffer.update(cx, |buffer, _| {
+            assert_eq!(buffer.encoding().name(), "UTF-16LE");
+            assert_eq!(buffer.text(), "caf\u{e9}\n");
+        });
+        assert_eq!(
+            app_state
+                .fs
+                .load_bytes(Path::new("/root/a.txt"))
+                .await
+                .unwrap(),
+            b"c\0a\0f\0\xE9\0\n\0"
+        );
+
+        choose(SaveWithLineEnding, "CRLF", cx);
+        assert_eq!(
+            app_state
+                .fs
+                .load_bytes(Path::new("/root/a.txt"))
+                .await
+                .unwrap(),
+            b"c\0a\0f\0\xE9\0\r\0\n\0"
+        );
+    }
+
+    fn choose(action: impl gpui::Action, query: &str, cx: &mut VisualTestContext) {
+        cx.dispatch_action(action);
+        cx.simulate_input(query);
+        cx.dispatch_action(menu::Confirm);
+        cx.run_until_parked();
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            super::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+
+    async fn build_workspace<'a>(
+        app_state: &Arc<AppState>,
+        cx: &'a mut TestAppContext,
+    ) -> (View<Workspace>, &'a mut VisualTestContext) {
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        cx.add_window_view(|cx| Workspace::test_new(project, cx))
+    }
+
+    async fn open_editor(
+        workspace: &View<Workspace>,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let worktree_id = cx.update(|cx| {
+            workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .worktrees()
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+        let path = path.to_string();
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, path), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap()
+    }
+}
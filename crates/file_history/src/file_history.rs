@@ -0,0 +1,772 @@
+use std::{
+    cell::Cell,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use db::file_history::FILE_HISTORY;
+use editor::{Editor, EditorEvent, MultiBuffer};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
+    Global, HighlightStyle, Model, ParentElement, Render, Styled, View, ViewContext, VisualContext,
+    WeakView,
+};
+use language::{Buffer, Point};
+use picker::{Picker, PickerDelegate};
+use settings::Settings;
+use theme::ActiveTheme;
+use time::OffsetDateTime;
+use ui::{prelude::*, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace, WorkspaceSettings};
+
+actions!(file_history, [Toggle]);
+
+/// Files bigger than this aren't recorded, to keep the history database small.
+const MAX_FILE_LEN: usize = 4 * 1024 * 1024;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// How many unchanged lines are shown around the changes in a diff.
+const DIFF_CONTEXT_LINES: u32 = 3;
+
+/// How often the expired snapshots of every file are pruned. Those of a file
+/// that is saved are pruned right away, so this only matters for the files
+/// that stopped being saved.
+const EXPIRED_SNAPSHOTS_PRUNING_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(ExpiredSnapshotsPruning::default());
+    cx.observe_new_views(FileHistory::register).detach();
+    cx.observe_new_views(record_snapshots).detach();
+}
+
+fn record_snapshots(_: &mut Editor, cx: &mut ViewContext<Editor>) {
+    cx.subscribe(&cx.view().clone(), |editor, _, event: &EditorEvent, cx| {
+        match event {
+            // The first time a file is edited, the version on disk is recorded
+            // as well, so that it can be restored once the file is saved.
+            EditorEvent::DirtyChanged => {
+                for buffer in editor.buffer().read(cx).all_buffers() {
+                    if buffer.read(cx).is_dirty() {
+                        save_original_snapshot(&buffer, cx);
+                    }
+                }
+            }
+            EditorEvent::Saved => {
+                for buffer in editor.buffer().read(cx).all_buffers() {
+                    save_snapshot(&buffer, cx);
+                }
+            }
+            _ => {}
+        }
+    })
+    .detach();
+}
+
+/// Returns the path to record the given buffer's file under, if it's a local
+/// file that isn't private.
+fn recorded_path(buffer: &Buffer, cx: &AppContext) -> Option<PathBuf> {
+    if !WorkspaceSettings::get_global(cx).file_history.enabled {
+        return None;
+    }
+    let file = buffer.file()?;
+    if file.is_private() {
+        return None;
+    }
+    Some(file.as_local()?.abs_path(cx))
+}
+
+fn save_snapshot(buffer: &Model<Buffer>, cx: &AppContext) {
+    let buffer = buffer.read(cx);
+    if buffer.is_dirty() || buffer.len() > MAX_FILE_LEN {
+        return;
+    }
+    let Some(path) = recorded_path(buffer, cx) else {
+        return;
+    };
+    let content = buffer.text();
+    let limits = HistoryLimits::new(cx);
+    let prune_expired = ExpiredSnapshotsPruning::is_due(cx);
+    cx.background_executor()
+        .spawn(async move { record(path, content, limits, prune_expired).await })
+        .detach_and_log_err(cx);
+}
+
+/// Records the content that the given buffer's file has on disk, unless the
+/// file already has snapshots.
+fn save_original_snapshot(buffer: &Model<Buffer>, cx: &AppContext) {
+    let Some(path) = recorded_path(buffer.read(cx), cx) else {
+        return;
+    };
+    let buffer = buffer.downgrade();
+    let limits = HistoryLimits::new(cx);
+    cx.spawn(|mut cx| async move {
+        let has_snapshots = cx
+            .background_executor()
+            .spawn({
+                let path = path.clone();
+                async move { FILE_HISTORY.has_snapshots(path) }
+            })
+            .await?;
+        if has_snapshots {
+            return Ok(());
+        }
+
+        let load = buffer.update(&mut cx, |buffer, cx| {
+            let file = buffer.file()?.as_local()?;
+            Some(file.load_bytes(cx))
+        })?;
+        let Some(load) = load else {
+            return Ok(());
+        };
+        let content = load.await?;
+        if content.len() > MAX_FILE_LEN {
+            return Ok(());
+        }
+        // Files that aren't valid UTF-8 are left out of the history.
+        let Ok(content) = String::from_utf8(content) else {
+            return Ok(());
+        };
+        let prune_expired = cx.update(|cx| ExpiredSnapshotsPruning::is_due(cx))?;
+        cx.background_executor()
+            .spawn(async move { record(path, content, limits, prune_expired).await })
+            .await
+    })
+    .detach_and_log_err(cx);
+}
+
+/// How long snapshots are kept for, from the settings.
+#[derive(Clone, Copy)]
+struct HistoryLimits {
+    max_snapshots_per_file: u32,
+    max_age: i64,
+}
+
+impl HistoryLimits {
+    fn new(cx: &AppContext) -> Self {
+        let settings = &WorkspaceSettings::get_global(cx).file_history;
+        Self {
+            max_snapshots_per_file: settings.max_snapshots_per_file,
+            max_age: settings.max_age_days as i64 * SECONDS_PER_DAY,
+        }
+    }
+}
+
+/// When the expired snapshots of every file were last pruned.
+#[derive(Default)]
+struct ExpiredSnapshotsPruning {
+    last_pruned_at: Cell<Option<Instant>>,
+}
+
+impl Global for ExpiredSnapshotsPruning {}
+
+impl ExpiredSnapshotsPruning {
+    /// Returns whether the expired snapshots of every file should be pruned
+    /// along with the next snapshot, assuming they will be.
+    fn is_due(cx: &AppContext) -> bool {
+        let last_pruned_at = &cx.global::<Self>().last_pruned_at;
+        let now = Instant::now();
+        let is_due = last_pruned_at.get().map_or(true, |last_pruned_at| {
+            now.duration_since(last_pruned_at) >= EXPIRED_SNAPSHOTS_PRUNING_INTERVAL
+        });
+        if is_due {
+            last_pruned_at.set(Some(now));
+        }
+        is_due
+    }
+}
+
+/// Records a snapshot of a file, then prunes the history, so that it never
+/// holds more than the settings allow.
+async fn record(
+    path: PathBuf,
+    content: String,
+    limits: HistoryLimits,
+    prune_expired: bool,
+) -> Result<()> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let saved_before = now - limits.max_age;
+    FILE_HISTORY
+        .save_snapshot(path.clone(), now, content)
+        .await?;
+    FILE_HISTORY
+        .prune_snapshots(path, saved_before, limits.max_snapshots_per_file)
+        .await?;
+    if prune_expired {
+        FILE_HISTORY.prune_expired_snapshots(saved_before).await?;
+    }
+    Ok(())
+}
+
+/// A timeline of the saved versions of the active file, which can be compared
+/// with the file's current content or restored.
+pub struct FileHistory {
+    picker: View<Picker<FileHistoryDelegate>>,
+}
+
+impl FileHistory {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &Toggle, cx| {
+            Self::toggle(workspace, cx);
+        });
+    }
+
+    fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> Option<()> {
+        let buffer = workspace
+            .active_item(cx)?
+            .act_as::<Editor>(cx)?
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .as_singleton()?;
+        let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+        let snapshots = cx
+            .background_executor()
+            .spawn(async move { FILE_HISTORY.snapshots(path) });
+        cx.spawn(|workspace, mut cx| async move {
+            let snapshots = snapshots.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let now = OffsetDateTime::now_utc();
+                let timezone = cx.local_timezone();
+                let snapshots = snapshots
+                    .into_iter()
+                    .filter_map(|(id, saved_at, len)| {
+                        let saved_at = OffsetDateTime::from_unix_timestamp(saved_at).ok()?;
+                        Some(Snapshot {
+                            id,
+                            label: time_format::format_localized_timestamp(now, saved_at, timezone),
+                            len,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                let workspace_handle = cx.view().downgrade();
+                workspace.toggle_modal(cx, move |cx| {
+                    FileHistory::new(workspace_handle, buffer, snapshots, cx)
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+        Some(())
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        buffer: Model<Buffer>,
+        snapshots: Vec<Snapshot>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate =
+            FileHistoryDelegate::new(cx.view().downgrade(), workspace, buffer, snapshots);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for FileHistory {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for FileHistory {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for FileHistory {}
+impl ModalView for FileHistory {}
+
+struct Snapshot {
+    id: i64,
+    label: String,
+    len: usize,
+}
+
+pub struct FileHistoryDelegate {
+    file_history: WeakView<FileHistory>,
+    workspace: WeakView<Workspace>,
+    buffer: Model<Buffer>,
+    snapshots: Vec<Snapshot>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+enum AddedLines {}
+enum DeletedLines {}
+
+impl FileHistoryDelegate {
+    fn new(
+        file_history: WeakView<FileHistory>,
+        workspace: WeakView<Workspace>,
+        buffer: Model<Buffer>,
+        snapshots: Vec<Snapshot>,
+    ) -> Self {
+        Self {
+            file_history,
+            workspace,
+            buffer,
+            snapshots,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    /// Opens a read-only editor with the changes made to the file since the
+    /// given snapshot was saved.
+    fn show_diff(&self, snapshot: &Snapshot, cx: &mut ViewContext<Picker<Self>>) -> Option<()> {
+        let snapshot_id = snapshot.id;
+        let buffer = self.buffer.read(cx);
+        let new_text = buffer.text();
+        let file_name = buffer.file()?.file_name(cx).to_string_lossy().into_owned();
+        let title = format!("{file_name} (since {})", snapshot.label);
+        let diff = cx.background_executor().spawn(async move {
+            let old_text = FILE_HISTORY
+                .snapshot_content(snapshot_id)?
+                .ok_or_else(|| anyhow!("snapshot {snapshot_id} not found"))?;
+            let diff = git::diff::unified_diff(&old_text, &new_text, DIFF_CONTEXT_LINES)
+                .ok_or_else(|| anyhow!("failed to compute diff"))?;
+            anyhow::Ok(diff)
+        });
+
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let mut diff = diff.await?;
+            if diff.is_empty() {
+                diff = "No changes since this version was saved.\n".to_string();
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                let diff_buffer =
+                    project.update(cx, |project, cx| project.create_buffer(&diff, None, cx))?;
+                let multibuffer =
+                    cx.new_model(|cx| MultiBuffer::singleton(diff_buffer, cx).with_title(title));
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(multibuffer, Some(project), cx);
+                    editor.set_read_only(true);
+                    highlight_diff(&mut editor, &diff, cx);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), cx);
+                anyhow::Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+        Some(())
+    }
+
+    /// Replaces the file's content with that of the given snapshot, as an edit
+    /// that can be undone.
+    fn restore(&self, snapshot: &Snapshot, cx: &mut ViewContext<Picker<Self>>) {
+        let snapshot_id = snapshot.id;
+        let text = cx
+            .background_executor()
+            .spawn(async move { FILE_HISTORY.snapshot_content(snapshot_id) });
+        let buffer = self.buffer.downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let text = text
+                .await?
+                .ok_or_else(|| anyhow!("snapshot {snapshot_id} not found"))?;
+            let diff = buffer.update(&mut cx, |buffer, cx| buffer.diff(text, cx))?;
+            let diff = diff.await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.apply_diff(diff, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+fn highlight_diff(editor: &mut Editor, diff: &str, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut added_lines = Vec::new();
+    let mut deleted_lines = Vec::new();
+    for (row, line) in diff.lines().enumerate() {
+        let lines = if line.starts_with('+') {
+            &mut added_lines
+        } else if line.starts_with('-') {
+            &mut deleted_lines
+        } else {
+            continue;
+        };
+        let row = row as u32;
+        lines.push(
+            snapshot.anchor_before(Point::new(row, 0))
+                ..snapshot.anchor_after(Point::new(row, line.len() as u32)),
+        );
+    }
+
+    let status = cx.theme().status();
+    let added_style = HighlightStyle {
+        color: Some(status.created),
+        ..Default::default()
+    };
+    let deleted_style = HighlightStyle {
+        color: Some(status.deleted),
+        ..Default::default()
+    };
+    editor.highlight_text::<AddedLines>(added_lines, added_style, cx);
+    editor.highlight_text::<DeletedLines>(deleted_lines, deleted_style, cx);
+}
+
+fn format_len(len: usize) -> String {
+    const KB: f64 = 1024.;
+    let len = len as f64;
+    if len < KB {
+        format!("{len} B")
+    } else if len < KB * KB {
+        format!("{:.1} KB", len / KB)
+    } else {
+        format!("{:.1} MB", len / (KB * KB))
+    }
+}
+
+impl PickerDelegate for FileHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search saved versions...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let snapshot = &self.snapshots[mat.candidate_id];
+            if secondary {
+                self.restore(snapshot, cx);
+            } else {
+                self.show_diff(snapshot, cx);
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.file_history
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .snapshots
+            .iter()
+            .enumerate()
+            .map(|(id, snapshot)| StringMatchCandidate::new(id, snapshot.label.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                let mut matches = match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await;
+                // Keep the timeline in order, newest first.
+                matches.sort_by_key(|mat| mat.candidate_id);
+                matches
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let snapshot = &self.snapshots[mat.candidate_id];
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(format_len(snapshot.len))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .p_2()
+                .gap_2()
+                .justify_end()
+                .child(
+                    Button::new("file-history-show-diff", "Compare")
+                        .key_binding(KeyBinding::for_action(&menu::Confirm, cx))
+                        .on_click(cx.listener(|picker, _, cx| picker.delegate.confirm(false, cx))),
+                )
+                .child(
+                    Button::new("file-history-restore", "Restore")
+                        .key_binding(KeyBinding::for_action(&menu::SecondaryConfirm, cx))
+                        .on_click(cx.listener(|picker, _, cx| picker.delegate.confirm(true, cx))),
+                )
+                .into_any(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::Editor;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use workspace::{item::Item, AppState};
+
+    #[gpui::test]
+    async fn test_recording_snapshots(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree("/recording", json!({ "a.txt": "one", ".env": "SECRET=1" }))
+            .await;
+        let (workspace, cx) = build_workspace(&app_state, "/recording", cx).await;
+        let path = PathBuf::from("/recording/a.txt");
+
+        // Files that are only opened aren't recorded.
+        let editor = open_editor(&workspace, "a.txt", cx).await;
+        cx.run_until_parked();
+        assert!(!FILE_HISTORY.has_snapshots(path.clone()).unwrap());
+
+        // Once the file is edited, its version on disk is recorded, and each
+        // version that gets saved is recorded after it.
+        edit(&editor, " two", cx);
+        assert_eq!(snapshot_contents(&path), ["one"]);
+        save(&workspace, &editor, cx).await;
+        assert_eq!(snapshot_contents(&path), ["one two", "one"]);
+
+        // Private files are never recorded.
+        let editor = open_editor(&workspace, ".env", cx).await;
+        edit(&editor, "2", cx);
+        save(&workspace, &editor, cx).await;
+        assert!(!FILE_HISTORY
+            .has_snapshots(PathBuf::from("/recording/.env"))
+            .unwrap());
+    }
+
+    #[gpui::test]
+    async fn test_pruning_snapshots(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree("/pruning", json!({ "a.txt": "" }))
+            .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store
+                    .set_user_settings(r#"{ "file_history": { "max_snapshots_per_file": 2 } }"#, cx)
+                    .unwrap();
+            })
+        });
+        let (workspace, cx) = build_workspace(&app_state, "/pruning", cx).await;
+        let path = PathBuf::from("/pruning/a.txt");
+
+        // Snapshots that are too old are dropped, even those of other files.
+        let expired_path = PathBuf::from("/pruning/expired.txt");
+        FILE_HISTORY
+            .save_snapshot(expired_path.clone(), 0, "expired".into())
+            .await
+            .unwrap();
+
+        let editor = open_editor(&workspace, "a.txt", cx).await;
+        for text in ["1", "2", "3"] {
+            edit(&editor, text, cx);
+            save(&workspace, &editor, cx).await;
+        }
+        assert_eq!(snapshot_contents(&path), ["123", "12"]);
+        assert!(!FILE_HISTORY.has_snapshots(expired_path.clone()).unwrap());
+
+        // The snapshots of other files are only pruned once in a while.
+        FILE_HISTORY
+            .save_snapshot(expired_path.clone(), 0, "expired".into())
+            .await
+            .unwrap();
+        edit(&editor, "4", cx);
+        save(&workspace, &editor, cx).await;
+        assert_eq!(snapshot_contents(&path), ["1234", "123"]);
+        assert!(FILE_HISTORY.has_snapshots(expired_path).unwrap());
+    }
+
+    #[gpui::test]
+    async fn test_restoring_snapshots(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree("/restoring", json!({ "a.txt": "one" }))
+            .await;
+        let (workspace, cx) = build_workspace(&app_state, "/restoring", cx).await;
+        let editor = open_editor(&workspace, "a.txt", cx).await;
+        edit(&editor, " two", cx);
+        save(&workspace, &editor, cx).await;
+
+        cx.dispatch_action(Toggle);
+        cx.run_until_parked();
+        let picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<FileHistory>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        picker
+            .update(cx, |picker, cx| {
+                picker.delegate.update_matches(String::new(), cx)
+            })
+            .await;
+        picker.update(cx, |picker, cx| {
+            assert_eq!(picker.delegate.match_count(), 2);
+            // Snapshots are listed newest first.
+            picker.delegate.set_selected_index(1, cx);
+            picker.delegate.confirm(true, cx);
+        });
+        cx.run_until_parked();
+
+        // The restored content is an unsaved edit, which can be undone.
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "one");
+            assert!(editor.is_dirty(cx));
+            editor.undo(&editor::actions::Undo, cx);
+            assert_eq!(editor.text(cx), "one two");
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            super::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+
+    async fn build_workspace<'a>(
+        app_state: &Arc<AppState>,
+        root: &str,
+        cx: &'a mut TestAppContext,
+    ) -> (View<Workspace>, &'a mut VisualTestContext) {
+        let project = Project::test(app_state.fs.clone(), [root.as_ref()], cx).await;
+        cx.add_window_view(|cx| Workspace::test_new(project, cx))
+    }
+
+    async fn open_editor(
+        workspace: &View<Workspace>,
+        path: &str,
+        cx: &mut VisualTestContext,
+    ) -> View<Editor> {
+        let worktree_id = cx.update(|cx| {
+            workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .worktrees()
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+        let path = path.to_string();
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, path), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap()
+    }
+
+    fn edit(editor: &View<Editor>, text: &str, cx: &mut VisualTestContext) {
+        editor.update(cx, |editor, cx| {
+            editor.move_to_end(&editor::actions::MoveToEnd, cx);
+            editor.insert(text, cx);
+        });
+        cx.run_until_parked();
+    }
+
+    async fn save(workspace: &View<Workspace>, editor: &View<Editor>, cx: &mut VisualTestContext) {
+        let project = workspace.update(cx, |workspace, _| workspace.project().clone());
+        editor
+            .update(cx, |editor, cx| editor.save(project, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+    }
+
+    fn snapshot_contents(path: &PathBuf) -> Vec<String> {
+        FILE_HISTORY
+            .snapshots(path.clone())
+            .unwrap()
+            .into_iter()
+            .map(|(id, _, _)| FILE_HISTORY.snapshot_content(id).unwrap().unwrap())
+            .collect()
+    }
+}
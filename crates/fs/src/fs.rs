@@ -49,7 +49,9 @@ pub trait Fs: Send + Sync {
     async fn remove_file(&self, path: &Path, options: RemoveOptions) -> Result<()>;
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>>;
     async fn load(&self, path: &Path) -> Result<String>;
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
+    async fn write(&self, path: &Path, content: &[u8]) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
@@ -101,6 +103,8 @@ pub struct RemoveOptions {
 pub struct Metadata {
     pub inode: u64,
     pub mtime: SystemTime,
+    /// The size of the file, in bytes.
+    pub len: u64,
     pub is_symlink: bool,
     pub is_dir: bool,
 }
@@ -187,23 +191,29 @@ impl Fs for RealFs {
         Ok(text)
     }
 
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        Ok(smol::fs::read(path).await?)
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+        smol::unblock(move || write_atomically(&path, data.as_bytes(), None)).await
+    }
+
+    async fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+        if let Some(path) = path.parent() {
+            self.create_dir(path).await?;
+        }
+        let path = path.to_path_buf();
+        let content = content.to_vec();
         smol::unblock(move || {
-            let mut tmp_file = if cfg!(target_os = "linux") {
-                // Use the directory of the destination as temp dir to avoid
-                // invalid cross-device link error, and XDG_CACHE_DIR for fallback.
-                // See https://github.com/zed-industries/zed/pull/8437 for more details.
-                NamedTempFile::new_in(path.parent().unwrap_or(&paths::TEMP_DIR))
-            } else {
-                NamedTempFile::new()
-            }?;
-            tmp_file.write_all(data.as_bytes())?;
-            tmp_file.persist(path)?;
-            Ok::<(), anyhow::Error>(())
+            // The file is replaced by a new one, which has to be given the
+            // permissions of the file it replaces.
+            let permissions = std::fs::metadata(&path)
+                .ok()
+                .map(|metadata| metadata.permissions());
+            write_atomically(&path, &content, permissions)
         })
-        .await?;
-
-        Ok(())
+        .await
     }
 
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
@@ -258,6 +268,7 @@ impl Fs for RealFs {
         Ok(Some(Metadata {
             inode,
             mtime: metadata.modified().unwrap(),
+            len: metadata.len(),
             is_symlink,
             is_dir: metadata.file_type().is_dir(),
         }))
@@ -400,6 +411,29 @@ impl Fs for RealFs {
     }
 }
 
+/// Writes the data to a temporary file, which is then renamed to the given
+/// path, so that the file is never left partially written.
+fn write_atomically(
+    path: &Path,
+    data: &[u8],
+    permissions: Option<std::fs::Permissions>,
+) -> Result<()> {
+    let mut tmp_file = if cfg!(target_os = "linux") {
+        // Use the directory of the destination as temp dir to avoid
+        // invalid cross-device link error, and XDG_CACHE_DIR for fallback.
+        // See https://github.com/zed-industries/zed/pull/8437 for more details.
+        NamedTempFile::new_in(path.parent().unwrap_or(&paths::TEMP_DIR))
+    } else {
+        NamedTempFile::new()
+    }?;
+    tmp_file.write_all(data)?;
+    if let Some(permissions) = permissions {
+        tmp_file.as_file().set_permissions(permissions)?;
+    }
+    tmp_file.persist(path)?;
+    Ok(())
+}
+
 pub fn fs_events_paths(events: Vec<Event>) -> Vec<PathBuf> {
     events.into_iter().map(|event| event.path).collect()
 }
@@ -429,7 +463,7 @@ enum FakeFsEntry {
     File {
         inode: u64,
         mtime: SystemTime,
-        content: String,
+        content: Vec<u8>,
     },
     Dir {
         inode: u64,
@@ -579,6 +613,10 @@ impl FakeFs {
         self.write_file_internal(path, content).unwrap()
     }
 
+    pub async fn insert_file_bytes(&self, path: impl AsRef<Path>, content: Vec<u8>) {
+        self.write_file_internal(path, content).unwrap()
+    }
+
     pub async fn insert_symlink(&self, path: impl AsRef<Path>, target: PathBuf) {
         let mut state = self.state.lock();
         let path = path.as_ref();
@@ -598,7 +636,12 @@ impl FakeFs {
         state.emit_event(&[path]);
     }
 
-    pub fn write_file_internal(&self, path: impl AsRef<Path>, content: String) -> Result<()> {
+    pub fn write_file_internal(
+        &self,
+        path: impl AsRef<Path>,
+        content: impl Into<Vec<u8>>,
+    ) -> Result<()> {
+        let content = content.into();
         let mut state = self.state.lock();
         let path = path.as_ref();
         let inode = state.next_inode;
@@ -832,7 +875,7 @@ impl FakeFsEntry {
         matches!(self, Self::Symlink { .. })
     }
 
-    fn file_content(&self, path: &Path) -> Result<&String> {
+    fn file_content(&self, path: &Path) -> Result<&Vec<u8>> {
         if let Self::File { content, .. } = self {
             Ok(content)
         } else {
@@ -840,7 +883,7 @@ impl FakeFsEntry {
         }
     }
 
-    fn set_file_content(&mut self, path: &Path, new_content: String) -> Result<()> {
+    fn set_file_content(&mut self, path: &Path, new_content: Vec<u8>) -> Result<()> {
         if let Self::File { content, mtime, .. } = self {
             *mtime = SystemTime::now();
             *content = new_content;
@@ -909,7 +952,7 @@ impl Fs for FakeFs {
         let file = Arc::new(Mutex::new(FakeFsEntry::File {
             inode,
             mtime,
-            content: String::new(),
+            content: Vec::new(),
         }));
         state.write_path(path, |entry| {
             match entry {
@@ -1000,7 +1043,7 @@ impl Fs for FakeFs {
                 e.insert(Arc::new(Mutex::new(FakeFsEntry::File {
                     inode,
                     mtime,
-                    content: String::new(),
+                    content: Vec::new(),
                 })))
                 .clone(),
             )),
@@ -1079,11 +1122,16 @@ impl Fs for FakeFs {
     }
 
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>> {
-        let text = self.load(path).await?;
-        Ok(Box::new(io::Cursor::new(text)))
+        let bytes = self.load_bytes(path).await?;
+        Ok(Box::new(io::Cursor::new(bytes)))
     }
 
     async fn load(&self, path: &Path) -> Result<String> {
+        let content = self.load_bytes(path).await?;
+        Ok(String::from_utf8(content)?)
+    }
+
+    async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>> {
         let path = normalize_path(path);
         self.simulate_random_delay().await;
         let state = self.state.lock();
@@ -1100,10 +1148,17 @@ impl Fs for FakeFs {
         Ok(())
     }
 
+    async fn write(&self, path: &Path, content: &[u8]) -> Result<()> {
+        self.simulate_random_delay().await;
+        let path = normalize_path(path);
+        self.write_file_internal(path, content.to_vec())?;
+        Ok(())
+    }
+
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
-        let content = chunks(text, line_ending).collect();
+        let content: String = chunks(text, line_ending).collect();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
@@ -1150,15 +1205,21 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    content,
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: content.len() as u64,
                     is_dir: false,
                     is_symlink,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
+                    len: 0,
                     is_dir: true,
                     is_symlink,
                 },
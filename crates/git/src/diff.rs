@@ -275,6 +275,50 @@ impl BufferDiff {
     }
 }
 
+/// Returns a unified diff of two texts, with the given number of context lines
+/// around each hunk, or `None` if git failed to diff them.
+pub fn unified_diff(old: &str, new: &str, context_lines: u32) -> Option<String> {
+    let mut options = GitOptions::default();
+    options.context_lines(context_lines);
+    let patch = GitPatch::from_buffers(
+        old.as_bytes(),
+        None,
+        new.as_bytes(),
+        None,
+        Some(&mut options),
+    )
+    .map_err(|err| log::error!("`GitPatch::from_buffers` failed: {}", err))
+    .ok()?;
+
+    let mut diff = String::new();
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index).ok()?;
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            hunk.old_start(),
+            hunk.old_lines(),
+            hunk.new_start(),
+            hunk.new_lines()
+        ));
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(hunk_index, line_index).ok()?;
+            let origin = match line.origin_value() {
+                GitDiffLineType::Addition => '+',
+                GitDiffLineType::Deletion => '-',
+                GitDiffLineType::Context => ' ',
+                // Skip the markers for a missing newline at the end of the text.
+                _ => continue,
+            };
+            diff.push(origin);
+            diff.push_str(&String::from_utf8_lossy(line.content()));
+            if !diff.ends_with('\n') {
+                diff.push('\n');
+            }
+        }
+    }
+    Some(diff)
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -409,4 +453,41 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "
+            one
+            two
+            three
+            four
+            five
+        "
+        .unindent();
+        let new = "
+            one
+            TWO
+            three
+            four
+            five
+            six
+        "
+        .unindent();
+
+        assert_eq!(unified_diff(&old, &old, 1).unwrap(), "");
+        assert_eq!(
+            unified_diff(&old, &new, 1).unwrap(),
+            "
+            @@ -1,3 +1,3 @@
+             one
+            -two
+            +TWO
+             three
+            @@ -5,1 +5,2 @@
+             five
+            +six
+            "
+            .unindent()
+        );
+    }
 }
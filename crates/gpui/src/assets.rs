@@ -52,6 +52,35 @@ impl ImageData {
         }
     }
 
+    /// Decode an image from the encoded bytes of a raster image format, such as PNG or JPEG.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let image = image::load_from_memory(bytes)?.into_bgra8();
+        Ok(Self::new(image))
+    }
+
+    /// Render an SVG document at its intrinsic size, multiplied by the given scale factor.
+    pub fn from_svg(bytes: &[u8], scale_factor: f32) -> Result<Self> {
+        let tree = usvg::Tree::from_data(bytes, &usvg::Options::default())?;
+        let svg_size = tree.svg_node().size;
+        let width = (svg_size.width() * scale_factor as f64).ceil().max(1.) as u32;
+        let height = (svg_size.height() * scale_factor as f64).ceil().max(1.) as u32;
+
+        let mut pixmap = tiny_skia::Pixmap::new(width, height)
+            .ok_or_else(|| anyhow!("invalid svg size {width}x{height}"))?;
+        resvg::render(&tree, usvg::FitTo::Width(width), pixmap.as_mut())
+            .ok_or_else(|| anyhow!("failed to render svg"))?;
+
+        // The pixmap holds premultiplied RGBA pixels, while images are stored as BGRA.
+        let mut data = Vec::with_capacity(pixmap.pixels().len() * 4);
+        for pixel in pixmap.pixels() {
+            let color = pixel.demultiply();
+            data.extend_from_slice(&[color.blue(), color.green(), color.red(), color.alpha()]);
+        }
+        let image = ImageBuffer::from_raw(width, height, data)
+            .ok_or_else(|| anyhow!("invalid svg image buffer"))?;
+        Ok(Self::new(image))
+    }
+
     /// Convert this image into a byte slice.
     pub fn as_bytes(&self) -> &[u8] {
         &self.data
@@ -0,0 +1,195 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use fs::Fs;
+use gpui::{AppContext, Context, EventEmitter, Model, ModelContext, Subscription, Task};
+use project::{worktree, Project, ProjectEntryId, ProjectPath, Worktree};
+
+/// The size of the largest file that can be opened, as files are held in memory
+/// in their entirety.
+pub(crate) const MAX_FILE_LEN: u64 = 16 * 1024 * 1024;
+
+/// The raw bytes of a file in the project, which can be edited in place and
+/// written back to disk.
+pub struct BinaryFile {
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    entry_id: Option<ProjectEntryId>,
+    fs: Arc<dyn Fs>,
+    bytes: Vec<u8>,
+    is_dirty: bool,
+    _worktree_subscription: Subscription,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryFileEvent {
+    Edited,
+    DirtyChanged,
+    Reloaded,
+}
+
+impl EventEmitter<BinaryFileEvent> for BinaryFile {}
+
+impl BinaryFile {
+    fn new(
+        project_path: ProjectPath,
+        abs_path: PathBuf,
+        worktree: Model<Worktree>,
+        fs: Arc<dyn Fs>,
+        bytes: Vec<u8>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let entry_id = worktree
+            .read(cx)
+            .entry_for_path(&project_path.path)
+            .map(|entry| entry.id);
+        let _worktree_subscription = cx.subscribe(&worktree, |this, worktree, event, cx| {
+            if let worktree::Event::UpdatedEntries(changes) = event {
+                if changes
+                    .iter()
+                    .any(|(path, _, _)| *path == this.project_path.path)
+                {
+                    this.entry_id = worktree
+                        .read(cx)
+                        .entry_for_path(&this.project_path.path)
+                        .map(|entry| entry.id);
+                    // Unsaved edits are never discarded because of a change on disk.
+                    if !this.is_dirty {
+                        this.reload(cx).detach_and_log_err(cx);
+                    }
+                }
+            }
+        });
+
+        Self {
+            project_path,
+            abs_path,
+            entry_id,
+            fs,
+            bytes,
+            is_dirty: false,
+            _worktree_subscription,
+        }
+    }
+
+    pub fn project_path(&self) -> &ProjectPath {
+        &self.project_path
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.is_dirty
+    }
+
+    /// Overwrites the byte at the given offset. The size of the file never changes.
+    pub fn set_byte(&mut self, offset: usize, value: u8, cx: &mut ModelContext<Self>) {
+        let Some(byte) = self.bytes.get_mut(offset) else {
+            return;
+        };
+        if *byte == value {
+            return;
+        }
+        *byte = value;
+        cx.emit(BinaryFileEvent::Edited);
+        self.set_dirty(true, cx);
+        cx.notify();
+    }
+
+    pub fn save(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let bytes = self.bytes.clone();
+        let abs_path = self.abs_path.clone();
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            fs.write(&abs_path, &bytes).await?;
+            this.update(&mut cx, |this, cx| {
+                // The file may have been edited again while it was being written.
+                if this.bytes == bytes {
+                    this.set_dirty(false, cx);
+                }
+            })
+        })
+    }
+
+    /// Reloads the file from disk, discarding any unsaved edits.
+    pub fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let abs_path = self.abs_path.clone();
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let bytes = load_bytes(fs.as_ref(), &abs_path).await?;
+            this.update(&mut cx, |this, cx| {
+                this.bytes = bytes;
+                this.set_dirty(false, cx);
+                cx.emit(BinaryFileEvent::Reloaded);
+                cx.notify();
+            })
+        })
+    }
+
+    fn set_dirty(&mut self, is_dirty: bool, cx: &mut ModelContext<Self>) {
+        if self.is_dirty != is_dirty {
+            self.is_dirty = is_dirty;
+            cx.emit(BinaryFileEvent::DirtyChanged);
+        }
+    }
+}
+
+async fn load_bytes(fs: &dyn Fs, abs_path: &Path) -> Result<Vec<u8>> {
+    let metadata = fs
+        .metadata(abs_path)
+        .await?
+        .ok_or_else(|| anyhow!("file not found: {abs_path:?}"))?;
+    if metadata.len > MAX_FILE_LEN {
+        return Err(anyhow!(
+            "{abs_path:?} is too large to be opened in a hex editor"
+        ));
+    }
+    fs.load_bytes(abs_path).await
+}
+
+impl project::Item for BinaryFile {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return None;
+        }
+
+        let worktree = project.worktree_for_id(path.worktree_id, cx)?;
+        let abs_path = project.absolute_path(path, cx)?;
+        let fs = project.fs().clone();
+        let project_path = path.clone();
+        Some(cx.spawn(|mut cx| async move {
+            let bytes = load_bytes(fs.as_ref(), &abs_path).await?;
+            cx.new_model(|cx| BinaryFile::new(project_path, abs_path, worktree, fs, bytes, cx))
+        }))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+}
@@ -0,0 +1,616 @@
+mod binary_file;
+#[cfg(test)]
+mod hex_editor_tests;
+
+use std::{ops::Range, path::PathBuf};
+
+use anyhow::Result;
+use gpui::{
+    actions, uniform_list, AnyElement, AppContext, ClipboardItem, EventEmitter, FocusHandle,
+    FocusableView, InteractiveElement, IntoElement, KeyDownEvent, Model, MouseButton,
+    MouseDownEvent, ParentElement, Render, Styled, Subscription, Task, UniformListScrollHandle,
+    View, ViewContext, VisualContext, WindowContext,
+};
+use project::Project;
+use settings::Settings;
+use theme::ThemeSettings;
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    item::{Item, ItemEvent, ProjectItem},
+    WorkspaceId,
+};
+
+pub use binary_file::{BinaryFile, BinaryFileEvent};
+
+actions!(
+    hex_editor,
+    [
+        MoveLeft,
+        MoveRight,
+        MoveUp,
+        MoveDown,
+        MoveToRowStart,
+        MoveToRowEnd,
+        PageUp,
+        PageDown,
+        MoveToStart,
+        MoveToEnd,
+        SelectLeft,
+        SelectRight,
+        SelectUp,
+        SelectDown,
+        SelectToRowStart,
+        SelectToRowEnd,
+        SelectAll,
+        CopyAsHex,
+        SwitchColumn,
+    ]
+);
+
+const BYTES_PER_ROW: usize = 16;
+const ROWS_PER_PAGE: usize = 32;
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_binary_project_item::<HexEditor>(cx);
+}
+
+/// The column of the hex editor that receives the typed input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexColumn {
+    Hex,
+    Ascii,
+}
+
+/// Displays the bytes of a file as rows of offsets, hex values and ASCII
+/// characters, and lets the user overwrite them.
+pub struct HexEditor {
+    file: Model<BinaryFile>,
+    focus_handle: FocusHandle,
+    cursor: usize,
+    /// The other end of the selection, if it spans more than the byte under the cursor.
+    selection_anchor: Option<usize>,
+    column: HexColumn,
+    /// The high nibble that was typed into the byte under the cursor, which
+    /// moves on to the next byte once its low nibble is typed as well.
+    pending_nibble: Option<u8>,
+    scroll_handle: UniformListScrollHandle,
+    _file_subscription: Subscription,
+}
+
+impl HexEditor {
+    pub fn new(file: Model<BinaryFile>, cx: &mut ViewContext<Self>) -> Self {
+        let _file_subscription = cx.subscribe(&file, |this, _, event, cx| {
+            match event {
+                BinaryFileEvent::Edited => cx.emit(ItemEvent::Edit),
+                BinaryFileEvent::DirtyChanged => cx.emit(ItemEvent::UpdateTab),
+                BinaryFileEvent::Reloaded => {
+                    let cursor = this.cursor;
+                    this.move_to(cursor, false, cx);
+                }
+            }
+            cx.notify();
+        });
+        Self {
+            file,
+            focus_handle: cx.focus_handle(),
+            cursor: 0,
+            selection_anchor: None,
+            column: HexColumn::Hex,
+            pending_nibble: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            _file_subscription,
+        }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn column(&self) -> HexColumn {
+        self.column
+    }
+
+    /// The range of bytes that are selected, which always contains the byte
+    /// under the cursor unless the file is empty.
+    pub fn selection(&self, cx: &AppContext) -> Range<usize> {
+        if self.file.read(cx).is_empty() {
+            return 0..0;
+        }
+        let anchor = self.selection_anchor.unwrap_or(self.cursor);
+        anchor.min(self.cursor)..anchor.max(self.cursor) + 1
+    }
+
+    fn move_to(&mut self, offset: usize, select: bool, cx: &mut ViewContext<Self>) {
+        let len = self.file.read(cx).len();
+        if select {
+            self.selection_anchor.get_or_insert(self.cursor);
+        } else {
+            self.selection_anchor = None;
+        }
+        self.cursor = offset.min(len.saturating_sub(1));
+        if self.selection_anchor == Some(self.cursor) {
+            self.selection_anchor = None;
+        }
+        self.pending_nibble = None;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW);
+        cx.notify();
+    }
+
+    fn move_by(&mut self, delta: isize, select: bool, cx: &mut ViewContext<Self>) {
+        // Moving up from the first row or down from the last row goes to the
+        // start or end of the file.
+        let offset = if delta < 0 {
+            self.cursor.saturating_sub(delta.unsigned_abs())
+        } else {
+            self.cursor.saturating_add(delta as usize)
+        };
+        self.move_to(offset, select, cx);
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, cx: &mut ViewContext<Self>) {
+        self.move_by(-1, false, cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, cx: &mut ViewContext<Self>) {
+        self.move_by(1, false, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, cx: &mut ViewContext<Self>) {
+        self.move_by(-(BYTES_PER_ROW as isize), false, cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, cx: &mut ViewContext<Self>) {
+        self.move_by(BYTES_PER_ROW as isize, false, cx);
+    }
+
+    fn move_to_row_start(&mut self, _: &MoveToRowStart, cx: &mut ViewContext<Self>) {
+        self.move_to(self.row_start(), false, cx);
+    }
+
+    fn move_to_row_end(&mut self, _: &MoveToRowEnd, cx: &mut ViewContext<Self>) {
+        self.move_to(self.row_start() + BYTES_PER_ROW - 1, false, cx);
+    }
+
+    fn page_up(&mut self, _: &PageUp, cx: &mut ViewContext<Self>) {
+        self.move_by(-((BYTES_PER_ROW * ROWS_PER_PAGE) as isize), false, cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, cx: &mut ViewContext<Self>) {
+        self.move_by((BYTES_PER_ROW * ROWS_PER_PAGE) as isize, false, cx);
+    }
+
+    fn move_to_start(&mut self, _: &MoveToStart, cx: &mut ViewContext<Self>) {
+        self.move_to(0, false, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, cx: &mut ViewContext<Self>) {
+        self.move_to(usize::MAX, false, cx);
+    }
+
+    fn select_left(&mut self, _: &SelectLeft, cx: &mut ViewContext<Self>) {
+        self.move_by(-1, true, cx);
+    }
+
+    fn select_right(&mut self, _: &SelectRight, cx: &mut ViewContext<Self>) {
+        self.move_by(1, true, cx);
+    }
+
+    fn select_up(&mut self, _: &SelectUp, cx: &mut ViewContext<Self>) {
+        self.move_by(-(BYTES_PER_ROW as isize), true, cx);
+    }
+
+    fn select_down(&mut self, _: &SelectDown, cx: &mut ViewContext<Self>) {
+        self.move_by(BYTES_PER_ROW as isize, true, cx);
+    }
+
+    fn select_to_row_start(&mut self, _: &SelectToRowStart, cx: &mut ViewContext<Self>) {
+        self.move_to(self.row_start(), true, cx);
+    }
+
+    fn select_to_row_end(&mut self, _: &SelectToRowEnd, cx: &mut ViewContext<Self>) {
+        self.move_to(self.row_start() + BYTES_PER_ROW - 1, true, cx);
+    }
+
+    fn select_all(&mut self, _: &SelectAll, cx: &mut ViewContext<Self>) {
+        self.move_to(0, false, cx);
+        self.move_to(usize::MAX, true, cx);
+    }
+
+    fn row_start(&self) -> usize {
+        self.cursor - self.cursor % BYTES_PER_ROW
+    }
+
+    fn copy_as_hex(&mut self, _: &CopyAsHex, cx: &mut ViewContext<Self>) {
+        let selection = self.selection(cx);
+        let bytes = &self.file.read(cx).bytes()[selection];
+        if !bytes.is_empty() {
+            cx.write_to_clipboard(ClipboardItem::new(format_hex(bytes)));
+        }
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, cx: &mut ViewContext<Self>) {
+        self.column = match self.column {
+            HexColumn::Hex => HexColumn::Ascii,
+            HexColumn::Ascii => HexColumn::Hex,
+        };
+        self.pending_nibble = None;
+        cx.notify();
+    }
+
+    fn handle_key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        let keystroke = &event.keystroke;
+        let modifiers = keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.command || modifiers.function {
+            return;
+        }
+        let input = keystroke.ime_key.clone().unwrap_or_else(|| {
+            if modifiers.shift {
+                keystroke.key.to_uppercase()
+            } else {
+                keystroke.key.clone()
+            }
+        });
+        let mut chars = input.chars();
+        let (Some(character), None) = (chars.next(), chars.next()) else {
+            return;
+        };
+
+        match self.column {
+            HexColumn::Hex => {
+                if let Some(digit) = character.to_digit(16) {
+                    self.input_nibble(digit as u8, cx);
+                    cx.stop_propagation();
+                }
+            }
+            HexColumn::Ascii => {
+                if character.is_ascii_graphic() || character == ' ' {
+                    self.input_byte(character as u8, cx);
+                    cx.stop_propagation();
+                }
+            }
+        }
+    }
+
+    /// Overwrites the high nibble of the byte under the cursor, or its low
+    /// nibble if the high one was just typed, in which case the cursor moves
+    /// on to the next byte.
+    fn input_nibble(&mut self, nibble: u8, cx: &mut ViewContext<Self>) {
+        let cursor = self.cursor;
+        let Some(&byte) = self.file.read(cx).bytes().get(cursor) else {
+            return;
+        };
+        self.selection_anchor = None;
+        match self.pending_nibble.take() {
+            Some(high_nibble) => {
+                self.file.update(cx, |file, cx| {
+                    file.set_byte(cursor, high_nibble << 4 | nibble, cx)
+                });
+                self.move_to(cursor + 1, false, cx);
+            }
+            None => {
+                self.file.update(cx, |file, cx| {
+                    file.set_byte(cursor, nibble << 4 | byte & 0x0f, cx)
+                });
+                self.pending_nibble = Some(nibble);
+                cx.notify();
+            }
+        }
+    }
+
+    fn input_byte(&mut self, byte: u8, cx: &mut ViewContext<Self>) {
+        let cursor = self.cursor;
+        if cursor >= self.file.read(cx).len() {
+            return;
+        }
+        self.file
+            .update(cx, |file, cx| file.set_byte(cursor, byte, cx));
+        self.move_to(cursor + 1, false, cx);
+    }
+
+    fn click_byte(
+        &mut self,
+        offset: usize,
+        column: HexColumn,
+        event: &MouseDownEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.column = column;
+        self.move_to(offset, event.modifiers.shift, cx);
+        cx.focus(&self.focus_handle);
+    }
+
+    fn render_rows(&mut self, rows: Range<usize>, cx: &mut ViewContext<Self>) -> Vec<AnyElement> {
+        let player = cx.theme().players().local();
+        let selected_background = player.selection;
+        let mut cursor_background = player.cursor;
+        cursor_background.fade_out(0.5);
+        let muted_text = cx.theme().colors().text_muted;
+        let selection = self.selection(cx);
+
+        let file_bytes = self.file.read(cx).bytes();
+        let first_offset = rows.start * BYTES_PER_ROW;
+        let bytes =
+            file_bytes[first_offset..file_bytes.len().min(rows.end * BYTES_PER_ROW)].to_vec();
+
+        rows.map(|row| {
+            let row_start = row * BYTES_PER_ROW;
+            let row_bytes = bytes.get(row_start - first_offset..).unwrap_or_default();
+
+            let mut hex_cells = h_flex().gap_1();
+            let mut ascii_cells = h_flex();
+            for column in 0..BYTES_PER_ROW {
+                let offset = row_start + column;
+                let byte = row_bytes.get(column).copied();
+                let background = |cell_column: HexColumn| {
+                    if offset == self.cursor && cell_column == self.column {
+                        Some(cursor_background)
+                    } else if selection.contains(&offset) {
+                        Some(selected_background)
+                    } else {
+                        None
+                    }
+                };
+
+                let hex_text = match byte {
+                    Some(byte) => format!("{byte:02X}"),
+                    None => "  ".to_string(),
+                };
+                let mut hex_cell = div()
+                    .when(column == BYTES_PER_ROW / 2, |cell| cell.ml_2())
+                    .child(hex_text);
+                if let Some(background) = background(HexColumn::Hex) {
+                    hex_cell = hex_cell.bg(background);
+                }
+                let mut ascii_cell = div().child(match byte {
+                    Some(byte) => ascii_char(byte).to_string(),
+                    None => " ".to_string(),
+                });
+                if let Some(background) = background(HexColumn::Ascii) {
+                    ascii_cell = ascii_cell.bg(background);
+                }
+                if byte.is_some() {
+                    hex_cell = hex_cell.on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event: &MouseDownEvent, cx| {
+                            this.click_byte(offset, HexColumn::Hex, event, cx)
+                        }),
+                    );
+                    ascii_cell = ascii_cell.on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event: &MouseDownEvent, cx| {
+                            this.click_byte(offset, HexColumn::Ascii, event, cx)
+                        }),
+                    );
+                }
+                hex_cells = hex_cells.child(hex_cell);
+                ascii_cells = ascii_cells.child(ascii_cell);
+            }
+
+            h_flex()
+                .gap_4()
+                .child(
+                    div()
+                        .text_color(muted_text)
+                        .child(format!("{row_start:08X}")),
+                )
+                .child(hex_cells)
+                .child(ascii_cells)
+                .into_any_element()
+        })
+        .collect()
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let len = self.file.read(cx).len();
+        let selection = self.selection(cx);
+        let mut details = vec![format!("Offset {0:#010X} ({0})", self.cursor)];
+        if selection.len() > 1 {
+            details.push(format!("{} bytes selected", selection.len()));
+        }
+        details.push(format!("{len} bytes"));
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(details.join(" · "))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
+
+/// Formats bytes as space-separated pairs of uppercase hex digits.
+pub fn format_hex(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len() * 3);
+    for (ix, byte) in bytes.iter().enumerate() {
+        if ix > 0 {
+            text.push(' ');
+        }
+        text.push_str(&format!("{byte:02X}"));
+    }
+    text
+}
+
+fn ascii_char(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font_family = settings.buffer_font.family.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let row_count = self.file.read(cx).len().div_ceil(BYTES_PER_ROW);
+
+        let content = if row_count == 0 {
+            v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(Label::new("This file is empty").color(Color::Muted))
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "hex-editor-rows",
+                row_count,
+                |this, rows, cx| this.render_rows(rows, cx),
+            )
+            .track_scroll(self.scroll_handle.clone())
+            .size_full()
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_to_row_start))
+            .on_action(cx.listener(Self::move_to_row_end))
+            .on_action(cx.listener(Self::page_up))
+            .on_action(cx.listener(Self::page_down))
+            .on_action(cx.listener(Self::move_to_start))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::select_left))
+            .on_action(cx.listener(Self::select_right))
+            .on_action(cx.listener(Self::select_up))
+            .on_action(cx.listener(Self::select_down))
+            .on_action(cx.listener(Self::select_to_row_start))
+            .on_action(cx.listener(Self::select_to_row_end))
+            .on_action(cx.listener(Self::select_all))
+            .on_action(cx.listener(Self::copy_as_hex))
+            .on_action(cx.listener(Self::switch_column))
+            .on_key_down(cx.listener(Self::handle_key_down))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                div()
+                    .flex_1()
+                    .w_full()
+                    .p_2()
+                    .font(font_family)
+                    .text_size(font_size)
+                    .text_color(cx.theme().colors().editor_foreground)
+                    .child(content),
+            )
+            .child(self.render_footer(cx))
+    }
+}
+
+impl FocusableView for HexEditor {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<ItemEvent> for HexEditor {}
+
+impl Item for HexEditor {
+    type Event = ItemEvent;
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        Some(
+            self.file
+                .read(cx)
+                .abs_path()
+                .compact()
+                .to_string_lossy()
+                .to_string()
+                .into(),
+        )
+    }
+
+    fn tab_content(&self, _: Option<usize>, selected: bool, cx: &WindowContext) -> AnyElement {
+        let path = &self.file.read(cx).project_path().path;
+        let title = path
+            .file_name()
+            .unwrap_or_else(|| path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("hex editor")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        f(self.file.entity_id(), self.file.read(cx))
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn clone_on_split(&self, _: WorkspaceId, cx: &mut ViewContext<Self>) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| HexEditor::new(self.file.clone(), cx)))
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.file.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(&mut self, _: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.file.update(cx, |file, cx| file.save(cx))
+    }
+
+    fn save_as(
+        &mut self,
+        project: Model<Project>,
+        abs_path: PathBuf,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        // The hex editor keeps editing the original file, of which a copy is written.
+        let fs = project.read(cx).fs().clone();
+        let bytes = self.file.read(cx).bytes().to_vec();
+        cx.background_executor()
+            .spawn(async move { fs.write(&abs_path, &bytes).await })
+    }
+
+    fn reload(&mut self, _: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.file.update(cx, |file, cx| file.reload(cx))
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = BinaryFile;
+
+    fn for_project_item(
+        _project: Model<Project>,
+        item: Model<BinaryFile>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self::new(item, cx)
+    }
+}
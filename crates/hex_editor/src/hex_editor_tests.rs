@@ -0,0 +1,286 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use fs::Fs;
+use gpui::{TestAppContext, View, VisualTestContext};
+use project::Project;
+use serde_json::json;
+use workspace::{AppState, Workspace};
+
+use super::*;
+
+#[gpui::test]
+async fn test_opening_binary_files(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    let fs = app_state.fs.as_fake();
+    fs.insert_tree("/root", json!({ "notes.txt": "hello" }))
+        .await;
+    fs.insert_file_bytes("/root/data.bin", vec![0x00, 0x9f, 0x92, 0x96, 0xff])
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let worktree_id = cx.update(|cx| {
+        workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .worktrees()
+            .next()
+            .unwrap()
+            .read(cx)
+            .id()
+    });
+
+    // Binary files fall back to a hex editor.
+    let item = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "data.bin"), None, true, cx)
+        })
+        .await
+        .unwrap();
+    let hex_editor = item.downcast::<HexEditor>().unwrap();
+    hex_editor.update(cx, |hex_editor, cx| {
+        assert_eq!(
+            hex_editor.file.read(cx).bytes(),
+            &[0x00, 0x9f, 0x92, 0x96, 0xff]
+        );
+    });
+
+    // Text files are still opened in an editor.
+    let item = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "notes.txt"), None, true, cx)
+        })
+        .await
+        .unwrap();
+    assert!(item.downcast::<Editor>().is_some());
+}
+
+#[gpui::test]
+async fn test_opening_files_in_legacy_encodings(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    let fs = app_state.fs.as_fake();
+    fs.insert_tree("/root", json!({})).await;
+    // Neither file is valid UTF-8 nor contains null bytes.
+    fs.insert_file_bytes("/root/latin1.txt", b"caf\xE9 cr\xE8me\n".to_vec())
+        .await;
+    fs.insert_file_bytes("/root/data.bin", vec![0x9f, 0x01, 0x92, 0x02, 0xff, 0x03])
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let worktree_id = cx.update(|cx| {
+        workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .worktrees()
+            .next()
+            .unwrap()
+            .read(cx)
+            .id()
+    });
+
+    // Text in a legacy encoding is decoded in an editor.
+    let item = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "latin1.txt"), None, true, cx)
+        })
+        .await
+        .unwrap();
+    let editor = item.downcast::<Editor>().unwrap();
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.text(cx), "caf\u{e9} cr\u{e8}me\n");
+    });
+
+    // Content with many control characters is opened in a hex editor.
+    let item = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "data.bin"), None, true, cx)
+        })
+        .await
+        .unwrap();
+    assert!(item.downcast::<HexEditor>().is_some());
+}
+
+#[gpui::test]
+async fn test_opening_large_binary_files(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    let fs = app_state.fs.as_fake();
+    fs.insert_tree("/root", json!({})).await;
+    let len = binary_file::MAX_FILE_LEN as usize + 4;
+    fs.insert_file_bytes("/root/large.bin", [0x00, 0x00, 0x9f, 0xff].repeat(len / 4))
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let worktree_id = cx.update(|cx| {
+        workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .worktrees()
+            .next()
+            .unwrap()
+            .read(cx)
+            .id()
+    });
+
+    // Files that are too large to be held in memory aren't opened at all.
+    let result = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "large.bin"), None, true, cx)
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_editing_and_saving(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    let fs = app_state.fs.as_fake();
+    fs.insert_tree("/root", json!({})).await;
+    fs.insert_file_bytes("/root/data.bin", vec![0x00, 0xff, 0x10, 0x20, 0x30])
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let hex_editor = open_hex_editor(&workspace, "data.bin", cx).await;
+
+    hex_editor.update(cx, |hex_editor, cx| {
+        hex_editor.move_right(&MoveRight, cx);
+        hex_editor.input_nibble(0xa, cx);
+        // The cursor stays on the byte until both of its nibbles are typed.
+        assert_eq!(hex_editor.cursor(), 1);
+        assert_eq!(hex_editor.file.read(cx).bytes()[1], 0xaf);
+        hex_editor.input_nibble(0xb, cx);
+        assert_eq!(hex_editor.cursor(), 2);
+
+        hex_editor.switch_column(&SwitchColumn, cx);
+        assert_eq!(hex_editor.column(), HexColumn::Ascii);
+        hex_editor.input_byte(b'Z', cx);
+        assert_eq!(hex_editor.cursor(), 3);
+
+        // The size of the file never changes.
+        hex_editor.move_to_end(&MoveToEnd, cx);
+        hex_editor.input_byte(b'!', cx);
+        hex_editor.input_byte(b'?', cx);
+        assert_eq!(hex_editor.cursor(), 4);
+        assert_eq!(
+            hex_editor.file.read(cx).bytes(),
+            &[0x00, 0xab, b'Z', 0x20, b'?']
+        );
+        assert!(hex_editor.is_dirty(cx));
+    });
+
+    let project = workspace.update(cx, |workspace, _| workspace.project().clone());
+    hex_editor
+        .update(cx, |hex_editor, cx| hex_editor.save(project, cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        fs.load_bytes("/root/data.bin".as_ref()).await.unwrap(),
+        [0x00, 0xab, b'Z', 0x20, b'?']
+    );
+    hex_editor.update(cx, |hex_editor, cx| assert!(!hex_editor.is_dirty(cx)));
+
+    // Changes on disk are picked up while there are no unsaved edits.
+    fs.insert_file_bytes("/root/data.bin", vec![0x01, 0x02])
+        .await;
+    cx.run_until_parked();
+    hex_editor.update(cx, |hex_editor, cx| {
+        assert_eq!(hex_editor.file.read(cx).bytes(), &[0x01, 0x02]);
+        assert_eq!(hex_editor.cursor(), 1);
+    });
+}
+
+#[gpui::test]
+async fn test_selection_and_copy(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    let fs = app_state.fs.as_fake();
+    fs.insert_tree("/root", json!({})).await;
+    fs.insert_file_bytes("/root/data.bin", (0..40).collect())
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let hex_editor = open_hex_editor(&workspace, "data.bin", cx).await;
+
+    hex_editor.update(cx, |hex_editor, cx| {
+        assert_eq!(hex_editor.selection(cx), 0..1);
+        hex_editor.move_down(&MoveDown, cx);
+        hex_editor.select_right(&SelectRight, cx);
+        hex_editor.select_right(&SelectRight, cx);
+        assert_eq!(hex_editor.selection(cx), 16..19);
+        hex_editor.copy_as_hex(&CopyAsHex, cx);
+
+        hex_editor.select_up(&SelectUp, cx);
+        assert_eq!(hex_editor.selection(cx), 2..17);
+        hex_editor.select_to_row_end(&SelectToRowEnd, cx);
+        assert_eq!(hex_editor.selection(cx), 15..17);
+
+        hex_editor.move_down(&MoveDown, cx);
+        hex_editor.move_down(&MoveDown, cx);
+        hex_editor.move_to_row_end(&MoveToRowEnd, cx);
+        assert_eq!(hex_editor.selection(cx), 39..40);
+
+        hex_editor.select_all(&SelectAll, cx);
+        assert_eq!(hex_editor.selection(cx), 0..40);
+    });
+    assert_eq!(
+        cx.read_from_clipboard().map(|item| item.text().clone()),
+        Some("10 11 12".to_string())
+    );
+}
+
+#[test]
+fn test_format_hex() {
+    assert_eq!(format_hex(&[]), "");
+    assert_eq!(format_hex(&[0x00, 0x0f, 0xab, 0xff]), "00 0F AB FF");
+}
+
+fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+    cx.update(|cx| {
+        let state = AppState::test(cx);
+        theme::init(theme::LoadThemes::JustBase, cx);
+        language::init(cx);
+        // Binary files fall back to a hex editor whichever is initialized first.
+        super::init(cx);
+        editor::init(cx);
+        workspace::init_settings(cx);
+        Project::init_settings(cx);
+        state
+    })
+}
+
+async fn build_workspace<'a>(
+    app_state: &Arc<AppState>,
+    cx: &'a mut TestAppContext,
+) -> (View<Workspace>, &'a mut VisualTestContext) {
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    cx.add_window_view(|cx| Workspace::test_new(project, cx))
+}
+
+async fn open_hex_editor(
+    workspace: &View<Workspace>,
+    path: &str,
+    cx: &mut VisualTestContext,
+) -> View<HexEditor> {
+    let worktree_id = cx.update(|cx| {
+        workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .worktrees()
+            .next()
+            .unwrap()
+            .read(cx)
+            .id()
+    });
+    let path = path.to_string();
+    workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, path), None, true, cx)
+        })
+        .await
+        .unwrap()
+        .downcast::<HexEditor>()
+        .unwrap()
+}
@@ -0,0 +1,222 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use fs::Fs;
+use gpui::{AppContext, Context, ImageData, Model, ModelContext, SharedString, Subscription, Task};
+use project::{worktree, Project, ProjectEntryId, ProjectPath, Worktree};
+use util::ResultExt;
+
+/// The file extensions of the images that are opened in an image viewer
+/// rather than in an editor.
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "bmp", "gif", "ico", "jpeg", "jpg", "png", "svg", "tga", "tif", "tiff", "webp",
+];
+
+/// An image file in the project, which is reloaded whenever the file changes on disk.
+pub struct ImageItem {
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    entry_id: Option<ProjectEntryId>,
+    fs: Arc<dyn Fs>,
+    image: Option<Arc<ImageData>>,
+    file_size: u64,
+    error: Option<SharedString>,
+    reload_task: Option<Task<()>>,
+    _worktree_subscription: Subscription,
+}
+
+struct LoadedImage {
+    image: Result<Arc<ImageData>>,
+    file_size: u64,
+}
+
+pub fn is_image_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image_extension| extension.eq_ignore_ascii_case(image_extension))
+        })
+}
+
+impl ImageItem {
+    pub fn project_path(&self) -> &ProjectPath {
+        &self.project_path
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn image(&self) -> Option<&Arc<ImageData>> {
+        self.image.as_ref()
+    }
+
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    pub fn error(&self) -> Option<&SharedString> {
+        self.error.as_ref()
+    }
+
+    fn new(
+        project_path: ProjectPath,
+        abs_path: PathBuf,
+        worktree: Model<Worktree>,
+        fs: Arc<dyn Fs>,
+        loaded: LoadedImage,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let entry_id = worktree
+            .read(cx)
+            .entry_for_path(&project_path.path)
+            .map(|entry| entry.id);
+        let _worktree_subscription = cx.subscribe(&worktree, |this, worktree, event, cx| {
+            if let worktree::Event::UpdatedEntries(changes) = event {
+                if changes
+                    .iter()
+                    .any(|(path, _, _)| *path == this.project_path.path)
+                {
+                    this.entry_id = worktree
+                        .read(cx)
+                        .entry_for_path(&this.project_path.path)
+                        .map(|entry| entry.id);
+                    this.reload(cx);
+                }
+            }
+        });
+
+        let mut this = Self {
+            project_path,
+            abs_path,
+            entry_id,
+            fs,
+            image: None,
+            file_size: 0,
+            error: None,
+            reload_task: None,
+            _worktree_subscription,
+        };
+        this.set_loaded_image(loaded);
+        this
+    }
+
+    fn reload(&mut self, cx: &mut ModelContext<Self>) {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let load = cx
+            .background_executor()
+            .spawn(async move { load_image(fs.as_ref(), &abs_path).await });
+        self.reload_task = Some(cx.spawn(|this, mut cx| async move {
+            // The file may have been removed, in which case the last image is kept.
+            let Some(loaded) = load.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.set_loaded_image(loaded);
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn set_loaded_image(&mut self, loaded: LoadedImage) {
+        self.file_size = loaded.file_size;
+        match loaded.image {
+            Ok(image) => {
+                self.image = Some(image);
+                self.error = None;
+            }
+            Err(error) => {
+                self.image = None;
+                self.error = Some(error.to_string().into());
+            }
+        }
+    }
+}
+
+/// Reads the image at the given path. Failing to read the file is an error,
+/// while failing to decode it is reported in the returned [LoadedImage].
+async fn load_image(fs: &dyn Fs, abs_path: &Path) -> Result<LoadedImage> {
+    let metadata = fs
+        .metadata(abs_path)
+        .await?
+        .ok_or_else(|| anyhow!("file not found: {abs_path:?}"))?;
+    let mut bytes = Vec::with_capacity(metadata.len as usize);
+    fs.open_sync(abs_path).await?.read_to_end(&mut bytes)?;
+
+    let is_svg = abs_path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("svg"));
+    let image = if is_svg {
+        ImageData::from_svg(&bytes, 1.)
+    } else {
+        ImageData::from_bytes(&bytes)
+    };
+    Ok(LoadedImage {
+        image: image.map(Arc::new),
+        file_size: bytes.len() as u64,
+    })
+}
+
+impl project::Item for ImageItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        // Images in remote projects can't be read from the local file system,
+        // so they keep being opened in an editor.
+        let project = project.read(cx);
+        if !is_image_path(&path.path) || !project.is_local() {
+            return None;
+        }
+
+        let worktree = project.worktree_for_id(path.worktree_id, cx)?;
+        let abs_path = project.absolute_path(path, cx)?;
+        let fs = project.fs().clone();
+        let project_path = path.clone();
+        Some(cx.spawn(|mut cx| async move {
+            let loaded = cx
+                .background_executor()
+                .spawn({
+                    let fs = fs.clone();
+                    let abs_path = abs_path.clone();
+                    async move { load_image(fs.as_ref(), &abs_path).await }
+                })
+                .await?;
+            cx.new_model(|cx| ImageItem::new(project_path, abs_path, worktree, fs, loaded, cx))
+        }))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::is_image_path;
+
+    #[test]
+    fn test_is_image_path() {
+        assert!(is_image_path(Path::new("assets/logo.png")));
+        assert!(is_image_path(Path::new("assets/Photo.JPG")));
+        assert!(is_image_path(Path::new("icons/file.svg")));
+        assert!(!is_image_path(Path::new("src/main.rs")));
+        assert!(!is_image_path(Path::new("png")));
+        assert!(!is_image_path(Path::new("Makefile")));
+    }
+}
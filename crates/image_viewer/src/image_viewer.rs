@@ -0,0 +1,426 @@
+mod image_item;
+#[cfg(test)]
+mod image_viewer_tests;
+
+use std::{cell::Cell, rc::Rc, sync::Arc};
+
+use gpui::{
+    actions, canvas, fill, point, px, size, AnyElement, AppContext, Bounds, Corners,
+    ElementContext, EventEmitter, FocusHandle, FocusableView, Hsla, ImageData, InteractiveElement,
+    IntoElement, Model, ParentElement, Pixels, Point, Render, ScrollWheelEvent, Size, Styled,
+    Subscription, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use project::Project;
+use ui::{prelude::*, Tooltip};
+use util::{paths::PathExt, ResultExt};
+use workspace::{
+    item::{Item, ItemEvent, ProjectItem},
+    WorkspaceId,
+};
+
+pub use image_item::{is_image_path, ImageItem};
+
+actions!(image_viewer, [ZoomIn, ZoomOut, ResetZoom, FitToWindow]);
+
+const ZOOM_STEP: f32 = 1.25;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 32.;
+const CHECKERBOARD_CELL_SIZE: Pixels = px(8.);
+
+pub fn init(cx: &mut AppContext) {
+    // Registered after the editor, so that image files are opened in an image
+    // view instead of an editor.
+    workspace::register_project_item::<ImageView>(cx);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ImageScale {
+    /// Scale the image down so that it fits in the view, without ever enlarging it.
+    Fit,
+    Zoom(f32),
+}
+
+/// The size of the view the image was last painted in, and the scale the
+/// image had to be displayed at in order to fit in it.
+#[derive(Clone, Copy)]
+struct ImageLayout {
+    viewport: Size<Pixels>,
+    fit_scale: f32,
+}
+
+pub struct ImageView {
+    image: Model<ImageItem>,
+    scale: ImageScale,
+    /// How far the center of the image is panned from the center of the view.
+    offset: Point<Pixels>,
+    layout: Rc<Cell<ImageLayout>>,
+    focus_handle: FocusHandle,
+    _image_subscription: Subscription,
+}
+
+impl ImageView {
+    pub fn new(image: Model<ImageItem>, cx: &mut ViewContext<Self>) -> Self {
+        let _image_subscription = cx.observe(&image, |_, _, cx| cx.notify());
+        Self {
+            image,
+            scale: ImageScale::Fit,
+            offset: Point::default(),
+            layout: Rc::new(Cell::new(ImageLayout {
+                viewport: Size::default(),
+                fit_scale: 1.,
+            })),
+            focus_handle: cx.focus_handle(),
+            _image_subscription,
+        }
+    }
+
+    fn current_scale(&self) -> f32 {
+        match self.scale {
+            ImageScale::Fit => self.layout.get().fit_scale,
+            ImageScale::Zoom(scale) => scale,
+        }
+    }
+
+    fn set_scale(&mut self, scale: ImageScale, cx: &mut ViewContext<Self>) {
+        let old_scale = self.current_scale();
+        self.scale = scale;
+        // Keep the same point of the image at the center of the view.
+        self.offset = self.offset * (self.current_scale() / old_scale);
+        self.offset = self.clamp_offset(self.offset, cx);
+        cx.notify();
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, cx: &mut ViewContext<Self>) {
+        let scale = (self.current_scale() * ZOOM_STEP).min(MAX_ZOOM);
+        self.set_scale(ImageScale::Zoom(scale), cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, cx: &mut ViewContext<Self>) {
+        let scale = (self.current_scale() / ZOOM_STEP).max(MIN_ZOOM);
+        self.set_scale(ImageScale::Zoom(scale), cx);
+    }
+
+    fn reset_zoom(&mut self, _: &ResetZoom, cx: &mut ViewContext<Self>) {
+        self.set_scale(ImageScale::Zoom(1.), cx);
+    }
+
+    fn fit_to_window(&mut self, _: &FitToWindow, cx: &mut ViewContext<Self>) {
+        self.offset = Point::default();
+        self.set_scale(ImageScale::Fit, cx);
+    }
+
+    fn scroll(&mut self, event: &ScrollWheelEvent, cx: &mut ViewContext<Self>) {
+        let delta = event.delta.pixel_delta(cx.line_height());
+        if event.modifiers.command || event.modifiers.control {
+            if delta.y > px(0.) {
+                self.zoom_in(&ZoomIn, cx);
+            } else if delta.y < px(0.) {
+                self.zoom_out(&ZoomOut, cx);
+            }
+        } else {
+            self.offset = self.clamp_offset(self.offset + delta, cx);
+            cx.notify();
+        }
+    }
+
+    /// Limits the offset so that an image larger than the view can be panned
+    /// until its edges, and an image smaller than the view stays centered.
+    fn clamp_offset(&self, offset: Point<Pixels>, cx: &AppContext) -> Point<Pixels> {
+        let Some(image) = self.image.read(cx).image() else {
+            return Point::default();
+        };
+        let image_size = scaled_image_size(image, self.current_scale());
+        let viewport = self.layout.get().viewport;
+        let max_x = ((image_size.width - viewport.width).0 / 2.).max(0.);
+        let max_y = ((image_size.height - viewport.height).0 / 2.).max(0.);
+        point(
+            px(offset.x.0.clamp(-max_x, max_x)),
+            px(offset.y.0.clamp(-max_y, max_y)),
+        )
+    }
+
+    fn render_image(&self, image: Arc<ImageData>, cx: &mut ViewContext<Self>) -> AnyElement {
+        let scale = self.scale;
+        let offset = self.offset;
+        let layout = self.layout.clone();
+        let view = cx.view().downgrade();
+        let colors = cx.theme().colors();
+        let checkerboard_colors = [colors.editor_background, colors.element_background];
+
+        canvas(move |bounds, cx| {
+            let fit_scale = fit_scale(&image, bounds.size);
+            let previous_layout = layout.replace(ImageLayout {
+                viewport: bounds.size,
+                fit_scale,
+            });
+            // The footer displays the scale the image is fitted at, which is
+            // only known once the view has been laid out.
+            if scale == ImageScale::Fit && previous_layout.fit_scale != fit_scale {
+                notify_on_next_frame(view, cx);
+            }
+
+            let scale = match scale {
+                ImageScale::Fit => fit_scale,
+                ImageScale::Zoom(scale) => scale,
+            };
+            let image_size = scaled_image_size(&image, scale);
+            let origin =
+                bounds.center() + offset + point(-image_size.width * 0.5, -image_size.height * 0.5);
+            let image_bounds = Bounds {
+                origin,
+                size: image_size,
+            };
+
+            paint_checkerboard(image_bounds, bounds, checkerboard_colors, cx);
+            cx.paint_image(image_bounds, Corners::default(), image, false)
+                .log_err();
+        })
+        .size_full()
+        .into_any_element()
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let image = self.image.read(cx);
+        let mut details = Vec::new();
+        if let Some(data) = image.image() {
+            let size = data.size();
+            details.push(format!(
+                "{} × {} px",
+                i32::from(size.width),
+                i32::from(size.height)
+            ));
+        }
+        details.push(format_file_size(image.file_size()));
+        if image.image().is_some() {
+            details.push(format!("{:.0}%", self.current_scale() * 100.));
+        }
+        let details = details.join(" · ");
+
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(details)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(div().flex_1())
+            .child(
+                IconButton::new("image-zoom-out", IconName::Dash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::for_action("Zoom Out", &ZoomOut, cx))
+                    .on_click(cx.listener(|this, _, cx| this.zoom_out(&ZoomOut, cx))),
+            )
+            .child(
+                IconButton::new("image-zoom-in", IconName::Plus)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::for_action("Zoom In", &ZoomIn, cx))
+                    .on_click(cx.listener(|this, _, cx| this.zoom_in(&ZoomIn, cx))),
+            )
+            .child(
+                IconButton::new("image-fit-to-window", IconName::Maximize)
+                    .icon_size(IconSize::Small)
+                    .selected(self.scale == ImageScale::Fit)
+                    .tooltip(|cx| Tooltip::for_action("Fit to Window", &FitToWindow, cx))
+                    .on_click(cx.listener(|this, _, cx| this.fit_to_window(&FitToWindow, cx))),
+            )
+    }
+}
+
+fn notify_on_next_frame(view: WeakView<ImageView>, cx: &mut ElementContext) {
+    cx.on_next_frame(move |cx| {
+        view.update(cx, |_, cx| cx.notify()).ok();
+    });
+}
+
+fn scaled_image_size(image: &ImageData, scale: f32) -> Size<Pixels> {
+    let image_size = image.size();
+    size(
+        px(i32::from(image_size.width) as f32 * scale),
+        px(i32::from(image_size.height) as f32 * scale),
+    )
+}
+
+fn fit_scale(image: &ImageData, viewport: Size<Pixels>) -> f32 {
+    let image_size = image.size();
+    let width = i32::from(image_size.width).max(1) as f32;
+    let height = i32::from(image_size.height).max(1) as f32;
+    (viewport.width.0 / width)
+        .min(viewport.height.0 / height)
+        .clamp(MIN_ZOOM, 1.)
+}
+
+/// Paints a checkerboard pattern behind the visible part of the image, so that
+/// its transparent regions can be told apart from the background.
+fn paint_checkerboard(
+    image_bounds: Bounds<Pixels>,
+    viewport: Bounds<Pixels>,
+    colors: [Hsla; 2],
+    cx: &mut ElementContext,
+) {
+    let visible_bounds = image_bounds
+        .intersect(&viewport)
+        .intersect(&cx.content_mask().bounds);
+    if visible_bounds.size.width <= px(0.) || visible_bounds.size.height <= px(0.) {
+        return;
+    }
+    cx.paint_quad(fill(visible_bounds, colors[0]));
+
+    // Only the cells that intersect the visible bounds are painted, which keeps
+    // the number of quads bounded by the size of the view, whatever the zoom.
+    let cell_size = CHECKERBOARD_CELL_SIZE;
+    let first_column = ((visible_bounds.left() - image_bounds.left()) / cell_size).floor() as i64;
+    let last_column = ((visible_bounds.right() - image_bounds.left()) / cell_size).ceil() as i64;
+    let first_row = ((visible_bounds.top() - image_bounds.top()) / cell_size).floor() as i64;
+    let last_row = ((visible_bounds.bottom() - image_bounds.top()) / cell_size).ceil() as i64;
+    for row in first_row..last_row {
+        for column in first_column..last_column {
+            if (row + column) % 2 == 0 {
+                continue;
+            }
+            let cell_bounds = Bounds {
+                origin: image_bounds.origin
+                    + point(cell_size * column as f32, cell_size * row as f32),
+                size: size(cell_size, cell_size),
+            };
+            cx.paint_quad(fill(cell_bounds.intersect(&visible_bounds), colors[1]));
+        }
+    }
+}
+
+fn format_file_size(len: u64) -> String {
+    const KB: f64 = 1024.;
+    let len = len as f64;
+    if len < KB {
+        format!("{len} B")
+    } else if len < KB * KB {
+        format!("{:.1} KB", len / KB)
+    } else {
+        format!("{:.1} MB", len / (KB * KB))
+    }
+}
+
+impl Render for ImageView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let image = self.image.read(cx);
+        let data = image.image().cloned();
+        let error = image.error().cloned();
+        let content = match data {
+            Some(data) => self.render_image(data, cx),
+            None => v_flex()
+                .size_full()
+                .items_center()
+                .justify_center()
+                .child(
+                    Label::new(format!(
+                        "Could not display image: {}",
+                        error
+                            .as_ref()
+                            .map_or("unknown error", |error| error.as_ref())
+                    ))
+                    .color(Color::Muted),
+                )
+                .into_any_element(),
+        };
+
+        v_flex()
+            .key_context("ImageView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::reset_zoom))
+            .on_action(cx.listener(Self::fit_to_window))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                div()
+                    .id("image-viewport")
+                    .flex_1()
+                    .w_full()
+                    .overflow_hidden()
+                    .on_scroll_wheel(cx.listener(Self::scroll))
+                    .child(content),
+            )
+            .child(self.render_footer(cx))
+    }
+}
+
+impl FocusableView for ImageView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+pub enum ImageViewEvent {}
+
+impl EventEmitter<ImageViewEvent> for ImageView {}
+
+impl Item for ImageView {
+    type Event = ImageViewEvent;
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        Some(
+            self.image
+                .read(cx)
+                .abs_path()
+                .compact()
+                .to_string_lossy()
+                .to_string()
+                .into(),
+        )
+    }
+
+    fn tab_content(&self, _: Option<usize>, selected: bool, cx: &WindowContext) -> AnyElement {
+        let path = &self.image.read(cx).project_path().path;
+        let title = path
+            .file_name()
+            .unwrap_or_else(|| path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("image viewer")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        f(self.image.entity_id(), self.image.read(cx))
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn clone_on_split(&self, _: WorkspaceId, cx: &mut ViewContext<Self>) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| ImageView::new(self.image.clone(), cx)))
+    }
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+}
+
+impl ProjectItem for ImageView {
+    type Item = ImageItem;
+
+    fn for_project_item(
+        _project: Model<Project>,
+        item: Model<ImageItem>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        Self::new(item, cx)
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use gpui::{TestAppContext, View, VisualTestContext};
+use project::{Project, WorktreeId};
+use serde_json::json;
+use workspace::{AppState, Workspace};
+
+use super::*;
+
+const SQUARE_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="4" height="4"><rect width="4" height="4"/></svg>"#;
+const WIDE_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="8" height="2"><rect width="8" height="2"/></svg>"#;
+
+#[gpui::test]
+async fn test_opening_images(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/root",
+            json!({
+                "image.svg": SQUARE_SVG,
+                "main.rs": "fn main() {}",
+            }),
+        )
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let worktree_id = worktree_id(&workspace, cx);
+
+    // Images are opened in an image view, even though an editor can open any file.
+    let item = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "image.svg"), None, true, cx)
+        })
+        .await
+        .unwrap();
+    let image_view = item.downcast::<ImageView>().unwrap();
+    image_view.update(cx, |image_view, cx| {
+        let image = image_view.image.read(cx);
+        assert_eq!(image_size(image), Some((4, 4)));
+        assert_eq!(image.file_size(), SQUARE_SVG.len() as u64);
+        assert_eq!(image.error(), None);
+    });
+
+    // Other files are still opened in an editor.
+    let item = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "main.rs"), None, true, cx)
+        })
+        .await
+        .unwrap();
+    assert!(item.downcast::<Editor>().is_some());
+}
+
+#[gpui::test]
+async fn test_reloading_images(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    let fs = app_state.fs.as_fake();
+    fs.insert_tree("/root", json!({ "image.svg": SQUARE_SVG }))
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+    let image_view = open_image_view(&workspace, "image.svg", cx).await;
+
+    fs.insert_file("/root/image.svg", WIDE_SVG.to_string())
+        .await;
+    cx.run_until_parked();
+    image_view.update(cx, |image_view, cx| {
+        let image = image_view.image.read(cx);
+        assert_eq!(image_size(image), Some((8, 2)));
+        assert_eq!(image.file_size(), WIDE_SVG.len() as u64);
+    });
+
+    // An image that can't be decoded anymore is replaced by an error.
+    fs.insert_file("/root/image.svg", "not an image".to_string())
+        .await;
+    cx.run_until_parked();
+    image_view.update(cx, |image_view, cx| {
+        let image = image_view.image.read(cx);
+        assert_eq!(image_size(image), None);
+        assert!(image.error().is_some());
+    });
+
+    fs.insert_file("/root/image.svg", SQUARE_SVG.to_string())
+        .await;
+    cx.run_until_parked();
+    image_view.update(cx, |image_view, cx| {
+        let image = image_view.image.read(cx);
+        assert_eq!(image_size(image), Some((4, 4)));
+        assert_eq!(image.error(), None);
+    });
+}
+
+#[gpui::test]
+async fn test_invalid_images(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree("/root", json!({ "broken.png": "not a png" }))
+        .await;
+
+    let (workspace, cx) = build_workspace(&app_state, cx).await;
+
+    // Images that can't be decoded are still opened in an image view, which
+    // displays the error.
+    let image_view = open_image_view(&workspace, "broken.png", cx).await;
+    image_view.update(cx, |image_view, cx| {
+        let image = image_view.image.read(cx);
+        assert_eq!(image_size(image), None);
+        assert_eq!(image.file_size(), "not a png".len() as u64);
+        assert!(image.error().is_some());
+    });
+}
+
+fn image_size(image: &ImageItem) -> Option<(i32, i32)> {
+    image.image().map(|image| {
+        let size = image.size();
+        (i32::from(size.width), i32::from(size.height))
+    })
+}
+
+fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+    cx.update(|cx| {
+        let state = AppState::test(cx);
+        theme::init(theme::LoadThemes::JustBase, cx);
+        language::init(cx);
+        editor::init(cx);
+        // Initialized after the editor, like in the app.
+        super::init(cx);
+        workspace::init_settings(cx);
+        Project::init_settings(cx);
+        state
+    })
+}
+
+async fn build_workspace<'a>(
+    app_state: &Arc<AppState>,
+    cx: &'a mut TestAppContext,
+) -> (View<Workspace>, &'a mut VisualTestContext) {
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    cx.add_window_view(|cx| Workspace::test_new(project, cx))
+}
+
+fn worktree_id(workspace: &View<Workspace>, cx: &mut VisualTestContext) -> WorktreeId {
+    cx.update(|cx| {
+        workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .worktrees()
+            .next()
+            .unwrap()
+            .read(cx)
+            .id()
+    })
+}
+
+async fn open_image_view(
+    workspace: &View<Workspace>,
+    path: &str,
+    cx: &mut VisualTestContext,
+) -> View<ImageView> {
+    let worktree_id = worktree_id(workspace, cx);
+    let path = path.to_string();
+    workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, path), None, true, cx)
+        })
+        .await
+        .unwrap()
+        .downcast::<ImageView>()
+        .unwrap()
+}
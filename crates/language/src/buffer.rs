@@ -6,14 +6,15 @@ pub use crate::{
 };
 use crate::{
     diagnostic_set::{DiagnosticEntry, DiagnosticGroup},
-    language_settings::{language_settings, LanguageSettings},
+    encoding::Encoding,
+    language_settings::{language_settings, LanguageSettingsRef},
     markdown::parse_markdown,
     outline::OutlineItem,
     syntax_map::{
         SyntaxLayer, SyntaxMap, SyntaxMapCapture, SyntaxMapCaptures, SyntaxMapMatches,
         SyntaxSnapshot, ToTreeSitterPoint,
     },
-    CodeLabel, LanguageScope, Outline,
+    CodeLabel, LanguageScope, Outline, TextObject,
 };
 use anyhow::{anyhow, Context, Result};
 pub use clock::ReplicaId;
@@ -110,6 +111,11 @@ pub struct Buffer {
     completion_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
     capability: Capability,
+    /// Whether the buffer's file is too large for its syntax to be parsed
+    /// and its git diff to be computed.
+    large_file_mode: bool,
+    /// The character encoding of the buffer's file.
+    encoding: Encoding,
 }
 
 /// An immutable, cheaply cloneable representation of a fixed
@@ -343,6 +349,8 @@ pub enum Event {
     CapabilityChanged,
     /// The buffer was explicitly requested to close.
     Closed,
+    /// The buffer entered or left large file mode.
+    LargeFileModeChanged,
 }
 
 /// The file associated with a buffer.
@@ -393,8 +401,8 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file.
     fn abs_path(&self, cx: &AppContext) -> PathBuf;
 
-    /// Loads the file's contents from disk.
-    fn load(&self, cx: &AppContext) -> Task<Result<String>>;
+    /// Loads the file's raw contents from disk.
+    fn load_bytes(&self, cx: &AppContext) -> Task<Result<Vec<u8>>>;
 
     /// Called when the buffer is reloaded from disk.
     fn buffer_reloaded(
@@ -698,6 +706,8 @@ impl Buffer {
             completion_triggers: Default::default(),
             completion_triggers_timestamp: Default::default(),
             deferred_ops: OperationQueue::new(),
+            large_file_mode: false,
+            encoding: Encoding::default(),
         }
     }
 
@@ -779,6 +789,49 @@ impl Buffer {
         cx.emit(Event::CapabilityChanged)
     }
 
+    /// The character encoding that the buffer's file is decoded from when it
+    /// is loaded, and encoded to when it is saved.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Sets the encoding that the buffer's file is saved with, and decoded
+    /// from the next time it's reloaded.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut ModelContext<Self>) {
+        if self.encoding != encoding {
+            self.encoding = encoding;
+            cx.notify();
+        }
+    }
+
+    /// Whether the buffer is in large file mode, see [Buffer::set_large_file_mode].
+    pub fn is_in_large_file_mode(&self) -> bool {
+        self.large_file_mode
+    }
+
+    /// Puts the buffer in or out of large file mode. In large file mode, the
+    /// buffer's syntax isn't parsed and its git diff isn't computed, so that
+    /// huge files stay responsive.
+    pub fn set_large_file_mode(&mut self, large_file_mode: bool, cx: &mut ModelContext<Self>) {
+        if self.large_file_mode == large_file_mode {
+            return;
+        }
+        self.large_file_mode = large_file_mode;
+        if large_file_mode {
+            self.parse_count += 1;
+            self.syntax_map.lock().clear();
+            self.git_diff = git::diff::BufferDiff::new();
+            self.git_diff_update_count += 1;
+        } else {
+            self.reparse(cx);
+            if let Some(task) = self.git_diff_recalc(cx) {
+                task.detach();
+            }
+        }
+        cx.emit(Event::LargeFileModeChanged);
+        cx.notify();
+    }
+
     /// This method is called to signal that the buffer has been saved.
     pub fn did_save(
         &mut self,
@@ -802,15 +855,19 @@ impl Buffer {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
-            let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
+            let Some((new_mtime, encoding, load)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.mtime(), file.load(cx)))
+                Some((file.mtime(), this.encoding, file.load_bytes(cx)))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let new_bytes = load.await?;
+            let new_text = cx
+                .background_executor()
+                .spawn(async move { encoding.decode(&new_bytes) })
+                .await;
             let diff = this
                 .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
@@ -843,6 +900,15 @@ impl Buffer {
         rx
     }
 
+    /// Sets the line ending that the buffer's lines are joined with when it is
+    /// saved.
+    pub fn set_line_ending(&mut self, line_ending: LineEnding, cx: &mut ModelContext<Self>) {
+        if self.line_ending() != line_ending {
+            self.text.set_line_ending(line_ending);
+            cx.notify();
+        }
+    }
+
     /// This method is called to signal that the buffer has been reloaded.
     pub fn did_reload(
         &mut self,
@@ -924,6 +990,9 @@ impl Buffer {
 
     /// Recomputes the Git diff status.
     pub fn git_diff_recalc(&mut self, cx: &mut ModelContext<Self>) -> Option<Task<()>> {
+        if self.large_file_mode {
+            return None;
+        }
         let diff_base = self.diff_base.clone()?; // TODO: Make this an Arc
         let snapshot = self.snapshot();
 
@@ -1025,7 +1094,7 @@ impl Buffer {
     /// for the same buffer, we only initiate a new parse if we are not already
     /// parsing in the background.
     pub fn reparse(&mut self, cx: &mut ModelContext<Self>) {
-        if self.parsing_in_background {
+        if self.parsing_in_background || self.large_file_mode {
             return;
         }
         let language = if let Some(language) = self.language.clone() {
@@ -2512,7 +2581,7 @@ impl BufferSnapshot {
         &self,
         position: D,
         cx: &'a AppContext,
-    ) -> &'a LanguageSettings {
+    ) -> LanguageSettingsRef<'a> {
         language_settings(self.language_at(position), self.file.as_ref(), cx)
     }
 
@@ -2886,6 +2955,61 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the text objects (from the language's `textobjects.scm` query)
+    /// that overlap the given range, along with the kind of each one.
+    ///
+    /// Several captures of the same kind in one match (such as consecutive
+    /// line comments) are combined into a single range.
+    pub fn text_object_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, TextObject)> + '_ {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+
+        // Widen the range so that objects that start or end at its edges are found.
+        let query_range = range.start.saturating_sub(1)..self.len().min(range.end + 1);
+        let mut matches = self.syntax.matches(query_range, &self.text, |grammar| {
+            grammar.text_object_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.text_object_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut captures = Vec::<(Range<usize>, TextObject)>::new();
+        iter::from_fn(move || loop {
+            while let Some((capture_range, text_object)) = captures.pop() {
+                if capture_range.start <= range.end && capture_range.end >= range.start {
+                    return Some((capture_range, text_object));
+                }
+            }
+
+            let mat = matches.peek()?;
+            let config = &configs[mat.grammar_index];
+            for capture in mat.captures {
+                let Some(text_object) = config
+                    .text_objects_by_capture_ix
+                    .iter()
+                    .find_map(|(ix, text_object)| (*ix == capture.index).then_some(*text_object))
+                else {
+                    continue;
+                };
+                let byte_range = capture.node.byte_range();
+                if let Some((existing, _)) = captures
+                    .iter_mut()
+                    .find(|(_, existing)| *existing == text_object)
+                {
+                    existing.start = existing.start.min(byte_range.start);
+                    existing.end = existing.end.max(byte_range.end);
+                } else {
+                    captures.push((byte_range, text_object));
+                }
+            }
+            matches.advance();
+        })
+    }
+
     /// Returns enclosing bracket ranges containing the given range
     pub fn enclosing_bracket_ranges<T: ToOffset>(
         &self,
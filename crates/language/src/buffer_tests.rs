@@ -479,6 +479,30 @@ async fn test_resetting_language(cx: &mut gpui::TestAppContext) {
     assert_eq!(get_tree_sexp(&buffer, cx), "(document (object))");
 }
 
+#[gpui::test]
+async fn test_large_file_mode(cx: &mut gpui::TestAppContext) {
+    let buffer = cx.new_model(|cx| {
+        let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "{}");
+        buffer.set_large_file_mode(true, cx);
+        buffer.set_sync_parse_timeout(Duration::ZERO);
+        buffer.with_language(Arc::new(rust_lang()), cx)
+    });
+
+    // Large files aren't parsed.
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert!(buffer.snapshot().syntax_layers().next().is_none());
+    });
+
+    // Until large file mode is turned off.
+    buffer.update(cx, |buffer, cx| buffer.set_large_file_mode(false, cx));
+    cx.executor().run_until_parked();
+    assert_eq!(
+        get_tree_sexp(&buffer, cx),
+        "(source_file (expression_statement (block)))"
+    );
+}
+
 #[gpui::test]
 async fn test_outline(cx: &mut gpui::TestAppContext) {
     let text = r#"
@@ -585,6 +609,51 @@ async fn test_outline(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+fn test_text_object_ranges(cx: &mut AppContext) {
+    let text = r#"
+        // A comment
+        // on two lines
+        fn a(b: usize, c: usize) {
+            b + c
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(rust_lang()), cx)
+    });
+    let snapshot = buffer.read(cx).snapshot();
+    let text = snapshot.text();
+    let offset = text.find("b +").unwrap();
+    let mut ranges = snapshot
+        .text_object_ranges(offset..offset)
+        .map(|(range, text_object)| (&text[range], text_object))
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|(text, _)| text.len());
+    assert_eq!(
+        ranges,
+        [
+            ("{\n    b + c\n}", TextObject::InsideFunction),
+            (
+                "fn a(b: usize, c: usize) {\n    b + c\n}",
+                TextObject::AroundFunction
+            ),
+        ]
+    );
+
+    // Consecutive line comments are captured together.
+    let offset = text.find("on two").unwrap();
+    assert_eq!(
+        snapshot
+            .text_object_ranges(offset..offset)
+            .map(|(range, text_object)| (&text[range], text_object))
+            .max_by_key(|(text, _)| text.len()),
+        Some(("// A comment\n// on two lines", TextObject::AroundComment))
+    );
+}
+
 #[gpui::test]
 async fn test_outline_nodes_with_newlines(cx: &mut gpui::TestAppContext) {
     let text = r#"
@@ -2460,6 +2529,14 @@ fn rust_lang() -> Language {
         "#,
     )
     .unwrap()
+    .with_text_object_query(
+        r#"
+        (function_item
+            body: (_) @function.inside) @function.around
+        (line_comment)+ @comment.around
+        "#,
+    )
+    .unwrap()
 }
 
 fn json_lang() -> Language {
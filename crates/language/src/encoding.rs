@@ -0,0 +1,369 @@
+//! Detection and conversion of the character encodings that files are stored in.
+
+use anyhow::{anyhow, Result};
+use encoding_rs::{
+    BIG5, EUC_JP, EUC_KR, GB18030, GBK, ISO_8859_15, KOI8_R, SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8,
+    WINDOWS_1250, WINDOWS_1251, WINDOWS_1252, WINDOWS_1253, WINDOWS_1254,
+};
+
+/// The character encoding of a file, and whether it starts with a byte order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    with_bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::utf8()
+    }
+}
+
+impl Encoding {
+    /// UTF-8 without a byte order mark, which is what files are saved as by default.
+    pub fn utf8() -> Self {
+        Self {
+            encoding: UTF_8,
+            with_bom: false,
+        }
+    }
+
+    /// The encodings that a file can be reopened or saved with.
+    pub fn all() -> Vec<Self> {
+        let mut encodings = vec![Self::utf8(), Self::utf8().with_bom(true)];
+        encodings.extend(
+            [
+                UTF_16LE,
+                UTF_16BE,
+                WINDOWS_1252,
+                ISO_8859_15,
+                WINDOWS_1250,
+                WINDOWS_1251,
+                KOI8_R,
+                WINDOWS_1253,
+                WINDOWS_1254,
+                SHIFT_JIS,
+                EUC_JP,
+                GBK,
+                GB18030,
+                BIG5,
+                EUC_KR,
+            ]
+            .into_iter()
+            .map(|encoding| Self {
+                encoding,
+                with_bom: false,
+            }),
+        );
+        encodings
+    }
+
+    /// Returns the encoding for the value of an EditorConfig `charset` property.
+    pub fn for_editorconfig_charset(charset: &str) -> Option<Self> {
+        let (encoding, with_bom) = match charset {
+            "utf-8" => (UTF_8, false),
+            "utf-8-bom" => (UTF_8, true),
+            "utf-16le" => (UTF_16LE, false),
+            "utf-16be" => (UTF_16BE, false),
+            // Windows-1252 is a superset of the printable characters in Latin-1.
+            "latin1" => (WINDOWS_1252, false),
+            _ => return None,
+        };
+        Some(Self { encoding, with_bom })
+    }
+
+    /// Guesses the encoding of a file's contents.
+    ///
+    /// A byte order mark is trusted, and UTF-16 without one is recognized by
+    /// its null bytes. Otherwise, content that is valid UTF-8 is taken to be
+    /// UTF-8. Content that isn't is considered binary, and `None` is returned,
+    /// if it contains null bytes or too many other control characters. Shift
+    /// JIS is then recognized, and anything else is decoded as Windows-1252.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        Self::detect_in(bytes, true)
+    }
+
+    /// Guesses the encoding of a file from the start of its contents, like
+    /// [`Encoding::detect`], allowing for the last character to be cut off.
+    pub fn detect_from_start(bytes: &[u8]) -> Option<Self> {
+        Self::detect_in(bytes, false)
+    }
+
+    fn detect_in(bytes: &[u8], is_complete: bool) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Some(Self {
+                encoding,
+                with_bom: true,
+            });
+        }
+        // Null bytes are valid UTF-8, so UTF-16 has to be recognized first.
+        let utf16_len = if is_complete {
+            bytes.len()
+        } else {
+            bytes.len() & !1
+        };
+        if let Some(encoding) = detect_utf16_without_bom(&bytes[..utf16_len]) {
+            return Some(Self {
+                encoding,
+                with_bom: false,
+            });
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(_) => return Some(Self::utf8()),
+            Err(error) if !is_complete && error.error_len().is_none() => return Some(Self::utf8()),
+            Err(_) => {}
+        }
+
+        if looks_binary(bytes) {
+            return None;
+        }
+        let encoding = if is_shift_jis(bytes) {
+            SHIFT_JIS
+        } else {
+            WINDOWS_1252
+        };
+        Some(Self {
+            encoding,
+            with_bom: false,
+        })
+    }
+
+    /// The name of this encoding, as shown in the status bar.
+    pub fn name(&self) -> String {
+        if self.with_bom {
+            format!("{} with BOM", self.encoding.name())
+        } else {
+            self.encoding.name().to_string()
+        }
+    }
+
+    /// The writing systems this encoding is used for.
+    pub fn description(&self) -> &'static str {
+        let encoding = self.encoding;
+        if encoding == UTF_8 || encoding == UTF_16LE || encoding == UTF_16BE {
+            "Unicode"
+        } else if encoding == WINDOWS_1252 || encoding == ISO_8859_15 {
+            "Western"
+        } else if encoding == WINDOWS_1250 {
+            "Central European"
+        } else if encoding == WINDOWS_1251 || encoding == KOI8_R {
+            "Cyrillic"
+        } else if encoding == WINDOWS_1253 {
+            "Greek"
+        } else if encoding == WINDOWS_1254 {
+            "Turkish"
+        } else if encoding == SHIFT_JIS || encoding == EUC_JP {
+            "Japanese"
+        } else if encoding == GBK || encoding == GB18030 {
+            "Simplified Chinese"
+        } else if encoding == BIG5 {
+            "Traditional Chinese"
+        } else if encoding == EUC_KR {
+            "Korean"
+        } else {
+            ""
+        }
+    }
+
+    /// Whether a byte order mark is written at the start of the file.
+    pub fn has_bom(&self) -> bool {
+        self.with_bom
+    }
+
+    /// Returns this encoding with or without a byte order mark. Only the
+    /// Unicode encodings have one.
+    pub fn with_bom(self, with_bom: bool) -> Self {
+        Self {
+            encoding: self.encoding,
+            with_bom: with_bom && self.is_unicode(),
+        }
+    }
+
+    fn is_unicode(&self) -> bool {
+        self.encoding == UTF_8 || self.encoding == UTF_16LE || self.encoding == UTF_16BE
+    }
+
+    /// Decodes a file's contents, replacing malformed sequences with the
+    /// replacement character. A leading byte order mark is skipped.
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let bytes = match encoding_rs::Encoding::for_bom(bytes) {
+            Some((encoding, bom_len)) if encoding == self.encoding => &bytes[bom_len..],
+            _ => bytes,
+        };
+        self.encoding
+            .decode_without_bom_handling(bytes)
+            .0
+            .into_owned()
+    }
+
+    /// Returns a decoder for a file's contents that are read a chunk at a
+    /// time. Like [`Encoding::decode`], it skips a leading byte order mark.
+    pub fn new_decoder(&self) -> encoding_rs::Decoder {
+        self.encoding.new_decoder_with_bom_removal()
+    }
+
+    /// Encodes text to be written to a file, failing if the text contains
+    /// characters that can't be represented in this encoding.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len());
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let big_endian = self.encoding == UTF_16BE;
+            let units = self.with_bom.then_some(0xFEFF).into_iter();
+            for unit in units.chain(text.encode_utf16()) {
+                if big_endian {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+        } else {
+            if self.with_bom {
+                bytes.extend_from_slice(b"\xEF\xBB\xBF");
+            }
+            let (encoded, _, has_unmappable_characters) = self.encoding.encode(text);
+            if has_unmappable_characters {
+                return Err(anyhow!(
+                    "the text contains characters that can't be saved as {}",
+                    self.name()
+                ));
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Recognizes UTF-16 text that is mostly ASCII by the null bytes in every
+/// other position.
+fn detect_utf16_without_bom(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    if bytes.len() < 2 || bytes.len() % 2 != 0 {
+        return None;
+    }
+
+    let unit_count = bytes.len() / 2;
+    let mut nulls_in_even_positions = 0;
+    let mut nulls_in_odd_positions = 0;
+    for unit in bytes.chunks_exact(2) {
+        if unit[0] == 0 {
+            nulls_in_even_positions += 1;
+        }
+        if unit[1] == 0 {
+            nulls_in_odd_positions += 1;
+        }
+    }
+
+    let encoding = if nulls_in_odd_positions * 2 >= unit_count && nulls_in_even_positions == 0 {
+        UTF_16LE
+    } else if nulls_in_even_positions * 2 >= unit_count && nulls_in_odd_positions == 0 {
+        UTF_16BE
+    } else {
+        return None;
+    };
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .map(|_| encoding)
+}
+
+/// Whether content that isn't valid UTF-8 looks binary rather than like text
+/// in a legacy encoding, in which control characters other than whitespace
+/// and escapes are rare.
+fn looks_binary(bytes: &[u8]) -> bool {
+    let mut control_count = 0;
+    for byte in bytes {
+        match byte {
+            0 => return true,
+            b'\t' | b'\n' | b'\x0C' | b'\r' | b'\x1B' => {}
+            0x01..=0x1F | 0x7F => control_count += 1,
+            _ => {}
+        }
+    }
+    control_count * MAX_CONTROL_CHARACTER_RATIO > bytes.len()
+}
+
+/// Content that isn't valid UTF-8 is considered binary if more than one in this
+/// many of its bytes are control characters.
+const MAX_CONTROL_CHARACTER_RATIO: usize = 100;
+
+/// Whether the content decodes as Shift JIS and contains at least one
+/// double-byte character, as most Windows-1252 text isn't valid Shift JIS.
+fn is_shift_jis(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .any(|byte| matches!(byte, 0x81..=0x9F | 0xE0..=0xFC))
+        && SHIFT_JIS
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(Encoding::detect(b"plain text"), Some(Encoding::utf8()));
+        assert_eq!(
+            Encoding::detect("caf\u{e9}".as_bytes()),
+            Some(Encoding::utf8())
+        );
+        assert_eq!(
+            Encoding::detect(b"\xEF\xBB\xBFwith bom"),
+            Some(Encoding::utf8().with_bom(true))
+        );
+        assert_eq!(
+            Encoding::detect(b"\xFF\xFEh\0i\0").map(|encoding| encoding.name()),
+            Some("UTF-16LE with BOM".to_string())
+        );
+        assert_eq!(
+            Encoding::detect(b"\0h\0i\0!").map(|encoding| encoding.name()),
+            Some("UTF-16BE".to_string())
+        );
+        assert_eq!(
+            Encoding::detect(b"caf\xE9 cr\xE8me").map(|encoding| encoding.name()),
+            Some("windows-1252".to_string())
+        );
+        assert_eq!(
+            Encoding::detect(b"\x82\xB1\x82\xF1\x82\xC9\x82\xBF\x82\xCD").map(|e| e.name()),
+            Some("Shift_JIS".to_string())
+        );
+        assert_eq!(Encoding::detect(b"\x00\x9F\x92\x96\xFF"), None);
+        // Content without null bytes is still binary if it has many control characters.
+        assert_eq!(Encoding::detect(b"\x9F\x92\x01\x96\xFF\x02\x03"), None);
+        assert_eq!(
+            Encoding::detect(b"caf\xE9\x1B[0m\tcr\xE8me\r\n").map(|e| e.name()),
+            Some("windows-1252".to_string())
+        );
+
+        // The start of a file can end in the middle of a character.
+        assert_eq!(
+            Encoding::detect(b"caf\xC3"),
+            Some(Encoding::for_editorconfig_charset("latin1").unwrap())
+        );
+        assert_eq!(
+            Encoding::detect_from_start(b"caf\xC3"),
+            Some(Encoding::utf8())
+        );
+        assert_eq!(
+            Encoding::detect_from_start(b"h\0i\0!").map(|encoding| encoding.name()),
+            Some("UTF-16LE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_encode_and_decode() {
+        for encoding in Encoding::all() {
+            let text = "hello\nworld";
+            let bytes = encoding.encode(text).unwrap();
+            assert_eq!(encoding.decode(&bytes), text, "{}", encoding.name());
+        }
+
+        let utf16 = Encoding::detect(b"\xFF\xFEh\0i\0").unwrap();
+        assert_eq!(utf16.decode(b"\xFF\xFEh\0i\0"), "hi");
+        assert_eq!(utf16.encode("hi").unwrap(), b"\xFF\xFEh\0i\0");
+
+        let latin1 = Encoding::for_editorconfig_charset("latin1").unwrap();
+        assert_eq!(latin1.decode(b"caf\xE9"), "caf\u{e9}");
+        assert_eq!(latin1.encode("caf\u{e9}").unwrap(), b"caf\xE9");
+        assert!(latin1.encode("\u{3053}").is_err());
+    }
+}
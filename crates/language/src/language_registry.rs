@@ -90,6 +90,7 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("highlights", |q| &mut q.highlights),
     ("brackets", |q| &mut q.brackets),
     ("outline", |q| &mut q.outline),
+    ("textobjects", |q| &mut q.text_objects),
     ("indents", |q| &mut q.indents),
     ("embedding", |q| &mut q.embedding),
     ("injections", |q| &mut q.injections),
@@ -104,6 +105,7 @@ pub struct LanguageQueries {
     pub brackets: Option<Cow<'static, str>>,
     pub indents: Option<Cow<'static, str>>,
     pub outline: Option<Cow<'static, str>>,
+    pub text_objects: Option<Cow<'static, str>>,
     pub embedding: Option<Cow<'static, str>>,
     pub injections: Option<Cow<'static, str>>,
     pub overrides: Option<Cow<'static, str>>,
@@ -4,30 +4,114 @@ use crate::{File, Language};
 use anyhow::Result;
 use collections::{HashMap, HashSet};
 use globset::GlobMatcher;
-use gpui::AppContext;
+use gpui::{AppContext, Global};
+use parking_lot::Mutex;
 use schemars::{
     schema::{InstanceType, ObjectValidation, Schema, SchemaObject},
     JsonSchema,
 };
 use serde::{Deserialize, Serialize};
-use settings::Settings;
-use std::{num::NonZeroU32, path::Path, sync::Arc};
+use settings::{
+    EditorConfigProperties, EndOfLine, IndentStyle, MaxLineLength, Settings, SettingsStore,
+};
+use std::{num::NonZeroU32, ops::Deref, path::Path, sync::Arc};
+use text::LineEnding;
 
 /// Initializes the language settings.
 pub fn init(cx: &mut AppContext) {
     AllLanguageSettings::register(cx);
+    cx.set_global(EditorConfigLanguageSettings::default());
 }
 
 /// Returns the settings for the specified language from the provided file.
+///
+/// Properties from any `.editorconfig` files that apply to the file take
+/// precedence over the settings from Zed's settings files.
 pub fn language_settings<'a>(
     language: Option<&Arc<Language>>,
     file: Option<&Arc<dyn File>>,
     cx: &'a AppContext,
-) -> &'a LanguageSettings {
+) -> LanguageSettingsRef<'a> {
     let language_name = language.map(|l| l.name());
-    all_language_settings(file, cx).language(language_name.as_deref())
+    let settings = all_language_settings(file, cx).language(language_name.as_deref());
+    let Some(file) = file else {
+        return LanguageSettingsRef::Borrowed(settings);
+    };
+    let store = cx.global::<SettingsStore>();
+    let Some(properties) = store.editorconfig_properties(file.worktree_id(), file.path()) else {
+        return LanguageSettingsRef::Borrowed(settings);
+    };
+    let merge = || {
+        let mut settings = settings.clone();
+        settings.merge_editorconfig(&properties);
+        Arc::new(settings)
+    };
+    let Some(cache) = cx.try_global::<EditorConfigLanguageSettings>() else {
+        return LanguageSettingsRef::Merged(merge());
+    };
+
+    let mut cache = cache.0.lock();
+    if cache.generation != store.generation() {
+        cache.generation = store.generation();
+        cache.settings.clear();
+    }
+    let cached_settings = cache
+        .settings
+        .entry((file.worktree_id(), language_name))
+        .or_default();
+    if let Some(settings) = cached_settings.get(file.path().as_ref()) {
+        return LanguageSettingsRef::Merged(settings.clone());
+    }
+    let merged = merge();
+    cached_settings.insert(file.path().clone(), merged.clone());
+    LanguageSettingsRef::Merged(merged)
+}
+
+/// The settings for a language, as returned by [`language_settings`].
+#[derive(Debug, Clone)]
+pub enum LanguageSettingsRef<'a> {
+    /// The settings from Zed's settings files.
+    Borrowed(&'a LanguageSettings),
+    /// The settings merged with the properties of the `.editorconfig` files
+    /// that apply to a file.
+    Merged(Arc<LanguageSettings>),
+}
+
+impl LanguageSettingsRef<'_> {
+    /// Returns an owned copy of the settings.
+    pub fn into_owned(self) -> LanguageSettings {
+        match self {
+            Self::Borrowed(settings) => settings.clone(),
+            Self::Merged(settings) => Arc::unwrap_or_clone(settings),
+        }
+    }
+}
+
+impl Deref for LanguageSettingsRef<'_> {
+    type Target = LanguageSettings;
+
+    fn deref(&self) -> &LanguageSettings {
+        match self {
+            Self::Borrowed(settings) => settings,
+            Self::Merged(settings) => settings,
+        }
+    }
+}
+
+/// The language settings merged with EditorConfig properties for each file,
+/// kept until any setting or `.editorconfig` file changes, so that they aren't
+/// merged again every time they're read.
+#[derive(Default)]
+struct EditorConfigLanguageSettings(Mutex<CachedLanguageSettings>);
+
+#[derive(Default)]
+struct CachedLanguageSettings {
+    generation: usize,
+    settings: HashMap<(usize, Option<Arc<str>>), HashMap<Arc<Path>, Arc<LanguageSettings>>>,
 }
 
+impl Global for EditorConfigLanguageSettings {}
+
 /// Returns the settings for all languages from the provided file.
 pub fn all_language_settings<'a>(
     file: Option<&Arc<dyn File>>,
@@ -66,6 +150,12 @@ pub struct LanguageSettings {
     pub show_wrap_guides: bool,
     /// Character counts at which to show wrap guides in the editor.
     pub wrap_guides: Vec<usize>,
+    /// Whether to show vertical guides at each indentation level, with the
+    /// guide of the scope containing the cursor highlighted.
+    pub show_indent_guides: bool,
+    /// Whether to color bracket pairs by how deeply they are nested, cycling
+    /// through the theme's accent colors.
+    pub colorize_brackets: bool,
     /// Whether or not to perform a buffer format before saving.
     pub format_on_save: FormatOnSave,
     /// Whether or not to remove any trailing whitespace from lines of a buffer
@@ -95,6 +185,46 @@ pub struct LanguageSettings {
     pub use_autoclose: bool,
     /// Which code actions to run on save
     pub code_actions_on_format: HashMap<String, bool>,
+    /// The line ending that buffers are converted to when saving them, as
+    /// specified by an `.editorconfig` file.
+    #[serde(skip)]
+    pub line_ending: Option<LineEnding>,
+    /// The character set that files are expected to be encoded with, as
+    /// specified by an `.editorconfig` file.
+    #[serde(skip)]
+    pub charset: Option<String>,
+}
+
+impl LanguageSettings {
+    /// Overrides these settings with the given EditorConfig properties.
+    ///
+    /// Carriage return line endings aren't supported, so `end_of_line = cr`
+    /// is ignored.
+    pub fn merge_editorconfig(&mut self, properties: &EditorConfigProperties) {
+        if let Some(indent_style) = properties.indent_style {
+            self.hard_tabs = indent_style == IndentStyle::Tab;
+        }
+        if let Some(tab_size) = properties.indent_width().and_then(NonZeroU32::new) {
+            self.tab_size = tab_size;
+        }
+        match properties.end_of_line {
+            Some(EndOfLine::Lf) => self.line_ending = Some(LineEnding::Unix),
+            Some(EndOfLine::CrLf) => self.line_ending = Some(LineEnding::Windows),
+            Some(EndOfLine::Cr) | None => {}
+        }
+        if let Some(charset) = &properties.charset {
+            self.charset = Some(charset.clone());
+        }
+        if let Some(trim_trailing_whitespace) = properties.trim_trailing_whitespace {
+            self.remove_trailing_whitespace_on_save = trim_trailing_whitespace;
+        }
+        if let Some(insert_final_newline) = properties.insert_final_newline {
+            self.ensure_final_newline_on_save = insert_final_newline;
+        }
+        if let Some(MaxLineLength::Columns(max_line_length)) = properties.max_line_length {
+            self.preferred_line_length = max_line_length;
+        }
+    }
 }
 
 /// The settings for [GitHub Copilot](https://github.com/features/copilot).
@@ -161,6 +291,18 @@ pub struct LanguageSettingsContent {
     /// Default: []
     #[serde(default)]
     pub wrap_guides: Option<Vec<usize>>,
+    /// Whether to show vertical guides at each indentation level, with the
+    /// guide of the scope containing the cursor highlighted.
+    ///
+    /// Default: true
+    #[serde(default)]
+    pub show_indent_guides: Option<bool>,
+    /// Whether to color bracket pairs by how deeply they are nested, cycling
+    /// through the theme's accent colors.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub colorize_brackets: Option<bool>,
     /// Whether or not to perform a buffer format before saving.
     ///
     /// Default: on
@@ -579,6 +721,8 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     merge(&mut settings.use_autoclose, src.use_autoclose);
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
+    merge(&mut settings.show_indent_guides, src.show_indent_guides);
+    merge(&mut settings.colorize_brackets, src.colorize_brackets);
     merge(
         &mut settings.code_actions_on_format,
         src.code_actions_on_format.clone(),
@@ -52,6 +52,7 @@ mod zig;
 // 5. Add indents.scm, outline.scm, and brackets.scm to implement indent on newline, outline/breadcrumbs,
 //    and autoclosing brackets respectively
 // 6. If the language has injections add an injections.scm query file
+// 7. Add textobjects.scm to implement vim text objects for functions, classes, comments and arguments
 
 #[derive(RustEmbed)]
 #[folder = "src/"]
@@ -0,0 +1,19 @@
+(function_definition
+    body: (block) @function.inside) @function.around
+
+(lambda
+    body: (_) @function.inside) @function.around
+
+(class_definition
+    body: (block) @class.inside) @class.around
+
+(comment)+ @comment.around
+
+(parameters
+    (_) @argument.inside)
+
+(lambda_parameters
+    (_) @argument.inside)
+
+(argument_list
+    (_) @argument.inside)
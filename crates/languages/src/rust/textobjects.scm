@@ -0,0 +1,66 @@
+(function_item
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(closure_expression
+    body: (block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(struct_item
+    body: (field_declaration_list
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(enum_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(union_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(trait_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(impl_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(mod_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(line_comment)+ @comment.around
+
+(block_comment) @comment.around
+
+(parameters
+    (_) @argument.inside)
+
+(closure_parameters
+    (_) @argument.inside)
+
+(arguments
+    (_) @argument.inside)
+
+(type_parameters
+    (_) @argument.inside)
+
+(type_arguments
+    (_) @argument.inside)
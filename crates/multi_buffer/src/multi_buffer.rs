@@ -10,7 +10,7 @@ use gpui::{AppContext, EventEmitter, Model, ModelContext};
 pub use language::Completion;
 use language::{
     char_kind,
-    language_settings::{language_settings, LanguageSettings},
+    language_settings::{language_settings, LanguageSettingsRef},
     AutoindentMode, Buffer, BufferChunks, BufferSnapshot, Capability, CharKind, Chunk, CursorShape,
     DiagnosticEntry, File, IndentSize, Language, LanguageScope, OffsetRangeExt, OffsetUtf16,
     Outline, OutlineItem, Point, PointUtf16, Selection, TextDimension, ToOffset as _,
@@ -94,6 +94,7 @@ pub enum Event {
     Closed,
     DirtyChanged,
     DiagnosticsUpdated,
+    LargeFileModeChanged,
 }
 
 #[derive(Clone)]
@@ -1466,6 +1467,7 @@ impl MultiBuffer {
             language::Event::Reparsed => Event::Reparsed,
             language::Event::DiagnosticsUpdated => Event::DiagnosticsUpdated,
             language::Event::Closed => Event::Closed,
+            language::Event::LargeFileModeChanged => Event::LargeFileModeChanged,
             language::Event::CapabilityChanged => {
                 self.capability = buffer.read(cx).capability();
                 Event::CapabilityChanged
@@ -1538,7 +1540,7 @@ impl MultiBuffer {
         &self,
         point: T,
         cx: &'a AppContext,
-    ) -> &'a LanguageSettings {
+    ) -> LanguageSettingsRef<'a> {
         let mut language = None;
         let mut file = None;
         if let Some((buffer, offset, _)) = self.point_to_buffer_offset(point, cx) {
@@ -3055,7 +3057,7 @@ impl MultiBufferSnapshot {
         &'a self,
         point: T,
         cx: &'a AppContext,
-    ) -> &'a LanguageSettings {
+    ) -> LanguageSettingsRef<'a> {
         let mut language = None;
         let mut file = None;
         if let Some((buffer, offset)) = self.point_to_buffer_offset(point) {
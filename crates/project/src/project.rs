@@ -59,7 +59,7 @@ use rand::prelude::*;
 use rpc::{ErrorCode, ErrorExt as _};
 use search::SearchQuery;
 use serde::Serialize;
-use settings::{watch_config_file, Settings, SettingsStore};
+use settings::{watch_config_file, Settings, SettingsStore, EDITORCONFIG_FILE_NAME};
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use smol::channel::{Receiver, Sender};
@@ -68,7 +68,7 @@ use std::{
     cmp::{self, Ordering},
     convert::TryInto,
     env,
-    ffi::OsString,
+    ffi::{OsStr, OsString},
     hash::Hash,
     mem,
     num::NonZeroU32,
@@ -108,6 +108,16 @@ const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub trait Item {
+    /// Opens the item at the given path, or returns `None` if this kind of
+    /// item can't be opened from it. Items are tried in reverse order of their
+    /// registration, so more specific kinds of items take precedence.
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>>
+    where
+        Self: Sized;
     fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId>;
     fn project_path(&self, cx: &AppContext) -> Option<ProjectPath>;
 }
@@ -2026,6 +2036,9 @@ impl Project {
     ) {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
+        if buffer.is_in_large_file_mode() {
+            return;
+        }
 
         if let Some(file) = File::from_dyn(buffer.file()) {
             if !file.is_local() {
@@ -2143,6 +2156,9 @@ impl Project {
         buffer_handle: &Model<Buffer>,
         cx: &mut ModelContext<Self>,
     ) {
+        if buffer_handle.read(cx).is_in_large_file_mode() {
+            return;
+        }
         if let Some(copilot) = Copilot::global(cx) {
             copilot.update(cx, |copilot, cx| copilot.register_buffer(buffer_handle, cx));
         }
@@ -2464,6 +2480,13 @@ impl Project {
                     remote_id,
                 );
             }
+            BufferEvent::LargeFileModeChanged => {
+                if !buffer.read(cx).is_in_large_file_mode() {
+                    self.register_buffer_with_language_servers(&buffer, cx);
+                    self.register_buffer_with_copilot(&buffer, cx);
+                    self.request_buffer_diff_recalculation(&buffer, cx);
+                }
+            }
             _ => {}
         }
 
@@ -2698,7 +2721,8 @@ impl Project {
         });
 
         let buffer_file = buffer.read(cx).file().cloned();
-        let settings = language_settings(Some(&new_language), buffer_file.as_ref(), cx).clone();
+        let settings =
+            language_settings(Some(&new_language), buffer_file.as_ref(), cx).into_owned();
         let buffer_file = File::from_dyn(buffer_file.as_ref());
         let worktree = buffer_file.as_ref().map(|f| f.worktree_id(cx));
         if let Some(prettier_plugins) =
@@ -3241,6 +3265,7 @@ impl Project {
 
                 if file.worktree.read(cx).id() != key.0
                     || !language.lsp_adapters().iter().any(|a| a.name == key.1)
+                    || buffer.is_in_large_file_mode()
                 {
                     continue;
                 }
@@ -4180,11 +4205,12 @@ impl Project {
                     })?;
 
                     let settings = buffer.update(&mut cx, |buffer, cx| {
-                        language_settings(buffer.language(), buffer.file(), cx).clone()
+                        language_settings(buffer.language(), buffer.file(), cx).into_owned()
                     })?;
 
                     let remove_trailing_whitespace = settings.remove_trailing_whitespace_on_save;
                     let ensure_final_newline = settings.ensure_final_newline_on_save;
+                    let line_ending = settings.line_ending;
                     let tab_size = settings.tab_size;
 
                     // First, format buffer's whitespace according to the settings.
@@ -4206,6 +4232,9 @@ impl Project {
                         if ensure_final_newline {
                             buffer.ensure_final_newline(cx);
                         }
+                        if let Some(line_ending) = line_ending {
+                            buffer.set_line_ending(line_ending, cx);
+                        }
                         buffer.end_transaction(cx)
                     })?;
 
@@ -6990,6 +7019,7 @@ impl Project {
         let remote_worktree_id = worktree.read(cx).id();
 
         let mut settings_contents = Vec::new();
+        let mut editorconfig_contents = Vec::new();
         for (path, _, change) in changes.iter() {
             let removed = change == &PathChange::Removed;
             let abs_path = match worktree.read(cx).absolutize(path) {
@@ -7017,6 +7047,21 @@ impl Project {
                         },
                     )
                 });
+            } else if path.file_name() == Some(OsStr::new(EDITORCONFIG_FILE_NAME)) {
+                let Some(editorconfig_dir) = path.parent().map(Arc::from) else {
+                    continue;
+                };
+                let fs = self.fs.clone();
+                editorconfig_contents.push(async move {
+                    (
+                        editorconfig_dir,
+                        if removed {
+                            None
+                        } else {
+                            Some(async move { fs.load(&abs_path).await }.await)
+                        },
+                    )
+                });
             } else if abs_path.ends_with(&*LOCAL_TASKS_RELATIVE_PATH) {
                 self.task_inventory().update(cx, |task_inventory, cx| {
                     if removed {
@@ -7045,7 +7090,7 @@ impl Project {
             }
         }
 
-        if settings_contents.is_empty() {
+        if settings_contents.is_empty() && editorconfig_contents.is_empty() {
             return;
         }
 
@@ -7053,8 +7098,20 @@ impl Project {
         cx.spawn(move |_, cx| async move {
             let settings_contents: Vec<(Arc<Path>, _)> =
                 futures::future::join_all(settings_contents).await;
+            let editorconfig_contents: Vec<(Arc<Path>, _)> =
+                futures::future::join_all(editorconfig_contents).await;
             cx.update(|cx| {
                 cx.update_global::<SettingsStore, _>(|store, cx| {
+                    // EditorConfig properties are only applied to the host's buffers,
+                    // so unlike settings files they aren't sent to collaborators.
+                    for (directory, file_content) in editorconfig_contents {
+                        let file_content = file_content.and_then(|content| content.log_err());
+                        store.set_local_editorconfig(
+                            worktree_id.as_u64() as usize,
+                            directory,
+                            file_content.as_deref(),
+                        );
+                    }
                     for (directory, file_content) in settings_contents {
                         let file_content = file_content.and_then(|content| content.log_err());
                         store
@@ -9023,6 +9080,10 @@ impl Project {
         buffer: &Buffer,
         cx: &AppContext,
     ) -> Vec<LanguageServerId> {
+        // Buffers in large file mode aren't opened in language servers.
+        if buffer.is_in_large_file_mode() {
+            return Vec::new();
+        }
         if let Some((file, language)) = File::from_dyn(buffer.file()).zip(buffer.language()) {
             let worktree_id = file.worktree_id(cx);
             language
@@ -9316,6 +9377,14 @@ fn resolve_path(base: &Path, path: &Path) -> PathBuf {
 }
 
 impl Item for Buffer {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        Some(project.update(cx, |project, cx| project.open_buffer(path.clone(), cx)))
+    }
+
     fn entry_id(&self, cx: &AppContext) -> Option<ProjectEntryId> {
         File::from_dyn(self.file()).and_then(|file| file.project_entry_id(cx))
     }
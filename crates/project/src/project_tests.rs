@@ -3,8 +3,8 @@ use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::AppContext;
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
+    language_settings::{AllLanguageSettings, LanguageSettingsContent, LanguageSettingsRef},
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, Encoding, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::Url;
@@ -185,6 +185,134 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     });
 }
 
+#[gpui::test]
+async fn test_editorconfig_settings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".editorconfig": r#"
+                root = true
+
+                [*]
+                indent_size = 4
+                end_of_line = crlf
+                insert_final_newline = true
+
+                [*.md]
+                trim_trailing_whitespace = false
+            "#.unindent(),
+            ".zed": {
+                "settings.json": r#"{ "tab_size": 8, "format_on_save": "off" }"#,
+            },
+            "a": {
+                ".editorconfig": "[*.rs]\nindent_style = tab\nindent_size = tab\ntab_width = 3\n",
+                "a.rs": "fn a() {\n\tA\n}",
+            },
+            "b": {
+                ".zed": {
+                    "settings.json": r#"{ "tab_size": 2 }"#,
+                },
+                "b.rs": "fn b() {\n    B\n}",
+            },
+            "README.md": "# Readme",
+            "notes.txt": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let worktree = project.update(cx, |project, _| project.worktrees().next().unwrap());
+
+    cx.executor().run_until_parked();
+    cx.update(|cx| {
+        let cx: &AppContext = cx;
+        let tree = worktree.read(cx);
+        let settings_for = |path: &str| {
+            let file =
+                File::for_entry(tree.entry_for_path(path).unwrap().clone(), worktree.clone());
+            language_settings(None, Some(&(file as _)), cx).into_owned()
+        };
+
+        // EditorConfig properties take precedence over settings files, and
+        // properties from nested `.editorconfig` files over those from parent
+        // directories.
+        let settings_a = settings_for("a/a.rs");
+        assert!(settings_a.hard_tabs);
+        assert_eq!(settings_a.tab_size.get(), 3);
+        assert_eq!(settings_a.line_ending, Some(LineEnding::Windows));
+
+        let settings_b = settings_for("b/b.rs");
+        assert!(!settings_b.hard_tabs);
+        assert_eq!(settings_b.tab_size.get(), 4);
+        assert!(settings_b.remove_trailing_whitespace_on_save);
+
+        let settings_readme = settings_for("README.md");
+        assert!(!settings_readme.remove_trailing_whitespace_on_save);
+        assert!(settings_readme.ensure_final_newline_on_save);
+
+        // Settings that no `.editorconfig` file specifies are left alone.
+        assert_eq!(settings_for("notes.txt").format_on_save, FormatOnSave::Off);
+
+        // The merged settings are shared between reads until a setting or an
+        // `.editorconfig` file changes.
+        let file = File::for_entry(
+            tree.entry_for_path("a/a.rs").unwrap().clone(),
+            worktree.clone(),
+        );
+        let merged_settings = || match language_settings(None, Some(&(file.clone() as _)), cx) {
+            LanguageSettingsRef::Merged(settings) => settings,
+            LanguageSettingsRef::Borrowed(_) => panic!("no EditorConfig properties were merged"),
+        };
+        assert!(Arc::ptr_eq(&merged_settings(), &merged_settings()));
+    });
+
+    // The line ending from the `.editorconfig` is used when saving.
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/b/b.rs", cx)
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Save,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load("/the-root/b/b.rs".as_ref()).await.unwrap(),
+        "fn b() {\r\n    B\r\n}\r\n"
+    );
+
+    // Removing an `.editorconfig` file stops its properties from applying.
+    fs.remove_file("/the-root/a/.editorconfig".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    cx.update(|cx| {
+        let tree = worktree.read(cx);
+        let file = File::for_entry(
+            tree.entry_for_path("a/a.rs").unwrap().clone(),
+            worktree.clone(),
+        );
+        let settings = language_settings(None, Some(&(file as _)), cx);
+        assert!(!settings.hard_tabs);
+        assert_eq!(settings.tab_size.get(), 4);
+    });
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2744,6 +2872,115 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_file_encodings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({})).await;
+    fs.insert_file_bytes("/dir/latin1.txt", b"caf\xE9\r\nna\xEFve\r\n".to_vec())
+        .await;
+    fs.insert_file_bytes("/dir/utf16.txt", b"\xFF\xFEh\0i\0\n\0".to_vec())
+        .await;
+    fs.insert_file_bytes("/dir/data.bin", vec![0x00, 0x9F, 0xFF])
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    // Files are decoded with the encoding they are detected to be in, and
+    // encoded with it again when saved.
+    let latin1_buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/latin1.txt", cx))
+        .await
+        .unwrap();
+    latin1_buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "caf\u{e9}\nna\u{ef}ve\n");
+        assert_eq!(buffer.encoding().name(), "windows-1252");
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        buffer.edit([(0..0, "d\u{e9}j\u{e0} ")], None, cx);
+    });
+    project
+        .update(cx, |project, cx| {
+            project.save_buffer(latin1_buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes("/dir/latin1.txt".as_ref()).await.unwrap(),
+        b"d\xE9j\xE0 caf\xE9\r\nna\xEFve\r\n"
+    );
+
+    let utf16_buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/utf16.txt", cx))
+        .await
+        .unwrap();
+    utf16_buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "hi\n");
+        assert_eq!(buffer.encoding().name(), "UTF-16LE with BOM");
+        buffer.edit([(2..2, "!")], None, cx);
+    });
+    project
+        .update(cx, |project, cx| {
+            project.save_buffer(utf16_buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes("/dir/utf16.txt".as_ref()).await.unwrap(),
+        b"\xFF\xFEh\0i\0!\0\n\0"
+    );
+
+    // Binary files can't be opened as buffers.
+    assert!(project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/data.bin", cx))
+        .await
+        .is_err());
+
+    // Text that can't be encoded isn't saved.
+    latin1_buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\u{3053}")], None, cx));
+    assert!(project
+        .update(cx, |project, cx| project
+            .save_buffer(latin1_buffer.clone(), cx))
+        .await
+        .is_err());
+    assert_eq!(
+        fs.load_bytes("/dir/latin1.txt".as_ref()).await.unwrap(),
+        b"d\xE9j\xE0 caf\xE9\r\nna\xEFve\r\n"
+    );
+
+    // Saving with another encoding and line ending converts the file.
+    latin1_buffer.update(cx, |buffer, cx| {
+        buffer.set_encoding(Encoding::utf8(), cx);
+        buffer.set_line_ending(LineEnding::Unix, cx);
+    });
+    project
+        .update(cx, |project, cx| {
+            project.save_buffer(latin1_buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load("/dir/latin1.txt".as_ref()).await.unwrap(),
+        "\u{3053}d\u{e9}j\u{e0} caf\u{e9}\nna\u{ef}ve\n"
+    );
+
+    // Reopening a file with another encoding decodes it again.
+    latin1_buffer
+        .update(cx, |buffer, cx| {
+            buffer.set_encoding(Encoding::detect(b"\xE9").unwrap(), cx);
+            buffer.reload(cx)
+        })
+        .await
+        .unwrap();
+    latin1_buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "\u{e3}\u{81}\u{201c}d\u{c3}\u{a9}j\u{c3}\u{a0} caf\u{c3}\u{a9}\nna\u{c3}\u{af}ve\n"
+        );
+        assert_eq!(buffer.encoding().name(), "windows-1252");
+    });
+}
+
 #[gpui::test(iterations = 30)]
 async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -39,6 +39,20 @@ pub struct ProjectSettings {
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Files bigger than this many megabytes are opened in large file mode,
+    /// which doesn't parse their syntax, compute their git diff or open them
+    /// in language servers.
+    ///
+    /// Default: 20
+    pub large_file_threshold_mb: Option<u64>,
+}
+
+impl ProjectSettings {
+    /// Returns the size in bytes above which files are opened in large file mode.
+    pub fn large_file_threshold(&self) -> u64 {
+        self.large_file_threshold_mb.unwrap_or(20) * 1024 * 1024
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -3,9 +3,10 @@ use crate::{
 };
 use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
 use anyhow::{anyhow, Context as _, Result};
-use client::{proto, Client};
+use client::{proto, proto::ErrorCode, Client, ErrorCodeExt};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet, VecDeque};
+use encoding_rs::CoderResult;
 use fs::{copy_recursive, RemoveOptions};
 use fs::{
     repository::{GitFileStatus, GitRepository, RepoPath},
@@ -32,8 +33,8 @@ use language::{
         deserialize_fingerprint, deserialize_version, serialize_fingerprint, serialize_line_ending,
         serialize_version,
     },
-    Buffer, Capability, DiagnosticEntry, File as _, LineEnding, PointUtf16, Rope, RopeFingerprint,
-    Unclipped,
+    Buffer, Capability, DiagnosticEntry, Encoding, File as _, LineEnding, PointUtf16, Rope,
+    RopeFingerprint, Unclipped,
 };
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
@@ -51,6 +52,7 @@ use std::{
     ffi::OsStr,
     fmt,
     future::Future,
+    io::Read,
     mem,
     ops::{AddAssign, Deref, DerefMut, Sub},
     path::{Path, PathBuf},
@@ -724,6 +726,89 @@ fn path_matchers(values: Option<&[String]>, context: &'static str) -> Vec<PathMa
         .collect()
 }
 
+/// A file loaded from disk, with its line endings normalized.
+struct LoadedFile {
+    file: File,
+    text: Rope,
+    line_ending: LineEnding,
+    encoding: Encoding,
+    diff_base: Option<String>,
+    /// Whether the file is over the large file threshold.
+    is_large: bool,
+}
+
+/// The error for a file that can't be opened in a buffer because it looks
+/// binary, which other items can open instead.
+fn binary_file_error(abs_path: &Path) -> anyhow::Error {
+    ErrorCode::BinaryFile
+        .message(format!("{abs_path:?} appears to be a binary file"))
+        .anyhow()
+}
+
+/// How many bytes of a large file are read at a time.
+const LARGE_FILE_CHUNK_LEN: usize = 64 * 1024;
+
+/// Reads a file into a rope a chunk at a time, so that a large file doesn't
+/// also have to be held in memory as one string. The file's encoding is
+/// detected from its first chunk.
+async fn load_in_chunks(fs: &dyn Fs, abs_path: &Path) -> Result<(Rope, LineEnding, Encoding)> {
+    let mut reader = fs.open_sync(abs_path).await?;
+    let mut text = Rope::new();
+    let mut line_ending = None;
+    let mut encoding = Encoding::utf8();
+    let mut decoder = None;
+    let mut buffer = vec![0; LARGE_FILE_CHUNK_LEN];
+    let mut chunk = String::new();
+    loop {
+        let read_len = reader.read(&mut buffer)?;
+        let is_last = read_len == 0;
+        let mut bytes = &buffer[..read_len];
+        let decoder = match &mut decoder {
+            Some(decoder) => decoder,
+            None => {
+                encoding = Encoding::detect_from_start(bytes)
+                    .ok_or_else(|| binary_file_error(abs_path))?;
+                decoder.insert(encoding.new_decoder())
+            }
+        };
+
+        // The decoder holds back a character that's split across chunks.
+        loop {
+            chunk.reserve(
+                decoder
+                    .max_utf8_buffer_length(bytes.len())
+                    .unwrap_or(bytes.len()),
+            );
+            let (result, read_len, _) = decoder.decode_to_string(bytes, &mut chunk, is_last);
+            bytes = &bytes[read_len..];
+            if let CoderResult::InputEmpty = result {
+                break;
+            }
+        }
+
+        // Hold back a carriage return that could be followed by a newline in
+        // the next chunk.
+        let ends_with_carriage_return = !is_last && chunk.ends_with('\r');
+        if ends_with_carriage_return {
+            chunk.pop();
+        }
+        if !chunk.is_empty() {
+            line_ending.get_or_insert_with(|| LineEnding::detect(&chunk));
+        }
+        LineEnding::normalize(&mut chunk);
+        text.push(&chunk);
+        chunk.clear();
+        if ends_with_carriage_return {
+            chunk.push('\r');
+        }
+
+        if is_last {
+            break;
+        }
+    }
+    Ok((text, line_ending.unwrap_or_default(), encoding))
+}
+
 impl LocalWorktree {
     pub fn contains_abs_path(&self, path: &Path) -> bool {
         path.starts_with(&self.abs_path)
@@ -737,20 +822,27 @@ impl LocalWorktree {
     ) -> Task<Result<Model<Buffer>>> {
         let path = Arc::from(path);
         cx.spawn(move |this, mut cx| async move {
-            let (file, contents, diff_base) = this
+            let loaded = this
                 .update(&mut cx, |t, cx| t.as_local().unwrap().load(&path, cx))?
                 .await?;
             let text_buffer = cx
                 .background_executor()
-                .spawn(async move { text::Buffer::new(0, id, contents) })
+                .spawn(async move {
+                    text::Buffer::new_normalized(0, id, loaded.line_ending, loaded.text)
+                })
                 .await;
-            cx.new_model(|_| {
-                Buffer::build(
+            cx.new_model(|cx| {
+                let mut buffer = Buffer::build(
                     text_buffer,
-                    diff_base,
-                    Some(Arc::new(file)),
+                    loaded.diff_base,
+                    Some(Arc::new(loaded.file)),
                     Capability::ReadWrite,
-                )
+                );
+                if loaded.is_large {
+                    buffer.set_large_file_mode(true, cx);
+                }
+                buffer.set_encoding(loaded.encoding, cx);
+                buffer
             })
         })
     }
@@ -1015,22 +1107,63 @@ impl LocalWorktree {
         }
     }
 
-    fn load(
-        &self,
-        path: &Path,
-        cx: &mut ModelContext<Worktree>,
-    ) -> Task<Result<(File, String, Option<String>)>> {
-        let path = Arc::from(path);
+    fn load(&self, path: &Path, cx: &mut ModelContext<Worktree>) -> Task<Result<LoadedFile>> {
+        let path: Arc<Path> = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
+        let large_file_threshold = ProjectSettings::get(
+            Some((cx.handle().entity_id().as_u64() as usize, path.as_ref())),
+            cx,
+        )
+        .large_file_threshold();
+        // Empty files have no content to detect an encoding from, so they use
+        // the one given by an `.editorconfig` file, if any.
+        let empty_file_encoding = cx
+            .global::<SettingsStore>()
+            .editorconfig_properties(cx.handle().entity_id().as_u64() as usize, &path)
+            .and_then(|properties| properties.charset.clone())
+            .and_then(|charset| Encoding::for_editorconfig_charset(&charset))
+            .unwrap_or_default();
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let is_large = fs
+                .metadata(&abs_path)
+                .await?
+                .map_or(false, |metadata| metadata.len > large_file_threshold);
+            let (text, line_ending, encoding) = if is_large {
+                let fs = fs.clone();
+                let abs_path = abs_path.clone();
+                cx.background_executor()
+                    .spawn(async move { load_in_chunks(fs.as_ref(), &abs_path).await })
+                    .await?
+            } else {
+                let bytes = fs.load_bytes(&abs_path).await?;
+                let encoding = if bytes.is_empty() {
+                    empty_file_encoding
+                } else {
+                    Encoding::detect(&bytes).ok_or_else(|| binary_file_error(&abs_path))?
+                };
+                cx.background_executor()
+                    .spawn(async move {
+                        let mut text = encoding.decode(&bytes);
+                        let line_ending = LineEnding::detect(&text);
+                        LineEnding::normalize(&mut text);
+                        (Rope::from(text.as_str()), line_ending, encoding)
+                    })
+                    .await
+            };
+
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
-            if let Some(repo) = snapshot.repository_for_path(&path) {
+            // Large files aren't diffed, so there's no need to load their index text.
+            let repo = if is_large {
+                None
+            } else {
+                snapshot.repository_for_path(&path)
+            };
+            if let Some(repo) = repo {
                 if let Some(repo_path) = repo.work_directory.relativize(&snapshot, &path).log_err()
                 {
                     if let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory) {
@@ -1053,8 +1186,8 @@ impl LocalWorktree {
                 .upgrade()
                 .ok_or_else(|| anyhow!("worktree was dropped"))?;
             match entry.await? {
-                Some(entry) => Ok((
-                    File {
+                Some(entry) => Ok(LoadedFile {
+                    file: File {
                         entry_id: Some(entry.id),
                         worktree,
                         path: entry.path,
@@ -1064,8 +1197,11 @@ impl LocalWorktree {
                         is_private: entry.is_private,
                     },
                     text,
+                    line_ending,
+                    encoding,
                     diff_base,
-                )),
+                    is_large,
+                }),
                 None => {
                     let metadata = fs
                         .metadata(&abs_path)
@@ -1077,8 +1213,8 @@ impl LocalWorktree {
                             format!("Excluded file {abs_path:?} got removed during loading")
                         })?;
                     let is_private = snapshot.is_path_private(path.as_ref());
-                    Ok((
-                        File {
+                    Ok(LoadedFile {
+                        file: File {
                             entry_id: None,
                             worktree,
                             path,
@@ -1088,8 +1224,11 @@ impl LocalWorktree {
                             is_private,
                         },
                         text,
+                        line_ending,
+                        encoding,
                         diff_base,
-                    ))
+                        is_large,
+                    })
                 }
             }
         })
@@ -1111,7 +1250,13 @@ impl LocalWorktree {
         let text = buffer.as_rope().clone();
         let fingerprint = text.fingerprint();
         let version = buffer.version();
-        let save = self.write_file(path.as_ref(), text, buffer.line_ending(), cx);
+        let save = self.write_file(
+            path.as_ref(),
+            text,
+            buffer.line_ending(),
+            buffer.encoding(),
+            cx,
+        );
         let fs = Arc::clone(&self.fs);
         let abs_path = self.absolutize(&path);
         let is_private = self.snapshot.is_path_private(&path);
@@ -1251,14 +1396,23 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Option<Entry>>> {
         let path: Arc<Path> = path.into();
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
-        let write = cx
-            .background_executor()
-            .spawn(async move { fs.save(&abs_path?, &text, line_ending).await });
+        let write = cx.background_executor().spawn(async move {
+            let abs_path = abs_path?;
+            if encoding == Encoding::utf8() {
+                return fs.save(&abs_path, &text, line_ending).await;
+            }
+            let mut content = String::with_capacity(text.len());
+            for chunk in text.chunks() {
+                content.push_str(&chunk.replace('\n', line_ending.as_str()));
+            }
+            fs.write(&abs_path, &encoding.encode(&content)?).await
+        });
 
         cx.spawn(|this, mut cx| async move {
             write.await?;
@@ -2932,12 +3086,12 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &AppContext) -> Task<Result<String>> {
+    fn load_bytes(&self, cx: &AppContext) -> Task<Result<Vec<u8>>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
         cx.background_executor()
-            .spawn(async move { fs.load(&abs_path?).await })
+            .spawn(async move { fs.load_bytes(&abs_path?).await })
     }
 
     fn buffer_reloaded(
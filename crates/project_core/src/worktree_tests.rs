@@ -669,6 +669,118 @@ async fn test_open_gitignored_files(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_load_large_files(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |project_settings| {
+                project_settings.large_file_threshold_mb = Some(0);
+            });
+        });
+    });
+
+    // Multi-byte characters and line endings straddle the chunk boundaries.
+    let large_text = "héllo\r\nwörld\r\n".repeat(10_000);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "large.txt": large_text,
+            "empty.txt": "",
+        }),
+    )
+    .await;
+    // The encoding is detected from the first chunk of a large file.
+    fs.insert_file_bytes("/root/latin1.txt", b"caf\xE9 cr\xE8me\n".repeat(10_000))
+        .await;
+    let utf16_bytes = "h\u{e9}llo\n"
+        .repeat(10_000)
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect::<Vec<_>>();
+    fs.insert_file_bytes(
+        "/root/utf16.txt",
+        [&[0xFF, 0xFE][..], &utf16_bytes].concat(),
+    )
+    .await;
+    fs.insert_file_bytes("/root/binary.bin", [0x00, 0x00, 0x9F, 0xFF].repeat(20_000))
+        .await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    for (id, path, expected_text, expected_encoding) in [
+        (3, "latin1.txt", "caf\u{e9} cr\u{e8}me\n", "windows-1252"),
+        (4, "utf16.txt", "h\u{e9}llo\n", "UTF-16LE with BOM"),
+    ] {
+        let buffer = tree
+            .update(cx, |tree, cx| {
+                tree.as_local_mut().unwrap().load_buffer(
+                    BufferId::new(id).unwrap(),
+                    path.as_ref(),
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        buffer.read_with(cx, |buffer, _| {
+            assert!(buffer.is_in_large_file_mode());
+            assert_eq!(buffer.encoding().name(), expected_encoding);
+            assert_eq!(buffer.text(), expected_text.repeat(10_000));
+        });
+    }
+    let binary_load = tree.update(cx, |tree, cx| {
+        tree.as_local_mut().unwrap().load_buffer(
+            BufferId::new(5).unwrap(),
+            "binary.bin".as_ref(),
+            cx,
+        )
+    });
+    assert!(binary_load.await.is_err());
+
+    let large_buffer = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut().unwrap().load_buffer(
+                BufferId::new(1).unwrap(),
+                "large.txt".as_ref(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    large_buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.is_in_large_file_mode());
+        assert_eq!(buffer.line_ending(), text::LineEnding::Windows);
+        assert_eq!(buffer.text(), large_text.replace("\r\n", "\n"));
+    });
+
+    let empty_buffer = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut().unwrap().load_buffer(
+                BufferId::new(2).unwrap(),
+                "empty.txt".as_ref(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    empty_buffer.read_with(cx, |buffer, _| {
+        assert!(!buffer.is_in_large_file_mode());
+        assert_eq!(buffer.text(), "");
+    });
+}
+
 #[gpui::test]
 async fn test_dirs_no_longer_ignored(cx: &mut TestAppContext) {
     init_test(cx);
@@ -936,6 +1048,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("tracked-dir/file.txt"),
             "hello".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -946,6 +1059,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("ignored-dir/file.txt"),
             "world".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -1726,8 +1840,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_executor().spawn(async move {
                     task.await?;
                     Ok(())
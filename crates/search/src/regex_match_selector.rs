@@ -0,0 +1,243 @@
+use editor::{actions::SelectRegexMatches, Editor};
+use gpui::{
+    div, prelude::*, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    SharedString, Styled, Subscription, View, ViewContext, VisualContext,
+};
+use theme::ActiveTheme;
+use ui::{h_flex, prelude::*, v_flex, Label};
+use workspace::ModalView;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(RegexMatchSelector::register).detach();
+}
+
+/// Prompts for the regular expression whose matches within the selections of
+/// an editor get selected, when [`SelectRegexMatches`] doesn't give one.
+pub struct RegexMatchSelector {
+    query_editor: View<Editor>,
+    active_editor: View<Editor>,
+    message: SharedString,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ModalView for RegexMatchSelector {}
+
+impl FocusableView for RegexMatchSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.query_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for RegexMatchSelector {}
+
+impl RegexMatchSelector {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let handle = cx.view().downgrade();
+        editor.register_action(move |action: &SelectRegexMatches, cx| {
+            // Actions with a pattern are handled by the editor itself.
+            if !action.pattern.is_empty() {
+                cx.propagate();
+                return;
+            }
+            let Some(editor) = handle.upgrade() else {
+                return;
+            };
+            let Some(workspace) = editor.read(cx).workspace() else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, move |cx| RegexMatchSelector::new(editor, cx));
+            })
+        });
+    }
+
+    pub fn new(active_editor: View<Editor>, cx: &mut ViewContext<Self>) -> Self {
+        let query_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Regular expression", cx);
+            editor
+        });
+        let query_editor_change = cx.subscribe(&query_editor, Self::on_query_editor_event);
+
+        let has_selection = active_editor.update(cx, |editor, cx| {
+            editor
+                .selections
+                .all::<usize>(cx)
+                .iter()
+                .any(|selection| !selection.is_empty())
+        });
+        let message = if has_selection {
+            "Select the matches within the selections"
+        } else {
+            "Select the matches in the buffer"
+        };
+
+        Self {
+            query_editor,
+            active_editor,
+            message: message.into(),
+            _subscriptions: vec![query_editor_change],
+        }
+    }
+
+    fn on_query_editor_event(
+        &mut self,
+        _: View<Editor>,
+        event: &editor::EditorEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let editor::EditorEvent::Blurred = event {
+            cx.emit(DismissEvent);
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let action = SelectRegexMatches {
+            pattern: self.query_editor.read(cx).text(cx),
+        };
+        let result = self
+            .active_editor
+            .update(cx, |editor, cx| editor.select_regex_matches(&action, cx));
+
+        // The modal stays open until the pattern matches something, so that
+        // it can be corrected.
+        match result {
+            Ok(0) => self.message = "No matches".into(),
+            Ok(_) => {
+                self.active_editor.update(cx, |editor, cx| editor.focus(cx));
+                cx.emit(DismissEvent);
+            }
+            Err(error) => self.message = error.to_string().into(),
+        }
+        cx.notify();
+    }
+}
+
+impl Render for RegexMatchSelector {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div()
+            .elevation_2(cx)
+            .key_context("RegexMatchSelector")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.query_editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(self.message.clone()).color(Color::Muted)),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::Workspace;
+
+    #[gpui::test]
+    async fn test_prompting_for_the_pattern(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({ "a.rs": "let a = 1;\nlet bb = 22;\nlet c = 3;" }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let worktree_id = workspace.update(cx, |workspace, cx| {
+            workspace.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.rs"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |selections| selections.select_ranges([0..23]))
+        });
+
+        cx.dispatch_action(SelectRegexMatches::default());
+        let selector = workspace.update(cx, |workspace, cx| {
+            workspace.active_modal::<RegexMatchSelector>(cx).unwrap()
+        });
+
+        // Invalid patterns are reported, and can be corrected.
+        type_query(&selector, "(", cx);
+        cx.dispatch_action(menu::Confirm);
+        selector.update(cx, |selector, _| {
+            assert!(selector.message.contains("regex"))
+        });
+        assert_eq!(selected_ranges(&editor, cx), [0..23]);
+
+        type_query(&selector, r"\d+", cx);
+        cx.dispatch_action(menu::Confirm);
+        assert_eq!(selected_ranges(&editor, cx), [8..9, 20..22]);
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<RegexMatchSelector>(cx).is_none())
+        });
+    }
+
+    fn type_query(selector: &View<RegexMatchSelector>, query: &str, cx: &mut VisualTestContext) {
+        let query_editor = selector.update(cx, |selector, _| selector.query_editor.clone());
+        query_editor.update(cx, |editor, cx| editor.set_text(query, cx));
+        cx.run_until_parked();
+    }
+
+    fn selected_ranges(
+        editor: &View<Editor>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<std::ops::Range<usize>> {
+        editor.update(cx, |editor, cx| {
+            editor
+                .selections
+                .all::<usize>(cx)
+                .into_iter()
+                .map(|selection| selection.range())
+                .collect()
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = settings::SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+        });
+    }
+}
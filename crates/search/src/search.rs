@@ -11,12 +11,14 @@ pub mod buffer_search;
 mod history;
 mod mode;
 pub mod project_search;
+mod regex_match_selector;
 pub(crate) mod search_bar;
 
 pub fn init(cx: &mut AppContext) {
     menu::init();
     buffer_search::init(cx);
     project_search::init(cx);
+    regex_match_selector::init(cx);
 }
 
 actions!(
@@ -0,0 +1,357 @@
+//! Support for [EditorConfig](https://editorconfig.org) files.
+//!
+//! An `.editorconfig` file consists of an optional preamble followed by
+//! sections, each of which starts with a glob in square brackets and lists the
+//! properties that apply to the files matching that glob. Brace expansion
+//! (`{a,b}`) is supported, but numeric ranges (`{1..3}`) are not.
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::Path;
+
+/// The name of the files that EditorConfig properties are read from.
+pub const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// A parsed `.editorconfig` file.
+#[derive(Debug, Default)]
+pub struct EditorConfig {
+    root: bool,
+    sections: Vec<EditorConfigSection>,
+}
+
+#[derive(Debug)]
+struct EditorConfigSection {
+    matcher: GlobMatcher,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfig {
+    /// Parses the contents of an `.editorconfig` file.
+    ///
+    /// Like other EditorConfig implementations, this is lenient: lines that
+    /// can't be parsed are skipped, and so are sections with an invalid glob.
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+        let mut in_preamble = true;
+        let mut skip_section = false;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_preamble = false;
+                match section_matcher(glob) {
+                    Ok(matcher) => {
+                        skip_section = false;
+                        config.sections.push(EditorConfigSection {
+                            matcher,
+                            properties: Vec::new(),
+                        });
+                    }
+                    Err(error) => {
+                        log::warn!("skipping .editorconfig section {glob:?}: {error}");
+                        skip_section = true;
+                    }
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            if key.is_empty() {
+                continue;
+            }
+
+            if in_preamble {
+                if key == "root" {
+                    config.root = value == "true";
+                }
+            } else if !skip_section {
+                if let Some(section) = config.sections.last_mut() {
+                    section.properties.push((key, value));
+                }
+            }
+        }
+        config
+    }
+
+    /// Whether this file is the top-most one, meaning that `.editorconfig`
+    /// files in parent directories are ignored.
+    pub fn is_root(&self) -> bool {
+        self.root
+    }
+
+    /// Applies the properties of the sections matching the given path, which
+    /// is relative to the directory containing this file. Later sections take
+    /// precedence over earlier ones.
+    pub fn apply(&self, relative_path: &Path, properties: &mut HashMap<String, String>) {
+        for section in &self.sections {
+            if !section.matcher.is_match(relative_path) {
+                continue;
+            }
+            for (key, value) in &section.properties {
+                if value == "unset" {
+                    properties.remove(key);
+                } else {
+                    properties.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+fn section_matcher(glob: &str) -> Result<GlobMatcher> {
+    // Globs without a slash match files in any subdirectory, while globs
+    // containing one are relative to the directory of the `.editorconfig`.
+    let glob = if let Some(glob) = glob.strip_prefix('/') {
+        glob.to_string()
+    } else if glob.contains('/') {
+        glob.to_string()
+    } else {
+        format!("**/{glob}")
+    };
+    if glob.is_empty() {
+        return Err(anyhow!("empty glob"));
+    }
+    Ok(GlobBuilder::new(&expand_recursive_wildcards(&glob))
+        .literal_separator(true)
+        .build()?
+        .compile_matcher())
+}
+
+/// EditorConfig allows `**` anywhere in a glob, while `globset` only accepts
+/// it as a whole path component, so e.g. `docs/**.md` becomes `docs/**/*.md`.
+fn expand_recursive_wildcards(glob: &str) -> String {
+    let mut result = String::with_capacity(glob.len());
+    let mut rest = glob;
+    while let Some(ix) = rest.find("**") {
+        let (before, after) = (&rest[..ix], &rest[ix + 2..]);
+        result.push_str(before);
+        if !(result.is_empty() || result.ends_with('/')) {
+            result.push_str("*/");
+        }
+        result.push_str("**");
+        if !(after.is_empty() || after.starts_with('/')) {
+            result.push_str("/*");
+        }
+        rest = after.trim_start_matches('*');
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The EditorConfig properties that apply to a particular file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorConfigProperties {
+    pub indent_style: Option<IndentStyle>,
+    pub indent_size: Option<IndentSize>,
+    pub tab_width: Option<u32>,
+    pub end_of_line: Option<EndOfLine>,
+    pub charset: Option<String>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    pub max_line_length: Option<MaxLineLength>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndentSize {
+    /// Indent by the value of `tab_width`.
+    Tab,
+    Columns(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndOfLine {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaxLineLength {
+    Off,
+    Columns(u32),
+}
+
+impl EditorConfigProperties {
+    /// Reads the supported properties from a set of raw properties. Values
+    /// that aren't valid for a property are ignored.
+    pub fn from_raw(properties: &HashMap<String, String>) -> Self {
+        let get = |key: &str| properties.get(key).map(String::as_str);
+        let columns = |key: &str| get(key).and_then(|value| value.parse::<u32>().ok());
+        let flag = |key: &str| match get(key) {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        };
+
+        Self {
+            indent_style: match get("indent_style") {
+                Some("tab") => Some(IndentStyle::Tab),
+                Some("space") => Some(IndentStyle::Space),
+                _ => None,
+            },
+            indent_size: match get("indent_size") {
+                Some("tab") => Some(IndentSize::Tab),
+                _ => columns("indent_size")
+                    .filter(|size| *size > 0)
+                    .map(IndentSize::Columns),
+            },
+            tab_width: columns("tab_width").filter(|width| *width > 0),
+            end_of_line: match get("end_of_line") {
+                Some("lf") => Some(EndOfLine::Lf),
+                Some("crlf") => Some(EndOfLine::CrLf),
+                Some("cr") => Some(EndOfLine::Cr),
+                _ => None,
+            },
+            charset: get("charset")
+                .filter(|charset| {
+                    ["latin1", "utf-8", "utf-8-bom", "utf-16be", "utf-16le"].contains(charset)
+                })
+                .map(str::to_string),
+            trim_trailing_whitespace: flag("trim_trailing_whitespace"),
+            insert_final_newline: flag("insert_final_newline"),
+            max_line_length: match get("max_line_length") {
+                Some("off") => Some(MaxLineLength::Off),
+                _ => columns("max_line_length")
+                    .filter(|length| *length > 0)
+                    .map(MaxLineLength::Columns),
+            },
+        }
+    }
+
+    /// The number of columns used for each level of indentation.
+    pub fn indent_width(&self) -> Option<u32> {
+        match self.indent_size {
+            Some(IndentSize::Columns(size)) => Some(size),
+            Some(IndentSize::Tab) | None => self.tab_width,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unindent::Unindent;
+
+    fn properties(config: &EditorConfig, path: &str) -> HashMap<String, String> {
+        let mut properties = HashMap::default();
+        config.apply(Path::new(path), &mut properties);
+        properties
+    }
+
+    #[test]
+    fn test_parse_editorconfig() {
+        let config = EditorConfig::parse(
+            &r#"
+            # A comment
+            root = true
+
+            [*]
+            indent_style = space
+            indent_size = 4
+            Insert_Final_Newline = TRUE
+
+            ; Another comment
+            [*.{js,ts}]
+            indent_size = 2
+
+            [Makefile]
+            indent_style = tab
+            indent_size = unset
+
+            [/docs/**.md]
+            trim_trailing_whitespace = false
+
+            [lib/*.rs]
+            max_line_length = 80
+
+            [[invalid]
+            this line is ignored
+            "#
+            .unindent(),
+        );
+
+        assert!(config.is_root());
+
+        let rust = EditorConfigProperties::from_raw(&properties(&config, "src/main.rs"));
+        assert_eq!(rust.indent_style, Some(IndentStyle::Space));
+        assert_eq!(rust.indent_size, Some(IndentSize::Columns(4)));
+        assert_eq!(rust.insert_final_newline, Some(true));
+        assert_eq!(rust.trim_trailing_whitespace, None);
+        assert_eq!(rust.max_line_length, None);
+
+        let script = EditorConfigProperties::from_raw(&properties(&config, "web/app.ts"));
+        assert_eq!(script.indent_size, Some(IndentSize::Columns(2)));
+
+        let makefile = EditorConfigProperties::from_raw(&properties(&config, "a/b/Makefile"));
+        assert_eq!(makefile.indent_style, Some(IndentStyle::Tab));
+        assert_eq!(makefile.indent_size, None);
+
+        // Globs containing a slash are relative to the `.editorconfig` file.
+        let docs = EditorConfigProperties::from_raw(&properties(&config, "docs/a/b.md"));
+        assert_eq!(docs.trim_trailing_whitespace, Some(false));
+        let other_docs = EditorConfigProperties::from_raw(&properties(&config, "x/docs/b.md"));
+        assert_eq!(other_docs.trim_trailing_whitespace, None);
+
+        // A single `*` doesn't match path separators.
+        let lib = EditorConfigProperties::from_raw(&properties(&config, "lib/a.rs"));
+        assert_eq!(lib.max_line_length, Some(MaxLineLength::Columns(80)));
+        let nested_lib = EditorConfigProperties::from_raw(&properties(&config, "lib/a/b.rs"));
+        assert_eq!(nested_lib.max_line_length, None);
+    }
+
+    #[test]
+    fn test_expand_recursive_wildcards() {
+        assert_eq!(expand_recursive_wildcards("**/*.rs"), "**/*.rs");
+        assert_eq!(expand_recursive_wildcards("docs/**.md"), "docs/**/*.md");
+        assert_eq!(expand_recursive_wildcards("a**/b"), "a*/**/b");
+        assert_eq!(expand_recursive_wildcards("src/**"), "src/**");
+    }
+
+    #[test]
+    fn test_editorconfig_property_values() {
+        let raw = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let properties = EditorConfigProperties::from_raw(&raw(&[
+            ("indent_style", "tab"),
+            ("indent_size", "tab"),
+            ("tab_width", "8"),
+            ("end_of_line", "crlf"),
+            ("charset", "utf-8-bom"),
+            ("max_line_length", "off"),
+        ]));
+        assert_eq!(properties.indent_width(), Some(8));
+        assert_eq!(properties.end_of_line, Some(EndOfLine::CrLf));
+        assert_eq!(properties.charset.as_deref(), Some("utf-8-bom"));
+        assert_eq!(properties.max_line_length, Some(MaxLineLength::Off));
+
+        // Invalid values are ignored.
+        let properties = EditorConfigProperties::from_raw(&raw(&[
+            ("indent_style", "tabs"),
+            ("indent_size", "0"),
+            ("tab_width", "wide"),
+            ("end_of_line", "native"),
+            ("charset", "ebcdic"),
+            ("insert_final_newline", "yes"),
+        ]));
+        assert_eq!(properties, EditorConfigProperties::default());
+    }
+}
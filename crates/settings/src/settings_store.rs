@@ -2,6 +2,7 @@ use anyhow::{anyhow, Context, Result};
 use collections::{btree_map, hash_map, BTreeMap, HashMap};
 use gpui::{AppContext, AsyncAppContext, Global};
 use lazy_static::lazy_static;
+use parking_lot::Mutex;
 use schemars::{gen::SchemaGenerator, schema::RootSchema, JsonSchema};
 use serde::{de::DeserializeOwned, Deserialize as _, Serialize};
 use smallvec::SmallVec;
@@ -15,6 +16,8 @@ use std::{
 };
 use util::{merge_non_null_json_value_into, RangeExt, ResultExt as _};
 
+use crate::{EditorConfig, EditorConfigProperties};
+
 /// A value that can be defined as a user setting.
 ///
 /// Settings can be loaded from a combination of multiple JSON files.
@@ -132,6 +135,12 @@ pub struct SettingsStore {
     raw_default_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
+    editorconfigs: BTreeMap<usize, BTreeMap<Arc<Path>, EditorConfig>>,
+    /// The EditorConfig properties resolved for each file, as they are looked up
+    /// whenever the file's language settings are read.
+    editorconfig_properties: Mutex<HashMap<usize, HashMap<Arc<Path>, ResolvedProperties>>>,
+    /// Incremented whenever any setting value or `.editorconfig` file changes.
+    generation: usize,
     tab_size_callback: Option<(
         TypeId,
         Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync + 'static>,
@@ -140,6 +149,8 @@ pub struct SettingsStore {
 
 impl Global for SettingsStore {}
 
+type ResolvedProperties = Option<Arc<EditorConfigProperties>>;
+
 impl Default for SettingsStore {
     fn default() -> Self {
         SettingsStore {
@@ -147,6 +158,9 @@ impl Default for SettingsStore {
             raw_default_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
+            editorconfigs: Default::default(),
+            editorconfig_properties: Default::default(),
+            generation: 0,
             tab_size_callback: Default::default(),
         }
     }
@@ -190,6 +204,7 @@ impl SettingsStore {
             return;
         }
 
+        self.generation += 1;
         let setting_value = entry.or_insert(Box::new(SettingValue::<T> {
             global_value: None,
             local_values: Vec::new(),
@@ -247,12 +262,20 @@ impl SettingsStore {
     ///
     /// The given value will be overwritten if the user settings file changes.
     pub fn override_global<T: Settings>(&mut self, value: T) {
+        self.generation += 1;
         self.setting_values
             .get_mut(&TypeId::of::<T>())
             .unwrap_or_else(|| panic!("unregistered setting type {}", type_name::<T>()))
             .set_global_value(Box::new(value))
     }
 
+    /// Returns a number that changes whenever the value of any setting, or any
+    /// `.editorconfig` file, changes, so that values derived from them can be
+    /// cached.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
     /// Get the user's settings as a raw JSON value.
     ///
     /// This is only for debugging and reporting. For user-facing functionality,
@@ -423,6 +446,8 @@ impl SettingsStore {
     /// Add or remove a set of local settings via a JSON string.
     pub fn clear_local_settings(&mut self, root_id: usize, cx: &mut AppContext) -> Result<()> {
         self.raw_local_settings.retain(|k, _| k.0 != root_id);
+        self.editorconfigs.remove(&root_id);
+        self.editorconfig_properties.lock().remove(&root_id);
         self.recompute_values(Some((root_id, "".as_ref())), cx)?;
         Ok(())
     }
@@ -433,6 +458,83 @@ impl SettingsStore {
             .map(|((_, path), content)| (path.clone(), serde_json::to_string(content).unwrap()))
     }
 
+    /// Add or remove an `.editorconfig` file in the given directory.
+    pub fn set_local_editorconfig(
+        &mut self,
+        root_id: usize,
+        directory: Arc<Path>,
+        content: Option<&str>,
+    ) {
+        self.generation += 1;
+        self.editorconfig_properties.lock().remove(&root_id);
+        if let Some(content) = content {
+            self.editorconfigs
+                .entry(root_id)
+                .or_default()
+                .insert(directory, EditorConfig::parse(content));
+        } else if let btree_map::Entry::Occupied(mut editorconfigs) =
+            self.editorconfigs.entry(root_id)
+        {
+            editorconfigs.get_mut().remove(&directory);
+            if editorconfigs.get().is_empty() {
+                editorconfigs.remove();
+            }
+        }
+    }
+
+    /// Returns the EditorConfig properties for the file at the given path.
+    ///
+    /// The `.editorconfig` files in the file's ancestor directories are applied
+    /// from the outermost to the innermost one, so that properties from files
+    /// closer to the path take precedence. Files above the first one marked
+    /// with `root = true` are ignored. The properties are cached until the
+    /// `.editorconfig` files in the worktree change.
+    pub fn editorconfig_properties(
+        &self,
+        root_id: usize,
+        path: &Path,
+    ) -> Option<Arc<EditorConfigProperties>> {
+        if !self.editorconfigs.contains_key(&root_id) {
+            return None;
+        }
+        let mut resolved_properties = self.editorconfig_properties.lock();
+        let resolved_properties = resolved_properties.entry(root_id).or_default();
+        if let Some(properties) = resolved_properties.get(path) {
+            return properties.clone();
+        }
+        let properties = self.resolve_editorconfig_properties(root_id, path);
+        resolved_properties.insert(path.into(), properties.clone());
+        properties
+    }
+
+    fn resolve_editorconfig_properties(
+        &self,
+        root_id: usize,
+        path: &Path,
+    ) -> Option<Arc<EditorConfigProperties>> {
+        let editorconfigs = self.editorconfigs.get(&root_id)?;
+        let mut applicable = SmallVec::<[_; 2]>::new();
+        for directory in path.ancestors().skip(1) {
+            if let Some(editorconfig) = editorconfigs.get(directory) {
+                applicable.push((directory, editorconfig));
+                if editorconfig.is_root() {
+                    break;
+                }
+            }
+        }
+        if applicable.is_empty() {
+            return None;
+        }
+
+        let mut properties = HashMap::default();
+        for (directory, editorconfig) in applicable.into_iter().rev() {
+            if let Ok(relative_path) = path.strip_prefix(directory) {
+                editorconfig.apply(relative_path, &mut properties);
+            }
+        }
+        Some(Arc::new(EditorConfigProperties::from_raw(&properties)))
+    }
+
     pub fn json_schema(
         &self,
         schema_params: &SettingsJsonSchemaParams,
@@ -524,6 +626,7 @@ impl SettingsStore {
         changed_local_path: Option<(usize, &Path)>,
         cx: &mut AppContext,
     ) -> Result<()> {
+        self.generation += 1;
         // Reload the global and local values for every setting.
         let mut user_settings_stack = Vec::<DeserializedSetting>::new();
         let mut paths_stack = Vec::<Option<(usize, &Path)>>::new();
@@ -922,6 +1025,7 @@ pub fn parse_json_with_comments<T: DeserializeOwned>(content: &str) -> Result<T>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{EndOfLine, IndentSize, IndentStyle};
     use serde_derive::Deserialize;
     use unindent::Unindent;
 
@@ -1085,6 +1189,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_editorconfig_precedence() {
+        let mut store = SettingsStore::default();
+        store.set_local_editorconfig(
+            1,
+            Path::new("").into(),
+            Some(
+                "[*]\nindent_size = 4\nend_of_line = lf\n[*.md]\ntrim_trailing_whitespace = false",
+            ),
+        );
+        store.set_local_editorconfig(
+            1,
+            Path::new("web").into(),
+            Some("[*.js]\nindent_size = 2\n[vendor/**]\nend_of_line = unset"),
+        );
+        store.set_local_editorconfig(
+            1,
+            Path::new("legacy").into(),
+            Some("root = true\n[*]\nindent_style = tab"),
+        );
+
+        assert_eq!(store.editorconfig_properties(2, Path::new("a.rs")), None);
+
+        let properties = store
+            .editorconfig_properties(1, Path::new("src/lib.rs"))
+            .unwrap();
+        assert_eq!(properties.indent_size, Some(IndentSize::Columns(4)));
+        assert_eq!(properties.end_of_line, Some(EndOfLine::Lf));
+
+        // Files closer to the path override properties from parent directories,
+        // and sections are matched relative to their file's directory.
+        let properties = store
+            .editorconfig_properties(1, Path::new("web/app.js"))
+            .unwrap();
+        assert_eq!(properties.indent_size, Some(IndentSize::Columns(2)));
+        assert_eq!(properties.end_of_line, Some(EndOfLine::Lf));
+        let properties = store
+            .editorconfig_properties(1, Path::new("web/vendor/lib.js"))
+            .unwrap();
+        assert_eq!(properties.indent_size, Some(IndentSize::Columns(2)));
+        assert_eq!(properties.end_of_line, None);
+        let properties = store
+            .editorconfig_properties(1, Path::new("web/README.md"))
+            .unwrap();
+        assert_eq!(properties.trim_trailing_whitespace, Some(false));
+
+        // A root file stops the search for files in parent directories.
+        let properties = store
+            .editorconfig_properties(1, Path::new("legacy/main.c"))
+            .unwrap();
+        assert_eq!(properties.indent_style, Some(IndentStyle::Tab));
+        assert_eq!(properties.indent_size, None);
+
+        // Resolved properties are reused until an `.editorconfig` file changes.
+        assert!(Arc::ptr_eq(
+            &store
+                .editorconfig_properties(1, Path::new("web/app.js"))
+                .unwrap(),
+            &store
+                .editorconfig_properties(1, Path::new("web/app.js"))
+                .unwrap(),
+        ));
+        store.set_local_editorconfig(1, Path::new("web").into(), None);
+        let properties = store
+            .editorconfig_properties(1, Path::new("web/app.js"))
+            .unwrap();
+        assert_eq!(properties.indent_size, Some(IndentSize::Columns(4)));
+    }
+
     #[gpui::test]
     fn test_setting_store_update(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
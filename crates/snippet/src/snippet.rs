@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Context, Result};
+use regex::{Captures, Regex, RegexBuilder};
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
 
@@ -6,45 +7,358 @@ use std::{collections::BTreeMap, ops::Range};
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    /// The values offered at each tabstop, as given by a choice such as
+    /// `${1|one,two|}`. Empty for tabstops without a choice.
+    pub choices: Vec<Vec<String>>,
 }
 
 type TabStop = SmallVec<[Range<isize>; 2]>;
 
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
+        Self::parse_with_variables(source, &|_| None)
+    }
+
+    /// Parses a snippet, resolving variables such as `$TM_FILENAME` with the
+    /// given function. Variables it doesn't know of are inserted by name, as
+    /// placeholders visited after the numbered tabstops.
+    pub fn parse_with_variables(
+        source: &str,
+        variables: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut parser = Parser::new(variables);
+        parser
+            .parse_snippet(source, false)
             .context("failed to parse snippet")?;
+        let Parser {
+            text,
+            mut tabstops,
+            mut choices,
+            unknown_variables,
+            ..
+        } = parser;
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
+        let final_choices = choices.remove(&0).unwrap_or_default();
+        let mut choices = tabstops
+            .keys()
+            .map(|index| choices.remove(index).unwrap_or_default())
+            .collect::<Vec<_>>();
         let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
+        for range in unknown_variables {
+            tabstops.push([range].into_iter().collect());
+            choices.push(Vec::new());
+        }
 
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
+            choices.push(final_choices);
         } else {
             let end_tabstop = [len..len].into_iter().collect();
             if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
                 tabstops.push(end_tabstop);
+                choices.push(Vec::new());
+            }
+        }
+
+        Ok(Snippet {
+            text,
+            tabstops,
+            choices,
+        })
+    }
+}
+
+struct Parser<'a> {
+    variables: &'a dyn Fn(&str) -> Option<String>,
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    choices: BTreeMap<usize, Vec<String>>,
+    /// The text first given to each tabstop, which transforms of it apply to.
+    placeholders: BTreeMap<usize, String>,
+    unknown_variables: Vec<Range<isize>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(variables: &'a dyn Fn(&str) -> Option<String>) -> Self {
+        Self {
+            variables,
+            text: String::new(),
+            tabstops: BTreeMap::new(),
+            choices: BTreeMap::new(),
+            placeholders: BTreeMap::new(),
+            unknown_variables: Vec::new(),
+        }
+    }
+
+    fn parse_snippet<'b>(&mut self, mut source: &'b str, nested: bool) -> Result<&'b str> {
+        loop {
+            match source.chars().next() {
+                None => return Ok(""),
+                Some('$') => {
+                    source = self.parse_tabstop_or_variable(&source[1..])?;
+                }
+                Some('\\') => {
+                    source = &source[1..];
+                    if let Some(c) = source.chars().next() {
+                        self.text.push(c);
+                        source = &source[c.len_utf8()..];
+                    }
+                }
+                Some('}') => {
+                    if nested {
+                        return Ok(source);
+                    } else {
+                        self.text.push('}');
+                        source = &source[1..];
+                    }
+                }
+                Some(_) => {
+                    let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
+                    let (chunk, rest) = source.split_at(chunk_end);
+                    self.text.push_str(chunk);
+                    source = rest;
+                }
+            }
+        }
+    }
+
+    fn parse_tabstop_or_variable<'b>(&mut self, source: &'b str) -> Result<&'b str> {
+        let name = source.strip_prefix('{').unwrap_or(source);
+        if name.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+            self.parse_variable(source)
+        } else if source.is_empty() || source.starts_with(char::is_whitespace) {
+            // A lone dollar sign is just text.
+            self.text.push('$');
+            Ok(source)
+        } else {
+            self.parse_tabstop(source)
+        }
+    }
+
+    fn parse_tabstop<'b>(&mut self, mut source: &'b str) -> Result<&'b str> {
+        let tabstop_start = self.text.len();
+        let tabstop_index;
+        if source.starts_with('{') {
+            let (index, rest) = parse_int(&source[1..])?;
+            tabstop_index = index;
+            source = rest;
+
+            if source.starts_with(':') {
+                source = self.parse_snippet(&source[1..], true)?;
+            } else if source.starts_with('|') {
+                let (choices, rest) = parse_choices(&source[1..])?;
+                self.text
+                    .push_str(choices.first().map_or("", String::as_str));
+                self.choices.entry(tabstop_index).or_insert(choices);
+                source = rest;
+            } else if source.starts_with('/') {
+                // Transforms of a tabstop apply to the text it was first given.
+                let (transform, rest) = Transform::parse(&source[1..])?;
+                if let Some(placeholder) = self.placeholders.get(&tabstop_index) {
+                    let transformed = transform.apply(placeholder);
+                    self.text.push_str(&transformed);
+                }
+                return expect_closing_brace(rest);
+            }
+
+            source = expect_closing_brace(source)?;
+        } else {
+            let (index, rest) = parse_int(source)?;
+            tabstop_index = index;
+            source = rest;
+        }
+
+        self.placeholders
+            .entry(tabstop_index)
+            .or_insert_with(|| self.text[tabstop_start..].to_string());
+        self.tabstops
+            .entry(tabstop_index)
+            .or_default()
+            .push(tabstop_start as isize..self.text.len() as isize);
+        Ok(source)
+    }
+
+    fn parse_variable<'b>(&mut self, mut source: &'b str) -> Result<&'b str> {
+        let braced = source.starts_with('{');
+        if braced {
+            source = &source[1..];
+        }
+        let name_len = source
+            .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+            .unwrap_or(source.len());
+        let (name, rest) = source.split_at(name_len);
+        source = rest;
+        let value = (self.variables)(name);
+
+        if braced && source.starts_with(':') {
+            // The default applies when the variable is unknown or empty.
+            if value.as_ref().map_or(false, |value| !value.is_empty()) {
+                source = Parser::new(self.variables).parse_snippet(&source[1..], true)?;
+                self.text.push_str(&value.unwrap());
+            } else {
+                source = self.parse_snippet(&source[1..], true)?;
+            }
+            return expect_closing_brace(source);
+        }
+
+        let transform = if braced && source.starts_with('/') {
+            let (transform, rest) = Transform::parse(&source[1..])?;
+            source = rest;
+            Some(transform)
+        } else {
+            None
+        };
+        if braced {
+            source = expect_closing_brace(source)?;
+        }
+
+        match value {
+            Some(value) => {
+                let value = match transform {
+                    Some(transform) => transform.apply(&value),
+                    None => value,
+                };
+                self.text.push_str(&value);
+            }
+            None => {
+                let start = self.text.len() as isize;
+                self.text.push_str(name);
+                self.unknown_variables.push(start..self.text.len() as isize);
+            }
+        }
+        Ok(source)
+    }
+}
+
+/// A regular expression replacement applied to a variable or tabstop, as in
+/// `${TM_FILENAME/(.*)\..+$/$1/}`.
+struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, Case),
+    /// Inserts the first text if the group matched, and the second otherwise.
+    Conditional(usize, String, String),
+}
+
+#[derive(Clone, Copy)]
+enum Case {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+}
+
+impl Transform {
+    /// Parses the `regex/format/options}` following the first slash.
+    fn parse(source: &str) -> Result<(Self, &str)> {
+        let (pattern, source) = parse_until_slash(source)?;
+        let (format, source) = parse_format(source)?;
+        let options_len = source.find('}').unwrap_or(source.len());
+        let (options, rest) = source.split_at(options_len);
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.contains('i'))
+            .multi_line(options.contains('m'))
+            .dot_matches_new_line(options.contains('s'))
+            .build()
+            .context("invalid transform regex")?;
+        let transform = Transform {
+            regex,
+            format,
+            global: options.contains('g'),
+        };
+        Ok((transform, rest))
+    }
+
+    fn apply(&self, value: &str) -> String {
+        let mut result = String::new();
+        let mut last_end = 0;
+        for captures in self.regex.captures_iter(value) {
+            let matched = captures.get(0).unwrap();
+            result.push_str(&value[last_end..matched.start()]);
+            for item in &self.format {
+                item.append(&captures, &mut result);
+            }
+            last_end = matched.end();
+            if !self.global {
+                break;
+            }
+        }
+        result.push_str(&value[last_end..]);
+        result
+    }
+}
+
+impl FormatItem {
+    fn append(&self, captures: &Captures, result: &mut String) {
+        let group = |index: usize| captures.get(index).map_or("", |group| group.as_str());
+        match self {
+            FormatItem::Text(text) => result.push_str(text),
+            FormatItem::Group(index) => result.push_str(group(*index)),
+            FormatItem::Case(index, case) => result.push_str(&case.apply(group(*index))),
+            FormatItem::Conditional(index, if_text, else_text) => {
+                if group(*index).is_empty() {
+                    result.push_str(else_text);
+                } else {
+                    result.push_str(if_text);
+                }
             }
         }
+    }
+}
 
-        Ok(Snippet { text, tabstops })
+impl Case {
+    fn apply(self, text: &str) -> String {
+        match self {
+            Case::Upcase => text.to_uppercase(),
+            Case::Downcase => text.to_lowercase(),
+            Case::Capitalize => capitalize(text),
+            Case::Camelcase | Case::Pascalcase => {
+                let words = text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty());
+                let mut result = String::new();
+                for (ix, word) in words.enumerate() {
+                    if ix == 0 && matches!(self, Case::Camelcase) {
+                        result.push_str(&word.to_lowercase());
+                    } else {
+                        result.push_str(&capitalize(&word.to_lowercase()));
+                    }
+                }
+                result
+            }
+        }
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Parses the format of a transform, up to and including its closing slash.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
     loop {
         match source.chars().next() {
-            None => return Ok(""),
-            Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+            None => return Err(anyhow!("expected a closing slash")),
+            Some('/') => {
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(text));
+                }
+                return Ok((items, &source[1..]));
             }
             Some('\\') => {
                 source = &source[1..];
@@ -53,56 +367,149 @@ fn parse_snippet<'a>(
                     source = &source[c.len_utf8()..];
                 }
             }
-            Some('}') => {
-                if nested {
-                    return Ok(source);
-                } else {
-                    text.push('}');
-                    source = &source[1..];
+            Some('$') => {
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(std::mem::take(&mut text)));
                 }
-            }
-            Some(_) => {
-                let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
-                let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
+                let (item, rest) = parse_format_item(&source[1..])?;
+                items.push(item);
                 source = rest;
             }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
         }
     }
 }
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
-        source = rest;
+fn parse_format_item(source: &str) -> Result<(FormatItem, &str)> {
+    let Some(source) = source.strip_prefix('{') else {
+        let (index, rest) = parse_int(source)?;
+        return Ok((FormatItem::Group(index), rest));
+    };
+
+    let (index, source) = parse_int(source)?;
+    let Some(source) = source.strip_prefix(':') else {
+        return Ok((FormatItem::Group(index), expect_closing_brace(source)?));
+    };
 
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+    if let Some(source) = source.strip_prefix('/') {
+        let name_len = source
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(source.len());
+        let (name, rest) = source.split_at(name_len);
+        let case = match name {
+            "upcase" => Case::Upcase,
+            "downcase" => Case::Downcase,
+            "capitalize" => Case::Capitalize,
+            "camelcase" => Case::Camelcase,
+            "pascalcase" => Case::Pascalcase,
+            _ => return Err(anyhow!("unknown format modifier {name:?}")),
+        };
+        Ok((FormatItem::Case(index, case), expect_closing_brace(rest)?))
+    } else if let Some(source) = source.strip_prefix('+') {
+        let (if_text, rest) = parse_format_text(source, &['}'])?;
+        let item = FormatItem::Conditional(index, if_text, String::new());
+        Ok((item, expect_closing_brace(rest)?))
+    } else if let Some(source) = source.strip_prefix('?') {
+        let (if_text, rest) = parse_format_text(source, &[':'])?;
+        let (else_text, rest) = parse_format_text(&rest[1..], &['}'])?;
+        let item = FormatItem::Conditional(index, if_text, else_text);
+        Ok((item, expect_closing_brace(rest)?))
+    } else {
+        let source = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, rest) = parse_format_text(source, &['}'])?;
+        let item = FormatItem::Conditional(index, String::new(), else_text);
+        Ok((item, expect_closing_brace(rest)?))
+    }
+}
+
+/// Parses escaped text up to (but not including) any of the given characters.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected one of {terminators:?}")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    text.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
         }
+    }
+}
 
-        if source.starts_with('}') {
-            source = &source[1..];
-        } else {
-            return Err(anyhow!("expected a closing brace"));
+/// Parses the regex of a transform, up to and including its closing slash.
+/// Escaped slashes are unescaped, while other escapes are left to the regex.
+fn parse_until_slash(mut source: &str) -> Result<(String, &str)> {
+    let mut pattern = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected a closing slash")),
+            Some('/') => return Ok((pattern, &source[1..])),
+            Some('\\') if source[1..].starts_with('/') => {
+                pattern.push('/');
+                source = &source[2..];
+            }
+            Some('\\') => {
+                pattern.push('\\');
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    pattern.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                pattern.push(c);
+                source = &source[c.len_utf8()..];
+            }
         }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
-        source = rest;
     }
+}
 
-    tabstops
-        .entry(tabstop_index)
-        .or_default()
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+/// Parses the `one,two|}` following the first pipe of a choice, leaving the
+/// closing brace.
+fn parse_choices(mut source: &str) -> Result<(Vec<String>, &str)> {
+    let mut choices = Vec::new();
+    let mut choice = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected a closing pipe")),
+            Some('|') => {
+                choices.push(choice);
+                return Ok((choices, &source[1..]));
+            }
+            Some(',') => {
+                choices.push(std::mem::take(&mut choice));
+                source = &source[1..];
+            }
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    choice.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                choice.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn expect_closing_brace(source: &str) -> Result<&str> {
+    source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -199,6 +606,85 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![3..3]]);
     }
 
+    #[test]
+    fn test_snippet_with_choices() {
+        let snippet = Snippet::parse("let ${1|mut ,|}x = ${2|one,two\\,three|};").unwrap();
+        assert_eq!(snippet.text, "let mut x = one;");
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![4..8], vec![12..15], vec![16..16]]
+        );
+        assert_eq!(
+            snippet.choices,
+            &[
+                vec!["mut ".to_string(), "".to_string()],
+                vec!["one".to_string(), "two,three".to_string()],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet = Snippet::parse_with_variables("// $TM_FILENAME: $1", &variables).unwrap();
+        assert_eq!(snippet.text, "// main.rs: ");
+        assert_eq!(tabstops(&snippet), &[vec![12..12]]);
+
+        // Defaults apply to empty and unknown variables, and can hold tabstops.
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME:x}(${TM_SELECTED_TEXT:${1:arg}})",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "main.rs(arg)");
+        assert_eq!(tabstops(&snippet), &[vec![8..11], vec![12..12]]);
+
+        // Unknown variables become placeholders after the numbered tabstops.
+        let snippet = Snippet::parse_with_variables("$1 ${UNKNOWN} $2", &variables).unwrap();
+        assert_eq!(snippet.text, " UNKNOWN ");
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![0..0], vec![9..9], vec![1..8], vec![9..9]]
+        );
+    }
+
+    #[test]
+    fn test_snippet_with_transforms() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("snippet_parser.rs".to_string()),
+            _ => None,
+        };
+
+        let snippet =
+            Snippet::parse_with_variables("${TM_FILENAME/(.*)\\..+$/$1/}", &variables).unwrap();
+        assert_eq!(snippet.text, "snippet_parser");
+
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME/(\\w+)\\.rs/${1:/pascalcase}/} ${TM_FILENAME/_/-/g}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "SnippetParser snippet-parser.rs");
+
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME/(parser)|(lexer)/${1:+P}${2:?L:none}/}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "snippet_Pnone.rs");
+
+        // Transforms of a tabstop apply to its placeholder.
+        let snippet = Snippet::parse("${1:name} ${1/(.*)/${1:/upcase}/}").unwrap();
+        assert_eq!(snippet.text, "name NAME");
+        assert_eq!(tabstops(&snippet), &[vec![0..4], vec![9..9]]);
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.to_vec()).collect()
     }
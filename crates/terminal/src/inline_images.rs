@@ -0,0 +1,865 @@
+//! Inline images, which programs display in the terminal with the Kitty
+//! graphics protocol or with iTerm2's inline images protocol (OSC 1337).
+//!
+//! Alacritty ignores these sequences, so [`InlineImageFilter`] takes them out
+//! of the PTY's output and decodes the images. In their place, it prints a
+//! placeholder for the cells that the image covers: the first cell of each of
+//! the image's rows gets an OSC 8 hyperlink with an [`IMAGE_URI_SCHEME`] URI,
+//! naming the image and the row. Images are painted over these cells, so they
+//! scroll with the rest of the content and go away when it's cleared. Like
+//! shell marks, these hyperlinks are removed from the cells that are rendered.
+
+use crate::pty_filter::PtyFilter;
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, EventListener, WindowSize},
+    sync::FairMutex,
+};
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashMap;
+use flate2::read::ZlibDecoder;
+use gpui::ImageData;
+use image::ImageBuffer;
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{Cursor, Read, Write},
+    mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt;
+
+/// The scheme of the hyperlink URIs that image placeholders are stored in.
+pub const IMAGE_URI_SCHEME: &str = "zed-image:";
+
+/// Image sequences longer than this are dropped without being decoded.
+const MAX_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+
+/// Images whose pixels would take more bytes than this are dropped without
+/// being decoded.
+const MAX_IMAGE_LEN: usize = 64 * 1024 * 1024;
+
+/// How many bytes of decoded images are kept, before the oldest are dropped.
+const MAX_IMAGES_LEN: usize = 256 * 1024 * 1024;
+
+/// How many images transmitted with the Kitty graphics protocol are kept to
+/// be displayed later.
+const MAX_TRANSMITTED_IMAGES: usize = 32;
+
+/// How many screens of rows an image covers at most.
+const MAX_IMAGE_SCREENS: usize = 4;
+
+/// Temporary files that images are transmitted in with the Kitty graphics
+/// protocol must be named like this.
+const KITTY_TEMPORARY_FILE_NAME: &str = "tty-graphics-protocol";
+
+const KITTY_PREFIX: &[u8] = b"\x1b_G";
+const ITERM_PREFIX: &[u8] = b"\x1b]1337;File=";
+
+/// An image displayed in the terminal, over a number of its cells.
+pub struct InlineImage {
+    pub data: Arc<ImageData>,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+/// The images displayed in a terminal. The oldest are dropped once they take
+/// too much memory, as they're unlikely to be scrolled back to.
+#[derive(Default)]
+pub struct InlineImages {
+    images: HashMap<u64, Arc<InlineImage>>,
+    order: VecDeque<u64>,
+    len: usize,
+}
+
+impl InlineImages {
+    pub fn get(&self, image_id: u64) -> Option<Arc<InlineImage>> {
+        self.images.get(&image_id).cloned()
+    }
+
+    fn insert(&mut self, image_id: u64, image: InlineImage) {
+        self.len += image.data.as_bytes().len();
+        self.images.insert(image_id, Arc::new(image));
+        self.order.push_back(image_id);
+        while self.len > MAX_IMAGES_LEN && self.order.len() > 1 {
+            if let Some(image) = self
+                .order
+                .pop_front()
+                .and_then(|image_id| self.images.remove(&image_id))
+            {
+                self.len -= image.data.as_bytes().len();
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.images.clear();
+        self.order.clear();
+        self.len = 0;
+    }
+}
+
+/// Returns the image and the row of it whose placeholder holds a hyperlink
+/// with the given URI, or `None` if the hyperlink isn't a placeholder.
+pub fn image_row_from_uri(uri: &str) -> Option<(u64, usize)> {
+    let (image_id, row) = uri.strip_prefix(IMAGE_URI_SCHEME)?.split_once('/')?;
+    Some((image_id.parse().ok()?, row.parse().ok()?))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Protocol {
+    Kitty,
+    ITerm,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    /// Bytes that may start an image sequence are held back until it's known
+    /// whether they do.
+    Prefix,
+    Sequence(Protocol),
+    SequenceEscape(Protocol),
+}
+
+/// Takes the image sequences out of a PTY's output, and prints placeholders
+/// for the images instead.
+pub(crate) struct InlineImageFilter<L> {
+    images: Arc<FairMutex<InlineImages>>,
+    listener: L,
+    window_size: WindowSize,
+    state: ScanState,
+    prefix: Vec<u8>,
+    payload: Vec<u8>,
+    payload_overflowed: bool,
+    next_image_id: u64,
+    /// A Kitty command whose payload is sent in chunks, with the chunks that
+    /// were received so far.
+    pending_kitty_command: Option<(KittyCommand, Vec<u8>)>,
+    transmitted_images: HashMap<u32, Arc<ImageData>>,
+    transmission_order: VecDeque<u32>,
+}
+
+impl<L: EventListener> InlineImageFilter<L> {
+    /// Creates a filter that stores the images it decodes in `images`, and
+    /// replies to the programs' queries through `listener`.
+    pub(crate) fn new(
+        images: Arc<FairMutex<InlineImages>>,
+        listener: L,
+        window_size: WindowSize,
+    ) -> Self {
+        Self {
+            images,
+            listener,
+            window_size,
+            state: ScanState::Ground,
+            prefix: Vec::new(),
+            payload: Vec::new(),
+            payload_overflowed: false,
+            next_image_id: 0,
+            pending_kitty_command: None,
+            transmitted_images: HashMap::default(),
+            transmission_order: VecDeque::new(),
+        }
+    }
+
+    fn advance(&mut self, byte: u8, output: &mut Vec<u8>) {
+        match self.state {
+            ScanState::Ground => {
+                if byte == 0x1b {
+                    self.prefix.push(byte);
+                    self.state = ScanState::Prefix;
+                } else {
+                    output.push(byte);
+                }
+            }
+            ScanState::Prefix => {
+                self.prefix.push(byte);
+                if self.prefix == KITTY_PREFIX {
+                    self.start_sequence(Protocol::Kitty);
+                } else if self.prefix == ITERM_PREFIX {
+                    self.start_sequence(Protocol::ITerm);
+                } else if !KITTY_PREFIX.starts_with(&self.prefix)
+                    && !ITERM_PREFIX.starts_with(&self.prefix)
+                {
+                    // The last byte may start an image sequence of its own.
+                    self.prefix.pop();
+                    output.extend_from_slice(&self.prefix);
+                    self.prefix.clear();
+                    self.state = ScanState::Ground;
+                    self.advance(byte, output);
+                }
+            }
+            ScanState::Sequence(protocol) => match byte {
+                0x07 => self.finish_sequence(protocol, output),
+                0x1b => self.state = ScanState::SequenceEscape(protocol),
+                // CAN and SUB abort any escape sequence.
+                0x18 | 0x1a => {
+                    self.payload.clear();
+                    self.state = ScanState::Ground;
+                }
+                _ => {
+                    if self.payload.len() < MAX_SEQUENCE_LEN {
+                        self.payload.push(byte);
+                    } else {
+                        self.payload_overflowed = true;
+                    }
+                }
+            },
+            ScanState::SequenceEscape(protocol) => {
+                if byte == b'\\' {
+                    self.finish_sequence(protocol, output);
+                } else {
+                    // The sequence was interrupted by another one.
+                    self.payload.clear();
+                    self.state = ScanState::Ground;
+                    self.advance(0x1b, output);
+                    self.advance(byte, output);
+                }
+            }
+        }
+    }
+
+    fn start_sequence(&mut self, protocol: Protocol) {
+        self.prefix.clear();
+        self.payload.clear();
+        self.payload_overflowed = false;
+        self.state = ScanState::Sequence(protocol);
+    }
+
+    fn finish_sequence(&mut self, protocol: Protocol, output: &mut Vec<u8>) {
+        self.state = ScanState::Ground;
+        let payload = mem::take(&mut self.payload);
+        if mem::take(&mut self.payload_overflowed) {
+            return;
+        }
+
+        match protocol {
+            Protocol::Kitty => self.handle_kitty_command(&payload, output),
+            Protocol::ITerm => {
+                self.handle_iterm_image(&payload, output)
+                    .context("failed to display inline image")
+                    .log_err();
+            }
+        }
+    }
+
+    fn handle_kitty_command(&mut self, payload: &[u8], output: &mut Vec<u8>) {
+        let (control_data, data) = match payload.iter().position(|&byte| byte == b';') {
+            Some(ix) => (&payload[..ix], &payload[ix + 1..]),
+            None => (payload, &[][..]),
+        };
+        let Ok(control_data) = std::str::from_utf8(control_data) else {
+            return;
+        };
+
+        // Only the first chunk of a command has its control data, and the
+        // others only say whether more chunks follow.
+        let (command, data) = match self.pending_kitty_command.take() {
+            Some((command, mut chunks)) => {
+                // Commands whose chunks add up to too much are dropped.
+                if chunks.len() + data.len() > MAX_SEQUENCE_LEN {
+                    return;
+                }
+                chunks.extend_from_slice(data);
+                if KittyCommand::parse(control_data).has_more_chunks {
+                    self.pending_kitty_command = Some((command, chunks));
+                    return;
+                }
+                (command, chunks)
+            }
+            None => {
+                let command = KittyCommand::parse(control_data);
+                if command.has_more_chunks {
+                    self.pending_kitty_command = Some((command, data.to_vec()));
+                    return;
+                }
+                (command, data.to_vec())
+            }
+        };
+
+        // Programs aren't told why an image couldn't be decoded, as that may
+        // reveal whether files they can't read otherwise exist.
+        let result = match command.action {
+            b't' | b'T' | b'q' => command
+                .decode_image(&data)
+                .context("failed to decode inline image")
+                .log_err()
+                .map(Arc::new)
+                .ok_or("EINVAL:could not decode the image"),
+            b'p' => command
+                .image_id
+                .and_then(|image_id| self.transmitted_images.get(&image_id).cloned())
+                .ok_or("ENOENT:image not found"),
+            b'd' => {
+                // Only deleting all of the images is supported.
+                if matches!(command.delete, b'a' | b'A') {
+                    self.images.lock().clear();
+                    self.transmitted_images.clear();
+                    self.transmission_order.clear();
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        match result {
+            Ok(image) => {
+                if matches!(command.action, b't' | b'T') {
+                    if let Some(image_id) = command.image_id {
+                        self.store_transmitted_image(image_id, image.clone());
+                    }
+                }
+                if matches!(command.action, b'T' | b'p') {
+                    self.display_image(
+                        image,
+                        command.columns,
+                        command.rows,
+                        !command.keep_cursor,
+                        output,
+                    );
+                }
+                if command.quiet == 0 {
+                    self.reply_to_kitty_command(&command, "OK");
+                }
+            }
+            Err(message) => {
+                if command.quiet < 2 {
+                    self.reply_to_kitty_command(&command, message);
+                }
+            }
+        }
+    }
+
+    fn store_transmitted_image(&mut self, image_id: u32, image: Arc<ImageData>) {
+        if self.transmitted_images.insert(image_id, image).is_none() {
+            self.transmission_order.push_back(image_id);
+        }
+        while self.transmission_order.len() > MAX_TRANSMITTED_IMAGES {
+            if let Some(image_id) = self.transmission_order.pop_front() {
+                self.transmitted_images.remove(&image_id);
+            }
+        }
+    }
+
+    /// Programs are only replied to when they named the image.
+    fn reply_to_kitty_command(&self, command: &KittyCommand, message: &str) {
+        if let Some(image_id) = command.image_id {
+            self.listener.send_event(AlacTermEvent::PtyWrite(format!(
+                "\x1b_Gi={image_id};{message}\x1b\\"
+            )));
+        }
+    }
+
+    fn handle_iterm_image(&mut self, payload: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let separator = payload
+            .iter()
+            .position(|&byte| byte == b':')
+            .ok_or_else(|| anyhow!("missing image data"))?;
+        let arguments = std::str::from_utf8(&payload[..separator])?;
+
+        let mut inline = false;
+        let mut width = None;
+        let mut height = None;
+        for argument in arguments.split(';') {
+            if let Some((key, value)) = argument.split_once('=') {
+                match key {
+                    "inline" => inline = value == "1",
+                    "width" => width = Some(value),
+                    "height" => height = Some(value),
+                    _ => {}
+                }
+            }
+        }
+        // Files that aren't displayed inline are downloads, which aren't supported.
+        if !inline {
+            return Ok(());
+        }
+
+        let data = base64::decode(&payload[separator + 1..])?;
+        let image = Arc::new(decode_image_file(&data)?);
+        let columns = width.and_then(|width| {
+            iterm_dimension(
+                width,
+                self.window_size.cell_width,
+                self.window_size.num_cols,
+            )
+        });
+        let rows = height.and_then(|height| {
+            iterm_dimension(
+                height,
+                self.window_size.cell_height,
+                self.window_size.num_lines,
+            )
+        });
+        self.display_image(image, columns, rows, true, output);
+        Ok(())
+    }
+
+    /// Stores an image to be displayed from the cursor on, and prints its
+    /// placeholder.
+    fn display_image(
+        &mut self,
+        data: Arc<ImageData>,
+        columns: Option<usize>,
+        rows: Option<usize>,
+        move_cursor: bool,
+        output: &mut Vec<u8>,
+    ) {
+        let (columns, rows) = self.image_extent(&data, columns, rows);
+        let image_id = self.next_image_id;
+        self.next_image_id += 1;
+        self.images.lock().insert(
+            image_id,
+            InlineImage {
+                data,
+                columns,
+                rows,
+            },
+        );
+
+        if !move_cursor {
+            output.extend_from_slice(b"\x1b7");
+        }
+        for row in 0..rows {
+            if row > 0 {
+                output.push(b'\n');
+            }
+            // The placeholder is a space printed in the image's first column,
+            // after which the cursor goes back to that column.
+            write!(
+                output,
+                "\x1b]8;;{IMAGE_URI_SCHEME}{image_id}/{row}\x1b\\ \x1b]8;;\x1b\\\x08"
+            )
+            .ok();
+        }
+        if move_cursor {
+            // Like in other terminals, the cursor ends up after the image's last row.
+            write!(output, "\x1b[{columns}C").ok();
+        } else {
+            output.extend_from_slice(b"\x1b8");
+        }
+    }
+
+    /// The number of columns and rows that an image covers. When only one of
+    /// them is given, the other follows from the image's aspect ratio, and
+    /// when neither is, the image covers the cells it fits in at its size.
+    /// Images are made smaller to fit in the terminal's width and in a few of
+    /// its screens.
+    fn image_extent(
+        &self,
+        image: &ImageData,
+        columns: Option<usize>,
+        rows: Option<usize>,
+    ) -> (usize, usize) {
+        let cell_width = self.window_size.cell_width.max(1) as f32;
+        let cell_height = self.window_size.cell_height.max(1) as f32;
+        let size = image.size();
+        let width = i32::from(size.width).max(1) as f32;
+        let height = i32::from(size.height).max(1) as f32;
+
+        let cells_for_width = |columns: f32| columns * cell_width * height / width / cell_height;
+        let cells_for_height = |rows: f32| rows * cell_height * width / height / cell_width;
+        let (columns, rows) = match (columns, rows) {
+            (Some(columns), Some(rows)) => (columns, rows),
+            (Some(columns), None) => (columns, cells_for_width(columns as f32).ceil() as usize),
+            (None, Some(rows)) => (cells_for_height(rows as f32).ceil() as usize, rows),
+            (None, None) => (
+                (width / cell_width).ceil() as usize,
+                (height / cell_height).ceil() as usize,
+            ),
+        };
+
+        let (mut columns, mut rows) = (columns.max(1), rows.max(1));
+        let max_columns = (self.window_size.num_cols as usize).max(1);
+        if columns > max_columns {
+            rows = ((rows as f32 * max_columns as f32 / columns as f32).ceil() as usize).max(1);
+            columns = max_columns;
+        }
+        let max_rows = (self.window_size.num_lines as usize).max(1) * MAX_IMAGE_SCREENS;
+        if rows > max_rows {
+            columns = ((columns as f32 * max_rows as f32 / rows as f32).ceil() as usize).max(1);
+            rows = max_rows;
+        }
+        (columns, rows)
+    }
+}
+
+impl<L: EventListener> PtyFilter for InlineImageFilter<L> {
+    fn filter(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        output.reserve(input.len());
+        for &byte in input {
+            self.advance(byte, output);
+        }
+    }
+
+    fn resize(&mut self, window_size: WindowSize) {
+        self.window_size = window_size;
+    }
+}
+
+/// Parses a width or height of iTerm2's protocol into a number of cells: it
+/// is either a number of cells, of pixels such as `100px`, a percentage of the
+/// terminal's size such as `50%`, or `auto`. Sizes that no terminal could
+/// have are ignored.
+fn iterm_dimension(value: &str, cell_size: u16, terminal_cells: u16) -> Option<usize> {
+    let cells = if let Some(pixels) = value.strip_suffix("px") {
+        pixels.parse::<f32>().ok()? / cell_size.max(1) as f32
+    } else if let Some(percentage) = value.strip_suffix('%') {
+        percentage.parse::<f32>().ok()? * terminal_cells as f32 / 100.
+    } else {
+        value.parse::<f32>().ok()?
+    };
+    (cells.is_finite() && cells > 0. && cells <= u16::MAX as f32).then(|| cells.ceil() as usize)
+}
+
+/// Decodes an image in a format such as PNG, after checking from its header
+/// that it isn't too large, as the decoder allocates whatever size it declares.
+fn decode_image_file(data: &[u8]) -> Result<ImageData> {
+    let (width, height) = image::io::Reader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_dimensions()?;
+    if width as usize * height as usize * 4 > MAX_IMAGE_LEN {
+        bail!("image of {width}x{height} pixels is too large");
+    }
+    ImageData::from_bytes(data)
+}
+
+/// A command of the Kitty graphics protocol, with the keys of its control data
+/// that are supported.
+struct KittyCommand {
+    action: u8,
+    format: u32,
+    medium: u8,
+    compressed: bool,
+    width: u32,
+    height: u32,
+    columns: Option<usize>,
+    rows: Option<usize>,
+    image_id: Option<u32>,
+    quiet: u32,
+    has_more_chunks: bool,
+    keep_cursor: bool,
+    delete: u8,
+}
+
+impl KittyCommand {
+    fn parse(control_data: &str) -> Self {
+        let mut command = Self {
+            action: b't',
+            format: 32,
+            medium: b'd',
+            compressed: false,
+            width: 0,
+            height: 0,
+            columns: None,
+            rows: None,
+            image_id: None,
+            quiet: 0,
+            has_more_chunks: false,
+            keep_cursor: false,
+            delete: b'a',
+        };
+
+        for (key, value) in control_data
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+        {
+            let byte = value.bytes().next().unwrap_or_default();
+            let number = value.parse::<u32>().ok();
+            match key {
+                "a" => command.action = byte,
+                "f" => command.format = number.unwrap_or(command.format),
+                "t" => command.medium = byte,
+                "o" => command.compressed = value == "z",
+                "s" => command.width = number.unwrap_or_default(),
+                "v" => command.height = number.unwrap_or_default(),
+                "c" => command.columns = number.filter(|&n| n > 0).map(|n| n as usize),
+                "r" => command.rows = number.filter(|&n| n > 0).map(|n| n as usize),
+                "i" => command.image_id = number.filter(|&n| n > 0),
+                "q" => command.quiet = number.unwrap_or_default(),
+                "m" => command.has_more_chunks = value == "1",
+                "C" => command.keep_cursor = value == "1",
+                "d" => command.delete = byte,
+                _ => {}
+            }
+        }
+        command
+    }
+
+    /// Decodes an image sent in the payload, or in a temporary file whose path
+    /// is sent in the payload. Other files aren't read, so that programs can't
+    /// read them through the terminal.
+    fn decode_image(&self, payload: &[u8]) -> Result<ImageData> {
+        let data = base64::decode(payload)?;
+        match self.medium {
+            b'd' => self.decode_image_data(data),
+            b't' => {
+                let path = kitty_temporary_file(Path::new(&String::from_utf8(data)?))?;
+                let image = self.decode_image_data(fs::read(&path)?)?;
+                // Temporary files are deleted once they're decoded.
+                fs::remove_file(&path).log_err();
+                Ok(image)
+            }
+            medium => bail!("unsupported transmission medium {:?}", medium as char),
+        }
+    }
+
+    fn decode_image_data(&self, mut data: Vec<u8>) -> Result<ImageData> {
+        if self.compressed {
+            let mut decompressed = Vec::new();
+            ZlibDecoder::new(data.as_slice())
+                .take(MAX_SEQUENCE_LEN as u64)
+                .read_to_end(&mut decompressed)?;
+            data = decompressed;
+        }
+
+        match self.format {
+            100 => decode_image_file(&data),
+            24 | 32 => {
+                let bytes_per_pixel = self.format as usize / 8;
+                if data.len() != self.width as usize * self.height as usize * bytes_per_pixel {
+                    bail!("image data doesn't match the image's size");
+                }
+                // Images are stored as BGRA.
+                let pixels = data
+                    .chunks_exact(bytes_per_pixel)
+                    .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], *pixel.get(3).unwrap_or(&255)])
+                    .collect();
+                let buffer = ImageBuffer::from_raw(self.width, self.height, pixels)
+                    .ok_or_else(|| anyhow!("invalid image size"))?;
+                Ok(ImageData::new(buffer))
+            }
+            _ => bail!("unsupported image format {}", self.format),
+        }
+    }
+}
+
+/// Returns the canonical path of a temporary file that an image was
+/// transmitted in, if it's a file of a supported size that is named like the
+/// protocol requires and is in the system's temporary directory.
+fn kitty_temporary_file(path: &Path) -> Result<PathBuf> {
+    let path = path.canonicalize()?;
+    let temp_dir = std::env::temp_dir().canonicalize()?;
+    let is_temporary_file = path.starts_with(&temp_dir)
+        && path.file_name().map_or(false, |name| {
+            name.to_string_lossy().contains(KITTY_TEMPORARY_FILE_NAME)
+        });
+    if !is_temporary_file {
+        bail!("{path:?} is not a temporary file");
+    }
+    let metadata = fs::metadata(&path)?;
+    if !metadata.is_file() || metadata.len() > MAX_SEQUENCE_LEN as u64 {
+        bail!("{path:?} is not a regular file of a supported size");
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[derive(Clone)]
+    struct TestListener(mpsc::Sender<AlacTermEvent>);
+
+    impl EventListener for TestListener {
+        fn send_event(&self, event: AlacTermEvent) {
+            self.0.send(event).ok();
+        }
+    }
+
+    fn filter() -> (
+        InlineImageFilter<TestListener>,
+        Arc<FairMutex<InlineImages>>,
+        mpsc::Receiver<AlacTermEvent>,
+    ) {
+        let images = Arc::new(FairMutex::new(InlineImages::default()));
+        let (events_tx, events_rx) = mpsc::channel();
+        let window_size = WindowSize {
+            num_lines: 24,
+            num_cols: 80,
+            cell_width: 10,
+            cell_height: 20,
+        };
+        (
+            InlineImageFilter::new(images.clone(), TestListener(events_tx), window_size),
+            images,
+            events_rx,
+        )
+    }
+
+    fn apply(filter: &mut InlineImageFilter<TestListener>, input: &[u8]) -> String {
+        let mut output = Vec::new();
+        filter.filter(input, &mut output);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_kitty_images() {
+        let (mut filter, images, events) = filter();
+
+        // A 30x30 image of RGBA pixels, sent in two chunks.
+        let data = base64::encode(vec![255; 30 * 30 * 4]);
+        let (first_chunk, second_chunk) = data.split_at(1200);
+        let input = format!(
+            "before\x1b_Ga=T,f=32,s=30,v=30,i=7,m=1;{first_chunk}\x1b\\\
+             \x1b_Gm=0;{second_chunk}\x1b\\after"
+        );
+        assert_eq!(
+            apply(&mut filter, input.as_bytes()),
+            "before\
+             \x1b]8;;zed-image:0/0\x1b\\ \x1b]8;;\x1b\\\x08\n\
+             \x1b]8;;zed-image:0/1\x1b\\ \x1b]8;;\x1b\\\x08\
+             \x1b[3Cafter"
+        );
+
+        let image = images.lock().get(0).unwrap();
+        assert_eq!((image.columns, image.rows), (3, 2));
+        match events.try_recv() {
+            Ok(AlacTermEvent::PtyWrite(reply)) => assert_eq!(reply, "\x1b_Gi=7;OK\x1b\\"),
+            _ => panic!("expected a reply"),
+        }
+
+        // Transmitted images can be displayed again, over the given cells.
+        assert_eq!(
+            apply(&mut filter, b"\x1b_Ga=p,i=7,c=1,r=1,q=1\x1b\\"),
+            "\x1b]8;;zed-image:1/0\x1b\\ \x1b]8;;\x1b\\\x08\x1b[1C"
+        );
+        assert!(events.try_recv().is_err());
+
+        // Other sequences are passed through untouched.
+        let input = b"\x1b_Xother\x1b\\\x1b]8;;https://zed.dev\x1b\\\x1b]1337;SetMark\x07";
+        assert_eq!(apply(&mut filter, input).as_bytes(), input);
+    }
+
+    #[test]
+    fn test_kitty_images_in_files() {
+        let (mut filter, images, events) = filter();
+        let png = encode_png(40, 10);
+        let temp_dir = std::env::temp_dir();
+        let process_id = std::process::id();
+        let temporary_path = temp_dir.join(format!("zed-{process_id}-tty-graphics-protocol.png"));
+        let other_path = temp_dir.join(format!("zed-{process_id}-image.png"));
+        fs::write(&temporary_path, &png).unwrap();
+        fs::write(&other_path, &png).unwrap();
+        let command = |medium: &str, image_id: u32, path: &Path| {
+            let path = base64::encode(path.to_string_lossy().as_bytes());
+            format!("\x1b_Ga=t,f=100,t={medium},i={image_id};{path}\x1b\\")
+        };
+
+        // Temporary files are deleted once they're decoded.
+        apply(&mut filter, command("t", 1, &temporary_path).as_bytes());
+        match events.try_recv() {
+            Ok(AlacTermEvent::PtyWrite(reply)) => assert_eq!(reply, "\x1b_Gi=1;OK\x1b\\"),
+            _ => panic!("expected a reply"),
+        }
+        assert!(!temporary_path.exists());
+
+        // Other files aren't read, and programs aren't told why.
+        for medium in ["t", "f"] {
+            apply(&mut filter, command(medium, 2, &other_path).as_bytes());
+            match events.try_recv() {
+                Ok(AlacTermEvent::PtyWrite(reply)) => {
+                    assert_eq!(reply, "\x1b_Gi=2;EINVAL:could not decode the image\x1b\\")
+                }
+                _ => panic!("expected a reply"),
+            }
+        }
+        assert!(other_path.exists());
+        assert_eq!(
+            apply(&mut filter, b"\x1b_Ga=p,i=2\x1b\\"),
+            "",
+            "only the image in the temporary file was transmitted"
+        );
+        assert!(images.lock().get(0).is_none());
+        fs::remove_file(&other_path).unwrap();
+    }
+
+    #[test]
+    fn test_iterm_images() {
+        let (mut filter, images, _) = filter();
+
+        let png = encode_png(40, 10);
+        let input = format!(
+            "\x1b]1337;File=name=cGxvdC5wbmc=;inline=1;width=50%:{}\x07",
+            base64::encode(&png)
+        );
+        apply(&mut filter, input.as_bytes());
+        let image = images.lock().get(0).unwrap();
+        assert_eq!((image.columns, image.rows), (40, 5));
+
+        // Sizes that no terminal could have are ignored.
+        for width in ["inf", "NaN", "1e30"] {
+            let input = format!(
+                "\x1b]1337;File=inline=1;width={width}:{}\x07",
+                base64::encode(&png)
+            );
+            apply(&mut filter, input.as_bytes());
+        }
+        for image_id in 1..=3 {
+            let image = images.lock().get(image_id).unwrap();
+            assert_eq!((image.columns, image.rows), (4, 1));
+        }
+
+        // Images cover at most a few screens of rows.
+        let input = format!(
+            "\x1b]1337;File=inline=1:{}\x07",
+            base64::encode(encode_png(10, 4000))
+        );
+        let output = apply(&mut filter, input.as_bytes());
+        assert_eq!(output.matches("zed-image:4/").count(), 96);
+        let image = images.lock().get(4).unwrap();
+        assert_eq!((image.columns, image.rows), (1, 96));
+
+        // Downloads aren't displayed.
+        let input = format!("\x1b]1337;File=inline=0:{}\x07", base64::encode(&png));
+        assert_eq!(apply(&mut filter, input.as_bytes()), "");
+        assert!(images.lock().get(5).is_none());
+    }
+
+    #[test]
+    fn test_oversized_images() {
+        let (mut filter, images, events) = filter();
+
+        // A PNG header declaring an image far too large to decode.
+        let mut header = b"IHDR".to_vec();
+        header.extend_from_slice(&60000u32.to_be_bytes());
+        header.extend_from_slice(&60000u32.to_be_bytes());
+        header.extend_from_slice(&[8, 6, 0, 0, 0]);
+        let mut crc = flate2::Crc::new();
+        crc.update(&header);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(&header);
+        png.extend_from_slice(&crc.sum().to_be_bytes());
+        let png = base64::encode(png);
+
+        let input = format!("\x1b]1337;File=inline=1:{png}\x07");
+        assert_eq!(apply(&mut filter, input.as_bytes()), "");
+        let input = format!("\x1b_Ga=T,f=100,i=1;{png}\x1b\\");
+        assert_eq!(apply(&mut filter, input.as_bytes()), "");
+        match events.try_recv() {
+            Ok(AlacTermEvent::PtyWrite(reply)) => {
+                assert_eq!(reply, "\x1b_Gi=1;EINVAL:could not decode the image\x1b\\")
+            }
+            _ => panic!("expected a reply"),
+        }
+        assert!(images.lock().get(0).is_none());
+    }
+
+    fn encode_png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgba8(width, height)
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_image_row_from_uri() {
+        assert_eq!(image_row_from_uri("zed-image:12/3"), Some((12, 3)));
+        assert_eq!(image_row_from_uri("zed-image:12"), None);
+        assert_eq!(image_row_from_uri("https://zed.dev"), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! PTYs whose output is rewritten before the terminal parses it, to support
+//! escape sequences that Alacritty ignores.
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use polling::{Event as PollingEvent, PollMode, Poller};
+use std::{
+    io::{self, Read},
+    sync::Arc,
+};
+
+/// Rewrites the output of a PTY.
+pub(crate) trait PtyFilter {
+    /// Copies the PTY's output to `output`, rewriting the sequences that the
+    /// filter handles.
+    fn filter(&mut self, input: &[u8], output: &mut Vec<u8>);
+
+    /// Called when the terminal is resized.
+    fn resize(&mut self, _window_size: WindowSize) {}
+}
+
+/// Applies one filter to the output of the other.
+impl<A: PtyFilter, B: PtyFilter> PtyFilter for (A, B) {
+    fn filter(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        let mut filtered = Vec::with_capacity(input.len());
+        self.0.filter(input, &mut filtered);
+        self.1.filter(&filtered, output);
+    }
+
+    fn resize(&mut self, window_size: WindowSize) {
+        self.0.resize(window_size);
+        self.1.resize(window_size);
+    }
+}
+
+/// A PTY whose output goes through a [`PtyFilter`] before the terminal reads it.
+pub(crate) struct FilteredPty<P, F> {
+    pty: P,
+    filter: F,
+    read_buffer: Vec<u8>,
+    output: Vec<u8>,
+    output_start: usize,
+}
+
+impl<P, F> FilteredPty<P, F> {
+    pub(crate) fn new(pty: P, filter: F) -> Self {
+        Self {
+            pty,
+            filter,
+            read_buffer: Vec::new(),
+            output: Vec::new(),
+            output_start: 0,
+        }
+    }
+}
+
+impl<P: EventedReadWrite, F: PtyFilter> Read for FilteredPty<P, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.output_start == self.output.len() {
+            self.output.clear();
+            self.output_start = 0;
+
+            // Only read half as much as requested, so that the filtered output,
+            // which can be longer than what was read, is returned all at once.
+            self.read_buffer.resize(buf.len().div_ceil(2), 0);
+
+            // Returning nothing would be taken for the end of the PTY's output,
+            // so keep reading while everything that was read is filtered out.
+            while self.output.is_empty() {
+                let len = self.pty.reader().read(&mut self.read_buffer)?;
+                if len == 0 {
+                    return Ok(0);
+                }
+                self.filter
+                    .filter(&self.read_buffer[..len], &mut self.output);
+            }
+        }
+
+        let len = buf.len().min(self.output.len() - self.output_start);
+        buf[..len].copy_from_slice(&self.output[self.output_start..self.output_start + len]);
+        self.output_start += len;
+        Ok(len)
+    }
+}
+
+impl<P: EventedReadWrite, F: PtyFilter> EventedReadWrite for FilteredPty<P, F> {
+    type Reader = Self;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self {
+        self
+    }
+
+    fn writer(&mut self) -> &mut P::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<P: EventedPty, F: PtyFilter> EventedPty for FilteredPty<P, F> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<P: OnResize, F: PtyFilter> OnResize for FilteredPty<P, F> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.filter.resize(window_size);
+        self.pty.on_resize(window_size)
+    }
+}
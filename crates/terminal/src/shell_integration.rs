@@ -0,0 +1,496 @@
+//! Shell integration through the escape sequences that shells print to mark
+//! where prompts, commands and their output begin and end: OSC 133, from the
+//! FinalTerm protocol, and OSC 633, VS Code's extension of it. Shells report
+//! their working directory with OSC 7 or with the `Cwd` property of OSC 633.
+//!
+//! Alacritty ignores these sequences, so [`ShellIntegrationFilter`] scans the
+//! PTY's output for them before the terminal parses it. Each mark is attached
+//! to the next character the shell prints, as an OSC 8 hyperlink with a
+//! [`MARK_URI_SCHEME`] URI. That way marks stay with their text as it scrolls,
+//! reflows or gets cleared. These hyperlinks are removed from the cells that
+//! are rendered, and never opened. When the character is part of a hyperlink
+//! that the program printed, that hyperlink is resumed after it.
+
+use crate::pty_filter::PtyFilter;
+use alacritty_terminal::sync::FairMutex;
+use std::{path::PathBuf, sync::Arc};
+
+/// The scheme of the hyperlink URIs that shell marks are stored in.
+pub const MARK_URI_SCHEME: &str = "zed-shell-mark:";
+
+/// OSC sequences longer than this aren't shell integration sequences, and
+/// aren't buffered.
+const MAX_OSC_LEN: usize = 4096;
+
+/// A point in a command's lifecycle that the shell marked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// The shell started printing its prompt.
+    PromptStart,
+    /// The prompt ended, and what follows is the command the user types.
+    CommandStart,
+    /// The command was submitted, and what follows is its output.
+    CommandExecuted,
+    /// The command finished, with the exit code if the shell reported it.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl ShellMark {
+    fn parse(payload: &str) -> Option<Self> {
+        let mut params = payload.split(';');
+        match params.next()? {
+            "A" => Some(Self::PromptStart),
+            "B" => Some(Self::CommandStart),
+            "C" => Some(Self::CommandExecuted),
+            "D" => Some(Self::CommandFinished {
+                exit_code: params.next().and_then(|code| code.parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, uri: &mut String) {
+        match self {
+            Self::PromptStart => uri.push('A'),
+            Self::CommandStart => uri.push('B'),
+            Self::CommandExecuted => uri.push('C'),
+            Self::CommandFinished { exit_code } => {
+                uri.push('D');
+                if let Some(exit_code) = exit_code {
+                    uri.push_str(&exit_code.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Returns the marks stored in a hyperlink's URI, or `None` if the hyperlink
+/// doesn't hold shell marks.
+pub fn marks_from_uri(uri: &str) -> Option<Vec<ShellMark>> {
+    let marks = uri.strip_prefix(MARK_URI_SCHEME)?;
+    Some(
+        marks
+            .split(',')
+            .filter_map(|mark| {
+                let (kind, exit_code) = mark.split_at(mark.len().min(1));
+                match kind {
+                    "D" => Some(ShellMark::CommandFinished {
+                        exit_code: exit_code.parse().ok(),
+                    }),
+                    _ => ShellMark::parse(kind),
+                }
+            })
+            .collect(),
+    )
+}
+
+fn uri_for_marks(marks: &[ShellMark]) -> String {
+    let mut uri = MARK_URI_SCHEME.to_string();
+    for (ix, mark) in marks.iter().enumerate() {
+        if ix > 0 {
+            uri.push(',');
+        }
+        mark.encode(&mut uri);
+    }
+    uri
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    Escape,
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    OscEscape,
+    /// A DCS, SOS, PM or APC string, which is ignored.
+    String,
+    StringEscape,
+}
+
+/// Finds shell integration sequences in a terminal's output, and attaches the
+/// marks to the character printed after them.
+pub(crate) struct ShellIntegrationScanner {
+    state: ScanState,
+    osc: Vec<u8>,
+    osc_overflowed: bool,
+    pending_marks: Vec<ShellMark>,
+    link_open: bool,
+    /// The OSC 8 sequence of the hyperlink that the program opened, if it
+    /// hasn't closed it yet.
+    program_link: Option<Vec<u8>>,
+    working_directory: Option<PathBuf>,
+}
+
+impl ShellIntegrationScanner {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: ScanState::Ground,
+            osc: Vec::new(),
+            osc_overflowed: false,
+            pending_marks: Vec::new(),
+            link_open: false,
+            program_link: None,
+            working_directory: None,
+        }
+    }
+
+    /// Copies the input to the output, adding the hyperlinks for the marks
+    /// found in it.
+    pub(crate) fn scan(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        output.reserve(input.len());
+        for &byte in input {
+            self.advance(byte, output);
+            output.push(byte);
+        }
+    }
+
+    /// The working directory that the shell last reported, if it changed
+    /// since this was last called.
+    pub(crate) fn take_working_directory(&mut self) -> Option<PathBuf> {
+        self.working_directory.take()
+    }
+
+    fn advance(&mut self, byte: u8, output: &mut Vec<u8>) {
+        // CAN and SUB abort any escape sequence.
+        if matches!(byte, 0x18 | 0x1a) {
+            self.state = ScanState::Ground;
+            return;
+        }
+
+        match self.state {
+            ScanState::Ground => {
+                let is_continuation_byte = (0x80..=0xbf).contains(&byte);
+                if is_continuation_byte {
+                    return;
+                }
+
+                // The link only covers the character that was printed after
+                // the marks, which ends with the next byte that isn't part of it.
+                if self.link_open {
+                    output.extend_from_slice(b"\x1b]8;;\x1b\\");
+                    if let Some(program_link) = &self.program_link {
+                        output.extend_from_slice(b"\x1b]");
+                        output.extend_from_slice(program_link);
+                        output.extend_from_slice(b"\x1b\\");
+                    }
+                    self.link_open = false;
+                }
+
+                if byte == 0x1b {
+                    self.state = ScanState::Escape;
+                } else if byte >= 0x20 && byte != 0x7f && !self.pending_marks.is_empty() {
+                    let uri = uri_for_marks(&self.pending_marks);
+                    output.extend_from_slice(b"\x1b]8;;");
+                    output.extend_from_slice(uri.as_bytes());
+                    output.extend_from_slice(b"\x1b\\");
+                    self.pending_marks.clear();
+                    self.link_open = true;
+                }
+            }
+            ScanState::Escape => self.advance_escape(byte),
+            ScanState::EscapeIntermediate => match byte {
+                0x20..=0x2f => {}
+                0x1b => self.state = ScanState::Escape,
+                _ => self.state = ScanState::Ground,
+            },
+            ScanState::Csi => match byte {
+                0x40..=0x7e => self.state = ScanState::Ground,
+                0x1b => self.state = ScanState::Escape,
+                _ => {}
+            },
+            ScanState::Osc => match byte {
+                0x07 => {
+                    self.dispatch_osc();
+                    self.state = ScanState::Ground;
+                }
+                0x1b => self.state = ScanState::OscEscape,
+                _ => {
+                    if self.osc.len() < MAX_OSC_LEN {
+                        self.osc.push(byte);
+                    } else {
+                        self.osc_overflowed = true;
+                    }
+                }
+            },
+            ScanState::OscEscape => {
+                if byte == b'\\' {
+                    self.dispatch_osc();
+                    self.state = ScanState::Ground;
+                } else {
+                    self.advance_escape(byte);
+                }
+            }
+            ScanState::String => {
+                if byte == 0x1b {
+                    self.state = ScanState::StringEscape;
+                }
+            }
+            ScanState::StringEscape => {
+                if byte == b'\\' {
+                    self.state = ScanState::Ground;
+                } else {
+                    self.advance_escape(byte);
+                }
+            }
+        }
+    }
+
+    fn advance_escape(&mut self, byte: u8) {
+        self.state = match byte {
+            b']' => {
+                self.osc.clear();
+                self.osc_overflowed = false;
+                ScanState::Osc
+            }
+            b'[' => ScanState::Csi,
+            b'P' | b'X' | b'^' | b'_' => ScanState::String,
+            0x20..=0x2f => ScanState::EscapeIntermediate,
+            0x1b => ScanState::Escape,
+            _ => ScanState::Ground,
+        };
+    }
+
+    fn dispatch_osc(&mut self) {
+        if self.osc_overflowed {
+            // Hyperlinks too long to be resumed are left closed.
+            if self.osc.starts_with(b"8;") {
+                self.program_link = None;
+            }
+            return;
+        }
+        let Ok(osc) = std::str::from_utf8(&self.osc) else {
+            return;
+        };
+        let Some((command, payload)) = osc.split_once(';') else {
+            return;
+        };
+
+        match command {
+            "133" => self.pending_marks.extend(ShellMark::parse(payload)),
+            "633" => {
+                if let Some(cwd) = payload.strip_prefix("P;Cwd=") {
+                    self.working_directory = Some(PathBuf::from(unescape_vscode_value(cwd)));
+                } else {
+                    self.pending_marks.extend(ShellMark::parse(payload));
+                }
+            }
+            "7" => {
+                if let Some(path) = local_path_from_file_uri(payload) {
+                    self.working_directory = Some(path);
+                }
+            }
+            "8" => {
+                let is_open = payload
+                    .split_once(';')
+                    .map_or(false, |(_, uri)| !uri.is_empty());
+                self.program_link = is_open.then(|| self.osc.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reverses the escaping of the values in VS Code's OSC 633 sequences, in
+/// which backslashes are doubled and other characters are written as `\xAB`.
+fn unescape_vscode_value(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        if byte != b'\\' {
+            bytes.push(byte);
+        } else if let Some(tail) = rest.strip_prefix(b"\\") {
+            bytes.push(b'\\');
+            rest = tail;
+        } else if let Some(escaped) = rest
+            .strip_prefix(b"x")
+            .and_then(|tail| Some((parse_hex_byte(tail.get(..2)?)?, &tail[2..])))
+        {
+            bytes.push(escaped.0);
+            rest = escaped.1;
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Returns the path of a `file://` URI, if it refers to this machine. Shells
+/// on other machines, such as over SSH, report paths that don't exist here.
+fn local_path_from_file_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let path_start = rest.find('/')?;
+    let host = &rest[..path_start];
+    if !(host.is_empty() || host == "localhost" || Some(host) == local_hostname().as_deref()) {
+        return None;
+    }
+
+    let path = rest[path_start..].as_bytes();
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut ix = 0;
+    while ix < path.len() {
+        let decoded = (path[ix] == b'%')
+            .then(|| parse_hex_byte(path.get(ix + 1..ix + 3)?))
+            .flatten();
+        if let Some(decoded) = decoded {
+            bytes.push(decoded);
+            ix += 3;
+        } else {
+            bytes.push(path[ix]);
+            ix += 1;
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+fn parse_hex_byte(digits: &[u8]) -> Option<u8> {
+    u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+}
+
+#[cfg(unix)]
+fn local_hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&byte| byte == 0)?;
+    String::from_utf8(buffer[..len].to_vec()).ok()
+}
+
+// todo!("windows")
+#[cfg(windows)]
+fn local_hostname() -> Option<String> {
+    None
+}
+
+/// Scans a PTY's output for shell integration sequences, and stores the
+/// working directory that the shell reports.
+pub(crate) struct ShellIntegrationFilter {
+    scanner: ShellIntegrationScanner,
+    working_directory: Arc<FairMutex<Option<PathBuf>>>,
+}
+
+impl ShellIntegrationFilter {
+    pub(crate) fn new(working_directory: Arc<FairMutex<Option<PathBuf>>>) -> Self {
+        Self {
+            scanner: ShellIntegrationScanner::new(),
+            working_directory,
+        }
+    }
+}
+
+impl PtyFilter for ShellIntegrationFilter {
+    fn filter(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        self.scanner.scan(input, output);
+        if let Some(working_directory) = self.scanner.take_working_directory() {
+            *self.working_directory.lock() = Some(working_directory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(input: &[u8]) -> (String, ShellIntegrationScanner) {
+        let mut scanner = ShellIntegrationScanner::new();
+        let mut output = Vec::new();
+        scanner.scan(input, &mut output);
+        (String::from_utf8(output).unwrap(), scanner)
+    }
+
+    #[test]
+    fn test_marks_are_attached_to_the_next_character() {
+        let (output, _) = scan(b"\x1b]133;D;1\x07\x1b]133;A\x1b\\\x1b[32m$ \x1b]133;B\x07ls");
+        assert_eq!(
+            output,
+            "\x1b]133;D;1\x07\x1b]133;A\x1b\\\x1b[32m\
+             \x1b]8;;zed-shell-mark:D1,A\x1b\\$\x1b]8;;\x1b\\ \x1b]133;B\x07\
+             \x1b]8;;zed-shell-mark:B\x1b\\l\x1b]8;;\x1b\\s"
+        );
+
+        // Multi-byte characters are covered by the link as a whole.
+        let (output, _) = scan("\x1b]633;C\x07é\r\n".as_bytes());
+        assert_eq!(
+            output,
+            "\x1b]633;C\x07\x1b]8;;zed-shell-mark:C\x1b\\é\x1b]8;;\x1b\\\r\n"
+        );
+
+        // Marks are kept until a character is printed.
+        let mut scanner = ShellIntegrationScanner::new();
+        let mut output = Vec::new();
+        scanner.scan(b"\x1b]133;C\x07\r\n", &mut output);
+        scanner.scan(b"out", &mut output);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1b]133;C\x07\r\n\x1b]8;;zed-shell-mark:C\x1b\\o\x1b]8;;\x1b\\ut"
+        );
+
+        // Other sequences are passed through untouched.
+        let input = b"\x1b]0;title\x07\x1bP1$r\x1b\\\x1b(Btext";
+        assert_eq!(scan(input).0.as_bytes(), input);
+    }
+
+    #[test]
+    fn test_program_hyperlinks_are_resumed_after_marks() {
+        let (output, _) = scan(
+            b"\x1b]133;C\x07\x1b]8;id=1;file:///tmp/a.txt\x07a.txt\x1b]8;;\x07\r\n\
+              \x1b]8;;file:///tmp/b.txt\x1b\\b.txt\x1b]8;;\x1b\\",
+        );
+        assert_eq!(
+            output,
+            "\x1b]133;C\x07\x1b]8;id=1;file:///tmp/a.txt\x07\
+             \x1b]8;;zed-shell-mark:C\x1b\\a\x1b]8;;\x1b\\\x1b]8;id=1;file:///tmp/a.txt\x1b\\\
+             .txt\x1b]8;;\x07\r\n\
+             \x1b]8;;file:///tmp/b.txt\x1b\\b.txt\x1b]8;;\x1b\\"
+        );
+
+        // Once the program's hyperlink is closed, it isn't resumed anymore.
+        let (output, _) = scan(b"\x1b]8;;https://zed.dev\x07zed\x1b]8;;\x07\x1b]133;A\x07$ ");
+        assert_eq!(
+            output,
+            "\x1b]8;;https://zed.dev\x07zed\x1b]8;;\x07\x1b]133;A\x07\
+             \x1b]8;;zed-shell-mark:A\x1b\\$\x1b]8;;\x1b\\ "
+        );
+    }
+
+    #[test]
+    fn test_working_directory() {
+        let (_, mut scanner) = scan(b"\x1b]7;file:///home/user/my%20project\x07");
+        assert_eq!(
+            scanner.take_working_directory(),
+            Some(PathBuf::from("/home/user/my project"))
+        );
+        assert_eq!(scanner.take_working_directory(), None);
+
+        let (_, mut scanner) = scan(b"\x1b]633;P;Cwd=/tmp/a\\x3bb\\\\c\x1b\\");
+        assert_eq!(
+            scanner.take_working_directory(),
+            Some(PathBuf::from("/tmp/a;b\\c"))
+        );
+
+        // Directories on other machines are ignored.
+        let (_, mut scanner) = scan(b"\x1b]7;file://some-other-host.invalid/home\x07");
+        assert_eq!(scanner.take_working_directory(), None);
+    }
+
+    #[test]
+    fn test_marks_from_uri() {
+        assert_eq!(
+            marks_from_uri("zed-shell-mark:D-1,A"),
+            Some(vec![
+                ShellMark::CommandFinished {
+                    exit_code: Some(-1)
+                },
+                ShellMark::PromptStart
+            ])
+        );
+        assert_eq!(
+            marks_from_uri("zed-shell-mark:D"),
+            Some(vec![ShellMark::CommandFinished { exit_code: None }])
+        );
+        assert_eq!(marks_from_uri("https://zed.dev"), None);
+    }
+}
@@ -2,6 +2,9 @@ pub mod mappings;
 
 pub use alacritty_terminal;
 
+pub mod inline_images;
+mod pty_filter;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -12,7 +15,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Hyperlink},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -32,11 +35,16 @@ use mappings::mouse::{
     scroll_report,
 };
 
-use collections::{HashMap, VecDeque};
+use collections::{HashMap, HashSet, VecDeque};
 use futures::StreamExt;
+use inline_images::{
+    image_row_from_uri, InlineImage, InlineImageFilter, InlineImages, IMAGE_URI_SCHEME,
+};
 use procinfo::LocalProcessInfo;
+use pty_filter::FilteredPty;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{marks_from_uri, ShellIntegrationFilter, ShellMark};
 use smol::channel::{Receiver, Sender};
 use task::TaskId;
 use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
@@ -66,7 +74,17 @@ use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
 
 actions!(
     terminal,
-    [Clear, Copy, Paste, ShowCharacterPalette, SearchTest,]
+    [
+        Clear,
+        Copy,
+        Paste,
+        ShowCharacterPalette,
+        SearchTest,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        SelectLastCommandOutput,
+        CopyLastCommandOutput,
+    ]
 );
 
 ///Scrolling is unbearably sluggish by default. Alacritty supports a configurable
@@ -125,6 +143,9 @@ enum InternalEvent {
     // Adjusted mouse position, should open
     FindHyperlink(Point<Pixels>, bool),
     Copy,
+    ScrollToPrompt(AlacDirection),
+    SelectLastCommandOutput,
+    CopyLastCommandOutput,
 }
 
 ///A translation struct for Alacritty to communicate with us from their event loop
@@ -403,6 +424,20 @@ impl TerminalBuilder {
         #[cfg(windows)]
         let (fd, shell_pid) = (-1, 0);
 
+        let shell_working_directory = Arc::new(FairMutex::new(None));
+        let inline_images = Arc::new(FairMutex::new(InlineImages::default()));
+        let pty = FilteredPty::new(
+            pty,
+            (
+                ShellIntegrationFilter::new(shell_working_directory.clone()),
+                InlineImageFilter::new(
+                    inline_images.clone(),
+                    ZedListener(events_tx.clone()),
+                    TerminalSize::default().into(),
+                ),
+            ),
+        );
+
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
@@ -431,6 +466,9 @@ impl TerminalBuilder {
             selection_head: None,
             shell_fd: fd as u32,
             shell_pid,
+            shell_working_directory,
+            inline_images,
+            reported_working_directory: None,
             foreground_process_info: None,
             breadcrumb_text: String::new(),
             scroll_px: px(0.),
@@ -539,6 +577,30 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub prompts: Vec<PromptMarker>,
+    pub images: Vec<ImagePlacement>,
+}
+
+/// A prompt that the shell marked in the visible part of the terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PromptMarker {
+    pub point: AlacPoint,
+    /// The status of the command entered at the prompt, if one was.
+    pub command_status: Option<CommandStatus>,
+}
+
+/// An image that a program displayed, whose top left corner is at `point`.
+/// The point can be above the viewport when the image is partly scrolled out.
+#[derive(Clone)]
+pub struct ImagePlacement {
+    pub point: AlacPoint,
+    pub image: Arc<InlineImage>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    Running,
+    Finished { exit_code: Option<i32> },
 }
 
 #[derive(Clone)]
@@ -563,6 +625,8 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            prompts: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -588,6 +652,11 @@ pub struct Terminal {
     pub breadcrumb_text: String,
     shell_pid: u32,
     shell_fd: u32,
+    /// The working directory that the shell reported through shell integration.
+    shell_working_directory: Arc<FairMutex<Option<PathBuf>>>,
+    /// The images that programs displayed in the terminal.
+    inline_images: Arc<FairMutex<InlineImages>>,
+    reported_working_directory: Option<PathBuf>,
     pub foreground_process_info: Option<LocalProcessInfo>,
     scroll_px: Pixels,
     next_link_id: usize,
@@ -648,7 +717,9 @@ impl Terminal {
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
 
-                if self.update_process_info() {
+                let process_info_changed = self.update_process_info();
+                let working_directory_changed = self.update_reported_working_directory();
+                if process_info_changed || working_directory_changed {
                     cx.emit(Event::TitleChanged);
                 }
             }
@@ -691,10 +762,25 @@ impl Terminal {
         }
     }
 
-    fn get_cwd(&self) -> Option<PathBuf> {
-        self.foreground_process_info
-            .as_ref()
-            .map(|info| info.cwd.clone())
+    /// Updates the working directory reported by the shell, returns whether it has changed
+    fn update_reported_working_directory(&mut self) -> bool {
+        let working_directory = self.shell_working_directory.lock().clone();
+        if working_directory != self.reported_working_directory {
+            self.reported_working_directory = working_directory;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The shell's working directory, as reported by the shell itself when it
+    /// supports shell integration, or else as read from the foreground process.
+    pub fn working_directory(&self) -> Option<PathBuf> {
+        self.reported_working_directory.clone().or_else(|| {
+            self.foreground_process_info
+                .as_ref()
+                .map(|info| info.cwd.clone())
+        })
     }
 
     ///Takes events from Alacritty and translates them to behavior on this view
@@ -790,6 +876,32 @@ impl Terminal {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word();
             }
+            InternalEvent::ScrollToPrompt(direction) => {
+                let viewport_top = -(term.grid().display_offset() as i32);
+
+                // Prompts are scrolled to the top of the viewport, as far as possible.
+                let scroll = match nearest_prompt_line(term, *direction) {
+                    Some(line) => AlacScroll::Delta(viewport_top - line.0.min(0)),
+                    None if *direction == AlacDirection::Right => AlacScroll::Bottom,
+                    None => return,
+                };
+                term.scroll_display(scroll);
+                self.refresh_hovered_word();
+            }
+            InternalEvent::SelectLastCommandOutput => {
+                if let Some(output) = last_command_output(term) {
+                    term.selection = Some(make_selection(&output));
+                    self.selection_head = Some(*output.end());
+                    term.scroll_to_point(*output.start());
+                    cx.emit(Event::SelectionsChanged)
+                }
+            }
+            InternalEvent::CopyLastCommandOutput => {
+                if let Some(output) = last_command_output(term) {
+                    let text = term.bounds_to_string(*output.start(), *output.end());
+                    cx.write_to_clipboard(ClipboardItem::new(text))
+                }
+            }
             InternalEvent::FindHyperlink(position, open) => {
                 let prev_hovered_word = self.last_content.last_hovered_word.take();
 
@@ -800,7 +912,11 @@ impl Terminal {
                 )
                 .grid_clamp(term, Boundary::Grid);
 
-                let link = term.grid().index(point).hyperlink();
+                let link = term
+                    .grid()
+                    .index(point)
+                    .hyperlink()
+                    .filter(|link| !is_internal_link(link));
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
@@ -873,7 +989,7 @@ impl Terminal {
                             } else {
                                 MaybeNavigationTarget::PathLike(PathLikeTarget {
                                     maybe_path: maybe_url_or_path,
-                                    terminal_dir: self.get_cwd(),
+                                    terminal_dir: self.working_directory(),
                                 })
                             };
                             cx.emit(Event::Open(target));
@@ -928,7 +1044,7 @@ impl Terminal {
         } else {
             MaybeNavigationTarget::PathLike(PathLikeTarget {
                 maybe_path: word,
-                terminal_dir: self.get_cwd(),
+                terminal_dir: self.working_directory(),
             })
         };
         cx.emit(Event::NewNavigationTarget(Some(navigation_target)));
@@ -989,6 +1105,27 @@ impl Terminal {
         self.events.push_back(InternalEvent::Copy);
     }
 
+    /// Scrolls to the closest prompt above the top of the viewport.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Left));
+    }
+
+    /// Scrolls to the closest prompt below the top of the viewport.
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Right));
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        self.events
+            .push_back(InternalEvent::SelectLastCommandOutput);
+    }
+
+    pub fn copy_last_command_output(&mut self) {
+        self.events.push_back(InternalEvent::CopyLastCommandOutput);
+    }
+
     pub fn clear(&mut self) {
         self.events.push_back(InternalEvent::Clear)
     }
@@ -1061,26 +1198,51 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content =
+            Self::make_content(&terminal, &self.inline_images.lock(), &self.last_content);
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        inline_images: &InlineImages,
+        last_content: &TerminalContent,
+    ) -> TerminalContent {
         let content = term.renderable_content();
-        TerminalContent {
-            cells: content
-                .display_iter
-                //TODO: Add this once there's a way to retain empty lines
-                // .filter(|ic| {
-                //     !ic.flags.contains(Flags::HIDDEN)
-                //         && !(ic.bg == Named(NamedColor::Background)
-                //             && ic.c == ' '
-                //             && !ic.flags.contains(Flags::INVERSE))
-                // })
-                .map(|ic| IndexedCell {
+        let mut visible_marks = Vec::new();
+        let mut visible_image_rows = Vec::new();
+        let cells = content
+            .display_iter
+            //TODO: Add this once there's a way to retain empty lines
+            // .filter(|ic| {
+            //     !ic.flags.contains(Flags::HIDDEN)
+            //         && !(ic.bg == Named(NamedColor::Background)
+            //             && ic.c == ' '
+            //             && !ic.flags.contains(Flags::INVERSE))
+            // })
+            .map(|ic| {
+                let mut cell = ic.cell.clone();
+                // Shell marks are stored as hyperlinks, which mustn't be shown.
+                if let Some(marks) = cell.hyperlink().and_then(|link| marks_from_uri(link.uri())) {
+                    visible_marks.extend(marks.into_iter().map(|mark| (ic.point, mark)));
+                    cell.set_hyperlink(None);
+                } else if let Some((image_id, row)) = cell
+                    .hyperlink()
+                    .and_then(|link| image_row_from_uri(link.uri()))
+                {
+                    visible_image_rows.push((ic.point, image_id, row));
+                    cell.set_hyperlink(None);
+                }
+                IndexedCell {
                     point: ic.point,
-                    cell: ic.cell.clone(),
-                })
-                .collect::<Vec<IndexedCell>>(),
+                    cell,
+                }
+            })
+            .collect::<Vec<IndexedCell>>();
+        let prompts = visible_prompts(term, visible_marks);
+        let images = visible_images(inline_images, visible_image_rows);
+
+        TerminalContent {
+            cells,
             mode: content.mode,
             display_offset: content.display_offset,
             selection_text: term.selection_to_string(),
@@ -1089,6 +1251,8 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            prompts,
+            images,
         }
     }
 
@@ -1455,6 +1619,164 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+/// Whether a hyperlink stores a shell mark or an image placeholder, rather
+/// than having been printed by a program.
+fn is_internal_link(link: &Hyperlink) -> bool {
+    link.uri().starts_with(shell_integration::MARK_URI_SCHEME)
+        || link.uri().starts_with(IMAGE_URI_SCHEME)
+}
+
+/// Iterates over the shell marks on the given lines, in order when going
+/// right, and in reverse order when going left. Marks aren't indexed, so the
+/// lines are searched from the end that the mark is expected to be closest to.
+fn shell_marks<T>(
+    term: &Term<T>,
+    lines: RangeInclusive<Line>,
+    direction: AlacDirection,
+) -> impl Iterator<Item = (AlacPoint, ShellMark)> + '_ {
+    let grid = term.grid();
+    let (start, end) = (lines.start().0, lines.end().0);
+    let lines: Box<dyn Iterator<Item = i32>> = match direction {
+        AlacDirection::Right => Box::new(start..=end),
+        AlacDirection::Left => Box::new((start..=end).rev()),
+    };
+    lines.flat_map(move |line| {
+        let line = Line(line);
+        let mut marks = (0..grid.columns())
+            .flat_map(|column| {
+                let point = AlacPoint::new(line, Column(column));
+                grid[point]
+                    .hyperlink()
+                    .and_then(|link| marks_from_uri(link.uri()))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |mark| (point, mark))
+            })
+            .collect::<Vec<_>>();
+        if direction == AlacDirection::Left {
+            marks.reverse();
+        }
+        marks
+    })
+}
+
+/// The line of the closest prompt above or below the top of the viewport.
+fn nearest_prompt_line<T>(term: &Term<T>, direction: AlacDirection) -> Option<Line> {
+    let viewport_top = Line(-(term.grid().display_offset() as i32));
+    let lines = match direction {
+        AlacDirection::Left => term.topmost_line()..=viewport_top - 1,
+        AlacDirection::Right => viewport_top + 1..=term.bottommost_line(),
+    };
+    shell_marks(term, lines, direction)
+        .find(|(_, mark)| *mark == ShellMark::PromptStart)
+        .map(|(point, _)| point.line)
+}
+
+/// Finds the prompts among the marks in the viewport, along with the status
+/// of the commands entered at them.
+fn visible_prompts<T>(
+    term: &Term<T>,
+    visible_marks: Vec<(AlacPoint, ShellMark)>,
+) -> Vec<PromptMarker> {
+    let mut prompts = Vec::new();
+    for (point, mark) in visible_marks {
+        apply_shell_mark(&mut prompts, point, mark);
+    }
+
+    // The command entered at the last visible prompt can be marked as
+    // executed or finished below the viewport. This is checked on every frame
+    // while the command runs, so only the lines close to the viewport are.
+    let is_finished = |prompts: &[PromptMarker]| {
+        prompts.last().map_or(true, |prompt| {
+            matches!(prompt.command_status, Some(CommandStatus::Finished { .. }))
+        })
+    };
+    if !is_finished(&prompts) {
+        let below_viewport = Line(term.screen_lines() as i32 - term.grid().display_offset() as i32);
+        let last_line = term
+            .bottommost_line()
+            .min(below_viewport + MAX_SEARCH_LINES);
+        for (point, mark) in shell_marks(term, below_viewport..=last_line, AlacDirection::Right) {
+            if mark == ShellMark::PromptStart {
+                break;
+            }
+            apply_shell_mark(&mut prompts, point, mark);
+            if is_finished(&prompts) {
+                break;
+            }
+        }
+    }
+    prompts
+}
+
+/// Finds the images whose placeholders are in the viewport, from the rows of
+/// them that are visible.
+fn visible_images(
+    inline_images: &InlineImages,
+    visible_image_rows: Vec<(AlacPoint, u64, usize)>,
+) -> Vec<ImagePlacement> {
+    let mut images = Vec::new();
+    let mut seen_image_ids = HashSet::default();
+    for (point, image_id, row) in visible_image_rows {
+        if !seen_image_ids.insert(image_id) {
+            continue;
+        }
+        // Images that were dropped to save memory are left blank.
+        if let Some(image) = inline_images.get(image_id) {
+            images.push(ImagePlacement {
+                point: AlacPoint::new(Line(point.line.0 - row as i32), point.column),
+                image,
+            });
+        }
+    }
+    images
+}
+
+fn apply_shell_mark(prompts: &mut Vec<PromptMarker>, point: AlacPoint, mark: ShellMark) {
+    match mark {
+        ShellMark::PromptStart => prompts.push(PromptMarker {
+            point,
+            command_status: None,
+        }),
+        ShellMark::CommandExecuted => {
+            if let Some(prompt) = prompts.last_mut() {
+                prompt.command_status = Some(CommandStatus::Running);
+            }
+        }
+        ShellMark::CommandFinished { exit_code } => {
+            if let Some(prompt) = prompts.last_mut() {
+                // Shells also mark commands as finished when none was entered.
+                if prompt.command_status == Some(CommandStatus::Running) {
+                    prompt.command_status = Some(CommandStatus::Finished { exit_code });
+                }
+            }
+        }
+        ShellMark::CommandStart => {}
+    }
+}
+
+/// The output of the last command that finished, from the mark of its start
+/// to the one of its end.
+fn last_command_output<T>(term: &Term<T>) -> Option<RangeInclusive<AlacPoint>> {
+    // The marks are searched from the bottom, so the end of the output is
+    // found before its start.
+    let mut output_end = None;
+    let lines = term.topmost_line()..=term.bottommost_line();
+    for (point, mark) in shell_marks(term, lines, AlacDirection::Left) {
+        match mark {
+            ShellMark::CommandFinished { .. } => output_end = Some(point),
+            ShellMark::CommandExecuted => {
+                if let Some(end) = output_end {
+                    // A command without output is finished where its output would start.
+                    return (end > point).then(|| point..=end.sub(term, Boundary::Grid, 1));
+                }
+            }
+            ShellMark::PromptStart | ShellMark::CommandStart => {}
+        }
+    }
+    None
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1565,14 +1887,18 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
-        index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        event::VoidListener,
+        index::{Column, Direction as AlacDirection, Line, Point as AlacPoint},
+        term::{cell::Cell, Config},
+        vte::ansi::Processor,
+        Term,
     };
-    use gpui::{point, size, Pixels};
+    use gpui::{point, px, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, last_command_output, nearest_prompt_line, rgb_for_index,
+        shell_integration::ShellIntegrationScanner, IndexedCell, TerminalContent, TerminalSize,
     };
 
     #[test]
@@ -1660,6 +1986,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_searching_shell_marks() {
+        // Three commands on a screen of 4 lines, of which the last one is
+        // still running and the first one is scrolled out of the viewport.
+        let term = term_with_output(
+            "\x1b]133;A\x07$ \x1b]133;B\x07one\r\n\x1b]133;C\x07out1\r\n\x1b]133;D;0\x07\
+             \x1b]133;A\x07$ \x1b]133;B\x07two\r\n\x1b]133;C\x07out2\r\n\x1b]133;D;0\x07\
+             \x1b]133;A\x07$ \x1b]133;B\x07three\r\n\x1b]133;C\x07running",
+            4,
+        );
+
+        assert_eq!(
+            nearest_prompt_line(&term, AlacDirection::Left),
+            Some(Line(-2))
+        );
+        assert_eq!(
+            nearest_prompt_line(&term, AlacDirection::Right),
+            Some(Line(2))
+        );
+        assert_eq!(
+            last_command_output(&term),
+            Some(AlacPoint::new(Line(1), Column(0))..=AlacPoint::new(Line(1), Column(19)))
+        );
+
+        // Commands without output have no output to select.
+        let term = term_with_output(
+            "\x1b]133;A\x07$ \x1b]133;B\x07true\r\n\x1b]133;C\x07\x1b]133;D;0\x07\
+             \x1b]133;A\x07$ ",
+            4,
+        );
+        assert_eq!(last_command_output(&term), None);
+        assert_eq!(nearest_prompt_line(&term, AlacDirection::Left), None);
+        assert_eq!(
+            nearest_prompt_line(&term, AlacDirection::Right),
+            Some(Line(1))
+        );
+    }
+
+    /// A terminal of 20 columns and the given number of lines, that the output
+    /// was printed to with its shell marks.
+    fn term_with_output(output: &str, lines: usize) -> Term<VoidListener> {
+        let mut scanner = ShellIntegrationScanner::new();
+        let mut bytes = Vec::new();
+        scanner.scan(output.as_bytes(), &mut bytes);
+
+        let size = TerminalSize::new(px(10.), px(5.), size(px(100.), px(10. * lines as f32)));
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        let mut parser: Processor = Processor::new();
+        for byte in bytes {
+            parser.advance(&mut term, byte);
+        }
+        term
+    }
+
     fn get_cells(size: TerminalSize, rng: &mut ThreadRng) -> Vec<Vec<char>> {
         let mut cells = Vec::new();
 
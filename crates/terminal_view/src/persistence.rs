@@ -1,7 +1,234 @@
 use std::path::PathBuf;
 
 use db::{define_connection, query, sqlez_macros::sql};
-use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+use futures::{future::LocalBoxFuture, FutureExt};
+use gpui::{AppContext, AsyncWindowContext, Axis, Model, View, WeakView};
+use project::Project;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+use workspace::{
+    item::Item, ItemId, Member, Pane, PaneAxis, PaneGroup, Workspace, WorkspaceDb, WorkspaceId,
+};
+
+use crate::{
+    terminal_panel::{new_terminal_pane, TerminalPanel},
+    TerminalView,
+};
+
+/// The split layout of the terminal panel, with the terminals of each pane.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum SerializedPaneGroup {
+    Pane(SerializedPane),
+    Group {
+        axis: SerializedAxis,
+        flexes: Option<Vec<f32>>,
+        children: Vec<SerializedPaneGroup>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SerializedPane {
+    pub items: Vec<ItemId>,
+    pub active_item_id: Option<ItemId>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) enum SerializedAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl SerializedPaneGroup {
+    pub fn contains_item(&self, item_id: ItemId) -> bool {
+        match self {
+            SerializedPaneGroup::Pane(pane) => pane.items.contains(&item_id),
+            SerializedPaneGroup::Group { children, .. } => {
+                children.iter().any(|child| child.contains_item(item_id))
+            }
+        }
+    }
+}
+
+impl From<Axis> for SerializedAxis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => SerializedAxis::Horizontal,
+            Axis::Vertical => SerializedAxis::Vertical,
+        }
+    }
+}
+
+impl From<SerializedAxis> for Axis {
+    fn from(axis: SerializedAxis) -> Self {
+        match axis {
+            SerializedAxis::Horizontal => Axis::Horizontal,
+            SerializedAxis::Vertical => Axis::Vertical,
+        }
+    }
+}
+
+pub(crate) fn serialize_pane_group(pane_group: &PaneGroup, cx: &AppContext) -> SerializedPaneGroup {
+    serialize_member(&pane_group.root, cx)
+}
+
+fn serialize_member(member: &Member, cx: &AppContext) -> SerializedPaneGroup {
+    match member {
+        Member::Axis(PaneAxis {
+            axis,
+            members,
+            flexes,
+            ..
+        }) => SerializedPaneGroup::Group {
+            axis: (*axis).into(),
+            flexes: Some(flexes.lock().clone()),
+            children: members
+                .iter()
+                .map(|member| serialize_member(member, cx))
+                .collect(),
+        },
+        Member::Pane(pane) => SerializedPaneGroup::Pane(serialize_pane(pane, cx)),
+    }
+}
+
+/// Task terminals are not restored, so only the other terminals are serialized.
+fn serialize_pane(pane: &View<Pane>, cx: &AppContext) -> SerializedPane {
+    let pane = pane.read(cx);
+    let items = pane
+        .items()
+        .filter_map(|item| {
+            let terminal_view = item.act_as::<TerminalView>(cx)?;
+            if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+                None
+            } else {
+                Some(item.item_id().as_u64())
+            }
+        })
+        .collect::<Vec<_>>();
+    let active_item_id = pane
+        .active_item()
+        .map(|item| item.item_id().as_u64())
+        .filter(|active_id| items.contains(active_id));
+    SerializedPane {
+        items,
+        active_item_id,
+    }
+}
+
+/// Restores the panes of the terminal panel with their terminals, leaving out
+/// the panes none of whose terminals could be restored. Returns the pane that
+/// contains the terminal which was active in the panel, if it was restored.
+pub(crate) fn deserialize_pane_group<'a>(
+    serialized: SerializedPaneGroup,
+    active_item_id: Option<ItemId>,
+    terminal_panel: WeakView<TerminalPanel>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    workspace_id: WorkspaceId,
+    cx: &'a mut AsyncWindowContext,
+) -> LocalBoxFuture<'a, Option<(Member, Option<View<Pane>>)>> {
+    async move {
+        match serialized {
+            SerializedPaneGroup::Group {
+                axis,
+                flexes,
+                children,
+            } => {
+                let child_count = children.len();
+                let mut members = Vec::new();
+                let mut active_pane = None;
+                for child in children {
+                    if let Some((member, child_active_pane)) = deserialize_pane_group(
+                        child,
+                        active_item_id,
+                        terminal_panel.clone(),
+                        workspace.clone(),
+                        project.clone(),
+                        workspace_id,
+                        cx,
+                    )
+                    .await
+                    {
+                        members.push(member);
+                        active_pane = active_pane.or(child_active_pane);
+                    }
+                }
+
+                if members.len() > 1 {
+                    let flexes = flexes.filter(|_| members.len() == child_count);
+                    let member = Member::Axis(PaneAxis::load(axis.into(), members, flexes));
+                    Some((member, active_pane))
+                } else {
+                    Some((members.pop()?, active_pane))
+                }
+            }
+            SerializedPaneGroup::Pane(serialized_pane) => {
+                let pane = terminal_panel
+                    .update(cx, |terminal_panel, cx| {
+                        let pane = new_terminal_pane(workspace.clone(), project.clone(), cx);
+                        terminal_panel.subscribe_to_pane(&pane, cx);
+                        pane
+                    })
+                    .log_err()?;
+                let items = pane
+                    .update(cx, |_, cx| {
+                        serialized_pane
+                            .items
+                            .iter()
+                            .map(|item_id| {
+                                TerminalView::deserialize(
+                                    project.clone(),
+                                    workspace.clone(),
+                                    workspace_id,
+                                    *item_id,
+                                    cx,
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .log_err()?;
+                let items = futures::future::join_all(items).await;
+
+                // The restored terminals have new ids, so they're matched with
+                // the serialized ones by their position.
+                let contains_active_item = pane
+                    .update(cx, |pane, cx| {
+                        let mut active_ix = None;
+                        let mut contains_active_item = false;
+                        for (item, item_id) in items.into_iter().zip(&serialized_pane.items) {
+                            if let Some(item) = item.log_err() {
+                                pane.add_item(Box::new(item), false, false, None, cx);
+                                if Some(*item_id) == serialized_pane.active_item_id {
+                                    active_ix = Some(pane.items_len() - 1);
+                                }
+                                if Some(*item_id) == active_item_id {
+                                    contains_active_item = true;
+                                }
+                            }
+                        }
+
+                        if let Some(active_ix) = active_ix {
+                            pane.activate_item(active_ix, false, false, cx)
+                        }
+                        contains_active_item
+                    })
+                    .log_err()?;
+
+                terminal_panel
+                    .update(cx, |terminal_panel, cx| {
+                        if pane.read(cx).items_len() > 0 {
+                            let active_pane = contains_active_item.then(|| pane.clone());
+                            Some((Member::Pane(pane), active_pane))
+                        } else {
+                            terminal_panel.unsubscribe_from_pane(&pane);
+                            None
+                        }
+                    })
+                    .log_err()?
+            }
+        }
+    }
+    .boxed_local()
+}
 
 define_connection! {
     pub static ref TERMINAL_DB: TerminalDb<WorkspaceDb> =
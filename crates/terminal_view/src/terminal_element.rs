@@ -1,11 +1,12 @@
 use editor::{Cursor, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    div, fill, point, px, relative, AnyElement, AvailableSpace, Bounds, DispatchPhase, Element,
-    ElementContext, ElementId, FocusHandle, Font, FontStyle, FontWeight, HighlightStyle, Hsla,
-    InputHandler, InteractiveBounds, InteractiveElement, InteractiveElementState, Interactivity,
-    IntoElement, LayoutId, Model, ModelContext, ModifiersChangedEvent, MouseButton, MouseMoveEvent,
-    Pixels, Point, ShapedLine, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun,
-    TextStyle, UnderlineStyle, WeakView, WhiteSpace, WindowContext, WindowTextSystem,
+    div, fill, point, px, relative, size, AnyElement, AvailableSpace, Bounds, ContentMask, Corners,
+    DispatchPhase, Element, ElementContext, ElementId, FocusHandle, Font, FontStyle, FontWeight,
+    HighlightStyle, Hsla, InputHandler, InteractiveBounds, InteractiveElement,
+    InteractiveElementState, Interactivity, IntoElement, LayoutId, Model, ModelContext,
+    ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point, ShapedLine,
+    StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle, UnderlineStyle,
+    WeakView, WhiteSpace, WindowContext, WindowTextSystem,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -17,15 +18,17 @@ use terminal::{
         term::{cell::Flags, TermMode},
         vte::ansi::{Color as AnsiColor, Color::Named, CursorShape as AlacCursorShape, NamedColor},
     },
+    inline_images::InlineImage,
     terminal_settings::TerminalSettings,
-    IndexedCell, Terminal, TerminalContent, TerminalSize,
+    CommandStatus, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::Tooltip;
 use workspace::Workspace;
 
 use std::mem;
-use std::{fmt::Debug, ops::RangeInclusive};
+use std::{fmt::Debug, ops::RangeInclusive, sync::Arc};
+use util::ResultExt;
 
 /// The information generated during layout that is necessary for painting.
 pub struct LayoutState {
@@ -39,6 +42,60 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    command_markers: Vec<CommandMarker>,
+    images: Vec<LayoutImage>,
+}
+
+/// An image that a program displayed, painted over the cells it covers.
+struct LayoutImage {
+    line: i32,
+    column: usize,
+    image: Arc<InlineImage>,
+}
+
+impl LayoutImage {
+    fn paint(&self, origin: Point<Pixels>, layout: &LayoutState, cx: &mut ElementContext) {
+        let cell_width = layout.dimensions.cell_width;
+        let line_height = layout.dimensions.line_height;
+        let cells_size = size(
+            cell_width * self.image.columns as f32,
+            line_height * self.image.rows as f32,
+        );
+
+        // The image keeps its aspect ratio within the cells.
+        let image_size = self.image.data.size();
+        let width = i32::from(image_size.width).max(1) as f32;
+        let height = i32::from(image_size.height).max(1) as f32;
+        let scale = (cells_size.width.0 / width).min(cells_size.height.0 / height);
+        let bounds = Bounds::new(
+            point(
+                origin.x + cell_width * self.column as f32,
+                origin.y + line_height * self.line as f32,
+            ),
+            size(px(scale * width), px(scale * height)),
+        );
+        cx.paint_image(bounds, Corners::default(), self.image.data.clone(), false)
+            .log_err();
+    }
+}
+
+/// A marker in the gutter for a command that the shell reported running,
+/// colored by its exit status.
+struct CommandMarker {
+    line: i32,
+    color: Hsla,
+}
+
+impl CommandMarker {
+    fn paint(&self, origin: Point<Pixels>, layout: &LayoutState, cx: &mut ElementContext) {
+        let width = (layout.gutter / 4.).max(px(2.));
+        let position = point(
+            origin.x - layout.gutter + (layout.gutter - width) / 2.,
+            origin.y + self.line as f32 * layout.dimensions.line_height,
+        );
+        let size = point(width, layout.dimensions.line_height).into();
+        cx.paint_quad(fill(Bounds::new(position, size), self.color));
+    }
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -513,9 +570,37 @@ impl TerminalElement {
             cursor_char,
             selection,
             cursor,
+            prompts,
+            images,
             ..
         } = &self.terminal.read(cx).last_content;
 
+        let status_colors = theme.status();
+        let command_markers = prompts
+            .iter()
+            .filter_map(|prompt| {
+                let color = match prompt.command_status? {
+                    CommandStatus::Running => status_colors.info,
+                    CommandStatus::Finished { exit_code: Some(0) } => status_colors.success,
+                    CommandStatus::Finished { exit_code: Some(_) } => status_colors.error,
+                    CommandStatus::Finished { exit_code: None } => status_colors.hint,
+                };
+                Some(CommandMarker {
+                    line: prompt.point.line.0 + *display_offset as i32,
+                    color,
+                })
+            })
+            .collect();
+
+        let images = images
+            .iter()
+            .map(|placement| LayoutImage {
+                line: placement.point.line.0 + *display_offset as i32,
+                column: placement.point.column.0,
+                image: placement.image.clone(),
+            })
+            .collect();
+
         // searches, highlights to a single range representations
         let mut relative_highlighted_ranges = Vec::new();
         for search_match in search_matches {
@@ -600,6 +685,8 @@ impl TerminalElement {
             display_offset: *display_offset,
             hyperlink_tooltip,
             gutter,
+            command_markers,
+            images,
         }
     }
 
@@ -817,6 +904,10 @@ impl Element for TerminalElement {
                     rect.paint(origin, &layout, cx);
                 }
 
+                for marker in &layout.command_markers {
+                    marker.paint(origin, &layout, cx);
+                }
+
                 cx.with_z_index(1, |cx| {
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
@@ -841,6 +932,16 @@ impl Element for TerminalElement {
                     for cell in &layout.cells {
                         cell.paint(origin, &layout, bounds, cx);
                     }
+
+                    // Images go over the cells they cover, but under the cursor,
+                    // and are cut off where they're scrolled out of view.
+                    cx.with_z_index(1, |cx| {
+                        cx.with_content_mask(Some(ContentMask { bounds }), |cx| {
+                            for image in &layout.images {
+                                image.paint(origin, &layout, cx);
+                            }
+                        });
+                    });
                 });
 
                 if self.cursor_visible {
@@ -1,16 +1,21 @@
-use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
+use std::{ops::ControlFlow, path::PathBuf, rc::Rc, sync::Arc};
 
-use crate::TerminalView;
-use collections::{HashMap, HashSet};
+use crate::{
+    persistence::{
+        deserialize_pane_group, serialize_pane_group, SerializedPane, SerializedPaneGroup,
+    },
+    TerminalView,
+};
+use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
 use futures::future::join_all;
 use gpui::{
-    actions, AppContext, AsyncWindowContext, Entity, EventEmitter, ExternalPaths, FocusHandle,
-    FocusableView, IntoElement, ParentElement, Pixels, Render, Styled, Subscription, Task, View,
-    ViewContext, VisualContext, WeakView, WindowContext,
+    actions, AppContext, AsyncWindowContext, Entity, EntityId, EventEmitter, ExternalPaths,
+    FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render, Styled,
+    Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{Fs, ProjectEntryId};
+use project::{Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -26,7 +31,7 @@ use workspace::{
     item::Item,
     pane,
     ui::IconName,
-    DraggedTab, Pane, Workspace,
+    DraggedTab, Pane, PaneGroup, SplitDirection, Workspace, WorkspaceId,
 };
 
 use anyhow::Result;
@@ -49,117 +54,26 @@ pub fn init(cx: &mut AppContext) {
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    center: PaneGroup,
+    active_pane: View<Pane>,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
-    _subscriptions: Vec<Subscription>,
+    /// The subscriptions to the workspace and to each of the panes, keyed by
+    /// the entity they're subscribed to.
+    subscriptions: HashMap<EntityId, Vec<Subscription>>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
 }
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let terminal_panel = cx.view().downgrade();
-        let pane = cx.new_view(|cx| {
-            let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
-                Default::default(),
-                None,
-                cx,
-            );
-            pane.set_can_split(false, cx);
-            pane.set_can_navigate(false, cx);
-            pane.display_nav_history_buttons(false);
-            pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
-                let terminal_panel = terminal_panel.clone();
-                h_flex()
-                    .gap_2()
-                    .child(
-                        IconButton::new("plus", IconName::Plus)
-                            .icon_size(IconSize::Small)
-                            .on_click(move |_, cx| {
-                                terminal_panel
-                                    .update(cx, |panel, cx| panel.add_terminal(None, None, cx))
-                                    .log_err();
-                            })
-                            .tooltip(|cx| Tooltip::text("New Terminal", cx)),
-                    )
-                    .child({
-                        let zoomed = pane.is_zoomed();
-                        IconButton::new("toggle_zoom", IconName::Maximize)
-                            .icon_size(IconSize::Small)
-                            .selected(zoomed)
-                            .selected_icon(IconName::Minimize)
-                            .on_click(cx.listener(|pane, _, cx| {
-                                pane.toggle_zoom(&workspace::ToggleZoom, cx);
-                            }))
-                            .tooltip(move |cx| {
-                                Tooltip::text(if zoomed { "Zoom Out" } else { "Zoom In" }, cx)
-                            })
-                    })
-                    .into_any_element()
-            });
-
-            let workspace = workspace.weak_handle();
-            pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
-                if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
-                    let item = if &tab.pane == cx.view() {
-                        pane.item_for_index(tab.ix)
-                    } else {
-                        tab.pane.read(cx).item_for_index(tab.ix)
-                    };
-                    if let Some(item) = item {
-                        if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
-                        } else if let Some(project_path) = item.project_path(cx) {
-                            if let Some(entry_path) = workspace
-                                .update(cx, |workspace, cx| {
-                                    workspace
-                                        .project()
-                                        .read(cx)
-                                        .absolute_path(&project_path, cx)
-                                })
-                                .log_err()
-                                .flatten()
-                            {
-                                add_paths_to_terminal(pane, &[entry_path], cx);
-                            }
-                        }
-                    }
-                } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
-                    if let Some(entry_path) = workspace
-                        .update(cx, |workspace, cx| {
-                            let project = workspace.project().read(cx);
-                            project
-                                .path_for_entry(entry_id, cx)
-                                .and_then(|project_path| project.absolute_path(&project_path, cx))
-                        })
-                        .log_err()
-                        .flatten()
-                    {
-                        add_paths_to_terminal(pane, &[entry_path], cx);
-                    }
-                } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
-                    add_paths_to_terminal(pane, paths.paths(), cx);
-                }
-
-                ControlFlow::Break(())
-            });
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
-            pane
-        });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let this = Self {
-            pane,
+        let pane = new_terminal_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+        let mut this = Self {
+            center: PaneGroup::new(pane.clone()),
+            active_pane: pane.clone(),
             fs: workspace.app_state().fs.clone(),
             workspace: workspace.weak_handle(),
             pending_serialization: Task::ready(None),
@@ -167,11 +81,33 @@ impl TerminalPanel {
             height: None,
             pending_terminals_to_add: 0,
             deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
+            subscriptions: HashMap::default(),
         };
+        if let Some(workspace) = workspace.weak_handle().upgrade() {
+            let subscription = cx.subscribe(&workspace, |terminal_panel, _, event, cx| {
+                if let workspace::Event::SpawnTask(spawn_in_terminal) = event {
+                    terminal_panel.spawn_task(spawn_in_terminal, cx);
+                };
+            });
+            this.subscriptions
+                .insert(workspace.entity_id(), vec![subscription]);
+        }
+        this.subscribe_to_pane(&pane, cx);
         this
     }
 
+    pub(crate) fn subscribe_to_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        let subscriptions = vec![
+            cx.observe(pane, |_, _, cx| cx.notify()),
+            cx.subscribe(pane, Self::handle_pane_event),
+        ];
+        self.subscriptions.insert(pane.entity_id(), subscriptions);
+    }
+
+    pub(crate) fn unsubscribe_from_pane(&mut self, pane: &View<Pane>) {
+        self.subscriptions.remove(&pane.entity_id());
+    }
+
     pub async fn load(
         workspace: WeakView<Workspace>,
         mut cx: AsyncWindowContext,
@@ -187,92 +123,111 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, project, workspace_id) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some(serialized_panel) = serialized_panel.as_ref() {
+            if let Some(serialized_panel) = serialized_panel.as_ref() {
                 panel.update(cx, |panel, cx| {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    workspace.database_id(),
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
-                })
-            } else {
-                Vec::new()
-            };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+                });
+            }
+            (panel, workspace.project().clone(), workspace.database_id())
         })?;
 
-        if let Some(workspace) = workspace.upgrade() {
-            panel
-                .update(&mut cx, |panel, cx| {
-                    panel._subscriptions.push(cx.subscribe(
-                        &workspace,
-                        |terminal_panel, _, e, cx| {
-                            if let workspace::Event::SpawnTask(spawn_in_terminal) = e {
-                                terminal_panel.spawn_task(spawn_in_terminal, cx);
-                            };
-                        },
-                    ))
-                })
-                .ok();
+        if let Some(serialized_panel) = serialized_panel {
+            Self::restore(
+                panel.downgrade(),
+                serialized_panel,
+                workspace,
+                project,
+                workspace_id,
+                &mut cx,
+            )
+            .await?;
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
-                    }
-                }
-            }
+        Ok(panel)
+    }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
-            }
-        })?;
+    /// Replaces the panes of the panel with the serialized ones, unless none
+    /// of their terminals could be restored.
+    async fn restore(
+        panel: WeakView<Self>,
+        serialized_panel: SerializedTerminalPanel,
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        workspace_id: WorkspaceId,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<()> {
+        let active_item_id = serialized_panel.active_item_id;
+        if let Some((root, active_pane)) = deserialize_pane_group(
+            serialized_panel.into_pane_group(),
+            active_item_id,
+            panel.clone(),
+            workspace,
+            project,
+            workspace_id,
+            cx,
+        )
+        .await
+        {
+            panel.update(cx, |panel, cx| {
+                panel.set_pane_group(PaneGroup::with_root(root), active_pane, cx)
+            })?;
+        }
+        Ok(())
+    }
 
-        Ok(panel)
+    /// Replaces the panes of the panel, activating the given one, or the first
+    /// one if there's none.
+    fn set_pane_group(
+        &mut self,
+        center: PaneGroup,
+        active_pane: Option<View<Pane>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let new_panes = center.panes();
+        let removed_panes = self
+            .center
+            .panes()
+            .into_iter()
+            .filter(|pane| !new_panes.contains(pane))
+            .cloned()
+            .collect::<Vec<_>>();
+        for pane in &removed_panes {
+            self.unsubscribe_from_pane(pane);
+        }
+        self.active_pane = active_pane.unwrap_or_else(|| center.first_pane());
+        self.center = center;
+        self.serialize(cx);
+        cx.notify();
     }
 
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemoveItem { .. } => self.serialize(cx),
-            pane::Event::Remove => cx.emit(PanelEvent::Close),
+            pane::Event::Remove => self.remove_pane(pane, cx),
+            pane::Event::Split(direction) => self.split_pane(pane, *direction, cx),
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
 
+            pane::Event::Focus => {
+                if self.active_pane != pane {
+                    self.active_pane = pane;
+                    self.serialize(cx);
+                    cx.notify();
+                }
+            }
+
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
@@ -281,6 +236,70 @@ impl TerminalPanel {
         }
     }
 
+    /// Splits the pane in the given direction, opening a terminal in the new
+    /// pane in the working directory of the one that was split.
+    fn split_pane(
+        &mut self,
+        pane: View<Pane>,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let working_directory = pane
+            .read(cx)
+            .active_item()
+            .and_then(|item| item.downcast::<TerminalView>())
+            .and_then(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .working_directory()
+            });
+
+        let new_pane = new_terminal_pane(
+            self.workspace.clone(),
+            workspace.read(cx).project().clone(),
+            cx,
+        );
+        self.subscribe_to_pane(&new_pane, cx);
+        if self
+            .center
+            .split(&pane, &new_pane, direction)
+            .log_err()
+            .is_none()
+        {
+            self.unsubscribe_from_pane(&new_pane);
+            return;
+        }
+        self.active_pane = new_pane.clone();
+        cx.focus_view(&new_pane);
+        self.add_terminal(working_directory, None, cx);
+        cx.notify();
+    }
+
+    /// Removes a pane that has no more terminals from the split layout, or
+    /// closes the panel if it was the last one.
+    fn remove_pane(&mut self, pane: View<Pane>, cx: &mut ViewContext<Self>) {
+        if !self.center.remove(&pane).log_err().unwrap_or(false) {
+            cx.emit(PanelEvent::Close);
+            return;
+        }
+
+        self.unsubscribe_from_pane(&pane);
+        if self.active_pane == pane {
+            let had_focus = pane.read(cx).has_focus(cx);
+            self.active_pane = self.center.first_pane();
+            if had_focus {
+                cx.focus_view(&self.active_pane);
+            }
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -334,7 +353,7 @@ impl TerminalPanel {
             self.spawn_in_new_terminal(spawn_task, working_directory, cx);
             return;
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let existing_terminal = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -343,13 +362,7 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(
-                working_directory,
-                spawn_task,
-                existing_item_index,
-                existing_terminal,
-                cx,
-            );
+            self.replace_terminal(working_directory, spawn_task, existing_terminal, cx);
         } else {
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
@@ -367,7 +380,6 @@ impl TerminalPanel {
                                 terminal_panel.replace_terminal(
                                     working_directory,
                                     spawn_task,
-                                    existing_item_index,
                                     existing_terminal,
                                     cx,
                                 );
@@ -408,31 +420,32 @@ impl TerminalPanel {
         this.update(cx, |this, cx| this.add_terminal(None, None, cx))
     }
 
-    fn terminals_for_task(
-        &self,
-        id: &TaskId,
-        cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.id == id {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    fn terminals_for_task(&self, id: &TaskId, cx: &mut AppContext) -> Vec<View<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| pane.read(cx).items())
+            .filter_map(|item| item.act_as::<TerminalView>(cx))
+            .filter(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .map_or(false, |task_state| &task_state.id == id)
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
-            pane.activate_item(item_index, true, true, cx)
-        })
+    fn activate_terminal_view(&self, terminal_view: &View<TerminalView>, cx: &mut WindowContext) {
+        for pane in self.center.panes() {
+            if let Some(item_index) = pane.read(cx).index_for_item(terminal_view) {
+                pane.update(cx, |pane, cx| {
+                    pane.activate_item(item_index, true, true, cx)
+                });
+                return;
+            }
+        }
     }
 
     fn add_terminal(
@@ -444,7 +457,7 @@ impl TerminalPanel {
         let workspace = self.workspace.clone();
         self.pending_terminals_to_add += 1;
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
+            let pane = terminal_panel.update(&mut cx, |this, _| this.active_pane.clone())?;
             workspace.update(&mut cx, |workspace, cx| {
                 let working_directory = if let Some(working_directory) = working_directory {
                     Some(working_directory)
@@ -484,41 +497,13 @@ impl TerminalPanel {
     }
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
-            })
-            .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
-        let height = self.height;
-        let width = self.width;
+        let serialized_panel = self.serialized(cx);
         self.pending_serialization = cx.background_executor().spawn(
             async move {
                 KEY_VALUE_STORE
                     .write_kvp(
                         TERMINAL_PANEL_KEY.into(),
-                        serde_json::to_string(&SerializedTerminalPanel {
-                            items,
-                            active_item_id,
-                            height,
-                            width,
-                        })?,
+                        serde_json::to_string(&serialized_panel)?,
                     )
                     .await?;
                 anyhow::Ok(())
@@ -527,11 +512,27 @@ impl TerminalPanel {
         );
     }
 
+    fn serialized(&self, cx: &AppContext) -> SerializedTerminalPanel {
+        let pane_group = serialize_pane_group(&self.center, cx);
+        let active_item_id = self
+            .active_pane
+            .read(cx)
+            .active_item()
+            .map(|item| item.item_id().as_u64())
+            .filter(|active_id| pane_group.contains_item(*active_id));
+        SerializedTerminalPanel {
+            items: Vec::new(),
+            pane_group: Some(pane_group),
+            active_item_id,
+            height: self.height,
+            width: self.width,
+        }
+    }
+
     fn replace_terminal(
         &self,
         working_directory: Option<PathBuf>,
         spawn_task: SpawnTask,
-        terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
@@ -548,7 +549,7 @@ impl TerminalPanel {
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
             terminal_to_replace.set_terminal(new_terminal, cx);
         });
-        self.activate_terminal_view(terminal_item_index, cx);
+        self.activate_terminal_view(&terminal_to_replace, cx);
         let task_workspace = self.workspace.clone();
         cx.spawn(|_, mut cx| async move {
             task_workspace
@@ -560,11 +561,113 @@ impl TerminalPanel {
     }
 }
 
+/// Creates a pane for the terminal panel, which doesn't navigate between its
+/// items and only accepts drops of terminals and paths.
+pub(crate) fn new_terminal_pane(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut ViewContext<TerminalPanel>,
+) -> View<Pane> {
+    let terminal_panel = cx.view().downgrade();
+    cx.new_view(|cx| {
+        let mut pane = Pane::new(workspace.clone(), project, Default::default(), None, cx);
+        pane.set_can_split(false, cx);
+        pane.set_can_navigate(false, cx);
+        pane.display_nav_history_buttons(false);
+        pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
+            let terminal_panel = terminal_panel.clone();
+            h_flex()
+                .gap_2()
+                .child(
+                    IconButton::new("split", IconName::Split)
+                        .icon_size(IconSize::Small)
+                        .on_click(cx.listener(|pane, _, cx| {
+                            pane.split(SplitDirection::Right, cx);
+                        }))
+                        .tooltip(|cx| Tooltip::text("Split Terminal", cx)),
+                )
+                .child(
+                    IconButton::new("plus", IconName::Plus)
+                        .icon_size(IconSize::Small)
+                        .on_click(move |_, cx| {
+                            terminal_panel
+                                .update(cx, |panel, cx| panel.add_terminal(None, None, cx))
+                                .log_err();
+                        })
+                        .tooltip(|cx| Tooltip::text("New Terminal", cx)),
+                )
+                .child({
+                    let zoomed = pane.is_zoomed();
+                    IconButton::new("toggle_zoom", IconName::Maximize)
+                        .icon_size(IconSize::Small)
+                        .selected(zoomed)
+                        .selected_icon(IconName::Minimize)
+                        .on_click(cx.listener(|pane, _, cx| {
+                            pane.toggle_zoom(&workspace::ToggleZoom, cx);
+                        }))
+                        .tooltip(move |cx| {
+                            Tooltip::text(if zoomed { "Zoom Out" } else { "Zoom In" }, cx)
+                        })
+                })
+                .into_any_element()
+        });
+
+        pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
+            if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
+                let item = if &tab.pane == cx.view() {
+                    pane.item_for_index(tab.ix)
+                } else {
+                    tab.pane.read(cx).item_for_index(tab.ix)
+                };
+                if let Some(item) = item {
+                    if item.downcast::<TerminalView>().is_some() {
+                        return ControlFlow::Continue(());
+                    } else if let Some(project_path) = item.project_path(cx) {
+                        if let Some(entry_path) = workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .project()
+                                    .read(cx)
+                                    .absolute_path(&project_path, cx)
+                            })
+                            .log_err()
+                            .flatten()
+                        {
+                            add_paths_to_terminal(pane, &[entry_path], cx);
+                        }
+                    }
+                }
+            } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
+                if let Some(entry_path) = workspace
+                    .update(cx, |workspace, cx| {
+                        let project = workspace.project().read(cx);
+                        project
+                            .path_for_entry(entry_id, cx)
+                            .and_then(|project_path| project.absolute_path(&project_path, cx))
+                    })
+                    .log_err()
+                    .flatten()
+                {
+                    add_paths_to_terminal(pane, &[entry_path], cx);
+                }
+            } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
+                add_paths_to_terminal(pane, paths.paths(), cx);
+            }
+
+            ControlFlow::Break(())
+        });
+        let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+        pane.toolbar()
+            .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+        pane
+    })
+}
+
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<View<TerminalView>>,
     cx: &mut AsyncWindowContext,
 ) {
-    let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
+    let pending_tasks = terminals_for_task.iter().filter_map(|terminal| {
         terminal
             .update(cx, |terminal_view, cx| {
                 terminal_view
@@ -599,7 +702,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -608,13 +711,23 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        let panel = cx.view().downgrade();
+        let on_resize = Rc::new(move |cx: &mut WindowContext| {
+            panel.update(cx, |panel, cx| panel.serialize(cx)).log_err();
+        });
+        registrar
+            .into_div()
+            .size_full()
+            .child(
+                self.center
+                    .render_panes(&self.active_pane, None, on_resize, cx),
+            )
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -662,21 +775,31 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.active_pane.read(cx).is_zoomed()
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in self.center.panes() {
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
-        if active && self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0 {
+        if active
+            && self.active_pane.read(cx).items_len() == 0
+            && self.pending_terminals_to_add == 0
+        {
             self.add_terminal(None, None, cx)
         }
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self
+            .center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum::<usize>();
         if count == 0 {
             None
         } else {
@@ -703,8 +826,237 @@ impl Panel for TerminalPanel {
 
 #[derive(Serialize, Deserialize)]
 struct SerializedTerminalPanel {
+    /// The terminals of a panel serialized before it could be split.
+    #[serde(default, skip_serializing)]
     items: Vec<u64>,
+    #[serde(default)]
+    pane_group: Option<SerializedPaneGroup>,
     active_item_id: Option<u64>,
     width: Option<Pixels>,
     height: Option<Pixels>,
 }
+
+impl SerializedTerminalPanel {
+    fn into_pane_group(self) -> SerializedPaneGroup {
+        self.pane_group.unwrap_or_else(|| {
+            SerializedPaneGroup::Pane(SerializedPane {
+                items: self.items,
+                active_item_id: self.active_item_id,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{
+        point, px, Axis, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+        TestAppContext, VisualTestContext,
+    };
+    use project::Project;
+    use workspace::{AppState, Member};
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_splitting_and_closing_panes(cx: &mut TestAppContext) {
+        let (_, panel, cx) = init_test(cx).await;
+        let first_pane = add_terminals(&panel, 1, cx);
+
+        first_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        cx.run_until_parked();
+        let second_pane = panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 2);
+            assert_ne!(panel.active_pane, first_pane);
+            // The new pane is opened with a terminal of its own.
+            assert_eq!(panel.active_pane.read(cx).items_len(), 1);
+            assert!(panel
+                .subscriptions
+                .contains_key(&panel.active_pane.entity_id()));
+            panel.active_pane.clone()
+        });
+
+        // Closing the last terminal of a pane removes the pane.
+        second_pane.update(cx, |pane, cx| pane.remove_item(0, false, cx));
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert_eq!(panel.center.panes(), [&first_pane]);
+            assert_eq!(panel.active_pane, first_pane);
+            assert!(!panel.subscriptions.contains_key(&second_pane.entity_id()));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_restoring_panes(cx: &mut TestAppContext) {
+        let (workspace, panel, cx) = init_test(cx).await;
+        let left_pane = add_terminals(&panel, 2, cx);
+        left_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        cx.run_until_parked();
+        left_pane.update(cx, |pane, cx| {
+            pane.activate_item(0, true, true, cx);
+            cx.emit(pane::Event::Focus);
+        });
+        cx.run_until_parked();
+
+        let serialized_panel = panel.update(cx, |panel, cx| {
+            serde_json::to_string(&panel.serialized(cx)).unwrap()
+        });
+        let serialized_panel = serde_json::from_str(&serialized_panel).unwrap();
+        let restored_panel = restore_panel(&workspace, serialized_panel, cx).await;
+        restored_panel.update(cx, |panel, cx| {
+            let Member::Axis(axis) = &panel.center.root else {
+                panic!("expected the panes to be split");
+            };
+            assert_eq!(axis.axis, Axis::Horizontal);
+            let panes = panel.center.panes();
+            assert_eq!(panes.len(), 2);
+            assert_eq!(panes[0].read(cx).items_len(), 2);
+            assert_eq!(panes[0].read(cx).active_item_index(), 0);
+            assert_eq!(panes[1].read(cx).items_len(), 1);
+            assert_eq!(&panel.active_pane, panes[0]);
+            assert_eq!(panel.subscriptions.len(), 3);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_restoring_resized_panes(cx: &mut TestAppContext) {
+        let (workspace, panel, cx) = init_test(cx).await;
+        let left_pane = add_terminals(&panel, 1, cx);
+        left_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_panel(panel.clone(), cx);
+            workspace.toggle_panel_focus::<TerminalPanel>(cx);
+        });
+        cx.run_until_parked();
+
+        // Dragging the divider between the panes serializes the panel.
+        let left_pane_bounds = panel.update(cx, |panel, _| {
+            let Member::Axis(axis) = &panel.center.root else {
+                panic!("expected the panes to be split");
+            };
+            let bounds = axis.bounding_boxes.lock()[0];
+            bounds.expect("the panel wasn't rendered")
+        });
+        let divider = point(left_pane_bounds.right(), left_pane_bounds.center().y);
+        cx.simulate_event(MouseDownEvent {
+            position: divider,
+            modifiers: Modifiers::default(),
+            button: MouseButton::Left,
+            click_count: 1,
+        });
+        cx.simulate_event(MouseMoveEvent {
+            position: divider + point(px(50.), px(0.)),
+            modifiers: Modifiers::default(),
+            pressed_button: Some(MouseButton::Left),
+        });
+        cx.simulate_event(MouseUpEvent {
+            position: divider + point(px(50.), px(0.)),
+            modifiers: Modifiers::default(),
+            button: MouseButton::Left,
+            click_count: 1,
+        });
+        cx.run_until_parked();
+
+        let serialized_panel = KEY_VALUE_STORE
+            .read_kvp(TERMINAL_PANEL_KEY)
+            .unwrap()
+            .expect("the panel wasn't serialized");
+        let serialized_panel = serde_json::from_str(&serialized_panel).unwrap();
+        let restored_panel = restore_panel(&workspace, serialized_panel, cx).await;
+        restored_panel.update(cx, |panel, _| {
+            let Member::Axis(axis) = &panel.center.root else {
+                panic!("expected the panes to be split");
+            };
+            let flexes = axis.flexes.lock();
+            assert!(flexes[0] > flexes[1], "unexpected flexes {flexes:?}");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_restoring_panels_serialized_before_splitting(cx: &mut TestAppContext) {
+        let (workspace, _, cx) = init_test(cx).await;
+        let serialized_panel = serde_json::from_value(serde_json::json!({
+            "items": [1, 2, 3],
+            "active_item_id": 2,
+            "width": null,
+            "height": null,
+        }))
+        .unwrap();
+        let restored_panel = restore_panel(&workspace, serialized_panel, cx).await;
+        restored_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 1);
+            let pane = panel.active_pane.read(cx);
+            assert_eq!(pane.items_len(), 3);
+            assert_eq!(pane.active_item_index(), 1);
+        });
+    }
+
+    /// Adds terminals to the active pane of the panel, returning the pane.
+    fn add_terminals(
+        panel: &View<TerminalPanel>,
+        count: usize,
+        cx: &mut VisualTestContext,
+    ) -> View<Pane> {
+        let pane = panel.update(cx, |panel, cx| {
+            for _ in 0..count {
+                panel.add_terminal(None, None, cx);
+            }
+            panel.active_pane.clone()
+        });
+        cx.run_until_parked();
+        assert_eq!(pane.update(cx, |pane, _| pane.items_len()), count);
+        pane
+    }
+
+    async fn restore_panel(
+        workspace: &View<Workspace>,
+        serialized_panel: SerializedTerminalPanel,
+        cx: &mut VisualTestContext,
+    ) -> View<TerminalPanel> {
+        let (panel, project, workspace_id) = workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+            (panel, workspace.project().clone(), workspace.database_id())
+        });
+        let workspace = workspace.downgrade();
+        panel
+            .update(cx, |_, cx| {
+                cx.spawn(|panel, mut cx| async move {
+                    TerminalPanel::restore(
+                        panel,
+                        serialized_panel,
+                        workspace,
+                        project,
+                        workspace_id,
+                        &mut cx,
+                    )
+                    .await
+                })
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        panel
+    }
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (View<Workspace>, View<TerminalPanel>, &mut VisualTestContext) {
+        // The terminals are backed by real processes.
+        cx.executor().allow_parking();
+        let app_state = cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            app_state
+        });
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let panel = workspace.update(cx, |workspace, cx| {
+            cx.new_view(|cx| TerminalPanel::new(workspace, cx))
+        });
+        (workspace, panel, cx)
+    }
+}
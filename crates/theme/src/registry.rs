@@ -148,6 +148,17 @@ impl ThemeRegistry {
                 }
             }
 
+            // Themes without accents of their own fall back to their player colors.
+            let mut accents = user_theme
+                .style
+                .accents
+                .iter()
+                .filter_map(|color| try_parse_color(color).ok())
+                .collect::<Vec<_>>();
+            if accents.is_empty() {
+                accents = player_colors.0.iter().map(|player| player.cursor).collect();
+            }
+
             let mut syntax_colors = match user_theme.appearance {
                 AppearanceContent::Light => SyntaxTheme::light(),
                 AppearanceContent::Dark => SyntaxTheme::dark(),
@@ -187,7 +198,7 @@ impl ThemeRegistry {
                     status: status_colors,
                     player: player_colors,
                     syntax: Arc::new(syntax_colors),
-                    accents: Vec::new(),
+                    accents,
                 },
             }
         }));
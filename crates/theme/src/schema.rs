@@ -62,6 +62,11 @@ pub struct ThemeStyleContent {
     #[serde(default)]
     pub players: Vec<PlayerColorContent>,
 
+    /// The accent colors, used in order for things like the nesting levels of
+    /// brackets.
+    #[serde(default)]
+    pub accents: Vec<String>,
+
     /// The styles for syntax nodes.
     #[serde(default)]
     pub syntax: IndexMap<String, HighlightStyleContent>,
@@ -350,6 +355,14 @@ pub struct ThemeColorsContent {
     #[serde(rename = "editor.active_wrap_guide")]
     pub editor_active_wrap_guide: Option<String>,
 
+    /// Color of the vertical guides marking each indentation level.
+    #[serde(rename = "editor.indent_guide")]
+    pub editor_indent_guide: Option<String>,
+
+    /// Color of the indent guide for the scope containing the cursor.
+    #[serde(rename = "editor.indent_guide_active")]
+    pub editor_indent_guide_active: Option<String>,
+
     /// Read-access of a symbol, like reading a variable.
     ///
     /// A document highlight is a range inside a text document which deserves
@@ -710,6 +723,14 @@ impl ThemeColorsContent {
                 .editor_active_wrap_guide
                 .as_ref()
                 .and_then(|color| try_parse_color(&color).ok()),
+            editor_indent_guide: self
+                .editor_indent_guide
+                .as_ref()
+                .and_then(|color| try_parse_color(&color).ok()),
+            editor_indent_guide_active: self
+                .editor_indent_guide_active
+                .as_ref()
+                .and_then(|color| try_parse_color(&color).ok()),
             editor_document_highlight_read_background: self
                 .editor_document_highlight_read_background
                 .as_ref()
@@ -147,6 +147,13 @@ impl Theme {
         &self.styles.status
     }
 
+    /// Returns the accent colors for the theme, used in order for things like
+    /// the nesting levels of brackets.
+    #[inline(always)]
+    pub fn accents(&self) -> &[Hsla] {
+        &self.styles.accents
+    }
+
     /// Returns the color for the syntax node with the given name.
     #[inline(always)]
     pub fn syntax_color(&self, name: &str) -> Hsla {
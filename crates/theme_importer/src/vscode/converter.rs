@@ -59,6 +59,7 @@ impl VsCodeThemeConverter {
                 colors: theme_colors,
                 status: status_colors,
                 players: Vec::new(),
+                accents: Vec::new(),
                 syntax: syntax_theme,
             },
         })
@@ -1,16 +1,30 @@
+mod global;
+mod lines;
+pub(crate) mod pattern;
+pub(crate) mod range;
+
+use anyhow::{anyhow, Result};
+use command_palette::CommandPalette;
 use command_palette_hooks::CommandInterceptResult;
-use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
-use gpui::{impl_actions, Action, AppContext, ViewContext};
+use editor::{Editor, ToPoint};
+use gpui::{actions, impl_actions, Action, AppContext, ViewContext, WindowContext};
+use search::BufferSearchBar;
 use serde_derive::Deserialize;
-use workspace::{SaveIntent, Workspace};
+use workspace::{SaveIntent, Toast, Workspace};
 
 use crate::{
+    command::{
+        lines::SortOptions,
+        pattern::translate_pattern,
+        range::{Address, CommandRange, Position},
+    },
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
+        mark::{self, DeleteMarks, ShowMarks},
         move_cursor,
-        search::{FindCommand, ReplaceCommand},
-        JoinLines,
+        search::{self, FindCommand, Substitution},
     },
+    register::ShowRegisters,
     state::Mode,
     Vim,
 };
@@ -20,7 +34,17 @@ pub struct GoToLine {
     pub line: u32,
 }
 
-impl_actions!(vim, [GoToLine]);
+/// An ex command that takes a range, such as `:'<,'>s/a/b/g` or `:g/^$/d`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExCommand {
+    pub query: String,
+}
+
+impl_actions!(vim, [GoToLine, ExCommand]);
+
+actions!(vim, [CommandLine]);
+
+const EX_COMMAND_TOAST_ID: usize = 0x7e0e3c1;
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, action: &GoToLine, cx| {
@@ -29,18 +53,317 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
             move_cursor(vim, Motion::StartOfDocument, Some(action.line as usize), cx);
         });
     });
+
+    workspace.register_action(|workspace: &mut Workspace, action: &ExCommand, cx| {
+        run_ex_command(workspace, &action.query, cx)
+    });
+
+    workspace.register_action(|workspace: &mut Workspace, _: &CommandLine, cx| {
+        // Like vim, start with the visual selection or the lines given by a
+        // count as the range.
+        let query = Vim::update(cx, |vim, cx| {
+            if vim.state().mode.is_visual() {
+                vim.switch_mode(Mode::Normal, false, cx);
+                return "'<,'>".to_string();
+            }
+            match vim.take_count(cx) {
+                Some(1) => ".".to_string(),
+                Some(count) => format!(".,.+{}", count - 1),
+                None => String::new(),
+            }
+        });
+        CommandPalette::toggle(workspace, &query, cx);
+    });
+}
+
+/// A command that applies to a range of lines.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum RangeCommand {
+    /// A range on its own, which moves to its last line.
+    GoTo,
+    Delete {
+        register: Option<char>,
+        count: Option<u32>,
+    },
+    Yank {
+        register: Option<char>,
+        count: Option<u32>,
+    },
+    Join {
+        count: Option<u32>,
+    },
+    Shift {
+        right: bool,
+        amount: usize,
+    },
+    Move {
+        destination: Position,
+    },
+    Copy {
+        destination: Position,
+    },
+    Sort(SortOptions),
+    Substitute(Substitution),
+    Global {
+        pattern: String,
+        command: String,
+        invert: bool,
+    },
+    Normal {
+        keys: String,
+    },
+}
+
+impl RangeCommand {
+    /// The range used when none is given: the current line, or the whole
+    /// file for `:g`, `:v` and `:sort`.
+    fn default_range(&self) -> CommandRange {
+        match self {
+            RangeCommand::Sort(_) | RangeCommand::Global { .. } => CommandRange::whole_file(),
+            _ => CommandRange::current_line(),
+        }
+    }
+}
+
+/// Whether `name` is `full` or an abbreviation of it at least as long as `min`,
+/// as in `:d[elete]`.
+fn is_abbreviation(name: &str, min: &str, full: &str) -> bool {
+    name.len() >= min.len() && full.starts_with(name)
+}
+
+/// Parses `{register} {count}` as given to `:d` and `:y`.
+fn parse_register_and_count(args: &str) -> Option<(Option<char>, Option<u32>)> {
+    let mut args = args.trim();
+    let mut register = None;
+    if let Some(c) = args.chars().next() {
+        if !c.is_ascii_digit() {
+            register = Some(c);
+            args = args[c.len_utf8()..].trim_start();
+        }
+    }
+    let count = parse_count(args)?;
+    Some((register, count))
+}
+
+fn parse_count(args: &str) -> Option<Option<u32>> {
+    let args = args.trim();
+    if args.is_empty() {
+        Some(None)
+    } else {
+        args.parse().ok().map(Some)
+    }
+}
+
+/// Parses an ex command that takes a range, returning the range (if one was
+/// given) and the command.
+pub(crate) fn parse_range_command(query: &str) -> Option<(Option<CommandRange>, RangeCommand)> {
+    let (range, rest) = CommandRange::parse(query);
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        return Some((Some(range?), RangeCommand::GoTo));
+    }
+
+    if let Some(c @ ('>' | '<')) = rest.chars().next() {
+        let amount = rest.chars().take_while(|next| *next == c).count();
+        if !rest[amount..].trim().is_empty() {
+            return None;
+        }
+        let command = RangeCommand::Shift {
+            right: c == '>',
+            amount,
+        };
+        return Some((range, command));
+    }
+
+    let name_end = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (name, mut args) = rest.split_at(name_end);
+    let bang = if let Some(after_bang) = args.strip_prefix('!') {
+        args = after_bang;
+        true
+    } else {
+        false
+    };
+
+    let command = if is_abbreviation(name, "d", "delete")
+        || (name.ends_with(['l', 'p']) && is_abbreviation(&name[..name.len() - 1], "d", "delete"))
+    {
+        // The `l` and `p` flags only change what is printed afterwards.
+        let (register, count) = parse_register_and_count(args)?;
+        RangeCommand::Delete { register, count }
+    } else if is_abbreviation(name, "y", "yank") {
+        let (register, count) = parse_register_and_count(args)?;
+        RangeCommand::Yank { register, count }
+    } else if is_abbreviation(name, "j", "join") {
+        RangeCommand::Join {
+            count: parse_count(args)?,
+        }
+    } else if is_abbreviation(name, "m", "move") {
+        let (destination, rest) = Position::parse(args.trim())?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        RangeCommand::Move { destination }
+    } else if name == "t" || is_abbreviation(name, "co", "copy") {
+        let (destination, rest) = Position::parse(args.trim())?;
+        if !rest.trim().is_empty() {
+            return None;
+        }
+        RangeCommand::Copy { destination }
+    } else if is_abbreviation(name, "sor", "sort") {
+        RangeCommand::Sort(SortOptions::parse(bang, args)?)
+    } else if is_abbreviation(name, "s", "substitute") && !bang {
+        RangeCommand::Substitute(Substitution::parse(args)?)
+    } else if is_abbreviation(name, "g", "global") || is_abbreviation(name, "v", "vglobal") {
+        let delimiter = args.chars().next()?;
+        if delimiter.is_alphanumeric() || matches!(delimiter, '\\' | '"' | '|' | ' ') {
+            return None;
+        }
+        let (pattern, command) = range::split_pattern(&args[delimiter.len_utf8()..], delimiter);
+        RangeCommand::Global {
+            pattern,
+            command: command.to_string(),
+            invert: bang || name.starts_with('v'),
+        }
+    } else if is_abbreviation(name, "norm", "normal") {
+        RangeCommand::Normal {
+            keys: args.trim_start().to_string(),
+        }
+    } else {
+        return None;
+    };
+    Some((range, command))
+}
+
+/// Runs an ex command on `range` (or the command's default range) in the
+/// active editor, returning a message to show if there is one.
+pub(crate) fn execute(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    range: Option<&CommandRange>,
+    command: &RangeCommand,
+    last_search: Option<&str>,
+    cx: &mut ViewContext<Editor>,
+) -> Result<Option<String>> {
+    let range = range.cloned().unwrap_or_else(|| command.default_range());
+    let rows = range.rows(vim, editor, last_search, cx)?;
+    match command {
+        RangeCommand::GoTo => lines::move_to_row(editor, rows.end - 1, cx),
+        RangeCommand::Delete { register, count } => {
+            let rows = lines::rows_with_count(rows, *count, editor, cx);
+            lines::delete(vim, editor, rows, *register, cx);
+        }
+        RangeCommand::Yank { register, count } => {
+            let rows = lines::rows_with_count(rows, *count, editor, cx);
+            lines::yank(vim, editor, rows, *register, cx);
+        }
+        RangeCommand::Join { count } => {
+            let rows = lines::rows_with_count(rows, *count, editor, cx);
+            lines::join(editor, rows, cx);
+        }
+        RangeCommand::Shift { right, amount } => lines::shift(editor, rows, *right, *amount, cx),
+        RangeCommand::Move { destination } | RangeCommand::Copy { destination } => {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let current_row = editor
+                .selections
+                .newest_anchor()
+                .head()
+                .to_point(&snapshot)
+                .row;
+            let destination =
+                destination.line_number(vim, &snapshot, current_row, last_search, cx)?;
+            let delete = matches!(command, RangeCommand::Move { .. });
+            lines::move_or_copy(editor, rows, destination, delete, cx)?;
+        }
+        RangeCommand::Sort(options) => lines::sort(editor, rows, options, last_search, cx)?,
+        RangeCommand::Substitute(substitution) => {
+            return search::substitute(editor, rows, substitution, last_search, cx)
+        }
+        RangeCommand::Global {
+            pattern,
+            command,
+            invert,
+        } => {
+            return global::global(
+                vim,
+                editor,
+                rows,
+                pattern,
+                command,
+                *invert,
+                last_search,
+                cx,
+            )
+        }
+        RangeCommand::Normal { keys } => global::normal(editor, rows, keys, cx),
+    }
+    Ok(None)
+}
+
+/// Parses and runs an ex command, showing any error (or message) it results in.
+pub(crate) fn run_ex_command(
+    workspace: &mut Workspace,
+    query: &str,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let query = query.trim_start_matches(':');
+    let Some((range, command)) = parse_range_command(query) else {
+        workspace.show_error(&anyhow!("E492: Not an editor command: {}", query), cx);
+        return;
+    };
+    if let RangeCommand::Substitute(substitution) = &command {
+        if substitution.confirm {
+            search::confirm_substitute(workspace, substitution, cx);
+            return;
+        }
+    }
+
+    let last_search = last_search(workspace, cx);
+    let result = Vim::update(cx, |vim, cx| {
+        if vim.state().mode.is_visual() {
+            vim.switch_mode(Mode::Normal, false, cx);
+        }
+        if command == RangeCommand::GoTo {
+            mark::record_jump(vim, cx);
+        }
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            execute(
+                vim,
+                editor,
+                range.as_ref(),
+                &command,
+                last_search.as_deref(),
+                cx,
+            )
+        })
+    });
+    match result {
+        Some(Err(err)) => workspace.show_error(&err, cx),
+        Some(Ok(Some(message))) => {
+            workspace.show_toast(Toast::new(EX_COMMAND_TOAST_ID, message), cx)
+        }
+        _ => {}
+    }
+}
+
+/// The query in the search bar, used when a pattern is left empty.
+fn last_search(workspace: &Workspace, cx: &WindowContext) -> Option<String> {
+    let search_bar = workspace
+        .active_pane()
+        .read(cx)
+        .toolbar()
+        .read(cx)
+        .item_of_type::<BufferSearchBar>()?;
+    let query = search_bar.read(cx).query(cx);
+    (!query.is_empty()).then_some(query)
 }
 
 pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
     // Note: this is a very poor simulation of vim's command palette.
-    // In the future we should adjust it to handle parsing range syntax,
-    // and then calling the appropriate commands with/without ranges.
-    //
-    // We also need to support passing arguments to commands like :w
+    // Commands that take a range are parsed by `parse_range_command`,
+    // but we still need to support passing arguments to commands like :w
     // (ideally with filename autocompletion).
-    //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
     while query.starts_with(":") {
         query = &query[1..];
     }
@@ -255,15 +578,6 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             ("lNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
         }
 
-        // modify the buffer (should accept [range])
-        "j" | "jo" | "joi" | "join" => ("join", JoinLines.boxed_clone()),
-        "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel" | "deletl"
-        | "deletel" | "dp" | "dep" | "delp" | "delep" | "deletp" | "deletep" => {
-            ("delete", editor::actions::DeleteLine.boxed_clone())
-        }
-        "sor" | "sor " | "sort" | "sort " => ("sort", SortLinesCaseSensitive.boxed_clone()),
-        "sor i" | "sort i" => ("sort i", SortLinesCaseInsensitive.boxed_clone()),
-
         // Explore, etc.
         "E" | "Ex" | "Exp" | "Expl" | "Explo" | "Explor" | "Explore" => (
             "Explore",
@@ -315,31 +629,72 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             cx.build_action("assistant::ToggleFocus", None).unwrap(),
         ),
 
+        // marks
+        "marks" => ("marks", ShowMarks.boxed_clone()),
+        "reg" | "regi" | "regis" | "regist" | "registe" | "register" | "registers" | "di"
+        | "dis" | "disp" | "displ" | "displa" | "display" => {
+            ("registers", ShowRegisters.boxed_clone())
+        }
+        "delm!" | "delma!" | "delmar!" | "delmark!" | "delmarks!" => (
+            "delmarks!",
+            DeleteMarks {
+                marks: String::new(),
+                all_local: true,
+            }
+            .boxed_clone(),
+        ),
+
         // goto (other ranges handled under _ => )
         "$" => ("$", EndOfDocument.boxed_clone()),
         "%" => ("%", EndOfDocument.boxed_clone()),
         "0" => ("0", StartOfDocument.boxed_clone()),
 
         _ => {
-            if query.starts_with("/") || query.starts_with("?") {
-                (
-                    query,
-                    FindCommand {
-                        query: query[1..].to_string(),
-                        backwards: query.starts_with("?"),
-                    }
-                    .boxed_clone(),
-                )
-            } else if query.starts_with("%") {
+            if let Ok(line) = query.parse::<u32>() {
+                (query, GoToLine { line }.boxed_clone())
+            } else if let Some(marks) = query.split_once(' ').and_then(|(command, marks)| {
+                ["delm", "delma", "delmar", "delmark", "delmarks"]
+                    .contains(&command)
+                    .then_some(marks)
+            }) {
                 (
                     query,
-                    ReplaceCommand {
-                        query: query.to_string(),
+                    DeleteMarks {
+                        marks: marks.to_string(),
+                        all_local: false,
                     }
                     .boxed_clone(),
                 )
-            } else if let Ok(line) = query.parse::<u32>() {
-                (query, GoToLine { line }.boxed_clone())
+            } else if let Some((range, command)) = parse_range_command(query) {
+                match (range, command) {
+                    // `:/pattern` and `:?pattern` on their own search like `/` and `?`
+                    (
+                        Some(CommandRange {
+                            start:
+                                Position {
+                                    address: Address::Search { pattern, backwards },
+                                    offset: 0,
+                                },
+                            end: None,
+                            ..
+                        }),
+                        RangeCommand::GoTo,
+                    ) => (
+                        query,
+                        FindCommand {
+                            query: translate_pattern(&pattern).0,
+                            backwards,
+                        }
+                        .boxed_clone(),
+                    ),
+                    _ => (
+                        query,
+                        ExCommand {
+                            query: query.to_string(),
+                        }
+                        .boxed_clone(),
+                    ),
+                }
             } else {
                 return None;
             }
@@ -382,7 +737,11 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 mod test {
     use std::path::Path;
 
-    use crate::test::{NeovimBackedTestContext, VimTestContext};
+    use super::{parse_range_command, range::CommandRange, RangeCommand};
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
     use gpui::TestAppContext;
     use indoc::indoc;
 
@@ -479,6 +838,298 @@ mod test {
             .await;
     }
 
+    #[gpui::test]
+    async fn test_command_ranges(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "2", ",", "4", "d", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            ˇe"})
+            .await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            "j", "m", "a", "j", "j", "m", "b", "g", "g", ":", "'", "a", ",", "'", "b", "m", "0",
+            "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            b
+            c
+            ˇd
+            a
+            e"})
+            .await;
+
+        cx.set_shared_state(indoc! {"
+            a
+            ˇb
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes([":", ".", ",", "$", "-", "1", "y", "enter", "p"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b
+            ˇb
+            c
+            c
+            d"})
+            .await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes(["shift-v", "j", ":", "d", "enter"])
+            .await;
+        cx.assert_shared_state("ˇc").await;
+    }
+
+    #[gpui::test]
+    async fn test_command_move_copy(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "m", "$", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            b
+            c
+            ˇa"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "t", "0", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            a"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "2", ",", "3", "t", ".", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b
+            ˇc
+            b
+            c
+            a"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_shift(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes([":", "2", ",", "3", ">", "enter"]);
+        cx.assert_state("a\n    b\n    ˇc", Mode::Normal);
+        cx.simulate_keystrokes([":", "<", "enter"]);
+        cx.assert_state("a\n    b\nˇc", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute_flags(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇaa
+            Aa
+            aa"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "%", "s", "/", "a", "/", "b", "/", "g", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            bb
+            Ab
+            ˇbb"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            ":", "1", ",", "2", "s", "/", "B", "/", "x", "/", "i", "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            xb
+            ˇAx
+            bb"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            ":", "s", "/", "b", "/", "y", "/", "g", "space", "2", "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            xb
+            Ax
+            ˇyy"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_sort(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            c
+            a
+            ˇB
+            b"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "s", "o", "r", "t", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            ˇB
+            a
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "s", "o", "r", "t", "!", "space", "i", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            ˇc
+            b
+            B
+            a"})
+            .await;
+
+        cx.set_shared_state(indoc! {"
+            ˇx10
+            x9
+            y
+            x-1"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "s", "o", "r", "t", "space", "n", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            ˇy
+            x-1
+            x9
+            x10"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa1
+            b
+            a2
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "g", "/", "a", "/", "d", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            b
+            ˇc"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "v", "/", "b", "/", "d", "enter"])
+            .await;
+        cx.assert_shared_state("ˇb").await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "g", "/", "^", "/", "m", "0", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            ˇc
+            b
+            a"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            ":", "%", "n", "o", "r", "m", "space", "shift-a", "x", "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            ax
+            bx
+            cˇx"})
+            .await;
+    }
+
+    #[test]
+    fn test_parse_range_command() {
+        assert_eq!(
+            parse_range_command("d a 3"),
+            Some((
+                None,
+                RangeCommand::Delete {
+                    register: Some('a'),
+                    count: Some(3)
+                }
+            ))
+        );
+        assert_eq!(
+            parse_range_command("%dl"),
+            Some((
+                Some(CommandRange::whole_file()),
+                RangeCommand::Delete {
+                    register: None,
+                    count: None
+                }
+            ))
+        );
+        assert_eq!(
+            parse_range_command("g!/a\\/b/norm x"),
+            Some((
+                None,
+                RangeCommand::Global {
+                    pattern: "a/b".into(),
+                    command: "norm x".into(),
+                    invert: true
+                }
+            ))
+        );
+        assert_eq!(
+            parse_range_command(">>"),
+            Some((
+                None,
+                RangeCommand::Shift {
+                    right: true,
+                    amount: 2
+                }
+            ))
+        );
+        assert_eq!(parse_range_command("sp"), None);
+        assert_eq!(parse_range_command("wq"), None);
+    }
+
     #[gpui::test]
     async fn test_command_write(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
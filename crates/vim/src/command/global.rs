@@ -0,0 +1,119 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use editor::{Editor, ToPoint};
+use gpui::{Keystroke, Modifiers, ViewContext};
+use language::Point;
+
+use crate::{
+    command::{
+        execute, parse_range_command, pattern::pattern_regex, range::line_text, RangeCommand,
+    },
+    state::Mode,
+    Vim,
+};
+
+/// `:g/{pattern}/{command}` (or `:v`, when `invert` is set), which runs the
+/// command on each line in `rows` that matches the pattern (or doesn't).
+///
+/// The lines are found before the command runs, and tracked with anchors so
+/// that commands which add or remove lines still apply to the right ones.
+pub(crate) fn global(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: Range<u32>,
+    pattern: &str,
+    command: &str,
+    invert: bool,
+    last_search: Option<&str>,
+    cx: &mut ViewContext<Editor>,
+) -> Result<Option<String>> {
+    let regex = pattern_regex(pattern, last_search, false)?;
+    let Some((range, command)) = parse_range_command(command) else {
+        return Err(anyhow!("E492: Not an editor command: {}", command));
+    };
+    if let RangeCommand::Global { .. } = command {
+        return Err(anyhow!("E147: Cannot do :global recursive"));
+    }
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let anchors = rows
+        .filter(|row| regex.is_match(&line_text(&snapshot, *row)) != invert)
+        .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+        .collect::<Vec<_>>();
+    if anchors.is_empty() {
+        return Err(anyhow!("E486: Pattern not found: {}", pattern));
+    }
+
+    let mut result = Ok(None);
+    editor.transact(cx, |editor, cx| {
+        for anchor in anchors {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let point = Point::new(anchor.to_point(&snapshot).row, 0);
+            editor.change_selections(None, cx, |s| s.select_ranges([point..point]));
+            result = execute(vim, editor, range.as_ref(), &command, last_search, cx);
+            if result.is_err() {
+                break;
+            }
+        }
+    });
+    result
+}
+
+/// `:normal {commands}`, which types the commands at the start of each line in
+/// `rows`. As in vim, a command left unfinished is ended as if with escape.
+///
+/// The keystrokes are dispatched once the current action has finished, so
+/// that they are handled by the editor like any others.
+pub(crate) fn normal(
+    editor: &mut Editor,
+    rows: Range<u32>,
+    keys: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let anchors = rows
+        .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+        .collect::<Vec<_>>();
+    let keystrokes = keys.chars().map(keystroke_for_char).collect::<Vec<_>>();
+    let editor = cx.view().downgrade();
+
+    cx.window_context().defer(move |cx| {
+        for anchor in anchors {
+            let Some(editor) = editor.upgrade() else {
+                return;
+            };
+            editor.update(cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let point = Point::new(anchor.to_point(&snapshot).row, 0);
+                editor.change_selections(None, cx, |s| s.select_ranges([point..point]));
+            });
+
+            for keystroke in &keystrokes {
+                cx.dispatch_keystroke(keystroke.clone());
+            }
+
+            let vim = Vim::read(cx);
+            if vim.state().mode != Mode::Normal || vim.active_operator().is_some() {
+                cx.dispatch_keystroke(Keystroke::parse("escape").unwrap());
+            }
+        }
+    });
+}
+
+fn keystroke_for_char(c: char) -> Keystroke {
+    let (key, shift) = match c {
+        ' ' => ("space".to_string(), false),
+        '\t' => ("tab".to_string(), false),
+        c if c.is_ascii_uppercase() => (c.to_ascii_lowercase().to_string(), true),
+        c => (c.to_string(), false),
+    };
+    Keystroke {
+        modifiers: Modifiers {
+            shift,
+            ..Default::default()
+        },
+        key,
+        ime_key: None,
+    }
+}
@@ -0,0 +1,322 @@
+use std::{cmp::Ordering, ops::Range};
+
+use anyhow::{anyhow, Result};
+use editor::{scroll::Autoscroll, Editor};
+use gpui::ViewContext;
+use language::{Bias, Point};
+
+use crate::{
+    command::{
+        pattern::pattern_regex,
+        range::{line_text, split_pattern},
+    },
+    utils::{copy_selections_content, yank_selections_content},
+    Vim,
+};
+
+/// The flags to `:sort` (`:help :sort`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SortOptions {
+    /// `!`
+    pub reverse: bool,
+    /// `i`
+    pub ignore_case: bool,
+    /// `n`: sort by the first decimal number in each line.
+    pub numeric: bool,
+    /// `u`: keep only the first of a run of equal lines.
+    pub unique: bool,
+    /// `/{pattern}/`: sort by what comes after the match.
+    pub pattern: Option<String>,
+    /// `r`: sort by the match itself instead.
+    pub use_match: bool,
+}
+
+impl SortOptions {
+    pub(crate) fn parse(reverse: bool, args: &str) -> Option<Self> {
+        let mut options = SortOptions {
+            reverse,
+            ..Default::default()
+        };
+        let mut rest = args.trim_start();
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+            match c {
+                'i' => options.ignore_case = true,
+                'n' => options.numeric = true,
+                'u' => options.unique = true,
+                'r' => options.use_match = true,
+                ' ' => {}
+                '/' => {
+                    let (pattern, after) = split_pattern(rest, '/');
+                    options.pattern = Some(pattern);
+                    rest = after;
+                }
+                _ => return None,
+            }
+        }
+        Some(options)
+    }
+}
+
+/// Selects all of `rows`, including the newline that ends them, in the same way
+/// as a linewise visual selection.
+fn select_rows(editor: &mut Editor, rows: &Range<u32>, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_point = snapshot.max_point();
+    let range = if rows.end <= max_point.row {
+        Point::new(rows.start, 0)..Point::new(rows.end, 0)
+    } else if rows.start > 0 {
+        let previous_row = rows.start - 1;
+        Point::new(previous_row, snapshot.line_len(previous_row))..max_point
+    } else {
+        Point::zero()..max_point
+    };
+    editor.change_selections(None, cx, |s| s.select_ranges([range]));
+}
+
+/// Moves the cursor to the first non-blank character of `row`.
+pub(crate) fn move_to_first_non_blank(editor: &mut Editor, row: u32, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let row = row.min(snapshot.max_point().row);
+    let point = Point::new(row, snapshot.indent_size_for_line(row).len);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([point..point])
+    });
+}
+
+/// Moves the cursor to `row`, keeping its column where possible.
+pub(crate) fn move_to_row(editor: &mut Editor, row: u32, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let column = editor.selections.newest::<Point>(cx).head().column;
+    let row = row.min(snapshot.max_point().row);
+    let point = snapshot.clip_point(Point::new(row, column), Bias::Left);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([point..point])
+    });
+}
+
+/// Applies a count given after a command, as in `:d 3`, which counts lines
+/// from the end of the range.
+pub(crate) fn rows_with_count(
+    rows: Range<u32>,
+    count: Option<u32>,
+    editor: &Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Range<u32> {
+    let Some(count) = count else {
+        return rows;
+    };
+    let row_count = editor.buffer().read(cx).snapshot(cx).max_point().row + 1;
+    let start = rows.end - 1;
+    start..(start + count.max(1)).min(row_count)
+}
+
+/// `:d`, which stores the lines in a register as a linewise delete.
+pub(crate) fn delete(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: Range<u32>,
+    register: Option<char>,
+    cx: &mut ViewContext<Editor>,
+) {
+    vim.workspace_state.selected_register = register;
+    editor.transact(cx, |editor, cx| {
+        select_rows(editor, &rows, cx);
+        copy_selections_content(vim, editor, true, cx);
+        editor.insert("", cx);
+        move_to_first_non_blank(editor, rows.start, cx);
+    });
+}
+
+/// `:y`, which leaves the cursor where it was.
+pub(crate) fn yank(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: Range<u32>,
+    register: Option<char>,
+    cx: &mut ViewContext<Editor>,
+) {
+    vim.workspace_state.selected_register = register;
+    let selections = editor.selections.disjoint_anchors();
+    select_rows(editor, &rows, cx);
+    yank_selections_content(vim, editor, true, cx);
+    editor.change_selections(None, cx, |s| s.select_anchors(selections.to_vec()));
+}
+
+/// `:j`, which joins the current line with the next one if given a single line.
+pub(crate) fn join(editor: &mut Editor, rows: Range<u32>, cx: &mut ViewContext<Editor>) {
+    let max_row = editor.buffer().read(cx).snapshot(cx).max_point().row;
+    let last_row = if rows.len() == 1 {
+        rows.start + 1
+    } else {
+        rows.end - 1
+    };
+    if last_row > max_row {
+        return;
+    }
+    editor.transact(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(rows.start, 0)..Point::new(last_row, 0)])
+        });
+        editor.join_lines(&Default::default(), cx);
+        move_to_first_non_blank(editor, rows.start, cx);
+    });
+}
+
+/// `:>` and `:<`, which shift once for each `>` or `<`.
+pub(crate) fn shift(
+    editor: &mut Editor,
+    rows: Range<u32>,
+    right: bool,
+    amount: usize,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let last_row = rows.end - 1;
+    let range = Point::new(rows.start, 0)..Point::new(last_row, snapshot.line_len(last_row));
+    editor.transact(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| s.select_ranges([range]));
+        for _ in 0..amount {
+            if right {
+                editor.indent(&Default::default(), cx);
+            } else {
+                editor.outdent(&Default::default(), cx);
+            }
+        }
+        move_to_first_non_blank(editor, last_row, cx);
+    });
+}
+
+/// `:m` (when `delete` is set) and `:t`, which put the lines below
+/// `destination`, a line number where zero is above the first line.
+pub(crate) fn move_or_copy(
+    editor: &mut Editor,
+    rows: Range<u32>,
+    destination: u32,
+    delete: bool,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    if delete && destination > rows.start && destination < rows.end {
+        return Err(anyhow!("E134: Cannot move a range of lines into itself"));
+    }
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_point = snapshot.max_point();
+    let last_row = rows.end - 1;
+    let text = snapshot
+        .text_for_range(
+            Point::new(rows.start, 0)..Point::new(last_row, snapshot.line_len(last_row)),
+        )
+        .collect::<String>();
+
+    let mut edits = Vec::new();
+    if delete {
+        if destination == rows.start || destination == rows.end {
+            move_to_row(editor, last_row, cx);
+            return Ok(());
+        }
+        let range = if rows.end <= max_point.row {
+            Point::new(rows.start, 0)..Point::new(rows.end, 0)
+        } else {
+            let previous_row = rows.start - 1;
+            Point::new(previous_row, snapshot.line_len(previous_row))..max_point
+        };
+        edits.push((range, String::new()));
+    }
+    if destination > max_point.row {
+        edits.push((max_point..max_point, format!("\n{}", text)));
+    } else {
+        let point = Point::new(destination, 0);
+        edits.push((point..point, format!("{}\n", text)));
+    }
+    // Insert before deleting when both happen at the same point.
+    edits.sort_by_key(|(range, _)| (range.start, range.end));
+
+    // The last line moved or copied ends up this many lines below the destination.
+    let line_count = rows.len() as u32;
+    let cursor_row = if delete && destination >= rows.end {
+        destination - 1
+    } else {
+        destination + line_count - 1
+    };
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        move_to_row(editor, cursor_row, cx);
+    });
+    Ok(())
+}
+
+/// `:sort`, which puts the cursor at the start of the range afterwards.
+pub(crate) fn sort(
+    editor: &mut Editor,
+    rows: Range<u32>,
+    options: &SortOptions,
+    last_search: Option<&str>,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let regex = options
+        .pattern
+        .as_ref()
+        .map(|pattern| pattern_regex(pattern, last_search, false))
+        .transpose()?;
+    let lines = rows
+        .clone()
+        .map(|row| line_text(&snapshot, row))
+        .collect::<Vec<_>>();
+
+    let key = |line: &String| -> String {
+        let key = match regex.as_ref().and_then(|regex| regex.find(line)) {
+            Some(found) if options.use_match => found.as_str(),
+            Some(found) => &line[found.end()..],
+            None => line,
+        };
+        if options.ignore_case {
+            key.to_lowercase()
+        } else {
+            key.to_string()
+        }
+    };
+    let compare = |a: &String, b: &String| -> Ordering {
+        if options.numeric {
+            first_number(&key(a)).cmp(&first_number(&key(b)))
+        } else {
+            key(a).cmp(&key(b))
+        }
+    };
+
+    // Like vim, reversing also reverses the order of lines that compare equal.
+    let mut sorted = lines.clone();
+    sorted.sort_by(|a, b| compare(a, b));
+    if options.reverse {
+        sorted.reverse();
+    }
+    if options.unique {
+        sorted.dedup_by(|a, b| compare(a, b) == Ordering::Equal);
+    }
+
+    editor.transact(cx, |editor, cx| {
+        if sorted != lines {
+            let last_row = rows.end - 1;
+            let range =
+                Point::new(rows.start, 0)..Point::new(last_row, snapshot.line_len(last_row));
+            editor.edit([(range, sorted.join("\n"))], cx);
+        }
+        move_to_first_non_blank(editor, rows.start, cx);
+    });
+    Ok(())
+}
+
+/// The first decimal number in `text`, used by `:sort n`. Lines without a
+/// number sort before all others.
+fn first_number(text: &str) -> Option<i64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let end = text[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(text.len(), |end| start + end);
+    let number = text[start..end].parse::<i64>().ok()?;
+    if text[..start].ends_with('-') {
+        Some(-number)
+    } else {
+        Some(number)
+    }
+}
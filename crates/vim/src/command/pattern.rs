@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Result};
+use regex::{Regex, RegexBuilder};
+
+/// How many characters have a special meaning without a backslash, as set by
+/// `\v`, `\m` and `\V` (`:help /magic`).
+#[derive(Clone, Copy, PartialEq)]
+enum Magic {
+    VeryMagic,
+    Magic,
+    VeryNoMagic,
+}
+
+/// Converts a vim pattern into a pattern for the `regex` crate, along with
+/// whether it should match case-insensitively (set with `\c` and `\C`).
+///
+/// This handles the differences that come up most often: which of `(`, `)`,
+/// `|`, `+`, `?`, `=` and `{` need a backslash, `\<` and `\>` as word
+/// boundaries, `\{-}` as a lazy repeat and vim's character class shorthands.
+/// Anything else is passed through unchanged.
+pub(crate) fn translate_pattern(pattern: &str) -> (String, Option<bool>) {
+    let mut result = String::new();
+    let mut case_insensitive = None;
+    let mut magic = Magic::Magic;
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let Some(c) = chars.next() else {
+                result.push_str("\\\\");
+                break;
+            };
+            match c {
+                'v' => magic = Magic::VeryMagic,
+                'm' | 'M' => magic = Magic::Magic,
+                'V' => magic = Magic::VeryNoMagic,
+                'c' => case_insensitive = Some(true),
+                'C' => case_insensitive = Some(false),
+                '<' | '>' if magic != Magic::VeryMagic => result.push_str("\\b"),
+                '{' if magic != Magic::VeryMagic => push_repeat(&mut chars, &mut result),
+                '(' | ')' | '|' | '+' | '?' if magic != Magic::VeryMagic => result.push(c),
+                '=' if magic != Magic::VeryMagic => result.push('?'),
+                '.' | '*' | '[' | '~' if magic == Magic::VeryNoMagic => result.push(c),
+                '^' | '$' if magic == Magic::VeryNoMagic => result.push(c),
+                'a' => result.push_str("[A-Za-z]"),
+                'A' => result.push_str("[^A-Za-z]"),
+                'l' => result.push_str("[a-z]"),
+                'L' => result.push_str("[^a-z]"),
+                'u' => result.push_str("[A-Z]"),
+                'U' => result.push_str("[^A-Z]"),
+                'x' => result.push_str("[0-9A-Fa-f]"),
+                'X' => result.push_str("[^0-9A-Fa-f]"),
+                'o' => result.push_str("[0-7]"),
+                'O' => result.push_str("[^0-7]"),
+                'h' => result.push_str("[A-Za-z_]"),
+                'H' => result.push_str("[^A-Za-z_]"),
+                's' | 'S' | 'd' | 'D' | 'w' | 'W' | 'n' | 't' => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                'e' => result.push_str("\\x1b"),
+                'r' => result.push_str("\\r"),
+                _ => result.push_str(&regex::escape(&c.to_string())),
+            }
+            continue;
+        }
+
+        match magic {
+            Magic::VeryNoMagic => result.push_str(&regex::escape(&c.to_string())),
+            Magic::VeryMagic => match c {
+                '<' | '>' => result.push_str("\\b"),
+                '=' => result.push('?'),
+                '{' => push_repeat(&mut chars, &mut result),
+                '[' => push_bracket(&mut chars, &mut result),
+                '~' | '}' => result.push_str(&regex::escape(&c.to_string())),
+                _ => result.push(c),
+            },
+            Magic::Magic => match c {
+                '[' => push_bracket(&mut chars, &mut result),
+                '(' | ')' | '|' | '+' | '?' | '{' | '}' => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                _ => result.push(c),
+            },
+        }
+    }
+
+    (result, case_insensitive)
+}
+
+/// Translates the contents of `\{n,m}` (or `{n,m}` with `\v`), where a leading
+/// `-` makes the repeat lazy.
+fn push_repeat(chars: &mut std::iter::Peekable<std::str::Chars>, result: &mut String) {
+    let mut contents = String::new();
+    while let Some(c) = chars.next() {
+        if c == '}' {
+            break;
+        }
+        if c == '\\' && chars.peek() == Some(&'}') {
+            chars.next();
+            break;
+        }
+        contents.push(c);
+    }
+
+    let lazy = contents.starts_with('-');
+    let contents = contents.trim_start_matches('-');
+    match contents {
+        "" => result.push('*'),
+        "1," => result.push('+'),
+        _ if contents.starts_with(',') => {
+            result.push_str(&format!("{{0{}}}", contents));
+        }
+        _ => result.push_str(&format!("{{{}}}", contents)),
+    }
+    if lazy {
+        result.push('?');
+    }
+}
+
+/// Copies a bracket expression through unchanged. A `[` without a matching
+/// `]` is a literal in vim.
+fn push_bracket(chars: &mut std::iter::Peekable<std::str::Chars>, result: &mut String) {
+    let rest = chars.clone().collect::<String>();
+    let mut end = None;
+    let mut offset = 0;
+    if rest.starts_with('^') {
+        offset += 1;
+    }
+    if rest[offset..].starts_with(']') {
+        offset += 1;
+    }
+    let mut escaped = false;
+    let mut in_class = false;
+    let mut previous = None;
+    for (ix, c) in rest[offset..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if in_class {
+            // POSIX classes like `[:alpha:]` end at `:]`
+            in_class = !(previous == Some(':') && c == ']');
+        } else if c == '\\' {
+            escaped = true;
+        } else if previous == Some('[') && c == ':' {
+            in_class = true;
+        } else if c == ']' {
+            end = Some(offset + ix);
+            break;
+        }
+        previous = Some(c);
+    }
+
+    let Some(end) = end else {
+        result.push_str("\\[");
+        return;
+    };
+    let contents = &rest[..end];
+    result.push('[');
+    if let Some(contents) = contents.strip_prefix('^') {
+        result.push('^');
+        push_bracket_contents(contents, result);
+    } else {
+        push_bracket_contents(contents, result);
+    }
+    result.push(']');
+    for _ in 0..contents.chars().count() + 1 {
+        chars.next();
+    }
+}
+
+fn push_bracket_contents(contents: &str, result: &mut String) {
+    let mut chars = contents.chars().peekable();
+    let mut is_first = true;
+    while let Some(c) = chars.next() {
+        let is_posix_class = c == '[' && chars.peek() == Some(&':');
+        if matches!(c, '[' | '&' | '~') && !is_posix_class || c == ']' && is_first {
+            result.push('\\');
+        }
+        result.push(c);
+        is_first = false;
+    }
+}
+
+/// Converts the replacement part of `:s` into the syntax used by
+/// [`Regex::replace`], where `&` and `\0`..`\9` refer to the match and its
+/// groups, and `\r` (or `\n`) inserts a newline.
+pub(crate) fn translate_replacement(replacement: &str) -> String {
+    let mut result = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => result.push_str(&format!("${{{}}}", digit)),
+                Some('r' | 'n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('$') => result.push_str("$$"),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            },
+            '&' => result.push_str("${0}"),
+            '$' => result.push_str("$$"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Builds a regex from a vim pattern. Matching is case-sensitive unless the
+/// pattern contains `\c` or `ignore_case` is set.
+pub(crate) fn build_regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    let (translated, case_insensitive) = translate_pattern(pattern);
+    RegexBuilder::new(&translated)
+        .case_insensitive(case_insensitive.unwrap_or(ignore_case))
+        .multi_line(true)
+        .build()
+        .map_err(|_| anyhow!("E383: Invalid search string: {}", pattern))
+}
+
+/// Builds the regex for a pattern in an ex command. An empty pattern reuses
+/// the last search, which is already in the syntax of the search bar.
+pub(crate) fn pattern_regex(
+    pattern: &str,
+    last_search: Option<&str>,
+    ignore_case: bool,
+) -> Result<Regex> {
+    if !pattern.is_empty() {
+        return build_regex(pattern, ignore_case);
+    }
+    let last_search = last_search.ok_or_else(|| anyhow!("E35: No previous regular expression"))?;
+    RegexBuilder::new(last_search)
+        .case_insensitive(ignore_case)
+        .build()
+        .or_else(|_| Regex::new(&regex::escape(last_search)))
+        .map_err(|_| anyhow!("E383: Invalid search string: {}", last_search))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{translate_pattern, translate_replacement};
+
+    #[test]
+    fn test_translate_pattern() {
+        let translate = |pattern| translate_pattern(pattern).0;
+
+        assert_eq!(translate(r"\(a\|b\)"), "(a|b)");
+        assert_eq!(translate("(a|b)"), r"\(a\|b\)");
+        assert_eq!(translate(r"a\+b\?c\=d*"), "a+b?c?d*");
+        assert_eq!(translate(r"\<word\>"), r"\bword\b");
+        assert_eq!(translate(r"a\{2,3}b\{-}"), "a{2,3}b*?");
+        assert_eq!(translate(r"\v(a|b)+"), "(a|b)+");
+        assert_eq!(translate(r"\V.*"), r"\.\*");
+        assert_eq!(translate(r"[(|)]x"), "[(|)]x");
+        assert_eq!(translate(r"[[:digit:]x]\+"), "[[:digit:]x]+");
+        assert_eq!(translate(r"\a\u"), "[A-Za-z][A-Z]");
+        assert_eq!(translate(r"a\/b"), "a/b");
+        assert_eq!(translate_pattern(r"\cfoo"), ("foo".to_string(), Some(true)));
+    }
+
+    #[test]
+    fn test_translate_replacement() {
+        assert_eq!(translate_replacement(r"\1-&"), "${1}-${0}");
+        assert_eq!(translate_replacement(r"a\rb\&"), "a\nb&");
+        assert_eq!(translate_replacement("$1"), "$$1");
+    }
+}
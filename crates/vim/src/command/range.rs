@@ -0,0 +1,367 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use editor::{Editor, MultiBufferSnapshot, ToPoint};
+use gpui::AppContext;
+use language::Point;
+
+use crate::{command::pattern::pattern_regex, Vim};
+
+/// A line address in an ex command (`:help cmdline-ranges`).
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Address {
+    /// `{number}`, counting from one.
+    Line(u32),
+    /// `.`
+    CurrentLine,
+    /// `$`
+    LastLine,
+    /// `'{mark}`
+    Mark(char),
+    /// `/{pattern}/` or `?{pattern}?`
+    Search { pattern: String, backwards: bool },
+}
+
+/// An address followed by any number of `+{number}` or `-{number}` offsets.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Position {
+    pub address: Address,
+    pub offset: i32,
+}
+
+/// The lines an ex command applies to, such as `.,$` or `'<,'>`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CommandRange {
+    pub start: Position,
+    pub end: Option<Position>,
+    /// Whether the end is relative to the start (`;`) rather than the cursor.
+    pub relative: bool,
+}
+
+impl Position {
+    fn new(address: Address) -> Self {
+        Self { address, offset: 0 }
+    }
+
+    /// Parses a position from the start of `query`, returning the rest.
+    pub fn parse(query: &str) -> Option<(Self, &str)> {
+        let (address, mut rest) = match query.chars().next()? {
+            '0'..='9' => {
+                let end = query
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(query.len());
+                (
+                    Some(Address::Line(query[..end].parse().ok()?)),
+                    &query[end..],
+                )
+            }
+            '.' => (Some(Address::CurrentLine), &query[1..]),
+            '$' => (Some(Address::LastLine), &query[1..]),
+            '\'' => {
+                let mut chars = query[1..].chars();
+                let name = chars.next()?;
+                (Some(Address::Mark(name)), chars.as_str())
+            }
+            delimiter @ ('/' | '?') => {
+                let (pattern, rest) = split_pattern(&query[1..], delimiter);
+                let address = Address::Search {
+                    pattern,
+                    backwards: delimiter == '?',
+                };
+                (Some(address), rest)
+            }
+            _ => (None, query),
+        };
+
+        let mut offset = 0;
+        let mut has_offset = false;
+        while let Some(sign @ ('+' | '-')) = rest.chars().next() {
+            rest = &rest[1..];
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let amount = if end == 0 {
+                1
+            } else {
+                rest[..end].parse::<i32>().ok()?
+            };
+            rest = &rest[end..];
+            offset += if sign == '+' { amount } else { -amount };
+            has_offset = true;
+        }
+
+        // An offset on its own is relative to the current line.
+        let address = match address {
+            Some(address) => address,
+            None if has_offset => Address::CurrentLine,
+            None => return None,
+        };
+        Some((Self { address, offset }, rest))
+    }
+
+    /// Returns the line number this position refers to, counting from one.
+    /// This can be zero, meaning "before the first line" for `:m` and `:t`.
+    pub fn line_number(
+        &self,
+        vim: &Vim,
+        snapshot: &MultiBufferSnapshot,
+        current_row: u32,
+        last_search: Option<&str>,
+        cx: &AppContext,
+    ) -> Result<u32> {
+        let last_row = snapshot.max_point().row;
+        let line = match &self.address {
+            Address::Line(line) => *line,
+            Address::CurrentLine => current_row + 1,
+            Address::LastLine => last_row + 1,
+            Address::Mark(name) => mark_row(vim, *name, snapshot, cx)? + 1,
+            Address::Search { pattern, backwards } => {
+                search_row(pattern, *backwards, snapshot, current_row, last_search)? + 1
+            }
+        };
+
+        let line = line as i64 + self.offset as i64;
+        if line < 0 || line > last_row as i64 + 1 {
+            return Err(anyhow!("E16: Invalid range"));
+        }
+        Ok(line as u32)
+    }
+}
+
+impl CommandRange {
+    pub fn current_line() -> Self {
+        Self {
+            start: Position::new(Address::CurrentLine),
+            end: None,
+            relative: false,
+        }
+    }
+
+    pub fn whole_file() -> Self {
+        Self {
+            start: Position::new(Address::Line(1)),
+            end: Some(Position::new(Address::LastLine)),
+            relative: false,
+        }
+    }
+
+    /// Parses a range from the start of an ex command, returning it along with
+    /// the rest of the command.
+    pub fn parse(query: &str) -> (Option<Self>, &str) {
+        if let Some(rest) = query.strip_prefix('%') {
+            return (Some(Self::whole_file()), rest);
+        }
+
+        let Some((start, rest)) = Position::parse(query) else {
+            return (None, query);
+        };
+        let mut range = Self {
+            start,
+            end: None,
+            relative: false,
+        };
+        let mut rest = rest;
+        if let Some(separator @ (',' | ';')) = rest.chars().next() {
+            match Position::parse(&rest[1..]) {
+                Some((end, after_end)) => {
+                    range.end = Some(end);
+                    rest = after_end;
+                }
+                // A missing end refers to the current line (`:3,`).
+                None => {
+                    range.end = Some(Position::new(Address::CurrentLine));
+                    rest = &rest[1..];
+                }
+            }
+            range.relative = separator == ';';
+        }
+        (Some(range), rest)
+    }
+
+    /// Resolves the range to buffer rows in `editor`. The range is reordered
+    /// if it was given backwards, and line zero is treated as the first line.
+    pub fn rows(
+        &self,
+        vim: &Vim,
+        editor: &Editor,
+        last_search: Option<&str>,
+        cx: &AppContext,
+    ) -> Result<Range<u32>> {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let current_row = editor
+            .selections
+            .newest_anchor()
+            .head()
+            .to_point(&snapshot)
+            .row;
+        let start = self
+            .start
+            .line_number(vim, &snapshot, current_row, last_search, cx)?
+            .max(1);
+        let end = match &self.end {
+            Some(end) => {
+                let current_row = if self.relative {
+                    start - 1
+                } else {
+                    current_row
+                };
+                end.line_number(vim, &snapshot, current_row, last_search, cx)?
+                    .max(1)
+            }
+            None => start,
+        };
+        Ok(start.min(end) - 1..start.max(end))
+    }
+}
+
+/// Splits `query` at the first `delimiter` not preceded by a backslash,
+/// returning the text before it (with `\{delimiter}` unescaped) and after it.
+pub(crate) fn split_pattern(query: &str, delimiter: char) -> (String, &str) {
+    let mut pattern = String::new();
+    let mut chars = query.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        if c == delimiter {
+            return (pattern, &query[ix + c.len_utf8()..]);
+        }
+        if c == '\\' {
+            match chars.next() {
+                Some((_, escaped)) if escaped == delimiter => pattern.push(escaped),
+                Some((_, escaped)) => {
+                    pattern.push('\\');
+                    pattern.push(escaped);
+                }
+                None => pattern.push('\\'),
+            }
+        } else {
+            pattern.push(c);
+        }
+    }
+    (pattern, "")
+}
+
+fn mark_row(vim: &Vim, name: char, snapshot: &MultiBufferSnapshot, cx: &AppContext) -> Result<u32> {
+    let name = if name == '`' { '\'' } else { name };
+    if name.is_ascii_uppercase() {
+        if let Some(location) = vim.workspace_state.global_marks.get(&name) {
+            let is_in_buffer = location.buffer().is_some_and(|buffer| {
+                snapshot
+                    .as_singleton()
+                    .is_some_and(|(_, buffer_id, _)| buffer_id == buffer.read(cx).remote_id())
+            });
+            if is_in_buffer {
+                return Ok(location.point(cx).row.min(snapshot.max_point().row));
+            }
+        }
+    } else if let Some(anchor) = vim
+        .state()
+        .marks
+        .get(&name)
+        .and_then(|anchors| anchors.last())
+    {
+        return Ok(anchor.to_point(snapshot).row);
+    }
+    Err(anyhow!("E20: Mark not set"))
+}
+
+/// Finds the next line matching `pattern` after (or before) `current_row`,
+/// wrapping around the end of the buffer. An empty pattern reuses the last
+/// search.
+fn search_row(
+    pattern: &str,
+    backwards: bool,
+    snapshot: &MultiBufferSnapshot,
+    current_row: u32,
+    last_search: Option<&str>,
+) -> Result<u32> {
+    let regex = pattern_regex(pattern, last_search, false)?;
+
+    let row_count = snapshot.max_point().row + 1;
+    (1..=row_count)
+        .map(|distance| {
+            if backwards {
+                (current_row + row_count - distance) % row_count
+            } else {
+                (current_row + distance) % row_count
+            }
+        })
+        .find(|row| regex.is_match(&line_text(snapshot, *row)))
+        .ok_or_else(|| anyhow!("E486: Pattern not found: {}", pattern))
+}
+
+pub(crate) fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Address, CommandRange, Position};
+
+    #[test]
+    fn test_parse_range() {
+        let position = |address, offset| Position { address, offset };
+
+        assert_eq!(
+            CommandRange::parse(".,$d"),
+            (
+                Some(CommandRange {
+                    start: position(Address::CurrentLine, 0),
+                    end: Some(position(Address::LastLine, 0)),
+                    relative: false,
+                }),
+                "d"
+            )
+        );
+        assert_eq!(
+            CommandRange::parse("'<,'>s/a/b/"),
+            (
+                Some(CommandRange {
+                    start: position(Address::Mark('<'), 0),
+                    end: Some(position(Address::Mark('>'), 0)),
+                    relative: false,
+                }),
+                "s/a/b/"
+            )
+        );
+        assert_eq!(
+            CommandRange::parse("/a\\/b/+1;?c?-2--y"),
+            (
+                Some(CommandRange {
+                    start: position(
+                        Address::Search {
+                            pattern: "a/b".into(),
+                            backwards: false
+                        },
+                        1
+                    ),
+                    end: Some(position(
+                        Address::Search {
+                            pattern: "c".into(),
+                            backwards: true
+                        },
+                        -4
+                    )),
+                    relative: true,
+                }),
+                "y"
+            )
+        );
+        assert_eq!(
+            CommandRange::parse("+3m0"),
+            (
+                Some(CommandRange {
+                    start: position(Address::CurrentLine, 3),
+                    end: None,
+                    relative: false,
+                }),
+                "m0"
+            )
+        );
+        assert_eq!(
+            CommandRange::parse("%"),
+            (Some(CommandRange::whole_file()), "")
+        );
+        assert_eq!(CommandRange::parse("sort"), (None, "sort"));
+    }
+}
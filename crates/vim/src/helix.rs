@@ -0,0 +1,401 @@
+//! Helix (and Kakoune) style editing, where a selection is made first and
+//! commands then act on it. Enabled with the `vim.helix_mode` setting, which
+//! replaces vim's normal mode with [`Mode::HelixNormal`].
+
+use std::sync::Arc;
+
+use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll};
+use gpui::{actions, ViewContext, WindowContext};
+use language::{Point, SelectionGoal};
+use regex::Regex;
+use workspace::Workspace;
+
+use crate::{
+    motion::Motion,
+    normal::{mark, normal_object},
+    object::Object,
+    state::{Mode, Operator},
+    Vim,
+};
+
+actions!(
+    vim,
+    [
+        HelixSelectLine,
+        HelixCollapseSelection,
+        HelixKeepPrimarySelection,
+        HelixInsert,
+        HelixAppend
+    ]
+);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(select_line);
+    workspace.register_action(collapse_selection);
+    workspace.register_action(keep_primary_selection);
+    workspace.register_action(|_: &mut Workspace, _: &HelixInsert, cx| insert(false, cx));
+    workspace.register_action(|_: &mut Workspace, _: &HelixAppend, cx| insert(true, cx));
+}
+
+/// Word motions select the text they move over, starting from the cursor.
+/// Other motions just move the cursor.
+pub(crate) fn helix_motion(motion: Motion, times: Option<usize>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        if motion.is_jump() {
+            mark::record_jump(vim, cx);
+        }
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let head = selection.head();
+                    let Some((new_head, goal)) =
+                        motion.move_point(map, head, selection.goal, times, &text_layout_details)
+                    else {
+                        return;
+                    };
+
+                    match motion {
+                        Motion::NextWordStart { .. }
+                        | Motion::PreviousWordStart { .. }
+                        | Motion::PreviousWordEnd { .. } => {
+                            selection.collapse_to(head, goal);
+                            selection.set_head(new_head, goal);
+                        }
+                        // vim's end of word is the last character, which should be selected.
+                        Motion::NextWordEnd { .. } => {
+                            selection.collapse_to(head, goal);
+                            selection.set_head(movement::right(map, new_head), goal);
+                        }
+                        _ => selection.collapse_to(new_head, goal),
+                    }
+                })
+            });
+        });
+    });
+}
+
+/// Selects a text object around each selection after `mi` or `ma`.
+pub(crate) fn helix_object(object: Object, cx: &mut WindowContext) {
+    let Some(Operator::Object { around }) = Vim::read(cx).active_operator() else {
+        // `md` and `mr` use the object to find the surrounding pair.
+        return normal_object(object, cx);
+    };
+
+    Vim::update(cx, |vim, cx| {
+        vim.clear_operator(cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let mut head = selection.head();
+                    if !selection.reversed && !selection.is_empty() {
+                        head = movement::left(map, head);
+                    }
+                    if let Some(range) = object.range(map, head, around) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                            selection.goal = SelectionGoal::None;
+                        }
+                    }
+                })
+            });
+        });
+    });
+}
+
+/// Reads the pattern for `s` one character at a time, and when enter is
+/// pressed replaces each selection with the matches inside of it.
+pub(crate) fn select_regex(mut pattern: String, text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        if text.as_ref() != "\n" {
+            pattern.push_str(&text);
+            vim.update_state(|state| {
+                state.operator_stack.pop();
+                state.operator_stack.push(Operator::SelectRegex { pattern });
+            });
+            return;
+        }
+
+        vim.clear_operator(cx);
+        if pattern.is_empty() {
+            return;
+        }
+        let Ok(regex) = Regex::new(&pattern) else {
+            return;
+        };
+
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let mut ranges = Vec::new();
+            for selection in editor.selections.all::<usize>(cx) {
+                let text = snapshot
+                    .text_for_range(selection.start..selection.end)
+                    .collect::<String>();
+                for found in regex.find_iter(&text) {
+                    if !found.range().is_empty() {
+                        ranges.push(selection.start + found.start()..selection.start + found.end());
+                    }
+                }
+            }
+
+            // Like helix, keep the selections if nothing matched.
+            if !ranges.is_empty() {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(ranges));
+            }
+        });
+    });
+}
+
+/// Selects the lines that each selection touches, or the next line when
+/// whole lines are already selected.
+fn select_line(_: &mut Workspace, _: &HelixSelectLine, cx: &mut ViewContext<Workspace>) {
+    Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1) as u32;
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let last_row = if start.column == 0 && end.column == 0 && end.row > start.row {
+                        end.row - 1 + count
+                    } else {
+                        end.row + count - 1
+                    };
+                    let end = if last_row >= max_point.row {
+                        max_point
+                    } else {
+                        Point::new(last_row + 1, 0)
+                    };
+
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                })
+            });
+        });
+    });
+}
+
+fn collapse_selection(
+    _: &mut Workspace,
+    _: &HelixCollapseSelection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.move_with(|_, selection| {
+                    let head = selection.head();
+                    selection.collapse_to(head, selection.goal)
+                })
+            });
+        });
+    });
+}
+
+fn keep_primary_selection(
+    _: &mut Workspace,
+    _: &HelixKeepPrimarySelection,
+    cx: &mut ViewContext<Workspace>,
+) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    });
+}
+
+/// `i` inserts before each selection, and `a` after it.
+fn insert(after: bool, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.start_recording(cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| {
+                    let point = if after {
+                        selection.end
+                    } else {
+                        selection.start
+                    };
+                    selection.collapse_to(point, SelectionGoal::None)
+                })
+            });
+        });
+        vim.switch_mode(Mode::Insert, false, cx);
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use settings::SettingsStore;
+
+    use crate::{state::Mode, test::VimTestContext, VimSettings};
+
+    async fn helix_context(cx: &mut gpui::TestAppContext) -> VimTestContext {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                s.helix_mode = Some(true);
+            });
+        });
+        cx.set_state("ˇ", Mode::Normal);
+        cx.assert_state("ˇ", Mode::HelixNormal);
+        cx
+    }
+
+    #[gpui::test]
+    async fn test_helix_motions(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["w"]);
+        cx.assert_state("«one ˇ»two three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["w"]);
+        cx.assert_state("one «two ˇ»three", Mode::HelixNormal);
+        cx.simulate_keystrokes(["b"]);
+        cx.assert_state("one «ˇtwo »three", Mode::HelixNormal);
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["e"]);
+        cx.assert_state("«oneˇ» two three", Mode::HelixNormal);
+
+        // Other motions move the cursor
+        cx.simulate_keystrokes(["l"]);
+        cx.assert_state("one ˇtwo three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state(
+            indoc! {"
+                one
+                twˇo
+                three
+                four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["x"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                «two
+                ˇ»three
+                four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes(["x"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                «two
+                three
+                ˇ»four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes(["x"]);
+        cx.assert_state(
+            indoc! {"
+                one
+                «two
+                three
+                fourˇ»"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+                ˇone
+                two
+                three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["x", "d"]);
+        cx.assert_state(
+            indoc! {"
+                ˇtwo
+                three"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_helix_select_regex(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("«one two one threeˇ»", Mode::Normal);
+        cx.simulate_keystrokes(["s", "o", "n", "e", "enter"]);
+        cx.assert_state("«oneˇ» two «oneˇ» three", Mode::HelixNormal);
+
+        // `,` keeps only the primary selection, and `;` collapses it
+        cx.simulate_keystrokes([","]);
+        cx.assert_state("one two «oneˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes([";"]);
+        cx.assert_state("one two oneˇ three", Mode::HelixNormal);
+
+        // Regexes only match within the selections
+        cx.set_state("a1 «b2 c3ˇ» d4", Mode::Normal);
+        cx.simulate_keystrokes(["s", "\\", "d", "enter"]);
+        cx.assert_state("a1 b«2ˇ» c«3ˇ» d4", Mode::HelixNormal);
+
+        // Nothing matching leaves the selections alone
+        cx.set_state("«oneˇ» two", Mode::Normal);
+        cx.simulate_keystrokes(["s", "x", "enter"]);
+        cx.assert_state("«oneˇ» two", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_match_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("ˇ(one two)", Mode::Normal);
+        cx.simulate_keystrokes(["m", "m"]);
+        cx.assert_state("(one twoˇ)", Mode::HelixNormal);
+
+        cx.set_state("one tˇwo three", Mode::Normal);
+        cx.simulate_keystrokes(["m", "i", "w"]);
+        cx.assert_state("one «twoˇ» three", Mode::HelixNormal);
+
+        cx.set_state("x (one tˇwo)", Mode::Normal);
+        cx.simulate_keystrokes(["m", "a", "("]);
+        cx.assert_state("x «(one two)ˇ»", Mode::HelixNormal);
+
+        cx.set_state("one «twoˇ» three", Mode::Normal);
+        cx.simulate_keystrokes(["m", "s", "\""]);
+        cx.assert_state("one ˇ\"two\" three", Mode::HelixNormal);
+
+        cx.set_state("f(a, ˇb)", Mode::Normal);
+        cx.simulate_keystrokes(["m", "r", "(", "]"]);
+        cx.assert_state("fˇ[a, b]", Mode::HelixNormal);
+
+        cx.set_state("f(a, ˇb)", Mode::Normal);
+        cx.simulate_keystrokes(["m", "d", "("]);
+        cx.assert_state("fˇa, b", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_helix_insert(cx: &mut gpui::TestAppContext) {
+        let mut cx = helix_context(cx).await;
+
+        cx.set_state("one «twoˇ» three", Mode::Normal);
+        cx.simulate_keystrokes(["i"]);
+        cx.assert_state("one ˇtwo three", Mode::Insert);
+
+        cx.set_state("one «twoˇ» three", Mode::Normal);
+        cx.simulate_keystrokes(["a", "s", "escape"]);
+        cx.assert_state("one twoˇs three", Mode::HelixNormal);
+
+        cx.set_state("one «twoˇ» three", Mode::Normal);
+        cx.simulate_keystrokes(["c", "x", "escape"]);
+        cx.assert_state("one ˇx three", Mode::HelixNormal);
+    }
+}
@@ -1,21 +1,63 @@
-use crate::{normal::repeat, state::Mode, Vim};
-use editor::{scroll::Autoscroll, Bias};
-use gpui::{actions, Action, ViewContext};
-use language::SelectionGoal;
-use workspace::Workspace;
+use std::{ops::Range, path::PathBuf};
 
-actions!(vim, [NormalBefore]);
+use crate::{
+    motion::{first_non_whitespace, previous_word_start},
+    normal::{mark, repeat},
+    state::Mode,
+    Vim,
+};
+use editor::{movement, scroll::Autoscroll, Anchor, Bias, MultiBufferSnapshot, ToOffset};
+use futures::StreamExt;
+use gpui::{actions, Action, KeystrokeEvent, ViewContext, WindowContext};
+use language::{char_kind, CharKind, SelectionGoal};
+use util::paths::HOME;
+use workspace::{searchable::Direction, Workspace};
+
+actions!(
+    vim,
+    [
+        NormalBefore,
+        TemporaryNormal,
+        DeletePreviousWord,
+        DeleteToLineStart,
+        CompleteNext,
+        CompletePrevious,
+        CompletePath
+    ]
+);
+
+/// The candidates for `ctrl-n` and `ctrl-p` in insert mode: words from the
+/// buffer, or after `ctrl-x ctrl-f`, paths.
+#[derive(Clone)]
+pub(crate) struct InsertCompletion {
+    /// The text that was typed before completing, which is put back after
+    /// cycling past the last candidate.
+    original: String,
+    candidates: Vec<String>,
+    index: Option<usize>,
+    /// The range of the buffer that holds the current candidate.
+    range: Range<Anchor>,
+}
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(normal_before);
+    workspace.register_action(temporary_normal);
+    workspace.register_action(delete_previous_word);
+    workspace.register_action(delete_to_line_start);
+    workspace
+        .register_action(|_: &mut Workspace, _: &CompleteNext, cx| complete(Direction::Next, cx));
+    workspace.register_action(|_: &mut Workspace, _: &CompletePrevious, cx| {
+        complete(Direction::Prev, cx)
+    });
+    workspace.register_action(complete_path);
 }
-
 fn normal_before(_: &mut Workspace, action: &NormalBefore, cx: &mut ViewContext<Workspace>) {
     let should_repeat = Vim::update(cx, |vim, cx| {
         let count = vim.take_count(cx).unwrap_or(1);
         vim.stop_recording_immediately(action.boxed_clone());
         if count <= 1 || vim.workspace_state.replaying {
-            vim.update_active_editor(cx, |_, editor, cx| {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                mark::set_insert_mark(vim, editor, cx);
                 editor.dismiss_menus_and_popups(cx);
                 editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.move_cursors_with(|map, mut cursor, _| {
@@ -36,8 +78,321 @@ fn normal_before(_: &mut Workspace, action: &NormalBefore, cx: &mut ViewContext<
     }
 }
 
+/// `ctrl-o`, which runs one normal mode command and then returns to insert mode.
+fn temporary_normal(_: &mut Workspace, _: &TemporaryNormal, cx: &mut ViewContext<Workspace>) {
+    Vim::update(cx, |vim, cx| {
+        vim.switch_mode(Mode::Normal, true, cx);
+        vim.update_state(|state| state.temporary_normal = true);
+    })
+}
+
+/// Called after every keystroke to return to insert mode once the command
+/// after `ctrl-o` is complete.
+pub(crate) fn resume_insert(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext) {
+    if keystroke_event
+        .action
+        .as_ref()
+        .is_some_and(|action| action.as_any().is::<TemporaryNormal>())
+    {
+        return;
+    }
+    Vim::update(cx, |vim, cx| {
+        let state = vim.state();
+        if state.temporary_normal
+            && state.mode == Mode::Normal
+            && state.operator_stack.is_empty()
+            && state.pre_count.is_none()
+        {
+            vim.switch_mode(Mode::Insert, true, cx);
+        }
+    })
+}
+
+/// `ctrl-w`, which deletes the word before the cursor, or the line break at
+/// the start of a line.
+fn delete_previous_word(
+    _: &mut Workspace,
+    _: &DeletePreviousWord,
+    cx: &mut ViewContext<Workspace>,
+) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_with(|map, selection| {
+                        if !selection.is_empty() {
+                            return;
+                        }
+                        let head = selection.head();
+                        let start = if head.column() == 0 {
+                            movement::left(map, head)
+                        } else {
+                            previous_word_start(map, head, false, 1)
+                                .max(map.prev_line_boundary(head.to_point(map)).1)
+                        };
+                        selection.set_head(start, SelectionGoal::None);
+                    })
+                });
+                editor.insert("", cx);
+            })
+        });
+    })
+}
+
+/// `ctrl-u`, which deletes the text before the cursor on the line, keeping
+/// the indentation unless there is only indentation before the cursor.
+fn delete_to_line_start(_: &mut Workspace, _: &DeleteToLineStart, cx: &mut ViewContext<Workspace>) {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.move_with(|map, selection| {
+                        if !selection.is_empty() {
+                            return;
+                        }
+                        let head = selection.head();
+                        let line_start = map.prev_line_boundary(head.to_point(map)).1;
+                        let indent_end = first_non_whitespace(map, false, head);
+                        let start = if head == line_start {
+                            movement::left(map, head)
+                        } else if indent_end < head {
+                            indent_end
+                        } else {
+                            line_start
+                        };
+                        selection.set_head(start, SelectionGoal::None);
+                    })
+                });
+                editor.insert("", cx);
+            })
+        });
+    })
+}
+
+/// `ctrl-n` and `ctrl-p`, which complete the word before the cursor with
+/// other words in the buffer (nearest after the cursor first for `ctrl-n`,
+/// nearest before for `ctrl-p`). Repeating them cycles through the words, and
+/// through the paths found by `ctrl-x ctrl-f`.
+fn complete(direction: Direction, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let completion = vim.state().insert_completion.clone();
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let cursor = editor.selections.newest::<usize>(cx).head();
+            let mut completion = completion
+                .filter(|completion| completion.is_at(&snapshot, cursor))
+                .unwrap_or_else(|| keyword_completion(&snapshot, cursor));
+            let count = completion.candidates.len();
+            if count == 0 {
+                return;
+            }
+            completion.index = match (direction, completion.index) {
+                (Direction::Next, None) => Some(0),
+                (Direction::Next, Some(ix)) => Some(ix + 1).filter(|ix| *ix < count),
+                (Direction::Prev, None) => count.checked_sub(1),
+                (Direction::Prev, Some(ix)) => ix.checked_sub(1),
+            };
+
+            let text = completion.text().to_string();
+            let range = completion.range.clone();
+            editor.transact(cx, |editor, cx| {
+                editor.edit([(range.clone(), text)], cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges([range.end..range.end])
+                });
+            });
+            vim.update_state(|state| state.insert_completion = Some(completion));
+        });
+    })
+}
+
+/// `ctrl-x ctrl-f`, which completes the path before the cursor. Relative paths
+/// are relative to the root of the project.
+fn complete_path(workspace: &mut Workspace, _: &CompletePath, cx: &mut ViewContext<Workspace>) {
+    let fs = workspace.project().read(cx).fs().clone();
+    let root = workspace
+        .project()
+        .read(cx)
+        .visible_worktrees(cx)
+        .next()
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+    let Some(editor) = Vim::read(cx).active_editor.clone() else {
+        return;
+    };
+    let Ok((typed, range)) = editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let cursor = editor.selections.newest::<usize>(cx).head();
+        let start = cursor
+            - snapshot
+                .reversed_chars_at(cursor)
+                .take_while(|c| c.is_alphanumeric() || "/.-_+,#$%~=".contains(*c))
+                .map(|c| c.len_utf8())
+                .sum::<usize>();
+        let typed = snapshot.text_for_range(start..cursor).collect::<String>();
+        (
+            typed,
+            snapshot.anchor_before(start)..snapshot.anchor_after(cursor),
+        )
+    }) else {
+        return;
+    };
+
+    let (dir_text, file_prefix) = match typed.rfind('/') {
+        Some(ix) => (typed[..ix + 1].to_string(), typed[ix + 1..].to_string()),
+        None => (String::new(), typed.clone()),
+    };
+    let dir = if let Some(rest) = dir_text.strip_prefix("~/") {
+        HOME.join(rest)
+    } else if dir_text.starts_with('/') {
+        PathBuf::from(&dir_text)
+    } else if let Some(root) = root {
+        root.join(&dir_text)
+    } else {
+        return;
+    };
+
+    cx.spawn(|_, mut cx| async move {
+        let mut candidates = Vec::new();
+        let mut entries = fs.read_dir(&dir).await?;
+        while let Some(path) = entries.next().await {
+            let path = path?;
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !name.starts_with(file_prefix.as_str())
+                || name.starts_with('.') && !file_prefix.starts_with('.')
+            {
+                continue;
+            }
+            let is_dir = fs
+                .metadata(&path)
+                .await?
+                .is_some_and(|metadata| metadata.is_dir);
+            candidates.push(format!(
+                "{}{}{}",
+                dir_text,
+                name,
+                if is_dir { "/" } else { "" }
+            ));
+        }
+        candidates.sort();
+
+        let completion = InsertCompletion {
+            original: typed,
+            candidates,
+            index: None,
+            range,
+        };
+        cx.update(|cx| {
+            let still_typing = Vim::update(cx, |vim, cx| {
+                let still_typing = vim.state().mode == Mode::Insert
+                    && vim
+                        .update_active_editor(cx, |_, editor, cx| {
+                            let snapshot = editor.buffer().read(cx).snapshot(cx);
+                            let cursor = editor.selections.newest::<usize>(cx).head();
+                            completion.is_at(&snapshot, cursor)
+                        })
+                        .unwrap_or(false);
+                if still_typing {
+                    vim.update_state(|state| state.insert_completion = Some(completion));
+                }
+                still_typing
+            });
+            // the cursor hasn't moved, so this inserts the first path
+            if still_typing {
+                complete(Direction::Next, cx)
+            }
+        })?;
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+impl InsertCompletion {
+    fn text(&self) -> &str {
+        match self.index {
+            Some(ix) => &self.candidates[ix],
+            None => &self.original,
+        }
+    }
+
+    /// Whether the cursor is still just after the completed text, so that
+    /// completing again should cycle through the candidates.
+    fn is_at(&self, snapshot: &MultiBufferSnapshot, cursor: usize) -> bool {
+        let range = self.range.start.to_offset(snapshot)..self.range.end.to_offset(snapshot);
+        range.end == cursor && snapshot.text_for_range(range).collect::<String>() == self.text()
+    }
+}
+
+/// How far before and after the cursor keyword completion looks for words, so
+/// that large buffers aren't scanned entirely.
+const MAX_KEYWORD_COMPLETION_DISTANCE: usize = 64 * 1024;
+
+/// Finds the words near the cursor that start with the word before it, in the
+/// order vim offers them: from the cursor to the end of the buffer, and then
+/// from the start.
+fn keyword_completion(snapshot: &MultiBufferSnapshot, cursor: usize) -> InsertCompletion {
+    let scope = snapshot.language_scope_at(cursor);
+    let is_word = |c: char| char_kind(&scope, c) == CharKind::Word;
+    let start = cursor
+        - snapshot
+            .reversed_chars_at(cursor)
+            .take_while(|c| is_word(*c))
+            .map(|c| c.len_utf8())
+            .sum::<usize>();
+    let original = snapshot.text_for_range(start..cursor).collect::<String>();
+
+    let window_start = snapshot.clip_offset(
+        start.saturating_sub(MAX_KEYWORD_COMPLETION_DISTANCE),
+        Bias::Left,
+    );
+    let window_end = snapshot.clip_offset(
+        (cursor + MAX_KEYWORD_COMPLETION_DISTANCE).min(snapshot.len()),
+        Bias::Right,
+    );
+    let text = snapshot
+        .text_for_range(window_start..window_end)
+        .collect::<String>();
+    let mut words = Vec::new();
+    let mut word_start = None;
+    for (ix, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (word_start, is_word(c)) {
+            (None, true) => word_start = Some(ix),
+            (Some(word_start_ix), false) => {
+                // Skip the words that the window cuts off.
+                let is_cut_off = (word_start_ix == 0 && window_start > 0)
+                    || (ix == text.len() && window_end < snapshot.len());
+                if !is_cut_off {
+                    words.push(word_start_ix..ix);
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    let split = words.partition_point(|word| word.start <= start - window_start);
+
+    let mut candidates = Vec::new();
+    for word in words[split..].iter().chain(&words[..split]) {
+        let word = &text[word.clone()];
+        if word.starts_with(original.as_str())
+            && word != original
+            && !candidates.iter().any(|candidate| candidate == word)
+        {
+            candidates.push(word.to_string());
+        }
+    }
+    InsertCompletion {
+        original,
+        candidates,
+        index: None,
+        range: snapshot.anchor_before(start)..snapshot.anchor_after(cursor),
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use super::{keyword_completion, MAX_KEYWORD_COMPLETION_DISTANCE};
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
@@ -122,4 +477,116 @@ mod test {
         cx.run_until_parked();
         cx.assert_shared_state("hello\nkk\nkk\nkk\nkk\nkˇk\n").await;
     }
+
+    #[gpui::test]
+    async fn test_insert_ctrl_r(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two\n").await;
+        cx.simulate_shared_keystrokes(["y", "e", "shift-a", " ", "ctrl-r", "\""])
+            .await;
+        cx.assert_shared_state("one two oneˇ\n").await;
+
+        cx.set_shared_state("ˇone two\n").await;
+        cx.simulate_shared_keystrokes(["\"", "a", "y", "i", "w", "w", "i", "ctrl-r", "a"])
+            .await;
+        cx.assert_shared_state("one oneˇtwo\n").await;
+    }
+
+    #[gpui::test]
+    async fn test_insert_ctrl_w_ctrl_u(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇfoo bar.baz\n").await;
+        cx.simulate_shared_keystrokes(["shift-a", "ctrl-w"]).await;
+        cx.assert_shared_state("foo bar.ˇ\n").await;
+
+        // at the start of a line, the line break is deleted
+        cx.set_shared_state("foo\nˇbar\n").await;
+        cx.simulate_shared_keystrokes(["i", "ctrl-w"]).await;
+        cx.assert_shared_state("fooˇbar\n").await;
+
+        // ctrl-u keeps the indentation, unless there is nothing else to delete
+        cx.set_shared_state("ˇ    foo bar\n").await;
+        cx.simulate_shared_keystrokes(["shift-a", "ctrl-u"]).await;
+        cx.assert_shared_state("    ˇ\n").await;
+        cx.simulate_shared_keystrokes(["ctrl-u"]).await;
+        cx.assert_shared_state("ˇ\n").await;
+    }
+
+    #[gpui::test]
+    async fn test_insert_ctrl_t_ctrl_d(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_neovim_option("shiftwidth=4").await;
+        cx.set_shared_state("fˇoo\n").await;
+        cx.simulate_shared_keystrokes(["i", "ctrl-t"]).await;
+        cx.assert_shared_state("    fˇoo\n").await;
+        cx.simulate_shared_keystrokes(["ctrl-d"]).await;
+        cx.assert_shared_state("fˇoo\n").await;
+    }
+
+    #[gpui::test]
+    async fn test_insert_ctrl_o(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("one two ˇthree\n").await;
+        cx.simulate_shared_keystrokes(["i", "ctrl-o", "b", "x"])
+            .await;
+        cx.assert_shared_state("one xˇtwo three\n").await;
+
+        cx.set_shared_state("one ˇtwo three\n").await;
+        cx.simulate_shared_keystrokes(["i", "ctrl-o", "d", "w", "x"])
+            .await;
+        cx.assert_shared_state("one xˇthree\n").await;
+    }
+
+    #[gpui::test]
+    async fn test_insert_ctrl_n_ctrl_p(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("fish fork fig\nˇf\n").await;
+        cx.simulate_shared_keystrokes(["shift-a", "ctrl-n"]).await;
+        cx.assert_shared_state("fish fork fig\nfishˇ\n").await;
+        cx.simulate_shared_keystrokes(["ctrl-n"]).await;
+        cx.assert_shared_state("fish fork fig\nforkˇ\n").await;
+        cx.simulate_shared_keystrokes(["ctrl-p"]).await;
+        cx.assert_shared_state("fish fork fig\nfishˇ\n").await;
+        cx.simulate_shared_keystrokes(["ctrl-p"]).await;
+        cx.assert_shared_state("fish fork fig\nfˇ\n").await;
+
+        cx.set_shared_state("fish fork fig\nˇf\n").await;
+        cx.simulate_shared_keystrokes(["shift-a", "ctrl-p"]).await;
+        cx.assert_shared_state("fish fork fig\nfigˇ\n").await;
+    }
+
+    #[gpui::test]
+    async fn test_insert_ctrl_x_ctrl_f(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("cat /root/dˇ", Mode::Insert);
+        cx.simulate_keystrokes(["ctrl-x", "ctrl-f"]);
+        cx.run_until_parked();
+        cx.assert_state("cat /root/dir/ˇ", Mode::Insert);
+
+        cx.simulate_keystrokes(["ctrl-x", "ctrl-f"]);
+        cx.run_until_parked();
+        cx.assert_state("cat /root/dir/file.rsˇ", Mode::Insert);
+    }
+
+    #[gpui::test]
+    async fn test_keyword_completion_distance(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        let padding = "\n".repeat(MAX_KEYWORD_COMPLETION_DISTANCE);
+        cx.set_state(
+            &format!("far{padding}fare\nfaˇ\nfast{padding}farther"),
+            Mode::Insert,
+        );
+        let snapshot = cx.update_editor(|editor, cx| editor.buffer().read(cx).snapshot(cx));
+        let cursor = "far".len() + padding.len() + "fare\nfa".len();
+        let completion = keyword_completion(&snapshot, cursor);
+        assert_eq!(completion.original, "fa");
+        assert_eq!(completion.candidates, ["fast", "fare"]);
+    }
 }
@@ -3,7 +3,7 @@ use editor::{
     movement::{
         self, find_boundary, find_preceding_boundary_display_point, FindRange, TextLayoutDetails,
     },
-    Bias, DisplayPoint, ToOffset,
+    Anchor, Bias, DisplayPoint, ToOffset,
 };
 use gpui::{actions, impl_actions, px, ViewContext, WindowContext};
 use language::{char_kind, CharKind, Point, Selection, SelectionGoal};
@@ -11,8 +11,10 @@ use serde::Deserialize;
 use workspace::Workspace;
 
 use crate::{
+    helix::helix_motion,
     normal::normal_motion,
     state::{Mode, Operator},
+    surrounds::SurroundsType,
     utils::coerce_punctuation,
     visual::visual_motion,
     Vim,
@@ -80,6 +82,10 @@ pub enum Motion {
     WindowTop,
     WindowMiddle,
     WindowBottom,
+    Jump {
+        anchor: Anchor,
+        line: bool,
+    },
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -320,9 +326,26 @@ pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
 
     let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
     let operator = Vim::read(cx).active_operator();
+    if let Some(Operator::AddSurrounds { target: None }) = operator {
+        // `ys{motion}` waits for the characters to surround the text with.
+        Vim::update(cx, |vim, cx| {
+            vim.clear_operator(cx);
+            vim.push_operator(
+                Operator::AddSurrounds {
+                    target: Some(SurroundsType::Motion {
+                        motion,
+                        times: count,
+                    }),
+                },
+                cx,
+            )
+        });
+        return;
+    }
     match Vim::read(cx).state().mode {
         Mode::Normal => normal_motion(motion, operator, count, cx),
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_motion(motion, count, cx),
+        Mode::HelixNormal => helix_motion(motion, count, cx),
         Mode::Insert => {
             // Shouldn't execute a motion in insert mode. Ignoring
         }
@@ -348,6 +371,7 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | EndOfParagraph => true,
+            Jump { line, .. } => *line,
             EndOfLine { .. }
             | NextWordEnd { .. }
             | Matching
@@ -372,7 +396,7 @@ impl Motion {
     pub fn infallible(&self) -> bool {
         use Motion::*;
         match self {
-            StartOfDocument | EndOfDocument | CurrentLine => true,
+            StartOfDocument | EndOfDocument | CurrentLine | Jump { .. } => true,
             Down { .. }
             | Up { .. }
             | EndOfLine { .. }
@@ -433,13 +457,32 @@ impl Motion {
             | NextWordStart { .. }
             | PreviousWordStart { .. }
             | FirstNonWhitespace { .. }
-            | FindBackward { .. } => false,
+            | FindBackward { .. }
+            | Jump { .. } => false,
             RepeatFind { last_find: motion } | RepeatFindReversed { last_find: motion } => {
                 motion.inclusive()
             }
         }
     }
 
+    /// Whether the motion is a "jump" in the sense of `:help jump-motions`,
+    /// i.e. whether the position before the motion is added to the jumplist.
+    pub fn is_jump(&self) -> bool {
+        use Motion::*;
+        matches!(
+            self,
+            StartOfDocument
+                | EndOfDocument
+                | Matching
+                | StartOfParagraph
+                | EndOfParagraph
+                | WindowTop
+                | WindowMiddle
+                | WindowBottom
+                | Jump { .. }
+        )
+    }
+
     pub fn move_point(
         &self,
         map: &DisplaySnapshot,
@@ -569,6 +612,14 @@ impl Motion {
             WindowTop => window_top(map, point, &text_layout_details, times - 1),
             WindowMiddle => window_middle(map, point, &text_layout_details),
             WindowBottom => window_bottom(map, point, &text_layout_details, times - 1),
+            Jump { anchor, line } => {
+                let point = map.clip_point(anchor.to_display_point(map), Bias::Left);
+                if *line {
+                    (first_non_whitespace(map, false, point), SelectionGoal::None)
+                } else {
+                    (point, SelectionGoal::None)
+                }
+            }
         };
 
         (new_point != point || infallible).then_some((new_point, goal))
@@ -883,7 +934,7 @@ fn next_word_end(
     point
 }
 
-fn previous_word_start(
+pub(crate) fn previous_word_start(
     map: &DisplaySnapshot,
     mut point: DisplayPoint,
     ignore_punctuation: bool,
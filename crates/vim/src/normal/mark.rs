@@ -0,0 +1,841 @@
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
+use collections::HashMap;
+use editor::{
+    display_map::ToDisplayPoint, movement, scroll::Autoscroll, Anchor, Bias, Editor,
+    MultiBufferSnapshot, ToPoint,
+};
+use gpui::{actions, impl_actions, AppContext, View, ViewContext, WindowContext};
+use language::Point;
+use serde::Deserialize;
+use util::ResultExt;
+use workspace::{Workspace, WorkspaceId};
+
+use crate::{
+    motion::{self, first_non_whitespace, Motion},
+    persistence::DB,
+    state::{MarkLocation, Mode},
+    utils::open_listing,
+    Vim,
+};
+
+/// The number of locations kept in the jumplist, as in vim.
+const MAX_JUMPLIST_LEN: usize = 100;
+
+/// Deletes the given marks (`:delmarks`). Marks are listed without separators,
+/// and `a-d` denotes a range of marks.
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteMarks {
+    #[serde(default)]
+    pub marks: String,
+    /// Deletes all lowercase marks for the current buffer (`:delmarks!`).
+    #[serde(default)]
+    pub all_local: bool,
+}
+
+actions!(vim, [JumpBack, JumpForward, ShowMarks]);
+impl_actions!(vim, [DeleteMarks]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(jump_back);
+    workspace.register_action(jump_forward);
+    workspace.register_action(show_marks);
+    workspace.register_action(delete_marks);
+}
+
+/// Sets the mark `text` (`m{a-zA-Z}`) at the current cursor positions.
+pub(crate) fn create_mark(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    vim.clear_operator(cx);
+    let Some(name) = text.chars().next() else {
+        return;
+    };
+    match name {
+        'a'..='z' | '[' | ']' | '<' | '>' => {
+            let Some(anchors) = vim.update_active_editor(cx, |vim, editor, cx| {
+                let anchors = cursor_anchors(editor, cx);
+                if name.is_ascii_lowercase() {
+                    if let Some(anchor) = anchors.last() {
+                        let point = anchor.to_point(&editor.buffer().read(cx).snapshot(cx));
+                        persist_mark(vim, editor, name, point, cx);
+                    }
+                }
+                anchors
+            }) else {
+                return;
+            };
+            vim.update_state(|state| state.marks.insert(name, anchors));
+        }
+        'A'..='Z' => {
+            let Some(location) = vim
+                .update_active_editor(cx, |_, editor, cx| {
+                    let anchor = *cursor_anchors(editor, cx).last()?;
+                    let location = location_for_anchor(editor, anchor, cx)?;
+                    if let Some((workspace_id, path)) =
+                        workspace_id(editor, cx).zip(location.path.clone())
+                    {
+                        save_mark(workspace_id, name, path, location.point, cx);
+                    }
+                    Some(location)
+                })
+                .flatten()
+            else {
+                return;
+            };
+            vim.workspace_state.global_marks.insert(name, location);
+        }
+        '\'' | '`' => record_jump(vim, cx),
+        _ => {}
+    }
+}
+
+/// Jumps to the mark `text`, either to its exact position (`` `{mark} ``) or to
+/// the first non-blank character of its line (`'{mark}`).
+pub(crate) fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
+    let Some(mut name) = text.chars().next() else {
+        return;
+    };
+    if name == '`' {
+        name = '\'';
+    }
+    Vim::update(cx, |vim, cx| vim.pop_operator(cx));
+
+    let vim = Vim::read(cx);
+    let anchors = if name.is_ascii_uppercase() {
+        let Some(location) = vim.workspace_state.global_marks.get(&name).cloned() else {
+            Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+            return;
+        };
+        match anchor_in_active_editor(&location, cx) {
+            Some(anchor) => vec![anchor],
+            None => {
+                let Some(workspace) = active_workspace(cx) else {
+                    return;
+                };
+                let has_operator = Vim::update(cx, |vim, cx| {
+                    let has_operator = vim.active_operator().is_some();
+                    vim.clear_operator(cx);
+                    if !has_operator {
+                        record_jump(vim, cx);
+                    }
+                    has_operator
+                });
+                // Jumping to another file can't be combined with an operator.
+                if !has_operator {
+                    workspace.update(cx, |workspace, cx| {
+                        jump_to_location(workspace, location, line, cx)
+                    });
+                }
+                return;
+            }
+        }
+    } else {
+        match vim.state().marks.get(&name) {
+            Some(anchors) if !anchors.is_empty() => anchors.clone(),
+            _ => {
+                Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+                return;
+            }
+        }
+    };
+
+    let vim = Vim::read(cx);
+    if anchors.len() == 1 || vim.active_operator().is_some() || vim.state().mode != Mode::Normal {
+        motion::motion(
+            Motion::Jump {
+                anchor: *anchors.last().unwrap(),
+                line,
+            },
+            cx,
+        );
+        return;
+    }
+
+    // A mark set with multiple cursors restores all of them.
+    Vim::update(cx, |vim, cx| {
+        vim.take_count(cx);
+        record_jump(vim, cx);
+        vim.update_active_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                let map = s.display_map();
+                s.select_display_ranges(anchors.iter().map(|anchor| {
+                    let mut point = anchor.to_display_point(&map);
+                    if line {
+                        point = first_non_whitespace(&map, false, point);
+                    }
+                    point..point
+                }))
+            })
+        });
+    });
+}
+
+/// Records the current position as the previous context mark (`''`) and adds
+/// it to the jumplist. Called before any motion that counts as a jump.
+pub(crate) fn record_jump(vim: &mut Vim, cx: &mut WindowContext) {
+    let Some((anchors, location)) = vim
+        .update_active_editor(cx, |_, editor, cx| {
+            let anchors = cursor_anchors(editor, cx);
+            let location = location_for_anchor(editor, *anchors.last()?, cx)?;
+            Some((anchors, location))
+        })
+        .flatten()
+    else {
+        return;
+    };
+    vim.update_state(|state| state.marks.insert('\'', anchors));
+    push_jump(vim, location, cx);
+}
+
+fn push_jump(vim: &mut Vim, location: MarkLocation, cx: &AppContext) {
+    let state = &mut vim.workspace_state;
+    state
+        .jumplist
+        .retain(|existing| !existing.is_same_line(&location, cx));
+    state.jumplist.push(location);
+    if state.jumplist.len() > MAX_JUMPLIST_LEN {
+        state.jumplist.remove(0);
+    }
+    state.jumplist_index = state.jumplist.len();
+}
+
+/// Sets the `[` and `]` marks to the start and the last character of the
+/// text that was just yanked or put.
+pub(crate) fn set_change_marks(
+    vim: &mut Vim,
+    snapshot: &MultiBufferSnapshot,
+    ranges: Vec<Range<Point>>,
+) {
+    let (starts, ends): (Vec<_>, Vec<_>) = ranges
+        .into_iter()
+        .map(|range| {
+            (
+                snapshot.anchor_before(range.start),
+                snapshot.anchor_before(last_char(snapshot, range)),
+            )
+        })
+        .unzip();
+    vim.update_state(|state| {
+        state.marks.insert('[', starts);
+        state.marks.insert(']', ends);
+    });
+}
+
+/// Sets the `<` and `>` marks to the bounds of the current visual selection.
+pub(crate) fn set_visual_marks(vim: &mut Vim, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let (starts, ends): (Vec<_>, Vec<_>) = editor
+        .selections
+        .all::<Point>(cx)
+        .into_iter()
+        .map(|selection| {
+            (
+                snapshot.anchor_before(selection.start),
+                snapshot.anchor_before(last_char(&snapshot, selection.range())),
+            )
+        })
+        .unzip();
+    vim.update_state(|state| {
+        state.marks.insert('<', starts);
+        state.marks.insert('>', ends);
+    });
+}
+
+/// Sets the mark for the position where insert mode was last stopped (`'^`).
+pub(crate) fn set_insert_mark(vim: &mut Vim, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let anchors = cursor_anchors(editor, cx);
+    vim.update_state(|state| state.marks.insert('^', anchors));
+}
+
+/// Sets the mark for the position of the last change (`'.`).
+pub(crate) fn set_last_change_mark(editor: &View<Editor>, cx: &mut WindowContext) {
+    let anchors = editor.update(cx, |editor, cx| cursor_anchors(editor, cx));
+    Vim::update(cx, |vim, _| {
+        vim.update_state(|state| state.marks.insert('.', anchors))
+    });
+}
+
+/// Returns the position of the last character in `range`, where `range.end`
+/// is exclusive.
+fn last_char(snapshot: &MultiBufferSnapshot, range: Range<Point>) -> Point {
+    let end = range.end;
+    if end <= range.start {
+        end
+    } else if end.column > 0 {
+        snapshot.clip_point(Point::new(end.row, end.column - 1), Bias::Left)
+    } else {
+        let row = end.row - 1;
+        snapshot.clip_point(
+            Point::new(row, snapshot.line_len(row).saturating_sub(1)),
+            Bias::Left,
+        )
+    }
+}
+
+/// The positions of the cursors, which in visual mode are on the last selected
+/// character rather than after it.
+fn cursor_anchors(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Vec<Anchor> {
+    let (map, selections) = editor.selections.all_display(cx);
+    selections
+        .into_iter()
+        .map(|selection| {
+            let mut head = selection.head();
+            if !selection.reversed && !selection.is_empty() {
+                head = movement::left(&map, head);
+            }
+            map.buffer_snapshot.anchor_before(head.to_point(&map))
+        })
+        .collect()
+}
+
+fn location_for_anchor(editor: &Editor, anchor: Anchor, cx: &AppContext) -> Option<MarkLocation> {
+    let (buffer, offset, _) = editor
+        .buffer()
+        .read(cx)
+        .point_to_buffer_offset(anchor, cx)?;
+    let snapshot = buffer.read(cx).snapshot();
+    let path = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx));
+    Some(MarkLocation {
+        buffer: Some((buffer.downgrade(), snapshot.anchor_before(offset))),
+        path,
+        point: snapshot.offset_to_point(offset),
+    })
+}
+
+fn active_workspace(cx: &mut WindowContext) -> Option<View<Workspace>> {
+    Vim::read(cx)
+        .active_editor
+        .as_ref()?
+        .upgrade()?
+        .read(cx)
+        .workspace()
+}
+
+/// Returns the location as an anchor in the active editor, if that editor
+/// is showing the location's buffer.
+fn anchor_in_active_editor(location: &MarkLocation, cx: &mut WindowContext) -> Option<Anchor> {
+    let buffer = location.buffer()?;
+    let editor = Vim::read(cx).active_editor.as_ref()?.upgrade()?;
+    let multibuffer = editor.read(cx).buffer().read(cx);
+    if multibuffer.as_singleton()? != buffer {
+        return None;
+    }
+    let snapshot = multibuffer.snapshot(cx);
+    Some(snapshot.anchor_before(snapshot.clip_point(location.point(cx), Bias::Left)))
+}
+
+/// Moves to `location`, activating or opening an editor for its file if needed.
+fn jump_to_location(
+    workspace: &mut Workspace,
+    location: MarkLocation,
+    line: bool,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let point = location.point(cx);
+    let buffer = location.buffer();
+    let editor = buffer.and_then(|buffer| {
+        workspace
+            .active_item_as::<Editor>(cx)
+            .into_iter()
+            .chain(workspace.items_of_type::<Editor>(cx))
+            .find(|editor| editor.read(cx).buffer().read(cx).as_singleton() == Some(buffer.clone()))
+    });
+
+    if let Some(editor) = editor {
+        workspace.activate_item(&editor, cx);
+        move_to_point(&editor, point, line, cx);
+    } else if let Some(path) = location.path {
+        let task = workspace.open_abs_path(path, true, cx);
+        cx.spawn(|_, mut cx| async move {
+            let item = task.await?;
+            if let Some(editor) = item.downcast::<Editor>() {
+                cx.update(|cx| move_to_point(&editor, point, line, cx))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+fn move_to_point(editor: &View<Editor>, point: Point, line: bool, cx: &mut WindowContext) {
+    editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let point = if line {
+            let row = point.row.min(snapshot.max_point().row);
+            Point::new(row, snapshot.indent_size_for_line(row).len)
+        } else {
+            snapshot.clip_point(point, Bias::Left)
+        };
+        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+            s.select_ranges([point..point])
+        });
+    });
+}
+
+fn jump_back(workspace: &mut Workspace, _: &JumpBack, cx: &mut ViewContext<Workspace>) {
+    let location = Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        // The first time going back from the end of the jumplist, the current
+        // position is added so that it can be returned to with `ctrl-i`.
+        if vim.workspace_state.jumplist_index == vim.workspace_state.jumplist.len() {
+            let current = vim
+                .update_active_editor(cx, |_, editor, cx| {
+                    let head = *cursor_anchors(editor, cx).last()?;
+                    location_for_anchor(editor, head, cx)
+                })
+                .flatten();
+            if let Some(current) = current {
+                push_jump(vim, current, cx);
+                vim.workspace_state.jumplist_index -= 1;
+            }
+        }
+        let state = &mut vim.workspace_state;
+        state.jumplist_index = state.jumplist_index.checked_sub(count)?;
+        state.jumplist.get(state.jumplist_index).cloned()
+    });
+    if let Some(location) = location {
+        jump_to_location(workspace, location, false, cx);
+    }
+}
+
+fn jump_forward(workspace: &mut Workspace, _: &JumpForward, cx: &mut ViewContext<Workspace>) {
+    let location = Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        let state = &mut vim.workspace_state;
+        let index = state.jumplist_index + count;
+        let location = state.jumplist.get(index).cloned()?;
+        state.jumplist_index = index;
+        Some(location)
+    });
+    if let Some(location) = location {
+        jump_to_location(workspace, location, false, cx);
+    }
+}
+
+fn show_marks(workspace: &mut Workspace, _: &ShowMarks, cx: &mut ViewContext<Workspace>) {
+    let editor = workspace.active_item_as::<Editor>(cx);
+    let snapshot = editor
+        .as_ref()
+        .map(|editor| editor.read(cx).buffer().read(cx).snapshot(cx));
+    let (marks, global_marks) = {
+        let vim = Vim::read(cx);
+        (
+            vim.state().marks.clone(),
+            vim.workspace_state.global_marks.clone(),
+        )
+    };
+
+    let mut text = "mark line  col file/text\n".to_string();
+    let mut names = vec!['\''];
+    names.extend('a'..='z');
+    names.extend('A'..='Z');
+    names.extend(['[', ']', '^', '.', '<', '>']);
+    for name in names {
+        let (point, description) = if name.is_ascii_uppercase() {
+            let Some(location) = global_marks.get(&name) else {
+                continue;
+            };
+            let point = location.point(cx);
+            let description = match (&snapshot, is_in_editor(location, editor.as_ref(), cx)) {
+                (Some(snapshot), true) => line_text(snapshot, point.row),
+                _ => location
+                    .path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            };
+            (point, description)
+        } else {
+            let (Some(anchor), Some(snapshot)) = (
+                marks.get(&name).and_then(|anchors| anchors.last()),
+                &snapshot,
+            ) else {
+                continue;
+            };
+            let point = anchor.to_point(snapshot);
+            (point, line_text(snapshot, point.row))
+        };
+        text.push_str(&format!(
+            " {} {:>6} {:>4} {}\n",
+            name,
+            point.row + 1,
+            point.column,
+            description
+        ));
+    }
+
+    open_listing(workspace, "marks", &text, cx).log_err();
+}
+
+fn is_in_editor(location: &MarkLocation, editor: Option<&View<Editor>>, cx: &AppContext) -> bool {
+    match (location.buffer(), editor) {
+        (Some(buffer), Some(editor)) => {
+            editor.read(cx).buffer().read(cx).as_singleton() == Some(buffer)
+        }
+        _ => false,
+    }
+}
+
+fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn delete_marks(workspace: &mut Workspace, action: &DeleteMarks, cx: &mut ViewContext<Workspace>) {
+    let mut names = parse_mark_names(&action.marks);
+    if action.all_local {
+        names.extend('a'..='z');
+    }
+    let workspace_id = workspace.database_id();
+    let path = workspace
+        .active_item_as::<Editor>(cx)
+        .and_then(|editor| buffer_path(editor.read(cx), cx));
+
+    Vim::update(cx, |vim, cx| {
+        for name in names {
+            if name.is_ascii_uppercase() {
+                vim.workspace_state.global_marks.remove(&name);
+                cx.background_executor()
+                    .spawn(DB.delete_global_mark(workspace_id, name.to_string()))
+                    .detach_and_log_err(cx);
+            } else {
+                vim.update_state(|state| state.marks.remove(&name));
+                if let Some(path) = path.clone() {
+                    if let Some(marks) = vim.workspace_state.serialized_marks.get_mut(&path) {
+                        marks.remove(&name);
+                    }
+                    cx.background_executor()
+                        .spawn(DB.delete_mark(workspace_id, name.to_string(), path))
+                        .detach_and_log_err(cx);
+                }
+            }
+        }
+    });
+}
+
+/// Parses the argument to `:delmarks`, e.g. `ab` or `a-dX`.
+fn parse_mark_names(marks: &str) -> Vec<char> {
+    let chars = marks
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    let mut names = Vec::new();
+    let mut ix = 0;
+    while ix < chars.len() {
+        if ix + 2 < chars.len() && chars[ix + 1] == '-' {
+            names.extend(chars[ix]..=chars[ix + 2]);
+            ix += 3;
+        } else {
+            names.push(chars[ix]);
+            ix += 1;
+        }
+    }
+    names
+}
+
+fn buffer_path(editor: &Editor, cx: &AppContext) -> Option<PathBuf> {
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx))
+}
+
+fn workspace_id(editor: &Editor, cx: &AppContext) -> Option<WorkspaceId> {
+    Some(editor.workspace()?.read(cx).database_id())
+}
+
+fn save_mark(workspace_id: WorkspaceId, name: char, path: PathBuf, point: Point, cx: &AppContext) {
+    cx.background_executor()
+        .spawn(DB.save_mark(
+            workspace_id,
+            name.to_string(),
+            path,
+            point.row,
+            point.column,
+        ))
+        .detach_and_log_err(cx);
+}
+
+/// Saves a lowercase mark for the editor's file, so that it is restored the
+/// next time the file is opened.
+fn persist_mark(vim: &mut Vim, editor: &Editor, name: char, point: Point, cx: &AppContext) {
+    let Some(path) = buffer_path(editor, cx) else {
+        return;
+    };
+    if let Some(workspace_id) = workspace_id(editor, cx) {
+        save_mark(workspace_id, name, path.clone(), point, cx);
+    }
+    vim.workspace_state
+        .serialized_marks
+        .entry(path)
+        .or_default()
+        .insert(name, point);
+}
+
+/// Saves the current positions of the marks in `editor`'s file, which may have
+/// moved since they were set. Called when the file is saved.
+pub(crate) fn persist_marks(editor: &View<Editor>, cx: &mut WindowContext) {
+    let (local_marks, global_marks) = {
+        let vim = Vim::read(cx);
+        (
+            vim.state().marks.clone(),
+            vim.workspace_state.global_marks.clone(),
+        )
+    };
+    let editor = editor.read(cx);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let Some(path) = buffer_path(editor, cx) else {
+        return;
+    };
+    let Some(workspace_id) = workspace_id(editor, cx) else {
+        return;
+    };
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+
+    let mut marks = local_marks
+        .iter()
+        .filter(|(name, _)| name.is_ascii_lowercase())
+        .filter_map(|(name, anchors)| Some((*name, anchors.last()?.to_point(&snapshot))))
+        .collect::<HashMap<_, _>>();
+    let global_marks = global_marks
+        .iter()
+        .filter(|(_, location)| location.buffer().as_ref() == Some(&buffer))
+        .map(|(name, location)| (*name, location.point(cx)))
+        .collect::<Vec<_>>();
+
+    for (name, point) in marks.iter().chain(global_marks.iter()) {
+        save_mark(workspace_id, *name, path.clone(), *point, cx);
+    }
+    Vim::update(cx, |vim, _| {
+        let serialized = vim
+            .workspace_state
+            .serialized_marks
+            .entry(path)
+            .or_default();
+        serialized.retain(|name, _| !name.is_ascii_lowercase());
+        serialized.extend(marks.drain());
+    });
+}
+
+/// Loads the marks saved for `workspace`. Uppercase marks are restored
+/// immediately, lowercase marks when an editor for their file is activated.
+pub(crate) fn load_marks(workspace: &Workspace, cx: &mut WindowContext) {
+    let Some(marks) = DB.get_marks(workspace.database_id()).log_err() else {
+        return;
+    };
+    Vim::update(cx, |vim, _| {
+        for (name, path, row, column) in marks {
+            let Some(name) = name.chars().next() else {
+                continue;
+            };
+            let point = Point::new(row, column);
+            if name.is_ascii_uppercase() {
+                vim.workspace_state
+                    .global_marks
+                    .entry(name)
+                    .or_insert(MarkLocation {
+                        buffer: None,
+                        path: Some(path),
+                        point,
+                    });
+            } else {
+                vim.workspace_state
+                    .serialized_marks
+                    .entry(path)
+                    .or_default()
+                    .insert(name, point);
+            }
+        }
+    });
+}
+
+/// Restores the saved lowercase marks for the file of the newly activated editor.
+pub(crate) fn restore_marks(vim: &mut Vim, editor: &View<Editor>, cx: &mut WindowContext) {
+    let editor = editor.read(cx);
+    let Some(marks) =
+        buffer_path(editor, cx).and_then(|path| vim.workspace_state.serialized_marks.get(&path))
+    else {
+        return;
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let marks = marks
+        .iter()
+        .map(|(name, point)| {
+            let point = snapshot.clip_point(*point, Bias::Left);
+            (*name, vec![snapshot.anchor_before(point)])
+        })
+        .collect::<Vec<_>>();
+    vim.update_state(|state| state.marks.extend(marks));
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
+
+    #[gpui::test]
+    async fn test_mark_and_jump(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+              ˇhello
+            world
+              foo
+            bar"})
+            .await;
+        cx.simulate_shared_keystrokes(["l", "l", "m", "a", "j", "j"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+              hello
+            world
+              foˇo
+            bar"})
+            .await;
+        cx.simulate_shared_keystrokes(["`", "a"]).await;
+        cx.assert_shared_state(indoc! {"
+              heˇllo
+            world
+              foo
+            bar"})
+            .await;
+        cx.simulate_shared_keystrokes(["j", "'", "a"]).await;
+        cx.assert_shared_state(indoc! {"
+              ˇhello
+            world
+              foo
+            bar"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_jump_with_operator(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(["m", "a", "j", "j", "d", "'", "a"])
+            .await;
+        cx.assert_shared_state("ˇd").await;
+    }
+
+    #[gpui::test]
+    async fn test_previous_context_mark(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(["shift-g", "'", "'"]).await;
+        cx.assert_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(["`", "`"]).await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b
+            c
+            ˇd"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_jumplist(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e"})
+            .await;
+        cx.simulate_shared_keystrokes(["shift-g", "g", "g", "ctrl-o"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b
+            c
+            d
+            ˇe"})
+            .await;
+        cx.simulate_shared_keystrokes(["ctrl-o"]).await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b
+            c
+            d
+            ˇe"})
+            .await;
+        cx.simulate_shared_keystrokes(["ctrl-i"]).await;
+        cx.assert_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_special_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(["j", "d", "d", "g", "g", "`", "."]);
+        cx.assert_state("a\nˇc\nd", Mode::Normal);
+
+        cx.set_state("ˇabc\ndef", Mode::Normal);
+        cx.simulate_keystrokes(["shift-a", "x", "escape", "j", "`", "^"]);
+        cx.assert_state("abcˇx\ndef", Mode::Normal);
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["v", "e", "escape", "w", "`", "<"]);
+        cx.assert_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["`", ">"]);
+        cx.assert_state("onˇe two three", Mode::Normal);
+
+        cx.set_state("ˇone\n  two\nthree", Mode::Normal);
+        cx.simulate_keystrokes(["y", "j", "shift-g", "'", "]"]);
+        cx.assert_state("one\n  ˇtwo\nthree", Mode::Normal);
+        cx.simulate_keystrokes(["'", "["]);
+        cx.assert_state("ˇone\n  two\nthree", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_delete_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(["m", "a", "j", "m", "b", "j"]);
+        cx.simulate_keystrokes([":", "d", "e", "l", "m", " ", "a", "enter"]);
+        cx.simulate_keystrokes(["'", "a"]);
+        cx.assert_state("a\nb\nˇc", Mode::Normal);
+        cx.simulate_keystrokes(["'", "b"]);
+        cx.assert_state("a\nˇb\nc", Mode::Normal);
+
+        cx.simulate_keystrokes([":", "d", "e", "l", "m", "!", "enter", "j", "'", "b"]);
+        cx.assert_state("a\nb\nˇc", Mode::Normal);
+    }
+}
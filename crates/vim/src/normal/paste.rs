@@ -1,15 +1,18 @@
 use std::cmp;
 
-use editor::{
-    display_map::ToDisplayPoint, movement, scroll::Autoscroll, ClipboardSelection, DisplayPoint,
-};
-use gpui::{impl_actions, AppContext, ViewContext};
-use language::{Bias, SelectionGoal};
+use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll, DisplayPoint, ToPoint};
+use gpui::{impl_actions, ViewContext};
+use language::{Bias, Point, SelectionGoal};
 use serde::Deserialize;
-use settings::Settings;
 use workspace::Workspace;
 
-use crate::{state::Mode, utils::copy_selections_content, UseSystemClipboard, Vim, VimSettings};
+use crate::{
+    normal::mark,
+    register::read_register,
+    state::{Mode, Register},
+    utils::copy_selections_content,
+    Vim,
+};
 
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -26,16 +29,6 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     workspace.register_action(paste);
 }
 
-fn system_clipboard_is_newer(vim: &Vim, cx: &mut AppContext) -> bool {
-    cx.read_from_clipboard().is_some_and(|item| {
-        if let Some(last_state) = vim.workspace_state.registers.get(".system.") {
-            last_state != item.text()
-        } else {
-            true
-        }
-    })
-}
-
 fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
     Vim::update(cx, |vim, cx| {
         vim.record_current_action(cx);
@@ -44,33 +37,18 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
             editor.transact(cx, |editor, cx| {
                 editor.set_clip_at_line_ends(false, cx);
 
-                let (clipboard_text, clipboard_selections): (String, Option<_>) =
-                    if VimSettings::get_global(cx).use_system_clipboard == UseSystemClipboard::Never
-                        || VimSettings::get_global(cx).use_system_clipboard
-                            == UseSystemClipboard::OnYank
-                            && !system_clipboard_is_newer(vim, cx)
-                    {
-                        (
-                            vim.workspace_state
-                                .registers
-                                .get("\"")
-                                .cloned()
-                                .unwrap_or_else(|| "".to_string()),
-                            None,
-                        )
-                    } else {
-                        if let Some(item) = cx.read_from_clipboard() {
-                            let clipboard_selections = item
-                                .metadata::<Vec<ClipboardSelection>>()
-                                .filter(|clipboard_selections| {
-                                    clipboard_selections.len() > 1
-                                        && vim.state().mode != Mode::VisualLine
-                                });
-                            (item.text().clone(), clipboard_selections)
-                        } else {
-                            ("".into(), None)
-                        }
-                    };
+                let selected_register = vim.workspace_state.selected_register.take();
+                let Some(Register {
+                    text: clipboard_text,
+                    clipboard_selections,
+                    ..
+                }) = read_register(vim, selected_register, cx)
+                else {
+                    return;
+                };
+                let clipboard_selections = clipboard_selections.filter(|clipboard_selections| {
+                    clipboard_selections.len() > 1 && vim.state().mode != Mode::VisualLine
+                });
 
                 if clipboard_text.is_empty() {
                     return;
@@ -119,6 +97,7 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
                 let mut edits = Vec::new();
                 let mut new_selections = Vec::new();
                 let mut original_indent_columns = Vec::new();
+                let mut inserted_ranges = Vec::new();
                 let mut start_offset = 0;
 
                 for (ix, (selection, preserve)) in selections_to_process.iter().enumerate() {
@@ -178,12 +157,30 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
                     if *preserve {
                         new_selections.push((anchor, line_mode, is_multiline));
                     }
+                    inserted_ranges.push((
+                        display_map.buffer_snapshot.anchor_before(point_range.start),
+                        display_map.buffer_snapshot.anchor_after(point_range.end),
+                        to_insert.starts_with("\n"),
+                    ));
                     edits.push((point_range, to_insert));
                     original_indent_columns.extend(original_indent_column);
                 }
 
                 editor.edit_with_block_indent(edits, original_indent_columns, cx);
 
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let inserted_ranges = inserted_ranges
+                    .into_iter()
+                    .map(|(start, end, starts_with_newline)| {
+                        let mut start = start.to_point(&snapshot);
+                        if starts_with_newline {
+                            start = Point::new(start.row + 1, 0);
+                        }
+                        start..end.to_point(&snapshot)
+                    })
+                    .collect();
+                mark::set_change_marks(vim, &snapshot, inserted_ranges);
+
                 // in line_mode vim will insert the new text on the next (or previous if before) line
                 // and put the cursor on the first non-blank character of the first inserted line (or at the end if the first line is blank).
                 // otherwise vim will insert the next text at (or before) the current cursor position,
@@ -1,23 +1,39 @@
 use crate::{
     insert::NormalBefore,
     motion::Motion,
-    state::{Mode, RecordedSelection, ReplayableAction},
+    observe_action,
+    state::{Mode, Operator, RecordedSelection, ReplayableAction},
     visual::visual_motion,
     Vim,
 };
 use gpui::{actions, Action, ViewContext, WindowContext};
+use std::{cell::RefCell, rc::Rc};
 use workspace::Workspace;
 
-actions!(vim, [Repeat, EndRepeat]);
+actions!(vim, [Repeat, EndRepeat, ToggleRecord, ReplayLastRecording]);
 
-fn should_replay(action: &Box<dyn Action>) -> bool {
+/// The maximum number of actions a single replay may run. Recursive macros
+/// (e.g. `qa...@aq`) are stopped once they reach this limit.
+const MAX_REPLAYED_ACTIONS: usize = 10000;
+
+fn should_replay(action: &dyn Action) -> bool {
     // skip so that we don't leave the character palette open
-    if editor::actions::ShowCharacterPalette.partial_eq(&**action) {
+    if editor::actions::ShowCharacterPalette.partial_eq(action) {
         return false;
     }
     true
 }
 
+fn end_repeat(cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.workspace_state.replaying = false;
+        vim.update_active_editor(cx, |_, editor, _| {
+            editor.show_local_selections = true;
+        });
+        vim.switch_mode(Mode::Normal, false, cx)
+    });
+}
+
 fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
     match action {
         ReplayableAction::Action(action) => {
@@ -40,14 +56,170 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
 }
 
 pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
-    workspace.register_action(|_: &mut Workspace, _: &EndRepeat, cx| {
+    workspace.register_action(|_: &mut Workspace, _: &EndRepeat, cx| end_repeat(cx));
+
+    workspace.register_action(|_: &mut Workspace, _: &Repeat, cx| repeat(cx, false));
+
+    workspace.register_action(|_: &mut Workspace, _: &ToggleRecord, cx| {
         Vim::update(cx, |vim, cx| {
-            vim.workspace_state.replaying = false;
-            vim.switch_mode(Mode::Normal, false, cx)
-        });
+            if let Some(register) = vim.workspace_state.recording_register.take() {
+                let recording = std::mem::take(&mut vim.workspace_state.current_recording);
+                vim.workspace_state.recordings.insert(register, recording);
+                vim.workspace_state.last_recorded_register = Some(register);
+            } else {
+                vim.push_operator(Operator::RecordRegister, cx);
+            }
+        })
     });
 
-    workspace.register_action(|_: &mut Workspace, _: &Repeat, cx| repeat(cx, false));
+    workspace.register_action(|_: &mut Workspace, _: &ReplayLastRecording, cx| {
+        let Some(register) = Vim::read(cx).workspace_state.last_recorded_register else {
+            return;
+        };
+        replay_register(register, cx)
+    });
+}
+
+/// Replays recorded actions one at a time, deferring between each so that the
+/// effects of one action (mode switches, pushed operators) are visible to the next.
+///
+/// Replays requested while another is running (`.` or `@b` inside a macro) are
+/// spliced in at the current position, so they complete before the outer replay
+/// continues.
+#[derive(Clone)]
+pub struct Replayer(Rc<RefCell<ReplayerState>>);
+
+struct ReplayerState {
+    actions: Vec<ReplayableAction>,
+    running: bool,
+    ix: usize,
+}
+
+impl Replayer {
+    fn new() -> Self {
+        Self(Rc::new(RefCell::new(ReplayerState {
+            actions: vec![],
+            running: false,
+            ix: 0,
+        })))
+    }
+
+    fn replay(&mut self, actions: Vec<ReplayableAction>, cx: &mut WindowContext) {
+        let mut lock = self.0.borrow_mut();
+        let range = lock.ix..lock.ix;
+        lock.actions.splice(range, actions);
+        if lock.running {
+            return;
+        }
+        lock.running = true;
+        let this = self.clone();
+        cx.defer(move |cx| this.next(cx))
+    }
+
+    fn next(self, cx: &mut WindowContext) {
+        let mut lock = self.0.borrow_mut();
+        if lock.ix >= MAX_REPLAYED_ACTIONS {
+            log::error!("Aborting replay after {} actions", MAX_REPLAYED_ACTIONS);
+            drop(lock);
+            // The `EndRepeat` queued at the end of the replay won't run, so
+            // do what it does here.
+            Vim::update(cx, |vim, _| vim.workspace_state.replayer.take());
+            end_repeat(cx);
+            return;
+        }
+        let action = lock.actions.get(lock.ix).cloned();
+        lock.ix += 1;
+        drop(lock);
+        let Some(action) = action else {
+            Vim::update(cx, |vim, _| vim.workspace_state.replayer.take());
+            return;
+        };
+        match action {
+            ReplayableAction::Action(action) => {
+                if should_replay(&*action) {
+                    cx.dispatch_action(action.boxed_clone());
+                    cx.defer(move |cx| observe_action(action, cx));
+                }
+            }
+            ReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => {
+                if let Some(editor) = Vim::read(cx)
+                    .active_editor
+                    .as_ref()
+                    .and_then(|editor| editor.upgrade())
+                {
+                    editor.update(cx, |editor, cx| {
+                        editor.replay_insert_event(&text, utf16_range_to_replace, cx)
+                    })
+                }
+            }
+        }
+        cx.defer(move |cx| self.next(cx));
+    }
+}
+
+fn replay(actions: Vec<ReplayableAction>, cx: &mut WindowContext) {
+    let mut replayer = Vim::update(cx, |vim, _| {
+        vim.workspace_state
+            .replayer
+            .get_or_insert_with(Replayer::new)
+            .clone()
+    });
+    replayer.replay(actions, cx);
+}
+
+/// Starts recording into the given register (`q{register}`). Uppercase
+/// registers append to the existing recording of their lowercase counterpart.
+pub(crate) fn record_register(register: char, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.clear_operator(cx);
+        if !register.is_ascii_alphanumeric() {
+            return;
+        }
+        let lowercase = register.to_ascii_lowercase();
+        vim.workspace_state.current_recording = if register.is_ascii_uppercase() {
+            vim.workspace_state
+                .recordings
+                .get(&lowercase)
+                .cloned()
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        vim.workspace_state.recording_register = Some(lowercase);
+    })
+}
+
+/// Replays the given register (`@{register}`), `@@` replays the register
+/// that was most recently replayed.
+pub(crate) fn replay_register(mut register: char, cx: &mut WindowContext) {
+    let count = Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        vim.clear_operator(cx);
+        count
+    });
+
+    if register == '@' {
+        let Some(last) = Vim::read(cx).workspace_state.last_replayed_register else {
+            return;
+        };
+        register = last;
+    }
+    let register = register.to_ascii_lowercase();
+    let Some(actions) = Vim::read(cx).workspace_state.recordings.get(&register) else {
+        return;
+    };
+
+    let mut repeated_actions = Vec::with_capacity(actions.len() * count);
+    for _ in 0..count {
+        repeated_actions.extend(actions.iter().cloned());
+    }
+    Vim::update(cx, |vim, _| {
+        vim.workspace_state.last_replayed_register = Some(register)
+    });
+    replay(repeated_actions, cx)
 }
 
 pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
@@ -166,34 +338,11 @@ pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
     }
 
     Vim::update(cx, |vim, _| vim.workspace_state.replaying = true);
-    let window = cx.window_handle();
-    cx.spawn(move |mut cx| async move {
-        editor.update(&mut cx, |editor, _| {
-            editor.show_local_selections = false;
-        })?;
-        for action in actions {
-            match action {
-                ReplayableAction::Action(action) => {
-                    if should_replay(&action) {
-                        window.update(&mut cx, |_, cx| cx.dispatch_action(action))
-                    } else {
-                        Ok(())
-                    }
-                }
-                ReplayableAction::Insertion {
-                    text,
-                    utf16_range_to_replace,
-                } => editor.update(&mut cx, |editor, cx| {
-                    editor.replay_insert_event(&text, utf16_range_to_replace.clone(), cx)
-                }),
-            }?
-        }
-        editor.update(&mut cx, |editor, _| {
-            editor.show_local_selections = true;
-        })?;
-        window.update(&mut cx, |_, cx| cx.dispatch_action(EndRepeat.boxed_clone()))
-    })
-    .detach_and_log_err(cx);
+    editor
+        .update(cx, |editor, _| editor.show_local_selections = false)
+        .ok();
+    actions.push(ReplayableAction::Action(EndRepeat.boxed_clone()));
+    replay(actions, cx)
 }
 
 #[cfg(test)]
@@ -207,6 +356,7 @@ mod test {
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
+        Vim,
     };
 
     #[gpui::test]
@@ -506,4 +656,93 @@ mod test {
         cx.simulate_shared_keystrokes(["."]).await;
         cx.assert_shared_state("ˇx hello\n").await;
     }
+
+    #[gpui::test]
+    async fn test_record_replay(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇHello world!!").await;
+        cx.simulate_shared_keystrokes(["q", "w", "c", "w", "j", "escape", "q"])
+            .await;
+        cx.assert_shared_state("ˇj world!!").await;
+        cx.simulate_shared_keystrokes(["w", "@", "w"]).await;
+        cx.assert_shared_state("j ˇj!!").await;
+    }
+
+    #[gpui::test]
+    async fn test_replay_count(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two three four five").await;
+        cx.simulate_shared_keystrokes(["q", "a", "d", "w", "q"])
+            .await;
+        cx.assert_shared_state("ˇtwo three four five").await;
+        cx.simulate_shared_keystrokes(["2", "@", "a"]).await;
+        cx.assert_shared_state("ˇfour five").await;
+        cx.simulate_shared_keystrokes(["@", "@"]).await;
+        cx.assert_shared_state("ˇfive").await;
+    }
+
+    #[gpui::test]
+    async fn test_replay_other_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇa b c d e f g h").await;
+        cx.simulate_shared_keystrokes(["q", "a", "d", "w", "q"])
+            .await;
+        cx.assert_shared_state("ˇb c d e f g h").await;
+        cx.simulate_shared_keystrokes(["q", "b", "@", "a", "w", "q"])
+            .await;
+        cx.assert_shared_state("c ˇd e f g h").await;
+        cx.simulate_shared_keystrokes(["@", "b"]).await;
+        cx.assert_shared_state("c e ˇf g h").await;
+    }
+
+    #[gpui::test]
+    async fn test_record_append(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two three four five six").await;
+        cx.simulate_shared_keystrokes(["q", "a", "d", "w", "q"])
+            .await;
+        cx.assert_shared_state("ˇtwo three four five six").await;
+        cx.simulate_shared_keystrokes(["q", "shift-a", "w", "q"])
+            .await;
+        cx.assert_shared_state("two ˇthree four five six").await;
+        cx.simulate_shared_keystrokes(["@", "a"]).await;
+        cx.assert_shared_state("two four ˇfive six").await;
+    }
+
+    #[gpui::test]
+    async fn test_record_insert_and_dot_repeat(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc\n", Mode::Normal);
+        cx.simulate_keystrokes(["q", "q", "shift-a", "!", "escape", "j", "q"]);
+        cx.assert_state("a!\nˇb\nc\n", Mode::Normal);
+        cx.simulate_keystrokes(["@", "q"]);
+        cx.assert_state("a!\nb!\nˇc\n", Mode::Normal);
+        // the last change made by the macro can be repeated with `.`
+        cx.simulate_keystrokes(["."]);
+        cx.assert_state("a!\nb!\ncˇ!\n", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_recursive_replay_is_aborted(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇab\ncd\n", Mode::Normal);
+        cx.simulate_keystrokes(["q", "a", "l", "@", "a", "q"]);
+        cx.simulate_keystrokes(["@", "a"]);
+        cx.run_until_parked();
+        cx.assert_state("aˇb\ncd\n", Mode::Normal);
+
+        // once aborted, the replay is ended as it would have been normally
+        cx.update_editor(|editor, _| assert!(editor.show_local_selections));
+        cx.read(|cx| assert!(!cx.global::<Vim>().workspace_state.replaying));
+        cx.simulate_keystrokes(["q", "b", "x", "q", "j", "@", "b"]);
+        cx.assert_state("a\nˇd\n", Mode::Normal);
+        cx.simulate_keystrokes(["2", "i", "x", "escape"]);
+        cx.assert_state("a\nxˇxd\n", Mode::Normal);
+    }
 }
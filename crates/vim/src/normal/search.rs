@@ -1,12 +1,22 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+use editor::{scroll::Autoscroll, Editor, MultiBufferSnapshot, ToOffset, ToPoint};
 use gpui::{actions, impl_actions, ViewContext};
+use language::{Bias, Point};
 use search::{buffer_search, BufferSearchBar, SearchMode, SearchOptions};
 use serde_derive::Deserialize;
 use workspace::{searchable::Direction, Workspace};
 
 use crate::{
-    motion::Motion,
-    normal::move_cursor,
-    state::{Mode, SearchState},
+    command::{
+        self,
+        pattern::{pattern_regex, translate_pattern, translate_replacement},
+        range::{line_text, split_pattern},
+    },
+    normal::mark,
+    state::{Mode, Operator, SearchState},
+    utils::{copy_selections_content, yank_selections_content},
     Vim,
 };
 
@@ -41,15 +51,56 @@ pub struct ReplaceCommand {
     pub query: String,
 }
 
-#[derive(Debug, Default)]
-struct Replacement {
-    search: String,
-    replacement: String,
-    should_replace_all: bool,
-    is_case_sensitive: bool,
+/// The arguments to `:s/{pattern}/{string}/[flags] [count]`, with the
+/// pattern and replacement still in vim syntax.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Substitution {
+    pub search: String,
+    pub replacement: String,
+    /// `g`: replace every match in the line, not just the first.
+    pub global: bool,
+    /// `c`: confirm each replacement with the search bar.
+    pub confirm: bool,
+    /// `i` or `I`: override the case sensitivity of the pattern.
+    pub ignore_case: bool,
+    /// `n`: count the matches instead of replacing them.
+    pub count_only: bool,
+    /// `e`: don't report an error when there are no matches.
+    pub ignore_errors: bool,
+    /// Replace in this many lines, starting from the end of the range.
+    pub count: Option<u32>,
+}
+
+/// Where to put the cursor relative to a match, as in `/foo/e+1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SearchOffset {
+    /// `[+-][num]`: lines below (or above) the match, in the first column.
+    Line(i32),
+    /// `e[+-][num]`: characters after (or before) the last character of the match.
+    End(i32),
+    /// `s[+-][num]` or `b[+-][num]`: characters after (or before) the start of the match.
+    Start(i32),
 }
 
-actions!(vim, [SearchSubmit]);
+/// One search typed after `/` or `?`. Searches can be chained with `;`, as
+/// in `/foo/;?bar`, each starting from where the previous one ended.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SearchQuery {
+    pub pattern: String,
+    pub direction: Direction,
+    pub offset: Option<SearchOffset>,
+}
+
+actions!(
+    vim,
+    [
+        SearchSubmit,
+        MoveToNextMatch,
+        MoveToPrevMatch,
+        SelectNextMatch,
+        SelectPrevMatch
+    ]
+);
 impl_actions!(
     vim,
     [FindCommand, ReplaceCommand, Search, MoveToPrev, MoveToNext]
@@ -61,6 +112,16 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     workspace.register_action(search);
     workspace.register_action(search_submit);
     workspace.register_action(search_deploy);
+    workspace.register_action(|workspace, _: &MoveToNextMatch, cx| {
+        move_to_match(workspace, Direction::Next, cx)
+    });
+    workspace.register_action(|workspace, _: &MoveToPrevMatch, cx| {
+        move_to_match(workspace, Direction::Prev, cx)
+    });
+    workspace
+        .register_action(|_, _: &SelectNextMatch, cx| select_search_match(Direction::Next, cx));
+    workspace
+        .register_action(|_, _: &SelectPrevMatch, cx| select_search_match(Direction::Prev, cx));
 
     workspace.register_action(find_command);
     workspace.register_action(replace_command);
@@ -83,6 +144,7 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
     };
     Vim::update(cx, |vim, cx| {
         let count = vim.take_count(cx).unwrap_or(1);
+        mark::record_jump(vim, cx);
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {
@@ -102,6 +164,7 @@ fn search(workspace: &mut Workspace, action: &Search, cx: &mut ViewContext<Works
                         direction,
                         count,
                         initial_query: query.clone(),
+                        offset: None,
                     };
                 });
             }
@@ -120,27 +183,163 @@ fn search_submit(workspace: &mut Workspace, _: &SearchSubmit, cx: &mut ViewConte
         let pane = workspace.active_pane().clone();
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
-                search_bar.update(cx, |search_bar, cx| {
+                let searches = search_bar.update(cx, |search_bar, cx| {
                     let state = &mut vim.workspace_state.search;
                     let mut count = state.count;
                     let direction = state.direction;
+                    let query = search_bar.query(cx);
 
                     // in the case that the query has changed, the search bar
                     // will have selected the next match already.
-                    if (search_bar.query(cx) != state.initial_query)
-                        && state.direction == Direction::Next
-                    {
+                    if (query != state.initial_query) && state.direction == Direction::Next {
                         count = count.saturating_sub(1)
                     }
                     state.count = 1;
-                    search_bar.select_match(direction, count, cx);
                     search_bar.focus_editor(&Default::default(), cx);
+
+                    // the search bar has been searching for everything typed,
+                    // so offsets and chained searches need searching again.
+                    let searches = SearchQuery::parse(&query, direction)
+                        .filter(|searches| searches.len() > 1 || searches[0].pattern != query);
+                    let Some(mut searches) = searches else {
+                        search_bar.select_match(direction, count, cx);
+                        return None;
+                    };
+                    let mut last_pattern = state.initial_query.clone();
+                    for search in searches.iter_mut() {
+                        if search.pattern.is_empty() {
+                            search.pattern = last_pattern.clone();
+                        }
+                        last_pattern = search.pattern.clone();
+                    }
+                    if let Some(last) = searches.last() {
+                        state.direction = last.direction;
+                        state.offset = last.offset;
+                    }
+                    Some((searches, count, search_bar.search_options()))
                 });
+                let Some((searches, count, options)) = searches else {
+                    return;
+                };
+                let search_bar = search_bar.downgrade();
+                cx.spawn(|_, mut cx| async move {
+                    for (ix, search) in searches.into_iter().enumerate() {
+                        let count = if ix == 0 { count } else { 1 };
+                        search_bar
+                            .update(&mut cx, |search_bar, cx| {
+                                search_bar.search(&search.pattern, Some(options), cx)
+                            })?
+                            .await?;
+                        search_bar.update(&mut cx, |search_bar, cx| {
+                            search_bar.select_match(search.direction, count, cx)
+                        })?;
+                        if let Some(offset) = search.offset {
+                            cx.update(|cx| {
+                                Vim::update(cx, |vim, cx| {
+                                    vim.update_active_editor(cx, |_, editor, cx| {
+                                        offset.apply(editor, cx)
+                                    });
+                                })
+                            })?;
+                        }
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
             }
         });
     })
 }
 
+/// `n` and `N`, which search in the direction of the last search (so `N`
+/// after `?` searches forwards) and reuse its offset.
+fn move_to_match(workspace: &mut Workspace, direction: Direction, cx: &mut ViewContext<Workspace>) {
+    let pane = workspace.active_pane().clone();
+    Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        let state = &vim.workspace_state.search;
+        let direction = match state.direction {
+            Direction::Next => direction,
+            Direction::Prev => direction.opposite(),
+        };
+        let offset = state.offset;
+        mark::record_jump(vim, cx);
+
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.select_match(direction, count, cx)
+                });
+            }
+        });
+        if let Some(offset) = offset {
+            vim.update_active_editor(cx, |_, editor, cx| offset.apply(editor, cx));
+        }
+        vim.clear_operator(cx);
+    })
+}
+
+/// `gn` and `gN` after an operator, which act on the next (or previous)
+/// search match, including one under the cursor. So `cgn` changes the match
+/// and `.` changes the one after it.
+fn select_search_match(direction: Direction, cx: &mut ViewContext<Workspace>) {
+    Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        let operator = vim.active_operator();
+        if operator == Some(Operator::Delete) {
+            vim.stop_recording();
+        }
+        let mut match_found = false;
+        vim.update_active_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let matches = editor
+                .search_matches()
+                .iter()
+                .map(|range| range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot))
+                .collect::<Vec<_>>();
+            if matches.is_empty() {
+                return;
+            }
+            let cursor = editor.selections.newest::<usize>(cx).head();
+            let count = (count - 1) % matches.len();
+            let ix = match direction {
+                Direction::Next => {
+                    let ix = matches.iter().position(|range| range.end > cursor);
+                    (ix.unwrap_or(0) + count) % matches.len()
+                }
+                Direction::Prev => {
+                    let ix = matches.iter().rposition(|range| range.start <= cursor);
+                    (ix.unwrap_or(matches.len() - 1) + matches.len() - count) % matches.len()
+                }
+            };
+            let range = matches[ix].clone();
+            match_found = true;
+
+            editor.transact(cx, |editor, cx| {
+                editor.set_clip_at_line_ends(false, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges([range.clone()])
+                });
+                if operator == Some(Operator::Yank) {
+                    yank_selections_content(vim, editor, false, cx);
+                    editor.change_selections(None, cx, |s| {
+                        s.select_ranges([range.start..range.start])
+                    });
+                } else {
+                    copy_selections_content(vim, editor, false, cx);
+                    editor.insert("", cx);
+                }
+            });
+        });
+
+        if match_found && operator == Some(Operator::Change) {
+            vim.switch_mode(Mode::Insert, false, cx);
+        } else {
+            vim.switch_mode(Mode::Normal, false, cx);
+        }
+    })
+}
+
 pub fn move_to_internal(
     workspace: &mut Workspace,
     direction: Direction,
@@ -150,6 +349,7 @@ pub fn move_to_internal(
     Vim::update(cx, |vim, cx| {
         let pane = workspace.active_pane().clone();
         let count = vim.take_count(cx).unwrap_or(1);
+        mark::record_jump(vim, cx);
 
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
@@ -187,11 +387,14 @@ pub fn move_to_internal(
             vim.switch_mode(Mode::Normal, false, cx)
         }
 
+        vim.workspace_state.search.direction = direction;
+        vim.workspace_state.search.offset = None;
         vim.clear_operator(cx);
     });
 }
 
 fn find_command(workspace: &mut Workspace, action: &FindCommand, cx: &mut ViewContext<Workspace>) {
+    Vim::update(cx, |vim, cx| mark::record_jump(vim, cx));
     let pane = workspace.active_pane().clone();
     pane.update(cx, |pane, cx| {
         if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
@@ -231,7 +434,261 @@ fn replace_command(
     action: &ReplaceCommand,
     cx: &mut ViewContext<Workspace>,
 ) {
-    let replacement = parse_replace_all(&action.query);
+    command::run_ex_command(workspace, &action.query, cx)
+}
+
+impl SearchQuery {
+    /// Parses everything typed after `/` (or `?` when searching backwards).
+    /// Returns `None` if there is an offset vim wouldn't understand, in which
+    /// case the query is better searched for as it is.
+    pub(crate) fn parse(query: &str, direction: Direction) -> Option<Vec<Self>> {
+        let mut searches = Vec::new();
+        let mut query = query;
+        let mut direction = direction;
+        loop {
+            let delimiter = match direction {
+                Direction::Next => '/',
+                Direction::Prev => '?',
+            };
+            let (pattern, rest) = split_pattern(query, delimiter);
+            let (offset, rest) = rest.split_once(';').unwrap_or((rest, ""));
+            let offset = if offset.is_empty() {
+                None
+            } else {
+                Some(SearchOffset::parse(offset)?)
+            };
+            searches.push(SearchQuery {
+                pattern,
+                direction,
+                offset,
+            });
+
+            let mut chars = rest.chars();
+            direction = match chars.next() {
+                None => return Some(searches),
+                Some('/') => Direction::Next,
+                Some('?') => Direction::Prev,
+                Some(_) => return None,
+            };
+            query = chars.as_str();
+        }
+    }
+}
+
+impl SearchOffset {
+    fn parse(offset: &str) -> Option<Self> {
+        let (kind, delta): (fn(i32) -> Self, _) = match offset.chars().next()? {
+            'e' => (SearchOffset::End, &offset[1..]),
+            's' | 'b' => (SearchOffset::Start, &offset[1..]),
+            _ => (SearchOffset::Line, offset),
+        };
+        let delta = match delta {
+            "" => 0,
+            "+" => 1,
+            "-" => -1,
+            _ => delta.parse().ok()?,
+        };
+        Some(kind(delta))
+    }
+
+    /// Moves the cursor from the start of the match it is on to the offset.
+    pub(crate) fn apply(self, editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let cursor = editor.selections.newest::<usize>(cx).head();
+        let Some(range) = editor
+            .search_matches()
+            .iter()
+            .map(|range| range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot))
+            .find(|range| range.start == cursor)
+        else {
+            return;
+        };
+
+        let point = match self {
+            SearchOffset::Line(delta) => {
+                let row = range.start.to_point(&snapshot).row as i64 + delta as i64;
+                Point::new(row.clamp(0, snapshot.max_point().row as i64) as u32, 0)
+            }
+            SearchOffset::Start(delta) => {
+                offset_by_chars(&snapshot, range.start, delta).to_point(&snapshot)
+            }
+            SearchOffset::End(delta) => {
+                let last = match snapshot.reversed_chars_at(range.end).next() {
+                    Some(c) if !range.is_empty() => range.end - c.len_utf8(),
+                    _ => range.start,
+                };
+                offset_by_chars(&snapshot, last, delta).to_point(&snapshot)
+            }
+        };
+        // like the rest of normal mode, stay off the end of the line.
+        let point = if point.column > 0 && point.column == snapshot.line_len(point.row) {
+            snapshot.clip_point(Point::new(point.row, point.column - 1), Bias::Left)
+        } else {
+            point
+        };
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select_ranges([point..point])
+        });
+    }
+}
+
+fn offset_by_chars(snapshot: &MultiBufferSnapshot, offset: usize, delta: i32) -> usize {
+    if delta >= 0 {
+        snapshot
+            .chars_at(offset)
+            .take(delta as usize)
+            .fold(offset, |offset, c| offset + c.len_utf8())
+    } else {
+        snapshot
+            .reversed_chars_at(offset)
+            .take(delta.unsigned_abs() as usize)
+            .fold(offset, |offset, c| offset - c.len_utf8())
+    }
+}
+
+impl Substitution {
+    /// Parses everything after `:s`, starting with the delimiter. Like vim,
+    /// the closing delimiters can be left off.
+    pub(crate) fn parse(query: &str) -> Option<Self> {
+        let delimiter = query.chars().next()?;
+        if delimiter.is_alphanumeric() || matches!(delimiter, '\\' | '"' | '|' | ' ') {
+            return None;
+        }
+        let (search, rest) = split_pattern(&query[delimiter.len_utf8()..], delimiter);
+        let (replacement, rest) = split_replacement(rest, delimiter);
+
+        let mut substitution = Substitution {
+            search,
+            replacement,
+            ..Default::default()
+        };
+        let flags_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic() && c != '&')
+            .unwrap_or(rest.len());
+        for flag in rest[..flags_end].chars() {
+            match flag {
+                'g' => substitution.global = !substitution.global,
+                'c' => substitution.confirm = true,
+                'i' => substitution.ignore_case = true,
+                'I' => substitution.ignore_case = false,
+                'n' => substitution.count_only = true,
+                'e' => substitution.ignore_errors = true,
+                '&' | 'r' => {}
+                _ => return None,
+            }
+        }
+        let count = rest[flags_end..].trim();
+        if !count.is_empty() {
+            substitution.count = Some(count.parse().ok()?);
+        }
+        Some(substitution)
+    }
+}
+
+/// Like [`split_pattern`], but keeps the backslash of escaped characters other
+/// than the delimiter, as they mean something in the replacement.
+fn split_replacement(query: &str, delimiter: char) -> (String, &str) {
+    let mut replacement = String::new();
+    let mut chars = query.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        if c == delimiter {
+            return (replacement, &query[ix + c.len_utf8()..]);
+        }
+        if c == '\\' {
+            match chars.next() {
+                Some((_, escaped)) if escaped == delimiter => replacement.push(escaped),
+                Some((_, escaped)) => {
+                    replacement.push('\\');
+                    replacement.push(escaped);
+                }
+                None => replacement.push('\\'),
+            }
+        } else {
+            replacement.push(c);
+        }
+    }
+    (replacement, "")
+}
+
+/// Runs `:s` on each line in `rows`, leaving the cursor on the first
+/// non-blank character of the last line that changed. With the `n` flag,
+/// returns a message with the number of matches instead.
+pub(crate) fn substitute(
+    editor: &mut Editor,
+    mut rows: Range<u32>,
+    substitution: &Substitution,
+    last_search: Option<&str>,
+    cx: &mut ViewContext<Editor>,
+) -> Result<Option<String>> {
+    let regex = pattern_regex(&substitution.search, last_search, substitution.ignore_case)?;
+    let replacement = translate_replacement(&substitution.replacement);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    if let Some(count) = substitution.count {
+        let start = rows.end - 1;
+        rows = start..(start + count).min(snapshot.max_point().row + 1);
+    }
+
+    let mut edits = Vec::new();
+    let mut match_count = 0;
+    let mut line_count = 0;
+    let mut last_row = None;
+    for row in rows {
+        let text = line_text(&snapshot, row);
+        let matches = regex.find_iter(&text).count();
+        if matches == 0 {
+            continue;
+        }
+        match_count += if substitution.global { matches } else { 1 };
+        line_count += 1;
+        last_row = Some(row);
+
+        let new_text = if substitution.global {
+            regex.replace_all(&text, replacement.as_str())
+        } else {
+            regex.replacen(&text, 1, replacement.as_str())
+        };
+        if new_text != text {
+            let range = Point::new(row, 0)..Point::new(row, text.len() as u32);
+            edits.push((range, new_text.into_owned()));
+        }
+    }
+
+    let Some(last_row) = last_row else {
+        if substitution.ignore_errors {
+            return Ok(None);
+        }
+        return Err(anyhow!("E486: Pattern not found: {}", substitution.search));
+    };
+    if substitution.count_only {
+        return Ok(Some(format!(
+            "{} match{} on {} line{}",
+            match_count,
+            if match_count == 1 { "" } else { "es" },
+            line_count,
+            if line_count == 1 { "" } else { "s" }
+        )));
+    }
+
+    let cursor = snapshot.anchor_before(Point::new(last_row, 0));
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits, cx);
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let row = cursor.to_point(&snapshot).row;
+        let point = Point::new(row, snapshot.indent_size_for_line(row).len);
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+            s.select_ranges([point..point])
+        });
+    });
+    Ok(None)
+}
+
+/// `:s` with the `c` flag, which uses the search bar so that each match can be
+/// replaced (or skipped) in turn.
+pub(crate) fn confirm_substitute(
+    workspace: &mut Workspace,
+    substitution: &Substitution,
+    cx: &mut ViewContext<Workspace>,
+) {
     let pane = workspace.active_pane().clone();
     pane.update(cx, |pane, cx| {
         let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
@@ -243,16 +700,18 @@ fn replace_command(
             }
 
             let mut options = SearchOptions::default();
-            if replacement.is_case_sensitive {
+            let (search, case_insensitive) = translate_pattern(&substitution.search);
+            if !case_insensitive.unwrap_or(substitution.ignore_case) {
                 options.set(SearchOptions::CASE_SENSITIVE, true)
             }
-            let search = if replacement.search == "" {
+            let search = if search.is_empty() {
                 search_bar.query(cx)
             } else {
-                replacement.search
+                search
             };
 
-            search_bar.set_replacement(Some(&replacement.replacement), cx);
+            let replacement = translate_replacement(&substitution.replacement);
+            search_bar.set_replacement(Some(&replacement), cx);
             search_bar.activate_search_mode(SearchMode::Regex, cx);
             Some(search_bar.search(&search, Some(options), cx))
         });
@@ -261,20 +720,7 @@ fn replace_command(
         cx.spawn(|_, mut cx| async move {
             search.await?;
             search_bar.update(&mut cx, |search_bar, cx| {
-                if replacement.should_replace_all {
-                    search_bar.select_last_match(cx);
-                    search_bar.replace_all(&Default::default(), cx);
-                    Vim::update(cx, |vim, cx| {
-                        move_cursor(
-                            vim,
-                            Motion::StartOfLine {
-                                display_lines: false,
-                            },
-                            None,
-                            cx,
-                        )
-                    })
-                }
+                search_bar.select_match(Direction::Next, 1, cx)
             })?;
             anyhow::Ok(())
         })
@@ -282,83 +728,6 @@ fn replace_command(
     })
 }
 
-// convert a vim query into something more usable by zed.
-// we don't attempt to fully convert between the two regex syntaxes,
-// but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
-// and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
-fn parse_replace_all(query: &str) -> Replacement {
-    let mut chars = query.chars();
-    if Some('%') != chars.next() || Some('s') != chars.next() {
-        return Replacement::default();
-    }
-
-    let Some(delimiter) = chars.next() else {
-        return Replacement::default();
-    };
-
-    let mut search = String::new();
-    let mut replacement = String::new();
-    let mut flags = String::new();
-
-    let mut buffer = &mut search;
-
-    let mut escaped = false;
-    // 0 - parsing search
-    // 1 - parsing replacement
-    // 2 - parsing flags
-    let mut phase = 0;
-
-    for c in chars {
-        if escaped {
-            escaped = false;
-            if phase == 1 && c.is_digit(10) {
-                buffer.push('$')
-            // unescape escaped parens
-            } else if phase == 0 && c == '(' || c == ')' {
-            } else if c != delimiter {
-                buffer.push('\\')
-            }
-            buffer.push(c)
-        } else if c == '\\' {
-            escaped = true;
-        } else if c == delimiter {
-            if phase == 0 {
-                buffer = &mut replacement;
-                phase = 1;
-            } else if phase == 1 {
-                buffer = &mut flags;
-                phase = 2;
-            } else {
-                break;
-            }
-        } else {
-            // escape unescaped parens
-            if phase == 0 && c == '(' || c == ')' {
-                buffer.push('\\')
-            }
-            buffer.push(c)
-        }
-    }
-
-    let mut replacement = Replacement {
-        search,
-        replacement,
-        should_replace_all: true,
-        is_case_sensitive: true,
-    };
-
-    for c in flags.chars() {
-        match c {
-            'g' | 'I' => {}
-            'c' | 'n' => replacement.should_replace_all = false,
-            'i' => replacement.is_case_sensitive = false,
-            _ => {}
-        }
-    }
-
-    replacement
-}
-
 #[cfg(test)]
 mod test {
     use editor::DisplayPoint;
@@ -507,5 +876,115 @@ mod test {
         cx.simulate_shared_keystrokes(["v", "3", "l", "*"]).await;
         cx.assert_shared_state("a.c. abcd ˇa.c. abcd").await;
         cx.assert_shared_mode(Mode::Normal).await;
+
+        cx.simulate_shared_keystrokes(["v", "3", "l", "#"]).await;
+        cx.assert_shared_state("ˇa.c. abcd a.c. abcd").await;
+        cx.assert_shared_mode(Mode::Normal).await;
+        // n continues backwards after #
+        cx.simulate_shared_keystrokes(["n"]).await;
+        cx.assert_shared_state("a.c. abcd ˇa.c. abcd").await;
+    }
+
+    #[gpui::test]
+    async fn test_search_offset(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two three\ntwo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["/", "t", "w", "o", "/", "e", "enter"])
+            .await;
+        cx.assert_shared_state("one twˇo three\ntwo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["n"]).await;
+        cx.assert_shared_state("one two three\ntwˇo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["shift-n"]).await;
+        cx.assert_shared_state("one twˇo three\ntwo four\nfive two\n")
+            .await;
+
+        cx.set_shared_state("ˇone two three\ntwo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["/", "t", "w", "o", "/", "+", "1", "enter"])
+            .await;
+        cx.assert_shared_state("one two three\nˇtwo four\nfive two\n")
+            .await;
+
+        cx.set_shared_state("ˇone two three\ntwo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["/", "t", "w", "o", "/", "s", "-", "1", "enter"])
+            .await;
+        cx.assert_shared_state("oneˇ two three\ntwo four\nfive two\n")
+            .await;
+
+        // searches can be chained with ;
+        cx.set_shared_state("ˇone two three\ntwo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes([
+            "/", "f", "o", "u", "r", "/", ";", "/", "t", "w", "o", "enter",
+        ])
+        .await;
+        cx.assert_shared_state("one two three\ntwo four\nfive ˇtwo\n")
+            .await;
+
+        // n and N keep the direction of ?
+        cx.set_shared_state("one two three\ntwo four\nˇfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["?", "t", "w", "o", "?", "e", "enter"])
+            .await;
+        cx.assert_shared_state("one two three\ntwˇo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["n"]).await;
+        cx.assert_shared_state("one twˇo three\ntwo four\nfive two\n")
+            .await;
+        cx.simulate_shared_keystrokes(["shift-n"]).await;
+        cx.assert_shared_state("one two three\ntwˇo four\nfive two\n")
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_gn(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇa two b two c two d\n").await;
+        cx.simulate_shared_keystrokes(["/", "t", "w", "o", "enter"])
+            .await;
+        cx.assert_shared_state("a ˇtwo b two c two d\n").await;
+        cx.simulate_shared_keystrokes(["c", "g", "n", "x"]).await;
+        cx.simulate_shared_keystrokes(["escape"]).await;
+        cx.assert_shared_state("a ˇx b two c two d\n").await;
+        cx.simulate_shared_keystrokes(["."]).await;
+        cx.assert_shared_state("a x b ˇx c two d\n").await;
+        cx.simulate_shared_keystrokes(["y", "g", "n"]).await;
+        cx.assert_shared_state("a x b x c ˇtwo d\n").await;
+        cx.simulate_shared_keystrokes(["d", "g", "shift-n"]).await;
+        cx.assert_shared_state("a x b x c ˇ d\n").await;
+    }
+
+    #[gpui::test]
+    async fn test_search_history(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state("ˇone two three", Mode::Normal);
+
+        cx.simulate_keystrokes(["/", "o", "n", "e", "enter"]);
+        cx.run_until_parked();
+        cx.simulate_keystrokes(["/", "t", "w", "o", "enter"]);
+        cx.run_until_parked();
+        cx.assert_state("one ˇtwo three", Mode::Normal);
+
+        let search_bar = cx.workspace(|workspace, cx| {
+            workspace
+                .active_pane()
+                .read(cx)
+                .toolbar()
+                .read(cx)
+                .item_of_type::<BufferSearchBar>()
+                .expect("Buffer search bar should be deployed")
+        });
+        cx.simulate_keystrokes(["/", "ctrl-p"]);
+        cx.update_view(search_bar.clone(), |bar, cx| {
+            assert_eq!(bar.query(cx), "one")
+        });
+        cx.simulate_keystrokes(["ctrl-n"]);
+        cx.update_view(search_bar, |bar, cx| assert_eq!(bar.query(cx), "two"));
     }
 }
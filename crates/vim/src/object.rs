@@ -1,8 +1,8 @@
 use std::ops::Range;
 
 use crate::{
-    motion::right, normal::normal_object, state::Mode, utils::coerce_punctuation,
-    visual::visual_object, Vim,
+    helix::helix_object, motion::right, normal::normal_object, state::Mode,
+    utils::coerce_punctuation, visual::visual_object, Vim,
 };
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
@@ -10,11 +10,13 @@ use editor::{
     Bias, DisplayPoint,
 };
 use gpui::{actions, impl_actions, ViewContext, WindowContext};
-use language::{char_kind, BufferSnapshot, CharKind, Selection};
+use language::{
+    char_kind, BufferSnapshot, CharKind, Point, Selection, TextObject, ToOffset as _, ToPoint as _,
+};
 use serde::Deserialize;
 use workspace::Workspace;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Object {
     Word { ignore_punctuation: bool },
     Sentence,
@@ -28,6 +30,9 @@ pub enum Object {
     AngleBrackets,
     Argument,
     Tag,
+    Function,
+    Class,
+    Comment,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -52,7 +57,10 @@ actions!(
         CurlyBrackets,
         AngleBrackets,
         Argument,
-        Tag
+        Tag,
+        Function,
+        Class,
+        Comment
     ]
 );
 
@@ -88,12 +96,17 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
     workspace
         .register_action(|_: &mut Workspace, _: &Argument, cx: _| object(Object::Argument, cx));
+    workspace
+        .register_action(|_: &mut Workspace, _: &Function, cx: _| object(Object::Function, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Class, cx: _| object(Object::Class, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Comment, cx: _| object(Object::Comment, cx));
 }
 
 fn object(object: Object, cx: &mut WindowContext) {
     match Vim::read(cx).state().mode {
         Mode::Normal => normal_object(object, cx),
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_object(object, cx),
+        Mode::HelixNormal => helix_object(object, cx),
         Mode::Insert => {
             // Shouldn't execute a text object in insert mode. Ignoring
         }
@@ -114,7 +127,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::CurlyBrackets
             | Object::SquareBrackets
-            | Object::Argument => true,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
@@ -129,7 +145,10 @@ impl Object {
             | Object::SquareBrackets
             | Object::Tag
             | Object::CurlyBrackets
-            | Object::AngleBrackets => true,
+            | Object::AngleBrackets
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
@@ -152,7 +171,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::VerticalBars
             | Object::Tag
-            | Object::Argument => Mode::Visual,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => Mode::Visual,
         }
     }
 
@@ -197,6 +219,31 @@ impl Object {
                 surrounding_markers(map, relative_to, around, self.is_multiline(), '<', '>')
             }
             Object::Argument => argument(map, relative_to, around),
+            Object::Function => {
+                let target = if around {
+                    TextObject::AroundFunction
+                } else {
+                    TextObject::InsideFunction
+                };
+                text_object(map, relative_to, target)
+            }
+            Object::Class => {
+                let target = if around {
+                    TextObject::AroundClass
+                } else {
+                    TextObject::InsideClass
+                };
+                text_object(map, relative_to, target)
+            }
+            Object::Comment => {
+                // Most languages only capture whole comments, so `ic` falls back to those.
+                if around {
+                    text_object(map, relative_to, TextObject::AroundComment)
+                } else {
+                    text_object(map, relative_to, TextObject::InsideComment)
+                        .or_else(|| text_object(map, relative_to, TextObject::AroundComment))
+                }
+            }
         }
     }
 
@@ -392,6 +439,115 @@ fn around_next_word(
     Some(start..end)
 }
 
+/// Returns the range of a text object captured by the language's
+/// `textobjects.scm` query. For the `inside` objects the cursor can be anywhere
+/// in the surrounding object, so that `dif` works from a function's signature.
+fn text_object(
+    map: &DisplaySnapshot,
+    relative_to: DisplayPoint,
+    target: TextObject,
+) -> Option<Range<DisplayPoint>> {
+    let snapshot = &map.buffer_snapshot;
+    let offset = relative_to.to_offset(map, Bias::Left);
+
+    // Text objects come from the syntax tree, so we operate at the buffer level and map back to the display level
+    let excerpt = snapshot.excerpt_containing(offset..offset)?;
+    let buffer = excerpt.buffer();
+    let offset = excerpt.map_offset_to_buffer(offset);
+
+    let innermost = |text_object: TextObject| {
+        buffer
+            .text_object_ranges(offset..offset)
+            .filter(|(range, found)| {
+                *found == text_object && range.start <= offset && offset < range.end
+            })
+            .map(|(range, _)| range)
+            .min_by_key(|range| range.len())
+    };
+    let range = match target.around().and_then(innermost) {
+        Some(around_range) => buffer
+            .text_object_ranges(around_range.clone())
+            .filter(|(range, found)| {
+                *found == target
+                    && around_range.start <= range.start
+                    && range.end <= around_range.end
+            })
+            .map(|(range, _)| range)
+            .max_by_key(|range| range.len())?,
+        None => innermost(target)?,
+    };
+    let range = expand_to_whole_lines(buffer, range);
+
+    if excerpt.contains_buffer_range(range.clone()) {
+        let range = excerpt.map_range_from_buffer(range);
+        Some(range.start.to_display_point(map)..range.end.to_display_point(map))
+    } else {
+        None
+    }
+}
+
+/// If `range` starts and ends on lines of its own (ignoring whitespace), extends
+/// it to cover those lines completely, so that deleting it doesn't leave a blank
+/// line behind.
+fn expand_to_whole_lines(buffer: &BufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let start = range.start.to_point(buffer);
+    let end = range.end.to_point(buffer);
+    if end.row >= buffer.max_point().row {
+        return range;
+    }
+
+    let line_start = Point::new(start.row, 0);
+    let line_end = Point::new(end.row, buffer.line_len(end.row));
+    let is_blank = |range: Range<Point>| {
+        buffer
+            .text_for_range(range)
+            .all(|chunk| chunk.trim().is_empty())
+    };
+    if is_blank(line_start..start) && is_blank(end..line_end) {
+        line_start.to_offset(buffer)..Point::new(end.row + 1, 0).to_offset(buffer)
+    } else {
+        range
+    }
+}
+
+/// Finds the argument at `offset` from the `@argument.inside` captures of the
+/// language's `textobjects.scm` query. The `around` range also covers the comma
+/// and whitespace separating it from the next argument, or from the previous
+/// argument if it is the last.
+fn argument_from_query(
+    buffer: &BufferSnapshot,
+    offset: usize,
+    around: bool,
+) -> Option<Range<usize>> {
+    let range = buffer
+        .text_object_ranges(offset..offset)
+        .filter(|(range, found)| {
+            *found == TextObject::InsideArgument && range.start <= offset && offset < range.end
+        })
+        .map(|(range, _)| range)
+        .min_by_key(|range| range.len())?;
+    if !around {
+        return Some(range);
+    }
+
+    let whitespace_len = |chars: &mut dyn Iterator<Item = char>| {
+        chars
+            .take_while(|c| c.is_whitespace())
+            .map(char::len_utf8)
+            .sum::<usize>()
+    };
+    let comma = range.end + whitespace_len(&mut buffer.chars_at(range.end));
+    if buffer.chars_at(comma).next() == Some(',') {
+        let end = comma + 1 + whitespace_len(&mut buffer.chars_at(comma + 1));
+        return Some(range.start..end);
+    }
+    let comma = range.start - whitespace_len(&mut buffer.reversed_chars_at(range.start));
+    if buffer.reversed_chars_at(comma).next() == Some(',') {
+        return Some(comma - 1..range.end);
+    }
+    Some(range)
+}
+
 fn argument(
     map: &DisplaySnapshot,
     relative_to: DisplayPoint,
@@ -533,7 +689,17 @@ fn argument(
         Some(start..end)
     }
 
-    let result = comma_delimited_range_at(buffer, excerpt.map_offset_to_buffer(offset), around)?;
+    // Prefer the innermost argument, as the query may not capture every kind of list.
+    let offset = excerpt.map_offset_to_buffer(offset);
+    let result = match (
+        argument_from_query(buffer, offset, around),
+        comma_delimited_range_at(buffer, offset, around),
+    ) {
+        (Some(from_query), Some(from_brackets)) if from_brackets.len() < from_query.len() => {
+            from_brackets
+        }
+        (from_query, from_brackets) => from_query.or(from_brackets)?,
+    };
 
     if excerpt.contains_buffer_range(result.clone()) {
         let result = excerpt.map_range_from_buffer(result);
@@ -1299,6 +1465,73 @@ mod test {
         cx.assert_state("let a = [«test::call(first_arg)ˇ»]", Mode::Visual);
     }
 
+    #[gpui::test]
+    async fn test_syntax_text_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                fn aˇ() {
+                    b();
+                }
+
+                fn c() {}"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "a", "f"]);
+        cx.assert_state(
+            indoc! {"
+                ˇ
+                fn c() {}"},
+            Mode::Normal,
+        );
+
+        // The inside of a function can be selected from its signature
+        cx.set_state(
+            indoc! {"
+                fn aˇ() {
+                    b();
+                    c();
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "i", "f"]);
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                ˇ}
+            "},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+                impl A {
+                    fn ˇb() {}
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["v", "a", "c"]);
+        cx.assert_state(
+            indoc! {"
+                «impl A {
+                    fn b() {}
+                }ˇ»"},
+            Mode::Visual,
+        );
+
+        cx.set_state(
+            indoc! {"
+                // one
+                // twˇo
+                fn a() {}"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "a", "g", "c"]);
+        cx.assert_state("ˇfn a() {}", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_delete_surrounding_character_objects(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    pub static ref DB: VimDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE vim_marks (
+                workspace_id INTEGER,
+                mark_name TEXT,
+                path BLOB,
+                point_row INTEGER,
+                point_column INTEGER,
+                PRIMARY KEY(workspace_id, mark_name, path),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl VimDb {
+    query! {
+        pub fn get_marks(workspace_id: WorkspaceId) -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT mark_name, path, point_row, point_column
+            FROM vim_marks
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub async fn save_mark(
+            workspace_id: WorkspaceId,
+            mark_name: String,
+            path: PathBuf,
+            point_row: u32,
+            point_column: u32
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO vim_marks(workspace_id, mark_name, path, point_row, point_column)
+            VALUES (?, ?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub async fn delete_mark(
+            workspace_id: WorkspaceId,
+            mark_name: String,
+            path: PathBuf
+        ) -> Result<()> {
+            DELETE FROM vim_marks
+            WHERE workspace_id = ? AND mark_name = ? AND path = ?
+        }
+    }
+
+    query! {
+        pub async fn delete_global_mark(workspace_id: WorkspaceId, mark_name: String) -> Result<()> {
+            DELETE FROM vim_marks
+            WHERE workspace_id = ? AND mark_name = ?
+        }
+    }
+}
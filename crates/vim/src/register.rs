@@ -0,0 +1,323 @@
+use std::sync::Arc;
+
+use gpui::{actions, AppContext, ClipboardItem, ViewContext, WindowContext};
+use settings::Settings;
+use util::ResultExt;
+use workspace::Workspace;
+
+use crate::{
+    state::{Register, RegisterKind},
+    utils::open_listing,
+    UseSystemClipboard, Vim, VimSettings,
+};
+
+actions!(vim, [ShowRegisters]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(show_registers);
+}
+
+/// Selects the register to use for the next yank, delete or put (`"{register}`).
+pub(crate) fn select_register(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    vim.pop_operator(cx);
+    match text.chars().next() {
+        Some(register) if is_valid_register(register) => {
+            vim.workspace_state.selected_register = Some(register);
+        }
+        _ => vim.clear_operator(cx),
+    }
+}
+
+/// Inserts the contents of a register in insert mode (`ctrl-r {register}`).
+pub(crate) fn insert_register(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
+    vim.pop_operator(cx);
+    let register = match text.chars().next() {
+        Some('"') => None,
+        Some(register) if is_valid_register(register) => Some(register),
+        _ => return,
+    };
+    let Some(content) = read_register(vim, register, cx) else {
+        return;
+    };
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.transact(cx, |editor, cx| editor.insert(&content.text, cx))
+    });
+}
+
+fn is_valid_register(register: char) -> bool {
+    register.is_ascii_alphanumeric() || matches!(register, '"' | '-' | '_' | '+' | '*')
+}
+
+/// Stores yanked or deleted text in the selected register, or if there isn't
+/// one in `"0` (yanks), `"-` (deletes within a line) or `"1` (other deletes,
+/// shifting the previous contents of `"1`-`"8` along by one).
+///
+/// The unnamed register (`""`) is always updated to the stored text, unless
+/// the black hole register (`"_`) was selected.
+pub(crate) fn write_registers(
+    vim: &mut Vim,
+    content: Register,
+    is_yank: bool,
+    cx: &mut AppContext,
+) {
+    let content = match vim.workspace_state.selected_register.take() {
+        Some('_') => return,
+        Some('+' | '*') => {
+            write_to_clipboard(vim, &content, cx);
+            content
+        }
+        Some(register) if register != '"' => {
+            let name = register.to_ascii_lowercase();
+            let content = match vim.workspace_state.registers.get(&name) {
+                Some(existing) if register.is_ascii_uppercase() => append(existing, content),
+                _ => content,
+            };
+            vim.workspace_state.registers.insert(name, content.clone());
+            content
+        }
+        _ => {
+            let setting = VimSettings::get_global(cx).use_system_clipboard;
+            if setting == UseSystemClipboard::Always
+                || setting == UseSystemClipboard::OnYank && is_yank
+            {
+                write_to_clipboard(vim, &content, cx);
+            } else {
+                vim.workspace_state.system_clipboard =
+                    cx.read_from_clipboard().map(|item| item.text().clone());
+            }
+
+            let registers = &mut vim.workspace_state.registers;
+            if is_yank {
+                registers.insert('0', content.clone());
+            } else if content.kind == RegisterKind::Charwise && !content.text.contains('\n') {
+                registers.insert('-', content.clone());
+            } else {
+                for ix in (1..9).rev() {
+                    let from = char::from_digit(ix, 10).unwrap();
+                    if let Some(previous) = registers.remove(&from) {
+                        registers.insert(char::from_digit(ix + 1, 10).unwrap(), previous);
+                    }
+                }
+                registers.insert('1', content.clone());
+            }
+            content
+        }
+    };
+    vim.workspace_state.registers.insert('"', content);
+}
+
+/// Returns the contents of the register to put from, reading the system
+/// clipboard for `"+` and `"*`, and for the unnamed register depending on
+/// the `use_system_clipboard` setting.
+pub(crate) fn read_register(
+    vim: &mut Vim,
+    register: Option<char>,
+    cx: &mut AppContext,
+) -> Option<Register> {
+    match register {
+        Some('_') => None,
+        Some('+' | '*') => cx.read_from_clipboard().map(Register::from),
+        Some(register) => vim
+            .workspace_state
+            .registers
+            .get(&register.to_ascii_lowercase())
+            .cloned(),
+        None => {
+            let setting = VimSettings::get_global(cx).use_system_clipboard;
+            if setting == UseSystemClipboard::Never
+                || setting == UseSystemClipboard::OnYank && !system_clipboard_is_newer(vim, cx)
+            {
+                vim.workspace_state.registers.get(&'"').cloned()
+            } else {
+                cx.read_from_clipboard().map(Register::from)
+            }
+        }
+    }
+}
+
+fn system_clipboard_is_newer(vim: &Vim, cx: &mut AppContext) -> bool {
+    cx.read_from_clipboard().is_some_and(|item| {
+        if let Some(last_state) = vim.workspace_state.system_clipboard.as_ref() {
+            last_state != item.text()
+        } else {
+            true
+        }
+    })
+}
+
+fn write_to_clipboard(vim: &mut Vim, content: &Register, cx: &mut AppContext) {
+    let item = ClipboardItem::new(content.text.clone());
+    cx.write_to_clipboard(match content.clipboard_selections.as_ref() {
+        Some(clipboard_selections) => item.with_metadata(clipboard_selections),
+        None => item,
+    });
+    vim.workspace_state.system_clipboard = Some(content.text.clone());
+}
+
+/// Appends to a register, as when yanking or deleting into `"A`-`"Z`. If
+/// either part is linewise the result is too, with each part on its own lines.
+fn append(existing: &Register, content: Register) -> Register {
+    let mut text = existing.text.clone();
+    let kind = if existing.kind == RegisterKind::Linewise || content.kind == RegisterKind::Linewise
+    {
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&content.text);
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        RegisterKind::Linewise
+    } else {
+        text.push_str(&content.text);
+        existing.kind
+    };
+    Register {
+        text,
+        kind,
+        clipboard_selections: None,
+    }
+}
+
+fn show_registers(workspace: &mut Workspace, _: &ShowRegisters, cx: &mut ViewContext<Workspace>) {
+    let mut registers = Vim::read(cx).workspace_state.registers.clone();
+    if let Some(item) = cx.read_from_clipboard() {
+        registers.insert('+', Register::from(item));
+    }
+
+    let mut names = vec!['"'];
+    names.extend('0'..='9');
+    names.extend('a'..='z');
+    names.extend(['-', '+']);
+
+    let mut text = "Type Name Content\n".to_string();
+    for name in names {
+        let Some(register) = registers.get(&name) else {
+            continue;
+        };
+        let kind = match register.kind {
+            RegisterKind::Charwise => 'c',
+            RegisterKind::Linewise => 'l',
+            RegisterKind::Blockwise => 'b',
+        };
+        text.push_str(&format!(
+            "  {}  \"{}   {}\n",
+            kind,
+            name,
+            register.text.replace('\n', "^J")
+        ));
+    }
+
+    open_listing(workspace, "registers", &text, cx).log_err();
+}
+
+#[cfg(test)]
+mod test {
+    use gpui::ClipboardItem;
+    use indoc::indoc;
+    use settings::SettingsStore;
+
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+        UseSystemClipboard, VimSettings,
+    };
+
+    #[gpui::test]
+    async fn test_named_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇa b c").await;
+        cx.simulate_shared_keystrokes(["\"", "a", "y", "w", "w", "d", "w"])
+            .await;
+        cx.assert_shared_state("a ˇc").await;
+        cx.assert_shared_register('a', "a ").await;
+        cx.simulate_shared_keystrokes(["\"", "a", "shift-p"]).await;
+        cx.assert_shared_state("a aˇ c").await;
+        cx.simulate_shared_keystrokes(["p"]).await;
+        cx.assert_shared_state("a a bˇ c").await;
+    }
+
+    #[gpui::test]
+    async fn test_append_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two three").await;
+        cx.simulate_shared_keystrokes(["\"", "a", "y", "w", "w", "\"", "shift-a", "y", "w"])
+            .await;
+        cx.assert_shared_register('a', "one two ").await;
+        cx.simulate_shared_keystrokes(["w", "\"", "a", "shift-p"])
+            .await;
+        cx.assert_shared_state("one two one twoˇ three").await;
+    }
+
+    #[gpui::test]
+    async fn test_numbered_and_small_delete_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇone
+            two
+            three x"})
+            .await;
+        cx.simulate_shared_keystrokes(["d", "d", "d", "d", "x"])
+            .await;
+        cx.assert_shared_state("ˇhree x").await;
+        cx.assert_shared_register('-', "t").await;
+        cx.simulate_shared_keystrokes(["\"", "2", "shift-p"]).await;
+        cx.assert_shared_state(indoc! {"
+            ˇone
+            hree x"})
+            .await;
+        cx.simulate_shared_keystrokes(["\"", "1", "p"]).await;
+        cx.assert_shared_state(indoc! {"
+            one
+            ˇtwo
+            hree x"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_black_hole_register(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two").await;
+        cx.simulate_shared_keystrokes(["y", "w", "w", "\"", "_", "d", "w", "p"])
+            .await;
+        cx.assert_shared_state("one one ˇ").await;
+    }
+
+    #[gpui::test]
+    async fn test_yank_keeps_register_zero(cx: &mut gpui::TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state("ˇone two three").await;
+        cx.simulate_shared_keystrokes(["y", "w", "w", "d", "w", "\"", "0", "shift-p"])
+            .await;
+        cx.assert_shared_state("one oneˇ three").await;
+    }
+
+    #[gpui::test]
+    async fn test_clipboard_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                s.use_system_clipboard = Some(UseSystemClipboard::Never)
+            });
+        });
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(["\"", "+", "y", "w"]);
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().clone()),
+            Some("one ".into())
+        );
+
+        cx.write_to_clipboard(ClipboardItem::new("copied".to_string()));
+        cx.simulate_keystrokes(["w", "\"", "*", "p"]);
+        cx.assert_state("one tcopieˇdwo", Mode::Normal);
+        cx.simulate_keystrokes(["p"]);
+        cx.assert_state("one tcopiedoneˇ wo", Mode::Normal);
+    }
+}
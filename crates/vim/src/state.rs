@@ -1,12 +1,19 @@
-use std::{fmt::Display, ops::Range, sync::Arc};
+use std::{fmt::Display, ops::Range, path::PathBuf, sync::Arc};
 
 use collections::HashMap;
-use gpui::{Action, KeyContext};
-use language::CursorShape;
+use editor::{Anchor, ClipboardSelection};
+use gpui::{Action, AppContext, ClipboardItem, KeyContext, Model, WeakModel};
+use language::{Buffer, CursorShape, Point, ToPoint};
 use serde::{Deserialize, Serialize};
 use workspace::searchable::Direction;
 
-use crate::motion::Motion;
+use crate::{
+    insert::InsertCompletion,
+    motion::Motion,
+    normal::{repeat::Replayer, search::SearchOffset},
+    object::Object,
+    surrounds::SurroundsType,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
 pub enum Mode {
@@ -15,6 +22,7 @@ pub enum Mode {
     Visual,
     VisualLine,
     VisualBlock,
+    HelixNormal,
 }
 
 impl Display for Mode {
@@ -25,6 +33,7 @@ impl Display for Mode {
             Mode::Visual => write!(f, "VISUAL"),
             Mode::VisualLine => write!(f, "VISUAL LINE"),
             Mode::VisualBlock => write!(f, "VISUAL BLOCK"),
+            Mode::HelixNormal => write!(f, "HELIX NORMAL"),
         }
     }
 }
@@ -32,7 +41,7 @@ impl Display for Mode {
 impl Mode {
     pub fn is_visual(&self) -> bool {
         match self {
-            Mode::Normal | Mode::Insert => false,
+            Mode::Normal | Mode::Insert | Mode::HelixNormal => false,
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => true,
         }
     }
@@ -44,15 +53,43 @@ impl Default for Mode {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
 pub enum Operator {
     Change,
     Delete,
     Yank,
     Replace,
-    Object { around: bool },
-    FindForward { before: bool },
-    FindBackward { after: bool },
+    Object {
+        around: bool,
+    },
+    FindForward {
+        before: bool,
+    },
+    FindBackward {
+        after: bool,
+    },
+    RecordRegister,
+    ReplayRegister,
+    Mark,
+    Jump {
+        line: bool,
+    },
+    Register,
+    AddSurrounds {
+        #[serde(skip)]
+        target: Option<SurroundsType>,
+    },
+    ChangeSurrounds {
+        #[serde(skip)]
+        target: Option<Object>,
+    },
+    DeleteSurrounds,
+    InsertRegister,
+    HelixMatch,
+    SelectRegex {
+        #[serde(skip)]
+        pattern: String,
+    },
 }
 
 #[derive(Default, Clone)]
@@ -66,6 +103,18 @@ pub struct EditorState {
     pub post_count: Option<usize>,
 
     pub operator_stack: Vec<Operator>,
+    /// The tag typed so far as the replacement for a surround operator (e.g.
+    /// `div class="a"` after `ysiw<div class="a"`).
+    pub surround_tag: Option<String>,
+    /// Set after `ctrl-o` in insert mode, to return to insert mode once the
+    /// next normal mode command has run.
+    pub temporary_normal: bool,
+    /// The words being cycled through with `ctrl-n` and `ctrl-p` in insert mode.
+    pub insert_completion: Option<InsertCompletion>,
+
+    /// Local marks (`a`-`z` and the special marks), keyed by name. Each mark
+    /// stores one anchor per cursor.
+    pub marks: HashMap<char, Vec<Anchor>>,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -100,7 +149,62 @@ pub struct WorkspaceState {
     pub recorded_actions: Vec<ReplayableAction>,
     pub recorded_selection: RecordedSelection,
 
-    pub registers: HashMap<String, String>,
+    /// The register currently being recorded into with `q{register}`.
+    pub recording_register: Option<char>,
+    /// The actions recorded so far for `recording_register`.
+    pub current_recording: Vec<ReplayableAction>,
+    pub last_recorded_register: Option<char>,
+    pub last_replayed_register: Option<char>,
+    pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    pub replayer: Option<Replayer>,
+
+    /// Uppercase marks, which can refer to any file.
+    pub global_marks: HashMap<char, MarkLocation>,
+    pub jumplist: Vec<MarkLocation>,
+    pub jumplist_index: usize,
+    /// Local marks restored from the database, applied to an editor for the
+    /// file the first time it is activated.
+    pub serialized_marks: HashMap<PathBuf, HashMap<char, Point>>,
+
+    pub registers: HashMap<char, Register>,
+    /// The register chosen with `"{register}` for the next yank, delete or put.
+    pub selected_register: Option<char>,
+    /// The system clipboard contents as of the last yank or delete, used to
+    /// tell whether something else has been copied since.
+    pub system_clipboard: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RegisterKind {
+    #[default]
+    Charwise,
+    Linewise,
+    Blockwise,
+}
+
+/// The contents of a register.
+#[derive(Clone, Debug, Default)]
+pub struct Register {
+    pub text: String,
+    pub kind: RegisterKind,
+    /// How the text is split between cursors, if it was yanked with more than
+    /// one selection.
+    pub clipboard_selections: Option<Vec<ClipboardSelection>>,
+}
+
+impl From<ClipboardItem> for Register {
+    fn from(item: ClipboardItem) -> Self {
+        let text = item.text().clone();
+        Register {
+            kind: if text.ends_with('\n') {
+                RegisterKind::Linewise
+            } else {
+                RegisterKind::Charwise
+            },
+            clipboard_selections: item.metadata::<Vec<ClipboardSelection>>(),
+            text,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -127,11 +231,53 @@ impl Clone for ReplayableAction {
     }
 }
 
+/// A position that is not tied to a particular editor, used for global marks
+/// and the jumplist.
+#[derive(Clone)]
+pub struct MarkLocation {
+    /// The buffer and position the location was recorded in, if that buffer
+    /// was open at the time. The anchor keeps the location up to date as the
+    /// buffer is edited.
+    pub buffer: Option<(WeakModel<Buffer>, language::Anchor)>,
+    /// The absolute path of the file, used to reopen it once closed.
+    pub path: Option<PathBuf>,
+    /// The position as of when the location was last resolved.
+    pub point: Point,
+}
+
+impl MarkLocation {
+    pub fn buffer(&self) -> Option<Model<Buffer>> {
+        self.buffer.as_ref()?.0.upgrade()
+    }
+
+    /// Returns the current position of the location, following any edits made
+    /// to its buffer since it was recorded.
+    pub fn point(&self, cx: &AppContext) -> Point {
+        match self.buffer.as_ref() {
+            Some((buffer, anchor)) => match buffer.upgrade() {
+                Some(buffer) => anchor.to_point(&buffer.read(cx).snapshot()),
+                None => self.point,
+            },
+            None => self.point,
+        }
+    }
+
+    pub fn is_same_line(&self, other: &MarkLocation, cx: &AppContext) -> bool {
+        let same_file = match (self.buffer(), other.buffer()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.path.is_some() && self.path == other.path,
+        };
+        same_file && self.point(cx).row == other.point(cx).row
+    }
+}
+
 #[derive(Clone)]
 pub struct SearchState {
     pub direction: Direction,
     pub count: usize,
     pub initial_query: String,
+    /// The offset of the last search (as in `/foo/e`), which `n` and `N` reuse.
+    pub offset: Option<SearchOffset>,
 }
 
 impl Default for SearchState {
@@ -140,6 +286,7 @@ impl Default for SearchState {
             direction: Direction::Next,
             count: 1,
             initial_query: "".to_string(),
+            offset: None,
         }
     }
 }
@@ -154,7 +301,9 @@ impl EditorState {
                     CursorShape::Underscore
                 }
             }
-            Mode::Visual | Mode::VisualLine | Mode::VisualBlock => CursorShape::Block,
+            Mode::Visual | Mode::VisualLine | Mode::VisualBlock | Mode::HelixNormal => {
+                CursorShape::Block
+            }
             Mode::Insert => CursorShape::Bar,
         }
     }
@@ -163,7 +312,9 @@ impl EditorState {
         !matches!(self.mode, Mode::Insert)
             || matches!(
                 self.operator_stack.last(),
-                Some(Operator::FindForward { .. }) | Some(Operator::FindBackward { .. })
+                Some(Operator::FindForward { .. })
+                    | Some(Operator::FindBackward { .. })
+                    | Some(Operator::InsertRegister)
             )
     }
 
@@ -173,13 +324,17 @@ impl EditorState {
 
     pub fn clip_at_line_ends(&self) -> bool {
         match self.mode {
-            Mode::Insert | Mode::Visual | Mode::VisualLine | Mode::VisualBlock => false,
+            Mode::Insert
+            | Mode::Visual
+            | Mode::VisualLine
+            | Mode::VisualBlock
+            | Mode::HelixNormal => false,
             Mode::Normal => true,
         }
     }
 
     pub fn active_operator(&self) -> Option<Operator> {
-        self.operator_stack.last().copied()
+        self.operator_stack.last().cloned()
     }
 
     pub fn keymap_context_layer(&self) -> KeyContext {
@@ -190,6 +345,7 @@ impl EditorState {
                 Mode::Normal => "normal",
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => "visual",
                 Mode::Insert => "insert",
+                Mode::HelixNormal => "helix_normal",
             },
         );
 
@@ -205,7 +361,7 @@ impl EditorState {
 
         let active_operator = self.active_operator();
 
-        if let Some(active_operator) = active_operator {
+        if let Some(active_operator) = &active_operator {
             for context_flag in active_operator.context_flags().into_iter() {
                 context.add(*context_flag);
             }
@@ -233,15 +389,38 @@ impl Operator {
             Operator::FindForward { before: true } => "t",
             Operator::FindBackward { after: false } => "F",
             Operator::FindBackward { after: true } => "T",
+            Operator::RecordRegister => "q",
+            Operator::ReplayRegister => "@",
+            Operator::Mark => "m",
+            Operator::Jump { line: true } => "'",
+            Operator::Jump { line: false } => "`",
+            Operator::Register => "\"",
+            Operator::AddSurrounds { .. } => "ys",
+            Operator::ChangeSurrounds { .. } => "cs",
+            Operator::DeleteSurrounds => "ds",
+            Operator::InsertRegister => "ctrl-r",
+            Operator::HelixMatch => "match",
+            Operator::SelectRegex { .. } => "select_regex",
         }
     }
 
     pub fn context_flags(&self) -> &'static [&'static str] {
         match self {
-            Operator::Object { .. } => &["VimObject"],
-            Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace => {
-                &["VimWaiting"]
-            }
+            Operator::Object { .. }
+            | Operator::ChangeSurrounds { target: None }
+            | Operator::DeleteSurrounds => &["VimObject"],
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Mark
+            | Operator::Jump { .. }
+            | Operator::Register
+            | Operator::InsertRegister
+            | Operator::SelectRegex { .. }
+            | Operator::AddSurrounds { target: Some(_) }
+            | Operator::ChangeSurrounds { target: Some(_) } => &["VimWaiting"],
             _ => &[],
         }
     }
@@ -0,0 +1,506 @@
+use std::{ops::Range, sync::Arc};
+
+use editor::{
+    display_map::DisplaySnapshot, scroll::Autoscroll, Bias, DisplayPoint, MultiBufferSnapshot,
+    ToOffset, ToPoint,
+};
+use gpui::WindowContext;
+use language::Point;
+
+use crate::{motion::Motion, object::Object, state::Mode, Vim};
+
+/// The text that `ys` adds surrounding characters to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SurroundsType {
+    /// `ys{motion}`
+    Motion {
+        motion: Motion,
+        times: Option<usize>,
+    },
+    /// `ys{object}`
+    Object { object: Object, around: bool },
+    /// Visual `S`
+    Selection,
+}
+
+/// The characters typed to surround text with.
+enum Surrounds {
+    /// A tag is still being typed.
+    Pending,
+    Invalid,
+    Pair {
+        open: String,
+        close: String,
+    },
+}
+
+/// `ys{motion}{char}`, `ys{object}{char}` and visual `S{char}`, which add
+/// `char` (and its pair) around the text of each selection.
+pub fn add_surrounds(text: Arc<str>, target: SurroundsType, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let (open, close) = match read_surrounds(vim, &text) {
+            Surrounds::Pending => return,
+            Surrounds::Invalid => {
+                vim.stop_recording();
+                vim.clear_operator(cx);
+                return;
+            }
+            Surrounds::Pair { open, close } => (open, close),
+        };
+        vim.stop_recording();
+        let mode = vim.state().mode;
+
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.transact(cx, |editor, cx| {
+                editor.set_clip_at_line_ends(false, cx);
+                let (map, selections) = editor.selections.all_display(cx);
+                let snapshot = &map.buffer_snapshot;
+
+                let mut ranges = selections
+                    .into_iter()
+                    .filter_map(|mut selection| {
+                        let range = match &target {
+                            SurroundsType::Motion { motion, times } => motion
+                                .expand_selection(
+                                    &map,
+                                    &mut selection,
+                                    *times,
+                                    false,
+                                    &text_layout_details,
+                                )
+                                .then(|| selection.range()),
+                            SurroundsType::Object { object, around } => {
+                                object.range(&map, selection.head(), *around)
+                            }
+                            SurroundsType::Selection => Some(selection.range()),
+                        }?;
+                        Some(
+                            range.start.to_offset(&map, Bias::Left)
+                                ..range.end.to_offset(&map, Bias::Left),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                ranges.sort_by_key(|range| range.start);
+
+                let mut edits = Vec::new();
+                let mut cursors = Vec::new();
+                let mut inserted = 0;
+                let mut last_end = None;
+                for range in ranges {
+                    if last_end.map_or(false, |end| range.start < end) {
+                        continue;
+                    }
+                    last_end = Some(range.end);
+
+                    // In visual line mode the surrounding characters go on
+                    // lines of their own, as in vim-surround.
+                    let (range, open, close, cursor) = if mode == Mode::VisualLine {
+                        let start_row = range.start.to_point(snapshot).row;
+                        let end = range.end.to_point(snapshot);
+                        let end_row = if end.column == 0 && end.row > start_row {
+                            end.row - 1
+                        } else {
+                            end.row
+                        };
+                        let indent = snapshot
+                            .chars_at(Point::new(start_row, 0))
+                            .take_while(|c| *c == ' ' || *c == '\t')
+                            .collect::<String>();
+                        (
+                            Point::new(start_row, 0).to_offset(snapshot)
+                                ..Point::new(end_row, snapshot.line_len(end_row))
+                                    .to_offset(snapshot),
+                            format!("{indent}{}\n", open.trim_end()),
+                            format!("\n{indent}{}", close.trim_start()),
+                            indent.len(),
+                        )
+                    } else {
+                        (
+                            trim_whitespace(snapshot, range),
+                            open.clone(),
+                            close.clone(),
+                            0,
+                        )
+                    };
+
+                    cursors.push(range.start + inserted + cursor);
+                    inserted += open.len() + close.len();
+                    edits.push((range.start..range.start, open));
+                    edits.push((range.end..range.end, close));
+                }
+
+                editor.edit(edits, cx);
+                editor.set_clip_at_line_ends(true, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges(cursors.into_iter().map(|cursor| cursor..cursor))
+                });
+            });
+        });
+
+        if mode.is_visual() {
+            vim.switch_mode(Mode::Normal, true, cx);
+        } else {
+            vim.clear_operator(cx);
+        }
+    });
+}
+
+/// `cs{target}{char}`, which replaces the characters surrounding each cursor.
+pub fn change_surrounds(text: Arc<str>, target: Object, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        match read_surrounds(vim, &text) {
+            Surrounds::Pending => return,
+            Surrounds::Invalid => {}
+            Surrounds::Pair { open, close } => replace_surrounds(vim, target, &open, &close, cx),
+        }
+        vim.stop_recording();
+        vim.clear_operator(cx);
+    });
+}
+
+/// `ds{target}`, which removes the characters surrounding each cursor.
+pub fn delete_surrounds(vim: &mut Vim, target: Object, cx: &mut WindowContext) {
+    vim.stop_recording();
+    replace_surrounds(vim, target, "", "", cx);
+}
+
+/// Whether `object` is delimited by characters that `cs` and `ds` can change.
+pub fn is_surround_object(object: Object) -> bool {
+    matches!(
+        object,
+        Object::Quotes
+            | Object::BackQuotes
+            | Object::DoubleQuotes
+            | Object::VerticalBars
+            | Object::Parentheses
+            | Object::SquareBrackets
+            | Object::CurlyBrackets
+            | Object::AngleBrackets
+            | Object::Tag
+    )
+}
+
+fn replace_surrounds(
+    vim: &mut Vim,
+    target: Object,
+    open: &str,
+    close: &str,
+    cx: &mut WindowContext,
+) {
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.transact(cx, |editor, cx| {
+            editor.set_clip_at_line_ends(false, cx);
+            let (map, selections) = editor.selections.all_display(cx);
+            let mut pairs = selections
+                .iter()
+                .filter_map(|selection| delimiters(&map, selection.head(), target))
+                .collect::<Vec<_>>();
+            pairs.sort_by_key(|(open, _)| open.start);
+
+            let mut edits = Vec::new();
+            let mut cursors = Vec::new();
+            let mut delta = 0isize;
+            let mut last_end = None;
+            for (open_range, close_range) in pairs {
+                // Several cursors may be in the same pair, or in nested ones.
+                if last_end.map_or(false, |end| open_range.start < end) {
+                    continue;
+                }
+                last_end = Some(close_range.end);
+
+                cursors.push((open_range.start as isize + delta) as usize);
+                delta += open.len() as isize - open_range.len() as isize;
+                delta += close.len() as isize - close_range.len() as isize;
+                edits.push((open_range, open.to_string()));
+                edits.push((close_range, close.to_string()));
+            }
+
+            if !edits.is_empty() {
+                editor.edit(edits, cx);
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_ranges(cursors.into_iter().map(|cursor| cursor..cursor))
+                });
+            }
+            editor.set_clip_at_line_ends(true, cx);
+        });
+    });
+}
+
+/// Returns the ranges of the opening and closing delimiters of the `object`
+/// surrounding `point`.
+fn delimiters(
+    map: &DisplaySnapshot,
+    point: DisplayPoint,
+    object: Object,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let offsets = |range: Range<DisplayPoint>| {
+        range.start.to_offset(map, Bias::Left)..range.end.to_offset(map, Bias::Left)
+    };
+    match object {
+        Object::Tag => {
+            let around = offsets(object.range(map, point, true)?);
+            let inside = offsets(object.range(map, point, false)?);
+            Some((around.start..inside.start, inside.end..around.end))
+        }
+        // Around quotes includes the whitespace after them, so the quotes
+        // are found from the inside instead.
+        Object::Quotes | Object::BackQuotes | Object::DoubleQuotes | Object::VerticalBars => {
+            let inside = offsets(object.range(map, point, false)?);
+            Some((inside.start - 1..inside.start, inside.end..inside.end + 1))
+        }
+        Object::Parentheses
+        | Object::SquareBrackets
+        | Object::CurlyBrackets
+        | Object::AngleBrackets => {
+            let around = offsets(object.range(map, point, true)?);
+            Some((around.start..around.start + 1, around.end - 1..around.end))
+        }
+        _ => None,
+    }
+}
+
+/// Reads the characters to surround text with, following vim-surround: an
+/// opening bracket adds a space inside the pair, `b`, `B`, `r` and `a` stand
+/// for `)`, `}`, `]` and `>`, and `<` or `t` start a tag, which is finished
+/// with `>` or enter.
+fn read_surrounds(vim: &mut Vim, text: &str) -> Surrounds {
+    if let Some(mut tag) = vim.state().surround_tag.clone() {
+        if text == ">" || text == "\n" {
+            vim.update_state(|state| state.surround_tag = None);
+            return tag_surrounds(&tag);
+        }
+        tag.push_str(text);
+        vim.update_state(|state| state.surround_tag = Some(tag));
+        return Surrounds::Pending;
+    }
+
+    let Some(ch) = text.chars().next() else {
+        return Surrounds::Invalid;
+    };
+    let (open, close) = match ch {
+        '<' | 't' => {
+            vim.update_state(|state| state.surround_tag = Some(String::new()));
+            return Surrounds::Pending;
+        }
+        '(' => ("( ", " )"),
+        ')' | 'b' => ("(", ")"),
+        '{' => ("{ ", " }"),
+        '}' | 'B' => ("{", "}"),
+        '[' => ("[ ", " ]"),
+        ']' | 'r' => ("[", "]"),
+        '>' | 'a' => ("<", ">"),
+        ch if ch.is_alphanumeric() || ch.is_whitespace() => return Surrounds::Invalid,
+        ch => (&text[..ch.len_utf8()], &text[..ch.len_utf8()]),
+    };
+    Surrounds::Pair {
+        open: open.to_string(),
+        close: close.to_string(),
+    }
+}
+
+fn tag_surrounds(tag: &str) -> Surrounds {
+    let tag = tag.trim();
+    let Some(name) = tag.split_whitespace().next() else {
+        return Surrounds::Invalid;
+    };
+    Surrounds::Pair {
+        open: format!("<{tag}>"),
+        close: format!("</{name}>"),
+    }
+}
+
+/// Shrinks `range` to exclude the whitespace at its start and end, which
+/// vim-surround leaves outside the surrounding characters.
+fn trim_whitespace(snapshot: &MultiBufferSnapshot, range: Range<usize>) -> Range<usize> {
+    let text = snapshot.text_for_range(range.clone()).collect::<String>();
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return range;
+    }
+    let start = range.start + text.len() - text.trim_start().len();
+    start..start + trimmed.len()
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_add_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("hello wˇorld", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", ")"]);
+        cx.assert_state("hello ˇ(world)", Mode::Normal);
+
+        // Opening brackets add a space inside the pair
+        cx.set_state("hello wˇorld", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "["]);
+        cx.assert_state("hello ˇ[ world ]", Mode::Normal);
+
+        // Whitespace covered by the motion stays outside
+        cx.set_state("ˇhello world", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "w", "\""]);
+        cx.assert_state("ˇ\"hello\" world", Mode::Normal);
+
+        cx.set_state("ˇone two three", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "2", "e", "shift-b"]);
+        cx.assert_state("ˇ{one two} three", Mode::Normal);
+
+        cx.set_state("    let ˇa = 1;", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "s", "*"]);
+        cx.assert_state("    ˇ*let a = 1;*", Mode::Normal);
+
+        // Tags
+        cx.set_state("hello wˇorld", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "<", "e", "m", ">"]);
+        cx.assert_state("hello ˇ<em>world</em>", Mode::Normal);
+
+        cx.set_state("hello wˇorld", Mode::Normal);
+        cx.simulate_keystrokes([
+            "y", "s", "i", "w", "t", "a", "space", "h", "r", "e", "f", "=", "\"", "x", "\"",
+            "enter",
+        ]);
+        cx.assert_state("hello ˇ<a href=\"x\">world</a>", Mode::Normal);
+
+        // Multiple cursors
+        cx.set_state("ˇone ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "'"]);
+        cx.assert_state("ˇ'one' ˇ'two'", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_visual_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("hello ˇworld", Mode::Normal);
+        cx.simulate_keystrokes(["v", "e", "shift-s", ")"]);
+        cx.assert_state("hello ˇ(world)", Mode::Normal);
+
+        cx.set_state(
+            indoc! {"
+                fn a() {
+                    ˇb();
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["shift-v", "shift-s", "{"]);
+        cx.assert_state(
+            indoc! {"
+                fn a() {
+                    ˇ{
+                    b();
+                    }
+                }"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+                ˇone
+                two"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["ctrl-v", "j", "e", "shift-s", "`"]);
+        cx.assert_state(
+            indoc! {"
+                ˇ`one`
+                ˇ`two`"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_change_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("say \"hˇi\" now", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "'"]);
+        cx.assert_state("say ˇ'hi' now", Mode::Normal);
+
+        cx.set_state("f(a, ˇb)", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "(", "]"]);
+        cx.assert_state("fˇ[a, b]", Mode::Normal);
+
+        cx.set_state("(oˇne)", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "b", "<", "b", ">"]);
+        cx.assert_state("ˇ<b>one</b>", Mode::Normal);
+
+        // Multiple cursors, two of them in the same pair
+        cx.set_state("{aˇ bˇ} {ˇc}", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "{", ")"]);
+        cx.assert_state("ˇ(a b) ˇ(c)", Mode::Normal);
+
+        // Objects without delimiters are ignored
+        cx.set_state("hˇi", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "w"]);
+        cx.assert_state("hˇi", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_delete_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("f(a, ˇb)", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "("]);
+        cx.assert_state("fˇa, b", Mode::Normal);
+
+        cx.set_state("x = 'oˇne' + 'two'", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "'"]);
+        cx.assert_state("x = ˇone + 'two'", Mode::Normal);
+
+        cx.set_state(
+            indoc! {"
+                [{a,
+                    ˇb
+                }]"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["d", "s", "shift-b"]);
+        cx.assert_state(
+            indoc! {"
+                [ˇa,
+                    b
+                ]"},
+            Mode::Normal,
+        );
+
+        // No surrounding pair
+        cx.set_state("oˇne", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "("]);
+        cx.assert_state("oˇne", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_surround_tags(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_html(cx).await;
+
+        cx.set_state("<div><b>hˇi</b></div>", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "t", "<", "i", ">"]);
+        cx.assert_state("<div>ˇ<i>hi</i></div>", Mode::Normal);
+
+        cx.set_state("<div><b>hˇi</b></div>", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "t"]);
+        cx.assert_state("<div>ˇhi</div>", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_repeat_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "<", "b", ">", "f", "t", "."]);
+        cx.assert_state("<b>one</b> ˇ<b>two</b>", Mode::Normal);
+
+        cx.set_state("\"oˇne\" \"two\"", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "]", "f", "t", "."]);
+        cx.assert_state("[one] ˇ[two]", Mode::Normal);
+
+        cx.set_state("(oˇne) (two)", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "b", "f", "t", "."]);
+        cx.assert_state("one ˇtwo", Mode::Normal);
+    }
+}
@@ -271,6 +271,49 @@ impl NeovimBackedTestContext {
         )
     }
 
+    pub async fn assert_shared_register(&mut self, name: char, text: &str) {
+        let neovim = self.neovim.read_register(name).await;
+        let editor = self.cx.register(name).unwrap_or_default();
+
+        if text == neovim && text == editor {
+            return;
+        }
+
+        let message = if neovim != text {
+            "Test is incorrect (currently expected != neovim)"
+        } else {
+            "Editor does not match nvim behaviour"
+        };
+
+        let initial_state = self
+            .last_set_state
+            .as_ref()
+            .unwrap_or(&"N/A".to_string())
+            .clone();
+
+        panic!(
+            indoc! {"{}
+                # initial state:
+                {}
+                # keystrokes:
+                {}
+                # currently expected:
+                {}
+                # neovim register \"{}:
+                {}
+                # zed register \"{}:
+                {}"},
+            message,
+            initial_state,
+            self.recent_keystrokes.join(" "),
+            text,
+            name,
+            neovim,
+            name,
+            editor
+        )
+    }
+
     pub async fn neovim_state(&mut self) -> String {
         self.neovim.marked_text().await
     }
@@ -124,7 +124,17 @@ impl VimTestContext {
 
     pub fn active_operator(&mut self) -> Option<Operator> {
         self.cx
-            .read(|cx| cx.global::<Vim>().state().operator_stack.last().copied())
+            .read(|cx| cx.global::<Vim>().state().operator_stack.last().cloned())
+    }
+
+    pub fn register(&mut self, name: char) -> Option<String> {
+        self.cx.read(|cx| {
+            cx.global::<Vim>()
+                .workspace_state
+                .registers
+                .get(&name)
+                .map(|register| register.text.clone())
+        })
     }
 
     pub fn set_state(&mut self, text: &str, mode: Mode) {
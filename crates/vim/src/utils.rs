@@ -1,11 +1,17 @@
 use std::time::Duration;
 
-use editor::{ClipboardSelection, Editor};
-use gpui::{ClipboardItem, ViewContext};
+use anyhow::Result;
+use editor::{ClipboardSelection, Editor, MultiBuffer};
+use gpui::ViewContext;
 use language::{CharKind, Point};
-use settings::Settings;
+use workspace::Workspace;
 
-use crate::{state::Mode, UseSystemClipboard, Vim, VimSettings};
+use crate::{
+    normal::mark,
+    register,
+    state::{Mode, Register, RegisterKind},
+    Vim,
+};
 
 pub struct HighlightOnYank;
 
@@ -39,6 +45,7 @@ fn copy_selections_content_internal(
     let mut text = String::new();
     let mut clipboard_selections = Vec::with_capacity(selections.len());
     let mut ranges_to_highlight = Vec::new();
+    let mut changed_ranges = Vec::new();
     {
         let mut is_first = true;
         for selection in selections.iter() {
@@ -68,6 +75,7 @@ fn copy_selections_content_internal(
             let start_anchor = buffer.anchor_after(start);
             let end_anchor = buffer.anchor_before(end);
             ranges_to_highlight.push(start_anchor..end_anchor);
+            changed_ranges.push(start..end);
 
             for chunk in buffer.text_for_range(start..end) {
                 text.push_str(chunk);
@@ -83,22 +91,25 @@ fn copy_selections_content_internal(
         }
     }
 
-    let setting = VimSettings::get_global(cx).use_system_clipboard;
-    if setting == UseSystemClipboard::Always || setting == UseSystemClipboard::OnYank && is_yank {
-        cx.write_to_clipboard(ClipboardItem::new(text.clone()).with_metadata(clipboard_selections));
-        vim.workspace_state
-            .registers
-            .insert(".system.".to_string(), text.clone());
+    let kind = if linewise {
+        RegisterKind::Linewise
+    } else if vim.state().mode == Mode::VisualBlock {
+        RegisterKind::Blockwise
     } else {
-        vim.workspace_state.registers.insert(
-            ".system.".to_string(),
-            cx.read_from_clipboard()
-                .map(|item| item.text().clone())
-                .unwrap_or_default(),
-        );
+        RegisterKind::Charwise
+    };
+    let content = Register {
+        text,
+        kind,
+        clipboard_selections: Some(clipboard_selections),
+    };
+    register::write_registers(vim, content, is_yank, cx);
+    if !is_yank {
+        return;
     }
-    vim.workspace_state.registers.insert("\"".to_string(), text);
-    if !is_yank || vim.state().mode == Mode::Visual {
+
+    mark::set_change_marks(vim, &buffer, changed_ranges);
+    if vim.state().mode == Mode::Visual {
         return;
     }
 
@@ -126,3 +137,23 @@ pub fn coerce_punctuation(kind: CharKind, treat_punctuation_as_word: bool) -> Ch
         kind
     }
 }
+
+/// Opens a read-only buffer in the active pane showing `text`, used to display
+/// the output of listing commands such as `:marks`.
+pub(crate) fn open_listing(
+    workspace: &mut Workspace,
+    title: &str,
+    text: &str,
+    cx: &mut ViewContext<Workspace>,
+) -> Result<()> {
+    let project = workspace.project().clone();
+    let buffer = project.update(cx, |project, cx| project.create_buffer(text, None, cx))?;
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title.into()));
+    let editor = cx.new_view(|cx| {
+        let mut editor = Editor::for_multibuffer(buffer, Some(project), cx);
+        editor.set_read_only(true);
+        editor
+    });
+    workspace.add_item_to_active_pane(Box::new(editor), cx);
+    Ok(())
+}
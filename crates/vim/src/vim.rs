@@ -5,12 +5,16 @@ mod test;
 
 mod command;
 mod editor_events;
+mod helix;
 mod insert;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
+mod register;
 mod state;
+mod surrounds;
 mod utils;
 mod visual;
 
@@ -28,13 +32,17 @@ use gpui::{
 use language::{CursorShape, Point, Selection, SelectionGoal};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
-use normal::normal_replace;
+use normal::{
+    mark, normal_replace,
+    repeat::{record_register, replay_register},
+};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use settings::{update_settings_file, Settings, SettingsStore};
 use state::{EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
 use std::{ops::Range, sync::Arc};
+use surrounds::SurroundsType;
 use visual::{visual_block_motion, visual_replace};
 use workspace::{self, Workspace};
 
@@ -103,11 +111,9 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, &SwitchMode(mode): &SwitchMode, cx| {
         Vim::update(cx, |vim, cx| vim.switch_mode(mode, false, cx))
     });
-    workspace.register_action(
-        |_: &mut Workspace, &PushOperator(operator): &PushOperator, cx| {
-            Vim::update(cx, |vim, cx| vim.push_operator(operator, cx))
-        },
-    );
+    workspace.register_action(|_: &mut Workspace, operator: &PushOperator, cx| {
+        Vim::update(cx, |vim, cx| vim.push_operator(operator.0.clone(), cx))
+    });
     workspace.register_action(|_: &mut Workspace, n: &Number, cx: _| {
         Vim::update(cx, |vim, cx| vim.push_count_digit(n.0, cx));
     });
@@ -132,8 +138,12 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     insert::register(workspace, cx);
     motion::register(workspace, cx);
     command::register(workspace, cx);
+    helix::register(workspace, cx);
     object::register(workspace, cx);
     visual::register(workspace, cx);
+    register::register(workspace, cx);
+
+    mark::load_marks(workspace, cx);
 }
 
 /// Called whenever an keystroke is typed so vim can observe all actions
@@ -144,29 +154,62 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
         .as_ref()
         .map(|action| action.boxed_clone())
     {
-        Vim::update(cx, |vim, _| {
-            if vim.workspace_state.recording {
-                vim.workspace_state
-                    .recorded_actions
-                    .push(ReplayableAction::Action(action.boxed_clone()));
+        observe_action(action, cx);
+    } else if !cx.has_pending_keystrokes() {
+        clear_pending_operator(cx);
+    }
+    if !cx.has_pending_keystrokes() {
+        insert::resume_insert(keystroke_event, cx);
+    }
+}
 
-                if vim.workspace_state.stop_recording_after_next_action {
-                    vim.workspace_state.recording = false;
-                    vim.workspace_state.stop_recording_after_next_action = false;
-                }
+/// Called for every action that is typed or replayed from a recording, so
+/// that it can be recorded for `.` and macros.
+pub(crate) fn observe_action(action: Box<dyn Action>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, _| {
+        if vim.workspace_state.recording {
+            vim.workspace_state
+                .recorded_actions
+                .push(ReplayableAction::Action(action.boxed_clone()));
+
+            if vim.workspace_state.stop_recording_after_next_action {
+                vim.workspace_state.recording = false;
+                vim.workspace_state.stop_recording_after_next_action = false;
             }
-        });
+        }
 
-        // Keystroke is handled by the vim system, so continue forward
-        if action.name().starts_with("vim::") {
-            return;
+        if vim.workspace_state.recording_register.is_some()
+            && vim.workspace_state.replayer.is_none()
+        {
+            vim.workspace_state
+                .current_recording
+                .push(ReplayableAction::Action(action.boxed_clone()));
         }
-    } else if cx.has_pending_keystrokes() {
+    });
+
+    // Keystroke is handled by the vim system, so continue forward
+    if action.name().starts_with("vim::") {
         return;
     }
+    clear_pending_operator(cx);
+}
 
+fn clear_pending_operator(cx: &mut WindowContext) {
     Vim::update(cx, |vim, cx| match vim.active_operator() {
-        Some(Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace) => {}
+        Some(
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Mark
+            | Operator::Jump { .. }
+            | Operator::Register
+            | Operator::InsertRegister
+            | Operator::SelectRegex { .. }
+            | Operator::AddSurrounds { target: Some(_) }
+            | Operator::ChangeSurrounds { target: Some(_) },
+        ) => {}
         Some(_) => {
             vim.clear_operator(cx);
         }
@@ -205,6 +248,12 @@ impl Vim {
         }
 
         self.active_editor = Some(editor.clone().downgrade());
+        if !self.editor_states.contains_key(&editor.entity_id()) {
+            if VimSettings::get_global(cx).helix_mode {
+                self.update_state(|state| state.mode = Mode::HelixNormal);
+            }
+            mark::restore_marks(self, &editor, cx);
+        }
         self.editor_subscription = Some(cx.subscribe(&editor, |editor, event, cx| match event {
             EditorEvent::SelectionsChanged { local: true } => {
                 let editor = editor.read(cx);
@@ -215,13 +264,19 @@ impl Vim {
                 }
             }
             EditorEvent::InputIgnored { text } => {
+                Vim::record_macro_insertion(text, None, cx);
                 Vim::active_editor_input_ignored(text.clone(), cx);
                 Vim::record_insertion(text, None, cx)
             }
             EditorEvent::InputHandled {
                 text,
                 utf16_range_to_replace: range_to_replace,
-            } => Vim::record_insertion(text, range_to_replace.clone(), cx),
+            } => {
+                Vim::record_macro_insertion(text, range_to_replace.clone(), cx);
+                Vim::record_insertion(text, range_to_replace.clone(), cx)
+            }
+            EditorEvent::Edited => mark::set_last_change_mark(&editor, cx),
+            EditorEvent::Saved => mark::persist_marks(&editor, cx),
             _ => {}
         }));
 
@@ -262,6 +317,30 @@ impl Vim {
         });
     }
 
+    /// Records typed text into the macro register being recorded (if any).
+    ///
+    /// For ignored input this must run before the input is handled, so that the
+    /// register name typed after `q` is not part of the macro, while the one
+    /// typed after `@` is (and is recorded before the replay begins).
+    fn record_macro_insertion(
+        text: &Arc<str>,
+        range_to_replace: Option<Range<isize>>,
+        cx: &mut WindowContext,
+    ) {
+        Vim::update(cx, |vim, _| {
+            if vim.workspace_state.recording_register.is_some()
+                && vim.workspace_state.replayer.is_none()
+            {
+                vim.workspace_state
+                    .current_recording
+                    .push(ReplayableAction::Insertion {
+                        text: text.clone(),
+                        utf16_range_to_replace: range_to_replace,
+                    });
+            }
+        });
+    }
+
     fn update_active_editor<S>(
         &mut self,
         cx: &mut WindowContext,
@@ -347,6 +426,12 @@ impl Vim {
     }
 
     fn switch_mode(&mut self, mode: Mode, leave_selections: bool, cx: &mut WindowContext) {
+        // In helix mode, normal mode is replaced by its selection-first counterpart.
+        let mode = if mode == Mode::Normal && VimSettings::get_global(cx).helix_mode {
+            Mode::HelixNormal
+        } else {
+            mode
+        };
         let state = self.state();
         let last_mode = state.mode;
         let prior_mode = state.last_mode;
@@ -354,6 +439,9 @@ impl Vim {
             state.last_mode = last_mode;
             state.mode = mode;
             state.operator_stack.clear();
+            state.surround_tag = None;
+            state.temporary_normal = false;
+            state.insert_completion = None;
         });
         if mode != Mode::Insert {
             self.take_count(cx);
@@ -367,7 +455,11 @@ impl Vim {
         }
 
         // Adjust selections
-        self.update_active_editor(cx, |_, editor, cx| {
+        self.update_active_editor(cx, |vim, editor, cx| {
+            if last_mode.is_visual() && !mode.is_visual() {
+                mark::set_visual_marks(vim, editor, cx);
+            }
+
             if last_mode != Mode::VisualBlock && last_mode.is_visual() && mode == Mode::VisualBlock
             {
                 visual_block_motion(true, editor, cx, |_, point, goal| Some((point, goal)))
@@ -448,6 +540,28 @@ impl Vim {
         ) {
             self.start_recording(cx)
         };
+        // The surround operators are typed after `y`, `c` or `d`, which they
+        // replace on the stack.
+        let operator = match operator {
+            Operator::AddSurrounds { target: None } => {
+                self.update_state(|state| state.operator_stack.clear());
+                self.start_recording(cx);
+                if self.state().mode.is_visual() || self.state().mode == Mode::HelixNormal {
+                    Operator::AddSurrounds {
+                        target: Some(SurroundsType::Selection),
+                    }
+                } else {
+                    operator
+                }
+            }
+            Operator::AddSurrounds { .. }
+            | Operator::ChangeSurrounds { .. }
+            | Operator::DeleteSurrounds => {
+                self.update_state(|state| state.operator_stack.clear());
+                operator
+            }
+            _ => operator,
+        };
         self.update_state(|state| state.operator_stack.push(operator));
         self.sync_vim_settings(cx);
     }
@@ -464,12 +578,16 @@ impl Vim {
     }
     fn clear_operator(&mut self, cx: &mut WindowContext) {
         self.take_count(cx);
-        self.update_state(|state| state.operator_stack.clear());
+        self.update_state(|state| {
+            state.operator_stack.clear();
+            state.surround_tag = None;
+        });
+        self.workspace_state.selected_register = None;
         self.sync_vim_settings(cx);
     }
 
     fn active_operator(&self) -> Option<Operator> {
-        self.state().operator_stack.last().copied()
+        self.state().operator_stack.last().cloned()
     }
 
     fn active_editor_input_ignored(text: Arc<str>, cx: &mut WindowContext) {
@@ -513,6 +631,23 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_replace(text, cx),
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
+            Some(Operator::RecordRegister) => record_register(text.chars().next().unwrap(), cx),
+            Some(Operator::ReplayRegister) => replay_register(text.chars().next().unwrap(), cx),
+            Some(Operator::Mark) => Vim::update(cx, |vim, cx| mark::create_mark(vim, text, cx)),
+            Some(Operator::Jump { line }) => mark::jump(text, line, cx),
+            Some(Operator::Register) => {
+                Vim::update(cx, |vim, cx| register::select_register(vim, text, cx))
+            }
+            Some(Operator::InsertRegister) => {
+                Vim::update(cx, |vim, cx| register::insert_register(vim, text, cx))
+            }
+            Some(Operator::SelectRegex { pattern }) => helix::select_regex(pattern, text, cx),
+            Some(Operator::AddSurrounds {
+                target: Some(target),
+            }) => surrounds::add_surrounds(text, target, cx),
+            Some(Operator::ChangeSurrounds {
+                target: Some(target),
+            }) => surrounds::change_surrounds(text, target, cx),
             _ => {}
         }
     }
@@ -639,12 +774,14 @@ struct VimSettings {
     // some magic where yy is system and dd is not.
     pub use_system_clipboard: UseSystemClipboard,
     pub use_multiline_find: bool,
+    pub helix_mode: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 struct VimSettingsContent {
     pub use_system_clipboard: Option<UseSystemClipboard>,
     pub use_multiline_find: Option<bool>,
+    pub helix_mode: Option<bool>,
 }
 
 impl Settings for VimSettings {
@@ -3,14 +3,19 @@ use anyhow::{anyhow, Result};
 use call::{ActiveCall, ParticipantLocation};
 use collections::HashMap;
 use gpui::{
-    point, size, AnyView, AnyWeakView, Axis, Bounds, IntoElement, Model, MouseButton, Pixels,
-    Point, View, ViewContext,
+    point, size, AnyElement, AnyView, AnyWeakView, Axis, Bounds, IntoElement, Model, MouseButton,
+    Pixels, Point, View, ViewContext, WindowContext,
 };
 use parking_lot::Mutex;
 use project::Project;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 use ui::prelude::*;
+use util::ResultExt;
+
+/// Called when the panes of a group are resized by dragging the dividers
+/// between them, so that the group's owner can serialize their sizes.
+pub type OnPaneResize = Rc<dyn Fn(&mut WindowContext)>;
 
 pub const HANDLE_HITBOX_SIZE: f32 = 4.0;
 const HORIZONTAL_MIN_SIZE: f32 = 80.;
@@ -18,11 +23,11 @@ const VERTICAL_MIN_SIZE: f32 = 100.;
 
 #[derive(Clone)]
 pub struct PaneGroup {
-    pub(crate) root: Member,
+    pub root: Member,
 }
 
 impl PaneGroup {
-    pub(crate) fn with_root(root: Member) -> Self {
+    pub fn with_root(root: Member) -> Self {
         Self { root }
     }
 
@@ -110,19 +115,32 @@ impl PaneGroup {
         )
     }
 
-    pub(crate) fn panes(&self) -> Vec<&View<Pane>> {
+    /// Renders the panes of a group that lives outside of the workspace's
+    /// center, without any of the decorations for following collaborators.
+    pub fn render_panes(
+        &self,
+        active_pane: &View<Pane>,
+        zoomed: Option<&AnyWeakView>,
+        on_resize: OnPaneResize,
+        cx: &mut WindowContext,
+    ) -> impl IntoElement {
+        self.root
+            .render_panes(0, active_pane, zoomed, &on_resize, cx)
+    }
+
+    pub fn panes(&self) -> Vec<&View<Pane>> {
         let mut panes = Vec::new();
         self.root.collect_panes(&mut panes);
         panes
     }
 
-    pub(crate) fn first_pane(&self) -> View<Pane> {
+    pub fn first_pane(&self) -> View<Pane> {
         self.root.first_pane()
     }
 }
 
 #[derive(Clone)]
-pub(crate) enum Member {
+pub enum Member {
     Axis(PaneAxis),
     Pane(View<Pane>),
 }
@@ -159,7 +177,7 @@ impl Member {
         }
     }
 
-    pub fn render(
+    pub(crate) fn render(
         &self,
         project: &Model<Project>,
         basis: usize,
@@ -296,6 +314,31 @@ impl Member {
         }
     }
 
+    fn render_panes(
+        &self,
+        basis: usize,
+        active_pane: &View<Pane>,
+        zoomed: Option<&AnyWeakView>,
+        on_resize: &OnPaneResize,
+        cx: &mut WindowContext,
+    ) -> AnyElement {
+        match self {
+            Member::Pane(pane) => {
+                if zoomed == Some(&pane.downgrade().into()) {
+                    return div().into_any();
+                }
+
+                div()
+                    .relative()
+                    .flex_1()
+                    .size_full()
+                    .child(AnyView::from(pane.clone()).cached())
+                    .into_any()
+            }
+            Member::Axis(axis) => axis.render_panes(basis + 1, active_pane, zoomed, on_resize, cx),
+        }
+    }
+
     fn collect_panes<'a>(&'a self, panes: &mut Vec<&'a View<Pane>>) {
         match self {
             Member::Axis(axis) => {
@@ -309,7 +352,7 @@ impl Member {
 }
 
 #[derive(Clone)]
-pub(crate) struct PaneAxis {
+pub struct PaneAxis {
     pub axis: Axis,
     pub members: Vec<Member>,
     pub flexes: Arc<Mutex<Vec<f32>>>,
@@ -484,13 +527,18 @@ impl PaneAxis {
     ) -> gpui::AnyElement {
         debug_assert!(self.members.len() == self.flexes.lock().len());
         let mut active_pane_ix = None;
+        let workspace = cx.view().downgrade();
 
         pane_axis(
             self.axis,
             basis,
             self.flexes.clone(),
             self.bounding_boxes.clone(),
-            cx.view().downgrade(),
+            Rc::new(move |cx| {
+                workspace
+                    .update(cx, |workspace, cx| workspace.schedule_serialize(cx))
+                    .log_err();
+            }),
         )
         .children(self.members.iter().enumerate().map(|(ix, member)| {
             if member.contains(active_pane) {
@@ -512,6 +560,34 @@ impl PaneAxis {
         .with_active_pane(active_pane_ix)
         .into_any_element()
     }
+
+    fn render_panes(
+        &self,
+        basis: usize,
+        active_pane: &View<Pane>,
+        zoomed: Option<&AnyWeakView>,
+        on_resize: &OnPaneResize,
+        cx: &mut WindowContext,
+    ) -> AnyElement {
+        debug_assert!(self.members.len() == self.flexes.lock().len());
+        let mut active_pane_ix = None;
+
+        pane_axis(
+            self.axis,
+            basis,
+            self.flexes.clone(),
+            self.bounding_boxes.clone(),
+            on_resize.clone(),
+        )
+        .children(self.members.iter().enumerate().map(|(ix, member)| {
+            if member.contains(active_pane) {
+                active_pane_ix = Some(ix);
+            }
+            member.render_panes((basis + ix) * 10, active_pane, zoomed, on_resize, cx)
+        }))
+        .with_active_pane(active_pane_ix)
+        .into_any_element()
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
@@ -590,19 +666,16 @@ mod element {
     use gpui::{
         px, relative, Along, AnyElement, Axis, Bounds, CursorStyle, Element, IntoElement,
         MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Size, Style,
-        WeakView, WindowContext,
+        WindowContext,
     };
     use parking_lot::Mutex;
     use settings::Settings;
     use smallvec::SmallVec;
     use ui::prelude::*;
-    use util::ResultExt;
-
-    use crate::Workspace;
 
     use crate::WorkspaceSettings;
 
-    use super::{HANDLE_HITBOX_SIZE, HORIZONTAL_MIN_SIZE, VERTICAL_MIN_SIZE};
+    use super::{OnPaneResize, HANDLE_HITBOX_SIZE, HORIZONTAL_MIN_SIZE, VERTICAL_MIN_SIZE};
 
     const DIVIDER_SIZE: f32 = 1.0;
 
@@ -611,7 +684,7 @@ mod element {
         basis: usize,
         flexes: Arc<Mutex<Vec<f32>>>,
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
-        workspace: WeakView<Workspace>,
+        on_resize: OnPaneResize,
     ) -> PaneAxisElement {
         PaneAxisElement {
             axis,
@@ -620,7 +693,7 @@ mod element {
             bounding_boxes,
             children: SmallVec::new(),
             active_pane_ix: None,
-            workspace,
+            on_resize,
         }
     }
 
@@ -631,7 +704,7 @@ mod element {
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
         children: SmallVec<[AnyElement; 2]>,
         active_pane_ix: Option<usize>,
-        workspace: WeakView<Workspace>,
+        on_resize: OnPaneResize,
     }
 
     impl PaneAxisElement {
@@ -647,7 +720,7 @@ mod element {
             axis: Axis,
             child_start: Point<Pixels>,
             container_size: Size<Pixels>,
-            workspace: WeakView<Workspace>,
+            on_resize: &OnPaneResize,
             cx: &mut WindowContext,
         ) {
             let min_size = match axis {
@@ -721,9 +794,7 @@ mod element {
                 proposed_current_pixel_change -= current_pixel_change;
             }
 
-            workspace
-                .update(cx, |this, cx| this.schedule_serialize(cx))
-                .log_err();
+            on_resize(cx);
             cx.stop_propagation();
             cx.refresh();
         }
@@ -735,7 +806,7 @@ mod element {
             ix: usize,
             pane_bounds: Bounds<Pixels>,
             axis_bounds: Bounds<Pixels>,
-            workspace: WeakView<Workspace>,
+            on_resize: OnPaneResize,
             cx: &mut ElementContext,
         ) {
             let handle_bounds = Bounds {
@@ -769,16 +840,14 @@ mod element {
                 cx.on_mouse_event({
                     let dragged_handle = dragged_handle.clone();
                     let flexes = flexes.clone();
-                    let workspace = workspace.clone();
+                    let on_resize = on_resize.clone();
                     move |e: &MouseDownEvent, phase, cx| {
                         if phase.bubble() && handle_bounds.contains(&e.position) {
                             dragged_handle.replace(Some(ix));
                             if e.click_count >= 2 {
                                 let mut borrow = flexes.lock();
                                 *borrow = vec![1.; borrow.len()];
-                                workspace
-                                    .update(cx, |this, cx| this.schedule_serialize(cx))
-                                    .log_err();
+                                on_resize(cx);
 
                                 cx.refresh();
                             }
@@ -787,7 +856,6 @@ mod element {
                     }
                 });
                 cx.on_mouse_event({
-                    let workspace = workspace.clone();
                     move |e: &MouseMoveEvent, phase, cx| {
                         let dragged_handle = dragged_handle.borrow();
 
@@ -799,7 +867,7 @@ mod element {
                                 axis,
                                 pane_bounds.origin,
                                 axis_bounds.size,
-                                workspace.clone(),
+                                &on_resize,
                                 cx,
                             )
                         }
@@ -905,7 +973,7 @@ mod element {
                                 ix,
                                 child_bounds,
                                 bounds,
-                                self.workspace.clone(),
+                                self.on_resize.clone(),
                                 cx,
                             );
                         }
@@ -98,7 +98,7 @@ impl Render for Toolbar {
             return div();
         }
 
-        let secondary_item = self.secondary_items().next().map(|item| item.to_any());
+        let secondary_items = self.secondary_items().map(|item| item.to_any());
 
         let has_left_items = self.left_items().count() > 0;
         let has_right_items = self.right_items().count() > 0;
@@ -133,7 +133,7 @@ impl Render for Toolbar {
                         )
                     }),
             )
-            .children(secondary_item)
+            .children(secondary_items)
     }
 }
 
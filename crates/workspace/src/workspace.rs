@@ -272,19 +272,57 @@ pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
     });
 }
 
+type ProjectItemOpener = fn(
+    &Model<Project>,
+    &ProjectPath,
+    &mut WindowContext,
+)
+    -> Option<Task<Result<(Option<ProjectEntryId>, WorkspaceItemBuilder)>>>;
+
+type WorkspaceItemBuilder = Box<dyn FnOnce(&mut ViewContext<Pane>) -> Box<dyn ItemHandle>>;
+
 #[derive(Clone, Default, Deref, DerefMut)]
-struct ProjectItemBuilders(
-    HashMap<TypeId, fn(Model<Project>, AnyModel, &mut ViewContext<Pane>) -> Box<dyn ItemHandle>>,
-);
+struct ProjectItemOpeners(Vec<ProjectItemOpener>);
+
+impl Global for ProjectItemOpeners {}
+
+#[derive(Clone, Default, Deref, DerefMut)]
+struct BinaryProjectItemOpeners(Vec<ProjectItemOpener>);
 
-impl Global for ProjectItemBuilders {}
+impl Global for BinaryProjectItemOpeners {}
 
+/// Registers a [ProjectItem] for the app. When opening a file, all the registered
+/// items are given a chance to open the file, starting from the most recently
+/// registered one.
 pub fn register_project_item<I: ProjectItem>(cx: &mut AppContext) {
-    let builders = cx.default_global::<ProjectItemBuilders>();
-    builders.insert(TypeId::of::<I::Item>(), |project, model, cx| {
-        let item = model.downcast::<I::Item>().unwrap();
-        Box::new(cx.new_view(|cx| I::for_project_item(project, item, cx)))
-    });
+    cx.default_global::<ProjectItemOpeners>()
+        .push(open_project_item::<I>);
+}
+
+/// Registers a [ProjectItem] that opens the files which the items registered with
+/// [register_project_item] fail to open because they are binary.
+pub fn register_binary_project_item<I: ProjectItem>(cx: &mut AppContext) {
+    cx.default_global::<BinaryProjectItemOpeners>()
+        .push(open_project_item::<I>);
+}
+
+fn open_project_item<I: ProjectItem>(
+    project: &Model<Project>,
+    project_path: &ProjectPath,
+    cx: &mut WindowContext,
+) -> Option<Task<Result<(Option<ProjectEntryId>, WorkspaceItemBuilder)>>> {
+    let project_item = <I::Item as project::Item>::try_open(project, project_path, cx)?;
+    let project = project.clone();
+    Some(cx.spawn(|cx| async move {
+        let project_item = project_item.await?;
+        let project_entry_id: Option<ProjectEntryId> =
+            project_item.read_with(&cx, |item, cx| project::Item::entry_id(item, cx))?;
+        let build_workspace_item = Box::new(|cx: &mut ViewContext<Pane>| {
+            Box::new(cx.new_view(|cx| I::for_project_item(project, project_item, cx)))
+                as Box<dyn ItemHandle>
+        }) as Box<_>;
+        Ok((project_entry_id, build_workspace_item))
+    }))
 }
 
 type FollowableItemBuilder = fn(
@@ -2029,25 +2067,35 @@ impl Workspace {
         &mut self,
         path: ProjectPath,
         cx: &mut WindowContext,
-    ) -> Task<
-        Result<(
-            Option<ProjectEntryId>,
-            impl 'static + Send + FnOnce(&mut ViewContext<Pane>) -> Box<dyn ItemHandle>,
-        )>,
-    > {
+    ) -> Task<Result<(Option<ProjectEntryId>, WorkspaceItemBuilder)>> {
         let project = self.project().clone();
-        let project_item = project.update(cx, |project, cx| project.open_path(path, cx));
+        let project_item_openers = cx.default_global::<ProjectItemOpeners>().clone();
+        let Some(open_task) = project_item_openers
+            .iter()
+            .rev()
+            .find_map(|open_project_item| open_project_item(&project, &path, cx))
+        else {
+            return Task::ready(Err(anyhow!("cannot open file {:?}", path.path)));
+        };
+        let binary_openers = cx.default_global::<BinaryProjectItemOpeners>().clone();
+
         cx.spawn(|mut cx| async move {
-            let (project_entry_id, project_item) = project_item.await?;
-            let build_item = cx.update(|cx| {
-                cx.default_global::<ProjectItemBuilders>()
-                    .get(&project_item.entity_type())
-                    .ok_or_else(|| anyhow!("no item builder for project item"))
-                    .cloned()
-            })??;
-            let build_item =
-                move |cx: &mut ViewContext<Pane>| build_item(project, project_item, cx);
-            Ok((project_entry_id, build_item))
+            let error = match open_task.await {
+                Ok(opened) => return Ok(opened),
+                Err(error) if error.error_code() == ErrorCode::BinaryFile => error,
+                Err(error) => return Err(error),
+            };
+
+            for open_project_item in binary_openers.iter().rev() {
+                let Some(task) = cx.update(|cx| open_project_item(&project, &path, cx))? else {
+                    continue;
+                };
+                match task.await {
+                    Ok(opened) => return Ok(opened),
+                    Err(error) => log::error!("failed to open binary file: {error:?}"),
+                }
+            }
+            Err(error)
         })
     }
 
@@ -8,6 +8,7 @@ pub struct WorkspaceSettings {
     pub confirm_quit: bool,
     pub show_call_status_icon: bool,
     pub autosave: AutosaveSetting,
+    pub file_history: FileHistorySettings,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -30,6 +31,31 @@ pub struct WorkspaceSettingsContent {
     ///
     /// Default: off
     pub autosave: Option<AutosaveSetting>,
+    /// Settings related to the local history of saved files.
+    pub file_history: Option<FileHistorySettingsContent>,
+}
+
+#[derive(Deserialize)]
+pub struct FileHistorySettings {
+    pub enabled: bool,
+    pub max_snapshots_per_file: u32,
+    pub max_age_days: u32,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct FileHistorySettingsContent {
+    /// Whether to keep a snapshot of each version of a file that gets saved.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// How many snapshots to keep for each file, dropping the oldest ones first.
+    ///
+    /// Default: 50
+    pub max_snapshots_per_file: Option<u32>,
+    /// How many days to keep snapshots for.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u32>,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
@@ -8,7 +8,7 @@ use cli::FORCE_CLI_MODE_ENV_VAR_NAME;
 use client::{Client, UserStore};
 use collab_ui::channel_view::ChannelView;
 use db::kvp::KEY_VALUE_STORE;
-use editor::Editor;
+use editor::{Editor, UserSnippets};
 use env_logger::Builder;
 use fs::RealFs;
 #[cfg(target_os = "macos")]
@@ -186,6 +186,7 @@ fn main() {
         load_user_themes_in_background(fs.clone(), cx);
         #[cfg(target_os = "macos")]
         watch_themes(fs.clone(), cx);
+        watch_snippets(fs.clone(), cx);
 
         cx.spawn(|_| watch_languages(fs.clone(), languages.clone()))
             .detach();
@@ -242,6 +243,9 @@ fn main() {
 
         go_to_line::init(cx);
         file_finder::init(cx);
+        file_history::init(cx);
+        image_viewer::init(cx);
+        hex_editor::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
@@ -250,10 +254,12 @@ fn main() {
         search::init(cx);
         semantic_index::init(fs.clone(), http.clone(), languages.clone(), cx);
         vim::init(cx);
+        emacs::init(cx);
         terminal_view::init(cx);
 
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
         theme_selector::init(cx);
         language_tools::init(cx);
         call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
@@ -491,6 +497,7 @@ async fn restore_or_create_workspace(app_state: &Arc<AppState>, cx: AsyncAppCont
 fn init_paths() {
     std::fs::create_dir_all(&*util::paths::CONFIG_DIR).expect("could not create config path");
     std::fs::create_dir_all(&*util::paths::LANGUAGES_DIR).expect("could not create languages path");
+    std::fs::create_dir_all(&*util::paths::SNIPPETS_DIR).expect("could not create snippets path");
     std::fs::create_dir_all(&*util::paths::DB_DIR).expect("could not create database path");
     std::fs::create_dir_all(&*util::paths::LOGS_DIR).expect("could not create logs path");
     #[cfg(target_os = "linux")]
@@ -1032,6 +1039,39 @@ fn watch_themes(fs: Arc<dyn fs::Fs>, cx: &mut AppContext) {
     .detach()
 }
 
+/// Loads the user's snippets, and spawns a background task to reload them as
+/// the snippets directory changes.
+fn watch_snippets(fs: Arc<dyn fs::Fs>, cx: &mut AppContext) {
+    use std::time::Duration;
+
+    async fn reload(path: &Path, fs: &dyn fs::Fs, cx: &AsyncAppContext) {
+        let content = fs.load(path).await.ok();
+        cx.update(|cx| UserSnippets::update_file(path, content.as_deref(), cx))
+            .and_then(|result| result)
+            .with_context(|| format!("loading snippets from {path:?}"))
+            .log_err();
+    }
+
+    cx.spawn(|cx| async move {
+        let snippets_dir = paths::SNIPPETS_DIR.as_path();
+        if let Some(mut paths) = fs.read_dir(snippets_dir).await.log_err() {
+            while let Some(path) = paths.next().await {
+                if let Some(path) = path.log_err() {
+                    reload(&path, fs.as_ref(), &cx).await;
+                }
+            }
+        }
+
+        let mut events = fs.watch(snippets_dir, Duration::from_millis(100)).await;
+        while let Some(events) = events.next().await {
+            for event in events {
+                reload(&event.path, fs.as_ref(), &cx).await;
+            }
+        }
+    })
+    .detach()
+}
+
 #[cfg(debug_assertions)]
 async fn watch_languages(fs: Arc<dyn fs::Fs>, languages: Arc<LanguageRegistry>) {
     use std::time::Duration;